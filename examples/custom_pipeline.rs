@@ -1,9 +1,14 @@
 use addrslips::Pipeline;
+use addrslips::detection::contours::Connectivity;
+use addrslips::detection::preprocessing::BorderMode;
 use addrslips::detection::steps::*;
 use image::ImageReader;
 use std::env;
 
 fn main() -> anyhow::Result<()> {
+    // Show the progress of the verbose pipeline runs
+    addrslips::pipeline::log_to_stderr(log::LevelFilter::Info);
+
     let args: Vec<String> = env::args().collect();
     if args.len() < 2 {
         eprintln!("Usage: {} <image_path>", args[0]);
@@ -22,19 +27,28 @@ fn main() -> anyhow::Result<()> {
     let mut standard_pipeline = Pipeline::new()
         .with_verbose(true)
         .add_step_boxed(Box::new(GrayscaleStep))
-        .add_step_boxed(Box::new(BlurStep { sigma: 1.5 }))
+        .add_step_boxed(Box::new(BlurStep {
+            sigma: 1.5,
+            border: BorderMode::Replicate,
+        }))
         .add_step_boxed(Box::new(EdgeDetectionStep {
             low_threshold: 50.0,
             high_threshold: 100.0,
         }))
-        .add_step_boxed(Box::new(ContourDetectionStep { min_area: 10, padding: 10 }))
+        .add_step_boxed(Box::new(ContourDetectionStep {
+            min_area: 10,
+            padding: 10,
+            max_area: None,
+            reject_border: false,
+            connectivity: Connectivity::Eight,
+        }))
         .add_step_boxed(Box::new(CircleFilterStep {
             min_radius: 10.0,
             max_radius: 200.0,
             circularity_threshold: 2.0,
         }))
         .add_step_boxed(Box::new(WhiteCircleFilterStep {
-            brightness_threshold: 200.0,
+            brightness_threshold: Some(200.0),
         }));
 
     let detections = standard_pipeline.run(img.clone())?;
@@ -57,19 +71,28 @@ fn main() -> anyhow::Result<()> {
     let mut custom_pipeline = Pipeline::new()
         .with_verbose(false)
         .add_step_boxed(Box::new(GrayscaleStep))
-        .add_step_boxed(Box::new(BlurStep { sigma: 2.0 }))  // More blur
+        .add_step_boxed(Box::new(BlurStep {
+            sigma: 2.0,  // More blur
+            border: BorderMode::Replicate,
+        }))
         .add_step_boxed(Box::new(EdgeDetectionStep {
             low_threshold: 40.0,  // Lower threshold
             high_threshold: 120.0,
         }))
-        .add_step_boxed(Box::new(ContourDetectionStep { min_area: 20, padding: 10 }))  // Larger min area
+        .add_step_boxed(Box::new(ContourDetectionStep {
+            min_area: 20,  // Larger min area
+            padding: 10,
+            max_area: None,
+            reject_border: false,
+            connectivity: Connectivity::Eight,
+        }))
         .add_step_boxed(Box::new(CircleFilterStep {
             min_radius: 15.0,  // Larger minimum
             max_radius: 150.0,
             circularity_threshold: 1.5,  // Stricter
         }))
         .add_step_boxed(Box::new(WhiteCircleFilterStep {
-            brightness_threshold: 210.0,  // Whiter
+            brightness_threshold: Some(210.0),  // Whiter
         }));
 
     let custom_detections = custom_pipeline.run(img.clone())?;
@@ -80,7 +103,10 @@ fn main() -> anyhow::Result<()> {
     let mut partial_pipeline = Pipeline::new()
         .with_verbose(false)
         .add_step_boxed(Box::new(GrayscaleStep))
-        .add_step_boxed(Box::new(BlurStep { sigma: 1.5 }))
+        .add_step_boxed(Box::new(BlurStep {
+            sigma: 1.5,
+            border: BorderMode::Replicate,
+        }))
         .add_step_boxed(Box::new(EdgeDetectionStep {
             low_threshold: 50.0,
             high_threshold: 100.0,
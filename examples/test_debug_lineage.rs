@@ -1,9 +1,14 @@
 use addrslips::Pipeline;
+use addrslips::detection::contours::Connectivity;
+use addrslips::detection::preprocessing::BorderMode;
 use addrslips::detection::steps::*;
 use image::ImageReader;
 use std::path::PathBuf;
 
 fn main() -> anyhow::Result<()> {
+    // Show the progress of the verbose pipeline runs
+    addrslips::pipeline::log_to_stderr(log::LevelFilter::Info);
+
     let img = ImageReader::open("image.png")?
         .decode()
         .map_err(|e| anyhow::anyhow!("Failed to decode image: {}", e))?;
@@ -23,27 +28,42 @@ fn main() -> anyhow::Result<()> {
         .with_verbose(true)
         .with_debug(debug_dir.clone())?
         .add_step_boxed(Box::new(GrayscaleStep))
-        .add_step_boxed(Box::new(BlurStep { sigma: 1.5 }))
+        .add_step_boxed(Box::new(BlurStep {
+            sigma: 1.5,
+            border: BorderMode::Replicate,
+        }))
         .add_step_boxed(Box::new(EdgeDetectionStep {
             low_threshold: 50.0,
             high_threshold: 100.0,
         }))
-        .add_step_boxed(Box::new(ContourDetectionStep { min_area: 10, padding: 10 }))
+        .add_step_boxed(Box::new(ContourDetectionStep {
+            min_area: 10,
+            padding: 10,
+            max_area: None,
+            reject_border: false,
+            connectivity: Connectivity::Eight,
+        }))
         .add_step_boxed(Box::new(CircleFilterStep {
             min_radius: 10.0,
             max_radius: 200.0,
             circularity_threshold: 2.0,
         }))
         .add_step_boxed(Box::new(WhiteCircleFilterStep {
-            brightness_threshold: 200.0,
+            brightness_threshold: Some(200.0),
         }));
 
+    // Each run writes into its own timestamped folder inside debug_dir
+    let run_dir = pipeline
+        .debug_run_dir()
+        .expect("debug mode is enabled")
+        .to_path_buf();
+
     println!("Running pipeline with executor (lineage tracking)...");
-    let result = pipeline.run_with_executor(img)?;
+    let result = pipeline.run_with_executor(img, 1)?;
 
     println!("\n✓ Pipeline completed!");
     println!("  Detected {} white circles", result.len());
-    println!("\nDebug outputs with lineage tracking saved to: {}/", debug_dir.display());
+    println!("\nDebug outputs with lineage tracking saved to: {}/", run_dir.display());
 
     println!("\nLineage explanation:");
     println!("  - Filenames show the path through the pipeline");
@@ -55,7 +75,7 @@ fn main() -> anyhow::Result<()> {
 
     // Show some example lineage files
     println!("\nExample lineage in contour detection (step splits 1→100):");
-    if let Ok(files) = std::fs::read_dir(debug_dir.join("04_contour_detection")) {
+    if let Ok(files) = std::fs::read_dir(run_dir.join("04_contour_detection")) {
         let mut filenames: Vec<_> = files
             .filter_map(|e| e.ok())
             .map(|e| e.file_name().to_string_lossy().to_string())
@@ -70,7 +90,7 @@ fn main() -> anyhow::Result<()> {
     }
 
     println!("\nExample lineage in circle filtering (filtering step):");
-    if let Ok(files) = std::fs::read_dir(debug_dir.join("05_circle_filtering")) {
+    if let Ok(files) = std::fs::read_dir(run_dir.join("05_circle_filtering")) {
         let mut filenames: Vec<_> = files
             .filter_map(|e| e.ok())
             .map(|e| e.file_name().to_string_lossy().to_string())
@@ -1,4 +1,6 @@
 use addrslips::Pipeline;
+use addrslips::detection::contours::Connectivity;
+use addrslips::detection::preprocessing::BorderMode;
 use addrslips::detection::steps::*;
 use image::ImageReader;
 
@@ -13,24 +15,33 @@ fn main() -> anyhow::Result<()> {
     let pipeline = Pipeline::new()
         .with_verbose(false)
         .add_step_boxed(Box::new(GrayscaleStep))
-        .add_step_boxed(Box::new(BlurStep { sigma: 1.5 }))
+        .add_step_boxed(Box::new(BlurStep {
+            sigma: 1.5,
+            border: BorderMode::Replicate,
+        }))
         .add_step_boxed(Box::new(EdgeDetectionStep {
             low_threshold: 50.0,
             high_threshold: 100.0,
         }))
-        .add_step_boxed(Box::new(ContourDetectionStep { min_area: 10, padding: 10 }))
+        .add_step_boxed(Box::new(ContourDetectionStep {
+            min_area: 10,
+            padding: 10,
+            max_area: None,
+            reject_border: false,
+            connectivity: Connectivity::Eight,
+        }))
         .add_step_boxed(Box::new(CircleFilterStep {
             min_radius: 10.0,
             max_radius: 200.0,
             circularity_threshold: 2.0,
         }))
         .add_step_boxed(Box::new(WhiteCircleFilterStep {
-            brightness_threshold: 200.0,
+            brightness_threshold: Some(200.0),
         }));
 
     println!("Running with executor (work queue)...");
     let start = std::time::Instant::now();
-    let result = pipeline.run_with_executor(img.clone())?;
+    let result = pipeline.run_with_executor(img.clone(), 1)?;
     let executor_time = start.elapsed();
 
     println!("✓ Executor completed in {:?}", executor_time);
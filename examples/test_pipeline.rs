@@ -1,8 +1,13 @@
 use addrslips::Pipeline;
+use addrslips::detection::contours::Connectivity;
+use addrslips::detection::preprocessing::BorderMode;
 use addrslips::detection::steps::*;
 use image::ImageReader;
 
 fn main() -> anyhow::Result<()> {
+    // Show the progress of the verbose pipeline runs
+    addrslips::pipeline::log_to_stderr(log::LevelFilter::Info);
+
     let img = ImageReader::open("image.png")?
         .decode()
         .map_err(|e| anyhow::anyhow!("Failed to decode image: {}", e))?;
@@ -13,19 +18,28 @@ fn main() -> anyhow::Result<()> {
     let mut pipeline = Pipeline::new()
         .with_verbose(true)
         .add_step_boxed(Box::new(GrayscaleStep))
-        .add_step_boxed(Box::new(BlurStep { sigma: 1.5 }))
+        .add_step_boxed(Box::new(BlurStep {
+            sigma: 1.5,
+            border: BorderMode::Replicate,
+        }))
         .add_step_boxed(Box::new(EdgeDetectionStep {
             low_threshold: 50.0,
             high_threshold: 100.0,
         }))
-        .add_step_boxed(Box::new(ContourDetectionStep { min_area: 10, padding: 10 }))
+        .add_step_boxed(Box::new(ContourDetectionStep {
+            min_area: 10,
+            padding: 10,
+            max_area: None,
+            reject_border: false,
+            connectivity: Connectivity::Eight,
+        }))
         .add_step_boxed(Box::new(CircleFilterStep {
             min_radius: 10.0,
             max_radius: 200.0,
             circularity_threshold: 2.0,
         }))
         .add_step_boxed(Box::new(WhiteCircleFilterStep {
-            brightness_threshold: 200.0,
+            brightness_threshold: Some(200.0),
         }));
 
     // Run pipeline without OCR
@@ -56,19 +70,28 @@ fn main() -> anyhow::Result<()> {
     let mut custom_pipeline = Pipeline::new()
         .with_verbose(false)
         .add_step_boxed(Box::new(GrayscaleStep))
-        .add_step_boxed(Box::new(BlurStep { sigma: 2.0 }))  // More blur
+        .add_step_boxed(Box::new(BlurStep {
+            sigma: 2.0,  // More blur
+            border: BorderMode::Replicate,
+        }))
         .add_step_boxed(Box::new(EdgeDetectionStep {
             low_threshold: 60.0,
             high_threshold: 120.0,
         }))
-        .add_step_boxed(Box::new(ContourDetectionStep { min_area: 20, padding: 10 }))
+        .add_step_boxed(Box::new(ContourDetectionStep {
+            min_area: 20,
+            padding: 10,
+            max_area: None,
+            reject_border: false,
+            connectivity: Connectivity::Eight,
+        }))
         .add_step_boxed(Box::new(CircleFilterStep {
             min_radius: 15.0,  // Stricter minimum
             max_radius: 150.0,
             circularity_threshold: 1.5,  // More circular
         }))
         .add_step_boxed(Box::new(WhiteCircleFilterStep {
-            brightness_threshold: 210.0,  // Whiter
+            brightness_threshold: Some(210.0),  // Whiter
         }));
 
     let custom_result = custom_pipeline.run(img)?;
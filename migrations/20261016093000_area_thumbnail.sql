@@ -0,0 +1,8 @@
+-- Cached downscaled copies of the area image, one per requested size
+CREATE TABLE area_thumbnail (
+    area_id INTEGER NOT NULL,
+    max_dim INTEGER NOT NULL,
+    thumbnail_fname TEXT NOT NULL,
+    PRIMARY KEY (area_id, max_dim),
+    FOREIGN KEY (area_id) REFERENCES area(id) ON DELETE CASCADE
+);
@@ -0,0 +1,4 @@
+-- Soft-deleted addresses stay in the table until purged
+ALTER TABLE address ADD COLUMN deleted_at TEXT;
+-- Team the address was assigned to when it was deleted, reattached on restore
+ALTER TABLE address ADD COLUMN deleted_team_id INTEGER REFERENCES team(id) ON DELETE SET NULL;
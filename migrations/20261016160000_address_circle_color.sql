@@ -0,0 +1,2 @@
+-- Color of the detected circle marker, as 0xRRGGBB
+ALTER TABLE address ADD COLUMN circle_color INTEGER CHECK (circle_color BETWEEN 0 AND 16777215);
@@ -0,0 +1,45 @@
+-- no-transaction
+-- A house number only has to be unique among the live addresses of a street, so a
+-- deleted address doesn't block adding the number again. SQLite can't drop the table
+-- constraint, so the table is rebuilt with foreign keys off to keep dependent rows.
+PRAGMA foreign_keys = OFF;
+
+BEGIN;
+
+CREATE TABLE address_new (
+    id INTEGER PRIMARY KEY,
+    street_id INTEGER,
+    area_id INTEGER NOT NULL,
+    house_number TEXT NOT NULL,
+    x INTEGER NOT NULL CHECK (x BETWEEN 0 AND 4294967295),
+    y INTEGER NOT NULL CHECK (y BETWEEN 0 AND 4294967295),
+    circle_radius INTEGER NOT NULL CHECK (circle_radius BETWEEN 0 AND 4294967295),
+    confidence REAL NOT NULL,
+    verified INTEGER NOT NULL DEFAULT 0,
+    estimated_flats INTEGER CHECK (estimated_flats BETWEEN 1 AND 65535),
+    deleted_at TEXT,
+    deleted_team_id INTEGER REFERENCES team(id) ON DELETE SET NULL,
+    circle_color INTEGER CHECK (circle_color BETWEEN 0 AND 16777215),
+    UNIQUE (area_id, id),
+    FOREIGN KEY (area_id) REFERENCES area(id) ON DELETE CASCADE,
+    FOREIGN KEY (street_id, area_id) REFERENCES street(id, area_id) ON DELETE SET NULL
+);
+
+INSERT INTO address_new
+    (id, street_id, area_id, house_number, x, y, circle_radius, confidence, verified,
+     estimated_flats, deleted_at, deleted_team_id, circle_color)
+SELECT
+    id, street_id, area_id, house_number, x, y, circle_radius, confidence, verified,
+    estimated_flats, deleted_at, deleted_team_id, circle_color
+FROM address;
+
+DROP TABLE address;
+ALTER TABLE address_new RENAME TO address;
+
+CREATE INDEX idx_address_street_id ON address(street_id);
+CREATE UNIQUE INDEX idx_address_live_house_number ON address(house_number, street_id)
+    WHERE deleted_at IS NULL;
+
+COMMIT;
+
+PRAGMA foreign_keys = ON;
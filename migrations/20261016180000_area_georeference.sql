@@ -0,0 +1,9 @@
+-- Pixel to lon/lat mapping per area; each area is its own scan with its own pixel grid
+CREATE TABLE area_georeference (
+    area_id INTEGER PRIMARY KEY NOT NULL,
+    origin_lat REAL NOT NULL,
+    origin_lon REAL NOT NULL,
+    meters_per_pixel REAL NOT NULL,
+    rotation REAL NOT NULL,
+    FOREIGN KEY (area_id) REFERENCES area(id) ON DELETE CASCADE
+);
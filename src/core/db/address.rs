@@ -1,8 +1,11 @@
-use std::future::Future;
+use std::{collections::HashMap, future::Future};
 
-use crate::core::db::{model::Point, street::Street};
+use futures::Stream;
 
-#[derive(Debug, Clone)]
+use crate::core::db::{model::{Color, Point}, street::Street};
+use crate::models::HouseNumberDetection;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Address {
     pub id: i64,
     pub area_id: i64,
@@ -13,6 +16,8 @@ pub struct Address {
     pub verified: bool,
     pub estimated_flats: Option<u16>,
     pub assigned_street_id: Option<i64>,
+    /// Color of the detected circle marker, if known
+    pub circle_color: Option<Color>,
     pub(super) _guard: (),
 }
 
@@ -24,6 +29,7 @@ pub struct NewAddress {
     pub estimated_flats: Option<u16>,
     pub assigned_street_id: Option<i64>,
     pub circle_radius: u32,
+    pub circle_color: Option<Color>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -35,13 +41,190 @@ pub struct AddressUpdate<'a> {
     pub verified: Option<bool>,
     pub estimated_flats: Option<Option<u16>>,
     pub street: Option<Option<&'a Street>>,
+    pub circle_color: Option<Option<Color>>,
 }
 
 pub trait AddressRepository {
     fn get_addresses(&self) -> impl Future<Output = anyhow::Result<Vec<Address>>>;
+    /// Same addresses and order as `get_addresses`, fetched a page at a time instead of collected.
+    ///
+    /// A connection is only borrowed while a page is fetched rather than for the stream's whole
+    /// lifetime: a held connection keeps the pool locked, so `save_project` (and autosave) would
+    /// wait for as long as the caller keeps the stream around. The stream doesn't keep the
+    /// project open either: once the project (or the transaction it was created in) is gone, it
+    /// ends with an error.
+    ///
+    /// Pages continue after the last id seen, so it is not a snapshot. No address is yielded
+    /// twice, and every address that stays live for the whole stream is yielded once. Addresses
+    /// added, deleted or edited while streaming are seen as of the page that reaches their id,
+    /// so changes to ids already passed are missed.
+    fn addresses_stream(&self) -> impl Stream<Item = anyhow::Result<Address>> + Send + 'static;
     fn get_address_by_id(&self, id: i64) -> impl Future<Output = anyhow::Result<Option<Address>>>;
     fn get_address_by_street(&self, street: &Street) -> impl Future<Output = anyhow::Result<Vec<Address>>>;
+    /// Addresses whose position lies inside the bounding box (inclusive)
+    fn get_addresses_within_bbox(&self, min: &Point, max: &Point) -> impl Future<Output = anyhow::Result<Vec<Address>>>;
+    /// Addresses whose position lies within `radius` pixels of `center` (inclusive)
+    fn get_addresses_within_radius(&self, center: &Point, radius: u32) -> impl Future<Output = anyhow::Result<Vec<Address>>>;
+    /// Ids of the other addresses within `distance` pixels of address `id` (inclusive), by id
+    fn neighbors_within(&self, id: i64, distance: u32) -> impl Future<Output = anyhow::Result<Vec<i64>>>;
+    /// All pairs `(a, b)` with `a < b` of addresses within `distance` pixels of each other
+    /// (inclusive), sorted. Likely duplicates of one marker; see `merge_addresses`.
+    fn all_duplicate_pairs(&self, distance: u32) -> impl Future<Output = anyhow::Result<Vec<(i64, i64)>>>;
+    /// Positions outside the area image are rejected unless the bounds check was turned off
+    /// with `AreaDb::with_bounds_check`; the same applies to batch inserts and moves.
     fn add_address(&self, address: &NewAddress) -> impl Future<Output = anyhow::Result<Address>>;
+    /// Insert all addresses in one transaction, returned in input order.
+    /// If any insert fails, none of the addresses are stored.
+    fn add_addresses(&self, addresses: &[NewAddress]) -> impl Future<Output = anyhow::Result<Vec<Address>>>;
+    /// Store pipeline detections as unverified addresses without a street, in one transaction,
+    /// then move the area to `AreaState::AddressesDetected`.
+    fn import_detections(&self, detections: &[HouseNumberDetection]) -> impl Future<Output = anyhow::Result<Vec<Address>>>;
+    /// Set `verified` on all listed addresses of this area in one statement.
+    /// Ids outside the area or in the trash are skipped; returns the number of addresses updated.
+    fn set_verified_bulk(&self, ids: &[i64], verified: bool) -> impl Future<Output = anyhow::Result<usize>>;
+    /// Fill in `estimated_flats` from the marker size: a `circle_radius` below `small` means 1 flat,
+    /// above `large` 8 flats, anything in between 4. Addresses that already have an estimate
+    /// are left alone; returns the number of addresses updated.
+    fn estimate_flats_from_radius(&self, small: u32, large: u32) -> impl Future<Output = anyhow::Result<usize>>;
     fn update_address(&self, address: &Address, update: &AddressUpdate) -> impl Future<Output = anyhow::Result<Address>>;
+    /// Insert addresses from CSV with the header `house_number,x,y,confidence,estimated_flats`
+    /// in one transaction. `estimated_flats` may be empty; imported addresses have no street
+    /// and a circle radius of 0.
+    fn import_csv(&self, reader: impl std::io::Read) -> impl Future<Output = anyhow::Result<Vec<Address>>>;
+    /// All addresses as a GeoJSON `FeatureCollection`, in longitude/latitude when the area
+    /// has a georeference and in pixel coordinates otherwise.
+    /// `street_name` is omitted for addresses without a (named) street.
+    fn export_geojson(&self) -> impl Future<Output = anyhow::Result<String>>;
+    /// Move the address to the trash: it disappears from all queries and loses its team
+    /// assignment until restored. It keeps its house number on the street until purged.
     fn delete_address(&self, address: Address) -> impl Future<Output = anyhow::Result<()>>;
-}
\ No newline at end of file
+    /// Addresses in the trash, by id
+    fn get_deleted_addresses(&self) -> impl Future<Output = anyhow::Result<Vec<Address>>>;
+    /// Take an address out of the trash, reattaching it to its team if that still exists
+    fn restore_address(&self, id: i64) -> impl Future<Output = anyhow::Result<Address>>;
+    /// Permanently remove all addresses in the trash, returning how many were removed
+    fn purge_deleted(&self) -> impl Future<Output = anyhow::Result<usize>>;
+    /// Fold the duplicate `remove` into `keep` in one transaction and delete `remove` for good.
+    /// The survivor keeps its house number and radius, takes over the street, flat estimate and
+    /// team assignment where it has none, is verified if either was, and keeps the higher
+    /// confidence. A verified survivor keeps its position, otherwise both positions are averaged.
+    /// Both addresses must belong to this area and must not be on different streets.
+    fn merge_addresses(&self, keep: i64, remove: i64) -> impl Future<Output = anyhow::Result<Address>>;
+}
+
+/// Pairs `(a, b)` with `a < b` of the `(id, x, y)` points within `distance` of each other, sorted
+/// Points are bucketed into a grid of `distance`-sized cells, so each point is only compared
+/// with the points in its own and the eight neighbouring cells instead of with all others.
+pub(super) fn close_pairs(points: &[(i64, i64, i64)], distance: u32) -> Vec<(i64, i64)> {
+    let cell = i64::from(distance.max(1));
+    let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (i, &(_, x, y)) in points.iter().enumerate() {
+        grid.entry((x / cell, y / cell)).or_default().push(i);
+    }
+
+    let max_squared = u128::from(distance).pow(2);
+    let mut pairs = Vec::new();
+    for &(id, x, y) in points {
+        let (cell_x, cell_y) = (x / cell, y / cell);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                let Some(bucket) = grid.get(&(cell_x + dx, cell_y + dy)) else {
+                    continue;
+                };
+                for &j in bucket {
+                    let (other, other_x, other_y) = points[j];
+                    let squared = u128::from(x.abs_diff(other_x)).pow(2) + u128::from(y.abs_diff(other_y)).pow(2);
+                    if id < other && squared <= max_squared {
+                        pairs.push((id, other));
+                    }
+                }
+            }
+        }
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+const CSV_HEADER: [&str; 5] = ["house_number", "x", "y", "confidence", "estimated_flats"];
+
+/// Parse the CSV accepted by `AddressRepository::import_csv`
+/// Errors name the 1-based line number (the header is line 1).
+pub(super) fn parse_csv(mut reader: impl std::io::Read) -> anyhow::Result<Vec<NewAddress>> {
+    let mut content = String::new();
+    reader.read_to_string(&mut content)?;
+    let mut lines = content.lines().enumerate();
+
+    let header: Vec<&str> = match lines.next() {
+        Some((_, header)) => header.split(',').map(str::trim).collect(),
+        None => anyhow::bail!("CSV is empty, expected header {}", CSV_HEADER.join(",")),
+    };
+    if header != CSV_HEADER {
+        anyhow::bail!("line 1: expected header {}, found {}", CSV_HEADER.join(","), header.join(","));
+    }
+
+    let mut addresses = Vec::new();
+    for (index, line) in lines {
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() != CSV_HEADER.len() {
+            anyhow::bail!(
+                "line {}: expected {} columns, found {}",
+                line_number,
+                CSV_HEADER.len(),
+                fields.len()
+            );
+        }
+        if fields[0].is_empty() {
+            anyhow::bail!("line {}: missing house_number", line_number);
+        }
+        let parse_coordinate = |name: &str, value: &str| {
+            value
+                .parse::<u32>()
+                .map_err(|_| anyhow::anyhow!("line {}: invalid {} coordinate '{}'", line_number, name, value))
+        };
+        let confidence = fields[3]
+            .parse::<f64>()
+            .map_err(|_| anyhow::anyhow!("line {}: invalid confidence '{}'", line_number, fields[3]))?;
+        let estimated_flats = match fields[4] {
+            "" => None,
+            value => Some(value.parse::<u16>().map_err(|_| {
+                anyhow::anyhow!("line {}: invalid estimated_flats '{}'", line_number, value)
+            })?),
+        };
+        addresses.push(NewAddress {
+            house_number: fields[0].to_string(),
+            position: Point {
+                x: parse_coordinate("x", fields[1])?,
+                y: parse_coordinate("y", fields[2])?,
+            },
+            confidence,
+            estimated_flats,
+            assigned_street_id: None,
+            circle_radius: 0,
+            circle_color: None,
+        });
+    }
+    Ok(addresses)
+}
+
+/// Map pipeline detections to the addresses `AddressRepository::import_detections` inserts
+pub(super) fn from_detections(detections: &[HouseNumberDetection]) -> Vec<NewAddress> {
+    detections
+        .iter()
+        .map(|detection| NewAddress {
+            house_number: detection.number.clone(),
+            position: Point {
+                x: detection.x,
+                y: detection.y,
+            },
+            confidence: detection.confidence as f64,
+            estimated_flats: None,
+            assigned_street_id: None,
+            circle_radius: detection.radius,
+            circle_color: None,
+        })
+        .collect()
+}
@@ -1,10 +1,12 @@
-use std::{future::Future, path::PathBuf, sync::Arc};
+use std::{future::Future, path::{Path, PathBuf}, sync::Arc};
 
 use image::DynamicImage;
 
-use crate::core::db::{address::AddressRepository, model::Color, street::StreetRepository, team::TeamRepository};
+use crate::core::db::{
+    address::AddressRepository, model::Color, street::StreetRepository, team::TeamRepository, Georeference,
+};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AreaState {
     Imported,
     AddressesDetected,
@@ -17,7 +19,7 @@ pub enum AreaState {
     Complete,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Area {
     pub id: i64,
     pub name: String,
@@ -29,8 +31,12 @@ pub struct Area {
 #[derive(Debug, Clone)]
 pub struct NewArea {
     pub name: String,
-    pub color: Color,
+    /// `None` picks the color most distinct from the existing areas, see
+    /// `AreaRepository::suggest_area_color`.
+    pub color: Option<Color>,
     pub image_path: PathBuf,
+    /// Decode and re-save the image in a compact format instead of copying the file as-is.
+    pub reencode: bool,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -43,7 +49,20 @@ pub struct AreaUpdate {
 pub trait BoundAreaRepository: TeamRepository + StreetRepository + AddressRepository {
     fn get_area(&self) -> impl Future<Output = anyhow::Result<Area>>;
     fn update_area(&self, update: &AreaUpdate) -> impl Future<Output = anyhow::Result<Area>>;
+    /// Move the area one step forward or back in the workflow; any other jump is rejected.
+    /// `update_area` still sets the state unchecked.
+    fn advance_state(&self, to: AreaState) -> impl Future<Output = anyhow::Result<Area>>;
     fn get_image(&self) -> &DynamicImage;
+    /// Swap the area's image for the file at `new_path`, keeping all addresses and streets.
+    /// The old image file is removed unless another area still references it.
+    fn replace_image(&mut self, new_path: &Path) -> impl Future<Output = anyhow::Result<()>>;
+    /// Pixel to longitude/latitude mapping for this area's image, if one was set.
+    fn get_georeference(&self) -> impl Future<Output = anyhow::Result<Option<Georeference>>>;
+    /// Store the georeference; `None` removes it so exports fall back to pixel coordinates.
+    fn set_georeference(&self, georeference: Option<&Georeference>) -> impl Future<Output = anyhow::Result<()>>;
+    /// Downscaled copy of the area image whose longest side is at most `max_dim`.
+    /// Each size is cached separately in the project and only generated when missing.
+    fn get_thumbnail(&self, max_dim: u32) -> impl Future<Output = anyhow::Result<DynamicImage>>;
     fn delete(self) -> impl Future<Output = anyhow::Result<()>>;
 }
 
@@ -52,6 +71,27 @@ pub trait AreaRepository: 'static {
     fn get_area_repo(&self, id: i64) -> impl Future<Output = anyhow::Result<Self::Repository>> + 'static;
     fn add_area(&self, area: NewArea) -> impl Future<Output = anyhow::Result<Self::Repository>>;
     fn get_areas(&self) -> impl Future<Output = anyhow::Result<Vec<Area>>>;
+    /// Color for a new area that is as distinct as possible from the existing areas' colors,
+    /// see `Color::most_distinct`.
+    fn suggest_area_color(&self) -> impl Future<Output = anyhow::Result<Color>>;
+    /// Copy the area with its image, streets, addresses and teams under `new_name`.
+    /// The copy starts over as `Imported`; soft-deleted addresses are left behind.
+    fn duplicate_area(&self, id: i64, new_name: &str) -> impl Future<Output = anyhow::Result<Self::Repository>>;
+    /// Save the project only if something changed since the last save.
+    /// Returns whether the archive was written.
+    fn save_if_dirty(&self) -> impl Future<Output = anyhow::Result<bool>>;
+}
+
+impl AreaState {
+    /// The following workflow step, `None` once complete
+    pub fn next(self) -> Option<AreaState> {
+        AreaState::try_from(i64::from(self) + 1).ok()
+    }
+
+    /// The preceding workflow step, `None` for a freshly imported area
+    pub fn previous(self) -> Option<AreaState> {
+        AreaState::try_from(i64::from(self) - 1).ok()
+    }
 }
 
 impl TryFrom<i64> for AreaState {
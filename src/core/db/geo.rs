@@ -0,0 +1,47 @@
+use crate::core::db::model::Point;
+
+/// Mean Earth radius in meters
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// Maps image pixels to WGS84 longitude/latitude for an area's map scan.
+///
+/// Uses a local equirectangular approximation around the origin, which is accurate to well
+/// under a meter over the few kilometers a canvassing map covers.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Georeference {
+    /// Latitude of pixel (0, 0) in degrees
+    pub origin_lat: f64,
+    /// Longitude of pixel (0, 0) in degrees
+    pub origin_lon: f64,
+    pub meters_per_pixel: f64,
+    /// Clockwise angle in degrees from north to the image's up direction
+    pub rotation: f64,
+}
+
+impl Georeference {
+    /// `(longitude, latitude)` in degrees of the pixel `p`
+    pub fn pixel_to_lonlat(&self, p: &Point) -> (f64, f64) {
+        // Image y grows downwards, so up in the image is -y
+        let right = p.x as f64 * self.meters_per_pixel;
+        let up = -(p.y as f64) * self.meters_per_pixel;
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let east = right * cos + up * sin;
+        let north = -right * sin + up * cos;
+
+        let lat = self.origin_lat + (north / EARTH_RADIUS).to_degrees();
+        let lon = self.origin_lon + (east / (EARTH_RADIUS * self.origin_lat.to_radians().cos())).to_degrees();
+        (lon, lat)
+    }
+
+    /// Pixel position `(x, y)` of a longitude/latitude in degrees; may be fractional or
+    /// outside the image
+    pub fn lonlat_to_pixel(&self, lon: f64, lat: f64) -> (f64, f64) {
+        let north = (lat - self.origin_lat).to_radians() * EARTH_RADIUS;
+        let east = (lon - self.origin_lon).to_radians() * EARTH_RADIUS * self.origin_lat.to_radians().cos();
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let right = east * cos - north * sin;
+        let up = east * sin + north * cos;
+
+        (right / self.meters_per_pixel, -up / self.meters_per_pixel)
+    }
+}
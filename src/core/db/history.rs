@@ -0,0 +1,180 @@
+use std::collections::VecDeque;
+
+use crate::core::db::{
+    address::{Address, AddressRepository, AddressUpdate, NewAddress},
+    street::StreetRepository,
+};
+
+/// Default number of edits kept by `EditHistory::default()`
+pub const DEFAULT_HISTORY_DEPTH: usize = 100;
+
+#[derive(Debug, Clone)]
+enum Edit {
+    Insert(Address),
+    Update { before: Address, after: Address },
+    Delete(Address),
+}
+
+/// In-memory undo/redo stack for address edits in one area.
+///
+/// Edits go through the wrapper methods, which call the repository and record how to
+/// reverse them; `undo` and `redo` replay the inverse through the same repository methods.
+/// Deletes are soft, so undoing a delete restores the same address from the trash and
+/// redoing an insert restores it again. Always pass the repository of the area the history
+/// belongs to.
+#[derive(Debug, Clone)]
+pub struct EditHistory {
+    undo: VecDeque<Edit>,
+    redo: Vec<Edit>,
+    max_depth: usize,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_DEPTH)
+    }
+}
+
+impl EditHistory {
+    /// Keep at most `max_depth` edits; older ones are forgotten
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            max_depth,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    pub async fn add_address<R: AddressRepository>(
+        &mut self,
+        repo: &R,
+        address: &NewAddress,
+    ) -> anyhow::Result<Address> {
+        let inserted = repo.add_address(address).await?;
+        self.record(Edit::Insert(inserted.clone()));
+        Ok(inserted)
+    }
+
+    pub async fn update_address<R: AddressRepository>(
+        &mut self,
+        repo: &R,
+        address: &Address,
+        update: &AddressUpdate<'_>,
+    ) -> anyhow::Result<Address> {
+        let updated = repo.update_address(address, update).await?;
+        self.record(Edit::Update {
+            before: address.clone(),
+            after: updated.clone(),
+        });
+        Ok(updated)
+    }
+
+    pub async fn delete_address<R: AddressRepository>(&mut self, repo: &R, address: Address) -> anyhow::Result<()> {
+        let deleted = address.clone();
+        repo.delete_address(address).await?;
+        self.record(Edit::Delete(deleted));
+        Ok(())
+    }
+
+    /// Reverse the most recent edit. Returns `false` when there is nothing to undo.
+    /// A failed undo leaves the edit on the undo stack.
+    pub async fn undo<R: AddressRepository + StreetRepository>(&mut self, repo: &R) -> anyhow::Result<bool> {
+        let Some(edit) = self.undo.pop_back() else {
+            return Ok(false);
+        };
+        let result = match &edit {
+            Edit::Insert(address) => remove(repo, address).await,
+            Edit::Update { before, .. } => restore(repo, before).await,
+            Edit::Delete(address) => repo.restore_address(address.id).await.map(|_| ()),
+        };
+        match result {
+            Ok(()) => {
+                self.redo.push(edit);
+                Ok(true)
+            }
+            Err(e) => {
+                self.undo.push_back(edit);
+                Err(e)
+            }
+        }
+    }
+
+    /// Re-apply the most recently undone edit. Returns `false` when there is nothing to redo.
+    /// A failed redo leaves the edit on the redo stack.
+    pub async fn redo<R: AddressRepository + StreetRepository>(&mut self, repo: &R) -> anyhow::Result<bool> {
+        let Some(edit) = self.redo.pop() else {
+            return Ok(false);
+        };
+        let result = match &edit {
+            Edit::Insert(address) => repo.restore_address(address.id).await.map(|_| ()),
+            Edit::Update { after, .. } => restore(repo, after).await,
+            Edit::Delete(address) => remove(repo, address).await,
+        };
+        match result {
+            Ok(()) => {
+                self.undo.push_back(edit);
+                Ok(true)
+            }
+            Err(e) => {
+                self.redo.push(edit);
+                Err(e)
+            }
+        }
+    }
+
+    fn record(&mut self, edit: Edit) {
+        self.redo.clear();
+        self.undo.push_back(edit);
+        while self.undo.len() > self.max_depth {
+            self.undo.pop_front();
+        }
+    }
+}
+
+async fn current<R: AddressRepository>(repo: &R, id: i64) -> anyhow::Result<Address> {
+    repo.get_address_by_id(id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Address with id {} no longer exists", id))
+}
+
+async fn remove<R: AddressRepository>(repo: &R, address: &Address) -> anyhow::Result<()> {
+    repo.delete_address(current(repo, address.id).await?).await
+}
+
+/// Set every field of the stored address back to `target`
+async fn restore<R: AddressRepository + StreetRepository>(repo: &R, target: &Address) -> anyhow::Result<()> {
+    let address = current(repo, target.id).await?;
+    let street = match target.assigned_street_id {
+        Some(id) => Some(
+            repo.get_street_by_id(id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Street with id {} no longer exists", id))?,
+        ),
+        None => None,
+    };
+    let update = AddressUpdate {
+        house_number: Some(target.house_number.clone()),
+        circle_radius: Some(target.circle_radius),
+        position: Some(target.position),
+        confidence: Some(target.confidence),
+        verified: Some(target.verified),
+        estimated_flats: Some(target.estimated_flats),
+        street: Some(street.as_ref()),
+        circle_color: Some(target.circle_color),
+    };
+    repo.update_address(&address, &update).await?;
+    Ok(())
+}
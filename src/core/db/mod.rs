@@ -1,49 +1,382 @@
 mod address;
 mod area;
+mod geo;
+mod history;
 mod model;
 mod project;
+mod snapshot;
 mod state;
 mod street;
 mod team;
+pub mod util;
 
-use std::{ops::Deref, path::Path, sync::Arc};
+use std::{collections::BTreeMap, future::Future, ops::Deref, path::Path, sync::Arc, time::Duration};
 
 use anyhow::Ok;
-use image::DynamicImage;
+use image::{DynamicImage, GenericImageView};
+use anyhow::Context;
+use crate::models::HouseNumberDetection;
+use crate::DetectionParams;
+use sha2::{Digest, Sha256};
 use sqlx::Connection;
-use state::ProjectState;
+use state::{ProjectState, StateRef};
 use time::OffsetDateTime;
 
 pub use address::{Address, AddressRepository, AddressUpdate, NewAddress};
 pub use area::{Area, AreaRepository, AreaState, AreaUpdate, BoundAreaRepository, NewArea};
-pub use model::{Color, Point};
-pub use project::{ProjectRepository, UpdateProjectSettings};
+pub use geo::Georeference;
+pub use history::{EditHistory, DEFAULT_HISTORY_DEPTH};
+pub use model::{polygon_contains, Color, Point, AREA_PALETTE};
+pub use project::{
+    AddressStats, AreaStats, ProjectRepository, ProjectStats, UpdateProjectSettings, RESERVED_METADATA_KEYS,
+};
+pub use snapshot::{ProjectDiff, ProjectSnapshot, RecordDiff};
 pub use street::{Street, StreetPolyline, StreetRepository, StreetUpdate};
 pub use team::{Team, TeamAddress, TeamBounds, TeamRepository};
 
 #[derive(Debug)]
 pub struct ProjectDb {
-    state: Arc<ProjectState>,
+    state: StateRef,
 }
 
 impl ProjectDb {
     pub async fn new<P: AsRef<Path>>(project_file: P) -> anyhow::Result<Self> {
         Ok(Self {
-            state: Arc::new(ProjectState::new(project_file).await?),
+            state: StateRef::new(ProjectState::new(project_file).await?),
         })
     }
 
+    /// Open an existing project; unlike [`ProjectDb::new`], a missing file is an error
+    /// instead of being created.
+    pub async fn open<P: AsRef<Path>>(project_file: P) -> anyhow::Result<Self> {
+        let project_file = project_file.as_ref();
+        if !project_file.is_file() {
+            anyhow::bail!("Project file {:?} does not exist", project_file);
+        }
+        Self::new(project_file).await
+    }
+
+    /// Remove all cached detection results, see [`AreaDb::detect_house_numbers`].
+    pub async fn clear_detection_cache(&self) -> anyhow::Result<()> {
+        self.state.clear_detection_cache().await
+    }
+
+    /// Detection runs answered from the cache since the project was opened.
+    pub fn detection_cache_hits(&self) -> usize {
+        self.state.detection_cache_hits()
+    }
+
     /// Explicitly save the project to disk.
-    /// This is required when dropping in an async context (e.g., tests with #[tokio::test]).
+    /// Preferred over relying on save-on-drop, which can only log failures.
     pub async fn save_project(&self) -> anyhow::Result<()> {
         self.state.save_project().await
     }
+
+    /// Run `f` inside one database transaction.
+    ///
+    /// `f` gets a handle to this project bound to the transaction; everything done through it
+    /// or through area repositories obtained from it is committed if `f` returns `Ok` and rolled
+    /// back if it returns `Err`. Handles must not outlive `f`.
+    ///
+    /// The transaction keeps one pooled connection and the pool read lock until it finishes,
+    /// so `save_project` (which takes the write lock) waits for it; saving from inside `f`
+    /// is an error. Queries through the transaction-bound handles are serialized on its
+    /// connection, while other handles keep using the pool but block on SQLite's write lock
+    /// when they try to write.
+    pub async fn transaction<F, Fut, T>(&self, f: F) -> anyhow::Result<T>
+    where
+        F: FnOnce(ProjectDb) -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        if self.state.in_transaction() {
+            anyhow::bail!("Nested transactions are not supported");
+        }
+        let (_pool_guard, tx) = self.state.begin().await?;
+        let tx = Arc::new(tokio::sync::Mutex::new(tx));
+        let result = f(ProjectDb {
+            state: self.state.with_transaction(tx.clone()),
+        })
+        .await;
+
+        // Dropping the transaction without committing rolls it back
+        let tx = Arc::try_unwrap(tx)
+            .map_err(|_| anyhow::anyhow!("Repository handle outlived its transaction"))?
+            .into_inner();
+        match result {
+            Result::Ok(value) => {
+                tx.commit().await?;
+                Ok(value)
+            }
+            Err(e) => {
+                tx.rollback().await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Compact the database by running `PRAGMA optimize` and `VACUUM`.
+    /// Worth calling before `save_project` after many deletions, as free pages
+    /// otherwise end up in the archive. Waits for all running queries to finish.
+    pub async fn optimize(&self) -> anyhow::Result<()> {
+        self.state.optimize().await
+    }
+
+    /// Spawn a background task calling `save_if_dirty` every `interval`.
+    /// The task only holds a weak reference and stops once the project is dropped;
+    /// abort the returned handle to stop it earlier. Save errors are logged.
+    pub fn autosave_every(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let state = self.state.downgrade();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            // The first tick completes immediately
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(state) = StateRef::upgrade(&state) else {
+                    break;
+                };
+                let project = ProjectDb { state };
+                if let Err(e) = project.save_if_dirty().await {
+                    log::warn!("Autosave failed: {e}");
+                }
+            }
+        })
+    }
+
+    /// Capture every area, address, street and team for later comparison.
+    /// Intended for tests and debugging; all tables are read on one connection.
+    pub async fn snapshot(&self) -> anyhow::Result<ProjectSnapshot> {
+        let mut conn = self.state.conn_readonly().await?;
+        let areas: BTreeMap<i64, Area> = sqlx::query!(
+            r#"SELECT id as "id!: i64", name, color, state FROM area ORDER BY id ASC"#
+        )
+        .fetch_all(&mut **conn)
+        .await?
+        .into_iter()
+        .map(|record| {
+            let color = Color::try_from(record.color)?;
+            let state = AreaState::try_from(record.state)?;
+            Ok((
+                record.id,
+                Area {
+                    id: record.id,
+                    name: record.name,
+                    color,
+                    state,
+                    _guard: (),
+                },
+            ))
+        })
+        .collect::<anyhow::Result<_>>()?;
+        let addresses: BTreeMap<i64, Address> = sqlx::query!(
+            r#"SELECT
+                id as "id!: i64",
+                area_id as "area_id!: i64",
+                house_number,
+                circle_radius as "circle_radius!: u32",
+                x,
+                y,
+                confidence,
+                verified,
+                estimated_flats,
+                circle_color,
+                street_id as "assigned_street_id"
+            FROM address
+            WHERE deleted_at IS NULL
+            ORDER BY id ASC"#
+        )
+        .fetch_all(&mut **conn)
+        .await?
+        .into_iter()
+        .map(|record| {
+            Ok((
+                record.id,
+                Address {
+                    id: record.id,
+                    area_id: record.area_id,
+                    house_number: record.house_number,
+                    circle_radius: record.circle_radius,
+                    position: Point {
+                        x: record
+                            .x
+                            .try_into()
+                            .expect("x coordinate bounded by database constraint"),
+                        y: record
+                            .y
+                            .try_into()
+                            .expect("y coordinate bounded by database constraint"),
+                    },
+                    confidence: record.confidence,
+                    verified: record.verified != 0,
+                    estimated_flats: record.estimated_flats.map(|v| v as u16),
+                    circle_color: record
+                        .circle_color
+                        .map(Color::try_from)
+                        .transpose()?,
+                    assigned_street_id: record.assigned_street_id,
+                    _guard: (),
+                },
+            ))
+        })
+        .collect::<anyhow::Result<_>>()?;
+        let streets: BTreeMap<i64, Street> = sqlx::query!(
+            r#"SELECT id as "id!: i64", name, verified FROM street ORDER BY id ASC"#
+        )
+        .fetch_all(&mut **conn)
+        .await?
+        .into_iter()
+        .map(|record| {
+            (
+                record.id,
+                Street {
+                    id: record.id,
+                    name: record.name,
+                    verified: record.verified != 0,
+                    _guard: (),
+                },
+            )
+        })
+        .collect();
+        let teams: BTreeMap<i64, Team> = sqlx::query!(
+            r#"SELECT id as "id!: i64", num FROM team ORDER BY id ASC"#
+        )
+        .fetch_all(&mut **conn)
+        .await?
+        .into_iter()
+        .map(|record| {
+            (
+                record.id,
+                Team {
+                    id: record.id,
+                    number: record.num as u16,
+                    _guard: (),
+                },
+            )
+        })
+        .collect();
+        Ok(ProjectSnapshot {
+            areas,
+            addresses,
+            streets,
+            teams,
+        })
+    }
 }
 
 pub struct AreaDb {
-    state: Arc<ProjectState>,
+    state: StateRef,
     area_id: i64,
     image: DynamicImage,
+    /// Reject address positions outside `image`, see [`AreaDb::with_bounds_check`]
+    check_bounds: bool,
+}
+
+impl AreaDb {
+    /// Turn the check that added and moved addresses lie inside the area image on or off.
+    /// It is on by default; turn it off to import addresses from external sources whose
+    /// positions may not match the image.
+    pub fn with_bounds_check(mut self, enabled: bool) -> Self {
+        self.check_bounds = enabled;
+        self
+    }
+
+    /// Detect house numbers in the area image, see [`crate::detect_house_numbers`].
+    /// Results are cached in the project, keyed by a hash of the image pixels and `params`,
+    /// so re-running with the same image and parameters returns immediately. The area's
+    /// entries are dropped when its image is replaced or the area is deleted.
+    /// The detections are not imported; use `import_detections` for that.
+    pub async fn detect_house_numbers(&self, params: &DetectionParams) -> anyhow::Result<Vec<HouseNumberDetection>> {
+        let image = self.image.clone();
+        let params = params.clone();
+        let (key, image, params) = tokio::task::spawn_blocking(move || {
+            let key = detection_cache_key(&image, &params);
+            (key, image, params)
+        })
+        .await
+        .context("Detection cache key task failed")?;
+        let key = key?;
+
+        if let Some(detections) = self.state.load_cached_detections(self.area_id, &key).await? {
+            return Ok(detections);
+        }
+
+        let detections = tokio::task::spawn_blocking(move || crate::detect_house_numbers_in_image(image, &params))
+            .await
+            .context("Detection task failed")??;
+        self.state.store_cached_detections(self.area_id, &key, &detections).await?;
+        Ok(detections)
+    }
+
+    fn ensure_in_bounds(&self, position: &Point) -> anyhow::Result<()> {
+        let (width, height) = self.image.dimensions();
+        if self.check_bounds && (position.x >= width || position.y >= height) {
+            anyhow::bail!(
+                "Position ({}, {}) lies outside the {}x{} image of area {}",
+                position.x,
+                position.y,
+                width,
+                height,
+                self.area_id
+            );
+        }
+        Ok(())
+    }
+
+    /// Histogram of the street's addresses along its polyline.
+    ///
+    /// Each address is projected onto the polyline and the resulting arc
+    /// lengths are split into `bins` equally long segments, from the first
+    /// vertex to the last.
+    pub async fn street_density(&self, street: &Street, bins: usize) -> anyhow::Result<Vec<usize>> {
+        if bins == 0 {
+            anyhow::bail!("Number of bins must be positive");
+        }
+        let polyline = self
+            .get_street_polyline(street)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Street with id {} has no polyline", street.id))?;
+        let length = util::polyline_length(&polyline.points);
+        let mut counts = vec![0; bins];
+        for address in self.get_address_by_street(street).await? {
+            let Some(projection) = util::project_onto_polyline(&address.position, &polyline.points)
+            else {
+                continue;
+            };
+            let bin = if length > 0.0 {
+                ((projection.arc_length / length) * bins as f64) as usize
+            } else {
+                0
+            };
+            counts[bin.min(bins - 1)] += 1;
+        }
+        Ok(counts)
+    }
+}
+
+async fn load_georeference(conn: &mut sqlx::SqliteConnection, area_id: i64) -> anyhow::Result<Option<Georeference>> {
+    Ok(sqlx::query!(
+        r#"SELECT origin_lat, origin_lon, meters_per_pixel, rotation FROM area_georeference WHERE area_id = $1"#,
+        area_id
+    )
+    .fetch_optional(&mut *conn)
+    .await?
+    .map(|record| Georeference {
+        origin_lat: record.origin_lat,
+        origin_lon: record.origin_lon,
+        meters_per_pixel: record.meters_per_pixel,
+        rotation: record.rotation,
+    }))
+}
+
+/// Hex SHA-256 of the image pixels and the serialized detection parameters
+fn detection_cache_key(image: &DynamicImage, params: &DetectionParams) -> anyhow::Result<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(image.width().to_le_bytes());
+    hasher.update(image.height().to_le_bytes());
+    hasher.update(format!("{:?}", image.color()).as_bytes());
+    hasher.update(image.as_bytes());
+    hasher.update(serde_json::to_vec(params)?);
+    Ok(format!("{:x}", hasher.finalize()))
 }
 
 impl std::fmt::Debug for AreaDb {
@@ -57,7 +390,7 @@ impl std::fmt::Debug for AreaDb {
 
 impl ProjectRepository for ProjectDb {
     async fn get_project_name(&self) -> anyhow::Result<String> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_readonly().await?;
         let name = sqlx::query!(r#"SELECT value FROM project_metadata WHERE key = 'name'"#)
             .fetch_one(&mut **conn)
             .await?
@@ -66,7 +399,7 @@ impl ProjectRepository for ProjectDb {
     }
 
     async fn get_project_created_at(&self) -> anyhow::Result<OffsetDateTime> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_readonly().await?;
         let created_at_str =
             sqlx::query!(r#"SELECT value FROM project_metadata WHERE key = 'created_at'"#)
                 .fetch_one(&mut **conn)
@@ -80,7 +413,7 @@ impl ProjectRepository for ProjectDb {
     }
 
     async fn get_target_address_count(&self) -> anyhow::Result<u64> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_readonly().await?;
 
         let value = sqlx::query!(
             r#"SELECT value FROM project_metadata WHERE key = 'target_address_count'"#
@@ -96,7 +429,7 @@ impl ProjectRepository for ProjectDb {
         &self,
         settings: project::UpdateProjectSettings,
     ) -> anyhow::Result<()> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_mut().await?;
         let mut items = vec![];
         if let Some(name) = settings.name {
             items.push(("name", name));
@@ -122,6 +455,73 @@ impl ProjectRepository for ProjectDb {
         }
         Ok(())
     }
+
+    async fn get_metadata(&self, key: &str) -> anyhow::Result<Option<String>> {
+        let mut conn = self.state.conn_readonly().await?;
+        let value = sqlx::query!(r#"SELECT value FROM project_metadata WHERE key = $1"#, key)
+            .fetch_optional(&mut **conn)
+            .await?
+            .map(|record| record.value);
+        Ok(value)
+    }
+
+    async fn set_metadata(&self, key: &str, value: &str) -> anyhow::Result<()> {
+        if project::RESERVED_METADATA_KEYS.contains(&key) {
+            anyhow::bail!("Metadata key '{}' is reserved; use its dedicated setter instead", key);
+        }
+        let mut conn = self.state.conn_mut().await?;
+        sqlx::query!(
+            r#"INSERT INTO project_metadata (key, value) VALUES ($1, $2)
+            ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value"#,
+            key,
+            value
+        )
+        .execute(&mut **conn)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_statistics(&self) -> anyhow::Result<project::ProjectStats> {
+        let mut conn = self.state.conn_readonly().await?;
+        let areas: Vec<project::AreaStats> = sqlx::query!(
+            r#"SELECT
+                area.id as "area_id!: i64",
+                area.name,
+                COUNT(address.id) as "total!: i64",
+                COALESCE(SUM(address.verified != 0), 0) as "verified!: i64",
+                COUNT(team_assignment.address_id) as "assigned_to_team!: i64",
+                COALESCE(SUM(address.estimated_flats), 0) as "estimated_flats!: i64"
+            FROM area
+            LEFT JOIN address ON address.area_id = area.id AND address.deleted_at IS NULL
+            LEFT JOIN team_assignment ON team_assignment.address_id = address.id
+            GROUP BY area.id
+            ORDER BY area.id ASC"#
+        )
+        .fetch_all(&mut **conn)
+        .await?
+        .into_iter()
+        .map(|record| project::AreaStats {
+            area_id: record.area_id,
+            name: record.name,
+            addresses: project::AddressStats {
+                total: record.total as u64,
+                verified: record.verified as u64,
+                assigned_to_team: record.assigned_to_team as u64,
+                estimated_flats: record.estimated_flats as u64,
+            },
+        })
+        .collect();
+
+        let totals = areas.iter().fold(project::AddressStats::default(), |acc, area| {
+            project::AddressStats {
+                total: acc.total + area.addresses.total,
+                verified: acc.verified + area.addresses.verified,
+                assigned_to_team: acc.assigned_to_team + area.addresses.assigned_to_team,
+                estimated_flats: acc.estimated_flats + area.addresses.estimated_flats,
+            }
+        });
+        Ok(project::ProjectStats { totals, areas })
+    }
 }
 
 impl AreaRepository for ProjectDb {
@@ -133,16 +533,18 @@ impl AreaRepository for ProjectDb {
     ) -> impl std::future::Future<Output = anyhow::Result<Self::Repository>> + 'static {
         let state = self.state.clone();
         async move {
-            let mut conn = state.conn().await?;
+            let mut conn = state.conn_readonly().await?;
             let image_fname = sqlx::query!("SELECT image_fname FROM area WHERE id = $1", id)
-                .fetch_one(&mut **conn)
+                .fetch_optional(&mut **conn)
                 .await?
+                .ok_or_else(|| anyhow::anyhow!("Area with id {} not found", id))?
                 .image_fname;
             let image = state.load_area_image(&image_fname).await?;
             Ok(AreaDb {
                 state: state.clone(),
                 area_id: id,
                 image,
+                check_bounds: true,
             })
         }
     }
@@ -153,9 +555,13 @@ impl AreaRepository for ProjectDb {
     ) -> impl std::future::Future<Output = anyhow::Result<Self::Repository>> + 'static {
         let state = self.state.clone();
         async move {
-            let mut conn = state.conn().await?;
-            let image_fname = state.store_area_image(&area.image_path).await?;
-            let color_int = i64::from(area.color);
+            let mut conn = state.conn_mut().await?;
+            let image_fname = state.store_area_image(&area.image_path, area.reencode).await?;
+            let color = match area.color {
+                Some(color) => color,
+                None => most_distinct_area_color(&mut conn).await?,
+            };
+            let color_int = i64::from(color);
             let initial_state = i64::from(AreaState::Imported);
             let area_id = sqlx::query!(
                 "INSERT INTO area (name, color, image_fname, state) VALUES ($1, $2, $3, $4) RETURNING id",
@@ -172,12 +578,13 @@ impl AreaRepository for ProjectDb {
                 state: state.clone(),
                 area_id,
                 image,
+                check_bounds: true,
             })
         }
     }
 
     async fn get_areas(&self) -> anyhow::Result<Vec<Area>> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_readonly().await?;
         sqlx::query!(r#"SELECT id as "id!: i64", name, color, state FROM area ORDER BY id ASC;"#)
             .fetch_all(&mut **conn)
             .await?
@@ -195,11 +602,297 @@ impl AreaRepository for ProjectDb {
             })
             .collect()
     }
+
+    async fn suggest_area_color(&self) -> anyhow::Result<Color> {
+        let mut conn = self.state.conn_readonly().await?;
+        most_distinct_area_color(&mut conn).await
+    }
+
+    async fn duplicate_area(&self, id: i64, new_name: &str) -> anyhow::Result<AreaDb> {
+        let area = {
+            let mut conn = self.state.conn_readonly().await?;
+            sqlx::query!("SELECT color, image_fname FROM area WHERE id = $1", id)
+                .fetch_optional(&mut **conn)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Area with id {} not found", id))?
+        };
+
+        // Copy the image before taking a connection, so the file IO doesn't hold up other writers
+        let image_fname = self.state.copy_area_image(&area.image_fname).await?;
+        let copied = {
+            let mut conn = self.state.conn_mut().await?;
+            copy_area_rows(&mut **conn, id, new_name, area.color, &image_fname).await
+        };
+        let new_id = match copied {
+            Result::Ok(new_id) => new_id,
+            Err(e) => {
+                self.state.delete_area_image(&image_fname).await?;
+                return Err(e);
+            }
+        };
+
+        let image = self.state.load_area_image(&image_fname).await?;
+        Ok(AreaDb {
+            state: self.state.clone(),
+            area_id: new_id,
+            image,
+            check_bounds: true,
+        })
+    }
+
+    async fn save_if_dirty(&self) -> anyhow::Result<bool> {
+        if !self.state.is_dirty() {
+            return Ok(false);
+        }
+        self.state.save_project().await?;
+        Ok(true)
+    }
+}
+
+/// `Color::most_distinct` from the colors of all areas
+async fn most_distinct_area_color(conn: &mut sqlx::SqliteConnection) -> anyhow::Result<Color> {
+    let existing = sqlx::query!(r#"SELECT color FROM area"#)
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .map(|record| Color::try_from(record.color))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(Color::most_distinct(&existing))
+}
+
+/// Insert `addresses` into area `area_id`, returning them as stored.
+/// Callers run this inside a transaction so a failed row leaves nothing behind.
+async fn insert_addresses(
+    conn: &mut sqlx::SqliteConnection,
+    area_id: i64,
+    addresses: &[address::NewAddress],
+) -> anyhow::Result<Vec<Address>> {
+    let mut inserted = Vec::with_capacity(addresses.len());
+    for address in addresses {
+        let estimated_flats = address.estimated_flats.map(|v| v as i64);
+        let circle_color = address.circle_color.map(i64::from);
+        let record = sqlx::query!(
+            r#"INSERT INTO address
+            (area_id, house_number, x, y, confidence, circle_radius, estimated_flats, street_id, circle_color)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING
+                id as "id!: i64",
+                area_id as "area_id!: i64",
+                circle_radius as "circle_radius!: u32",
+                house_number,
+                x,
+                y,
+                confidence,
+                verified,
+                estimated_flats,
+                circle_color,
+                street_id as "assigned_street_id""#,
+            area_id,
+            address.house_number,
+            address.position.x,
+            address.position.y,
+            address.confidence,
+            address.circle_radius,
+            estimated_flats,
+            address.assigned_street_id,
+            circle_color
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        inserted.push(Address {
+            id: record.id,
+            area_id: record.area_id,
+            house_number: record.house_number,
+            position: Point {
+                x: record
+                    .x
+                    .try_into()
+                    .expect("x coordinate bounded by database constraint"),
+                y: record
+                    .y
+                    .try_into()
+                    .expect("y coordinate bounded by database constraint"),
+            },
+            confidence: record.confidence,
+            verified: record.verified != 0,
+            estimated_flats: record.estimated_flats.map(|v| v as u16),
+            circle_color: record
+                .circle_color
+                .map(Color::try_from)
+                .transpose()?,
+            assigned_street_id: record.assigned_street_id,
+            circle_radius: record.circle_radius,
+            _guard: (),
+        });
+    }
+    Ok(inserted)
+}
+
+/// Point area `area_id` at `new_fname` and forget its cached thumbnails, which show the
+/// old image. Returns the old image filename and the thumbnail files to remove.
+async fn replace_image_rows(
+    conn: &mut sqlx::SqliteConnection,
+    area_id: i64,
+    new_fname: &str,
+) -> anyhow::Result<(String, Vec<String>)> {
+    let old_fname = sqlx::query!("SELECT image_fname FROM area WHERE id = $1", area_id)
+        .fetch_one(&mut *conn)
+        .await?
+        .image_fname;
+    let old_thumbnails = sqlx::query!(
+        "DELETE FROM area_thumbnail WHERE area_id = $1 RETURNING thumbnail_fname",
+        area_id
+    )
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(|record| record.thumbnail_fname)
+    .collect();
+    sqlx::query!("UPDATE area SET image_fname = $1 WHERE id = $2", new_fname, area_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok((old_fname, old_thumbnails))
+}
+
+/// Insert a copy of area `id` and everything in it, returning the new area's id.
+/// Runs in its own transaction, so a failure leaves no half-copied area behind.
+async fn copy_area_rows(
+    conn: &mut sqlx::SqliteConnection,
+    id: i64,
+    new_name: &str,
+    color: i64,
+    image_fname: &str,
+) -> anyhow::Result<i64> {
+    let mut tx = conn.begin().await?;
+    let initial_state = i64::from(AreaState::Imported);
+    let new_id = sqlx::query!(
+        "INSERT INTO area (name, color, image_fname, state) VALUES ($1, $2, $3, $4) RETURNING id",
+        new_name,
+        color,
+        image_fname,
+        initial_state
+    )
+    .fetch_one(&mut *tx)
+    .await?
+    .id;
+
+    let mut street_ids = std::collections::HashMap::new();
+    let streets = sqlx::query!(
+        r#"SELECT id as "id!: i64", name, verified FROM street WHERE area_id = $1 ORDER BY id"#,
+        id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    for street in streets {
+        let new_street_id = sqlx::query!(
+            r#"INSERT INTO street (area_id, name, verified) VALUES ($1, $2, $3) RETURNING id as "id!: i64""#,
+            new_id,
+            street.name,
+            street.verified
+        )
+        .fetch_one(&mut *tx)
+        .await?
+        .id;
+        sqlx::query!(
+            r#"INSERT INTO street_polyline_vertices (street_id, position, x, y)
+            SELECT $1, position, x, y FROM street_polyline_vertices WHERE street_id = $2"#,
+            new_street_id,
+            street.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        street_ids.insert(street.id, new_street_id);
+    }
+
+    let mut team_ids = std::collections::HashMap::new();
+    let teams = sqlx::query!(
+        r#"SELECT id as "id!: i64", num FROM team WHERE area_id = $1 ORDER BY id"#,
+        id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    for team in teams {
+        let new_team_id = sqlx::query!(
+            r#"INSERT INTO team (area_id, num) VALUES ($1, $2) RETURNING id as "id!: i64""#,
+            new_id,
+            team.num
+        )
+        .fetch_one(&mut *tx)
+        .await?
+        .id;
+        sqlx::query!(
+            r#"INSERT INTO team_bounds_vertices (team_id, position, x, y)
+            SELECT $1, position, x, y FROM team_bounds_vertices WHERE team_id = $2"#,
+            new_team_id,
+            team.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        team_ids.insert(team.id, new_team_id);
+    }
+
+    let addresses = sqlx::query!(
+        r#"SELECT address.id as "id!: i64", street_id, house_number, x, y, circle_radius,
+            confidence, verified, estimated_flats, circle_color, team_assignment.team_id as "team_id?: i64"
+        FROM address
+        LEFT JOIN team_assignment ON team_assignment.address_id = address.id
+        WHERE address.area_id = $1 AND address.deleted_at IS NULL
+        ORDER BY address.id"#,
+        id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    for address in addresses {
+        let street_id = address.street_id.and_then(|s| street_ids.get(&s).copied());
+        let new_address_id = sqlx::query!(
+            r#"INSERT INTO address
+            (area_id, street_id, house_number, x, y, circle_radius, confidence, verified, estimated_flats, circle_color)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING id as "id!: i64""#,
+            new_id,
+            street_id,
+            address.house_number,
+            address.x,
+            address.y,
+            address.circle_radius,
+            address.confidence,
+            address.verified,
+            address.estimated_flats,
+            address.circle_color
+        )
+        .fetch_one(&mut *tx)
+        .await?
+        .id;
+        if let Some(team_id) = address.team_id.and_then(|t| team_ids.get(&t).copied()) {
+            sqlx::query!(
+                r#"INSERT INTO team_assignment (team_id, address_id, area_id) VALUES ($1, $2, $3)"#,
+                team_id,
+                new_address_id,
+                new_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    // The copy shares the image, so the same georeference applies
+    sqlx::query!(
+        r#"INSERT INTO area_georeference (area_id, origin_lat, origin_lon, meters_per_pixel, rotation)
+        SELECT $1, origin_lat, origin_lon, meters_per_pixel, rotation FROM area_georeference WHERE area_id = $2"#,
+        new_id,
+        id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // Dropping the transaction on an early return rolls everything back
+    tx.commit().await?;
+    Ok(new_id)
 }
 
 impl TeamRepository for AreaDb {
     async fn get_teams(&self) -> anyhow::Result<Vec<Team>> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_readonly().await?;
         Ok(sqlx::query!(
             r#"SELECT id as "id!: i64", num FROM team WHERE area_id = $1 ORDER BY id ASC"#,
             self.area_id
@@ -216,7 +909,7 @@ impl TeamRepository for AreaDb {
     }
 
     async fn get_team_by_id(&self, id: i64) -> anyhow::Result<Option<Team>> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_readonly().await?;
         if let Some(record) = sqlx::query!(
             r#"SELECT id as "id!: i64", num FROM team WHERE area_id = $1 AND id = $2"#,
             self.area_id,
@@ -236,7 +929,7 @@ impl TeamRepository for AreaDb {
     }
 
     async fn add_team(&self) -> anyhow::Result<Team> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_mut().await?;
         let record = sqlx::query!(
             r#"INSERT INTO team (area_id, num) VALUES ($1, (
                 SELECT COALESCE(MAX(num), -1) + 1 FROM team WHERE area_id = $1
@@ -253,7 +946,7 @@ impl TeamRepository for AreaDb {
     }
 
     async fn add_address(&self, team: &Team, address: &Address) -> anyhow::Result<()> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_mut().await?;
         sqlx::query!(
             r#"INSERT INTO team_assignment (team_id, address_id, area_id) VALUES ($1, $2, $3)"#,
             team.id,
@@ -266,7 +959,7 @@ impl TeamRepository for AreaDb {
     }
 
     async fn remove_address(&self, team: &Team, address: &Address) -> anyhow::Result<()> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_mut().await?;
         sqlx::query!(
             r#"DELETE FROM team_assignment WHERE team_id = $1 AND address_id = $2 AND area_id = $3"#,
             team.id,
@@ -279,7 +972,7 @@ impl TeamRepository for AreaDb {
     }
 
     async fn get_team_addresses(&self, team: &Team) -> anyhow::Result<Vec<team::TeamAddress>> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_readonly().await?;
         Ok(sqlx::query!(
             r#"SELECT
                 a.id as "address_id!: i64",
@@ -311,7 +1004,7 @@ impl TeamRepository for AreaDb {
     async fn get_team_addresses_all(
         &self,
     ) -> anyhow::Result<std::collections::HashMap<i64, Vec<team::TeamAddress>>> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_readonly().await?;
         let records = sqlx::query!(
             r#"SELECT
                 ta.team_id as "team_id!: i64",
@@ -344,7 +1037,7 @@ impl TeamRepository for AreaDb {
     }
 
     async fn set_team_bounds(&self, team: &Team, bounds: &[Point]) -> anyhow::Result<TeamBounds> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_mut().await?;
         let mut tx = conn.begin().await?;
         sqlx::query!(
             r#"DELETE FROM team_bounds_vertices WHERE team_id = $1"#,
@@ -370,7 +1063,7 @@ impl TeamRepository for AreaDb {
     }
 
     async fn get_team_bounds(&self, team: &Team) -> anyhow::Result<Option<TeamBounds>> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_readonly().await?;
         let records = sqlx::query!(
             r#"SELECT position, x, y FROM team_bounds_vertices
             WHERE team_id = $1
@@ -403,7 +1096,7 @@ impl TeamRepository for AreaDb {
     }
 
     async fn remove_team_bounds(&self, team: &Team) -> anyhow::Result<()> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_mut().await?;
         sqlx::query!(
             r#"DELETE FROM team_bounds_vertices WHERE team_id = $1"#,
             team.id
@@ -412,12 +1105,60 @@ impl TeamRepository for AreaDb {
         .await?;
         Ok(())
     }
+
+    async fn balance_teams(&self, team_count: usize) -> anyhow::Result<()> {
+        if team_count == 0 {
+            anyhow::bail!("Number of teams must be positive");
+        }
+        let mut teams = self.get_teams().await?;
+        while teams.len() < team_count {
+            teams.push(self.add_team().await?);
+        }
+        let addresses = AddressRepository::get_addresses(self).await?;
+        let positions: Vec<Point> = addresses.iter().map(|address| address.position).collect();
+        let clusters = util::balanced_clusters(&positions, team_count);
+
+        let mut conn = self.state.conn_mut().await?;
+        let mut tx = conn.begin().await?;
+        sqlx::query!(r#"DELETE FROM team_assignment WHERE area_id = $1"#, self.area_id)
+            .execute(&mut *tx)
+            .await?;
+        for (address, cluster) in addresses.iter().zip(clusters) {
+            sqlx::query!(
+                r#"INSERT INTO team_assignment (team_id, address_id, area_id) VALUES ($1, $2, $3)"#,
+                teams[cluster].id,
+                address.id,
+                self.area_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
 }
 
-impl AddressRepository for AreaDb {
-    async fn get_addresses(&self) -> anyhow::Result<Vec<Address>> {
-        let mut conn = self.state.conn().await?;
-        Ok(sqlx::query!(
+/// Addresses fetched per query by `addresses_stream`, which pages instead of holding a
+/// connection so the stream never blocks saving
+const ADDRESS_STREAM_PAGE: i64 = 64;
+
+/// Position of an `addresses_stream` in the area's addresses
+struct AddressCursor {
+    state: state::WeakStateRef,
+    area_id: i64,
+    last_id: i64,
+    page: std::collections::VecDeque<Address>,
+    done: bool,
+}
+
+impl AddressCursor {
+    /// The next page of live addresses after `last_id`, on a connection held only for the query
+    async fn next_page(&self) -> anyhow::Result<Vec<Address>> {
+        let Some(state) = self.state.upgrade() else {
+            anyhow::bail!("Project or transaction closed while streaming addresses");
+        };
+        let mut conn = state.conn_readonly().await?;
+        sqlx::query!(
             r#"SELECT
                 id as "id!: i64",
                 area_id as "area_id!: i64",
@@ -428,23 +1169,106 @@ impl AddressRepository for AreaDb {
                 confidence,
                 verified,
                 estimated_flats,
+                circle_color,
                 street_id as "assigned_street_id"
             FROM address
-            WHERE area_id = $1
-            ORDER BY id ASC"#,
-            self.area_id
+            WHERE area_id = $1 AND deleted_at IS NULL AND id > $2
+            ORDER BY id ASC
+            LIMIT $3"#,
+            self.area_id,
+            self.last_id,
+            ADDRESS_STREAM_PAGE
         )
         .fetch_all(&mut **conn)
         .await?
         .into_iter()
-        .map(|record| Address {
-            id: record.id,
-            area_id: record.area_id,
-            house_number: record.house_number,
-            circle_radius: record.circle_radius,
-            position: Point {
-                x: record
-                    .x
+        .map(|record| {
+            Ok(Address {
+                id: record.id,
+                area_id: record.area_id,
+                house_number: record.house_number,
+                circle_radius: record.circle_radius,
+                position: Point {
+                    x: record
+                        .x
+                        .try_into()
+                        .expect("x coordinate bounded by database constraint"),
+                    y: record
+                        .y
+                        .try_into()
+                        .expect("y coordinate bounded by database constraint"),
+                },
+                confidence: record.confidence,
+                verified: record.verified != 0,
+                estimated_flats: record.estimated_flats.map(|v| v as u16),
+                circle_color: record.circle_color.map(Color::try_from).transpose()?,
+                assigned_street_id: record.assigned_street_id,
+                _guard: (),
+            })
+        })
+        .collect()
+    }
+}
+
+impl AddressRepository for AreaDb {
+    fn addresses_stream(&self) -> impl futures::Stream<Item = anyhow::Result<Address>> + Send + 'static {
+        let cursor = AddressCursor {
+            state: self.state.to_weak(),
+            area_id: self.area_id,
+            last_id: 0,
+            page: std::collections::VecDeque::new(),
+            done: false,
+        };
+        futures::stream::unfold(cursor, |mut cursor| async move {
+            if cursor.page.is_empty() && !cursor.done {
+                match cursor.next_page().await {
+                    Result::Ok(page) => {
+                        cursor.done = (page.len() as i64) < ADDRESS_STREAM_PAGE;
+                        cursor.page = page.into();
+                    }
+                    Err(e) => {
+                        cursor.done = true;
+                        return Some((Err(e), cursor));
+                    }
+                }
+            }
+            let address = cursor.page.pop_front()?;
+            cursor.last_id = address.id;
+            Some((Ok(address), cursor))
+        })
+    }
+
+    async fn get_addresses(&self) -> anyhow::Result<Vec<Address>> {
+        let mut conn = self.state.conn_readonly().await?;
+        sqlx::query!(
+            r#"SELECT
+                id as "id!: i64",
+                area_id as "area_id!: i64",
+                house_number,
+                circle_radius as "circle_radius!: u32",
+                x,
+                y,
+                confidence,
+                verified,
+                estimated_flats,
+                circle_color,
+                street_id as "assigned_street_id"
+            FROM address
+            WHERE area_id = $1 AND deleted_at IS NULL
+            ORDER BY id ASC"#,
+            self.area_id
+        )
+        .fetch_all(&mut **conn)
+        .await?
+        .into_iter()
+        .map(|record| Ok(Address {
+            id: record.id,
+            area_id: record.area_id,
+            house_number: record.house_number,
+            circle_radius: record.circle_radius,
+            position: Point {
+                x: record
+                    .x
                     .try_into()
                     .expect("x coordinate bounded by database constraint"),
                 y: record
@@ -455,14 +1279,18 @@ impl AddressRepository for AreaDb {
             confidence: record.confidence,
             verified: record.verified != 0,
             estimated_flats: record.estimated_flats.map(|v| v as u16),
+            circle_color: record
+                .circle_color
+                .map(Color::try_from)
+                .transpose()?,
             assigned_street_id: record.assigned_street_id,
             _guard: (),
-        })
-        .collect())
+        }))
+        .collect()
     }
 
     async fn get_address_by_id(&self, id: i64) -> anyhow::Result<Option<Address>> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_readonly().await?;
         if let Some(record) = sqlx::query!(
             r#"SELECT
                 id as "id!: i64",
@@ -473,10 +1301,11 @@ impl AddressRepository for AreaDb {
                 confidence,
                 verified,
                 estimated_flats,
+                circle_color,
                 circle_radius as "circle_radius!: u32",
                 street_id as "assigned_street_id"
             FROM address
-            WHERE area_id = $1 AND id = $2"#,
+            WHERE area_id = $1 AND id = $2 AND deleted_at IS NULL"#,
             self.area_id,
             id
         )
@@ -500,6 +1329,10 @@ impl AddressRepository for AreaDb {
                 confidence: record.confidence,
                 verified: record.verified != 0,
                 estimated_flats: record.estimated_flats.map(|v| v as u16),
+                circle_color: record
+                    .circle_color
+                    .map(Color::try_from)
+                    .transpose()?,
                 circle_radius: record.circle_radius,
                 assigned_street_id: record.assigned_street_id,
                 _guard: (),
@@ -510,32 +1343,548 @@ impl AddressRepository for AreaDb {
     }
 
     async fn get_address_by_street(&self, street: &Street) -> anyhow::Result<Vec<Address>> {
-        let mut conn = self.state.conn().await?;
-        Ok(sqlx::query!(
+        let mut conn = self.state.conn_readonly().await?;
+        sqlx::query!(
+            r#"SELECT
+                id as "id!: i64",
+                area_id as "area_id!: i64",
+                house_number,
+                x,
+                y,
+                confidence,
+                verified,
+                estimated_flats,
+                circle_color,
+                circle_radius as "circle_radius!: u32",
+                street_id as "assigned_street_id"
+            FROM address
+            WHERE area_id = $1 AND street_id = $2 AND deleted_at IS NULL
+            ORDER BY id ASC"#,
+            self.area_id,
+            street.id
+        )
+        .fetch_all(&mut **conn)
+        .await?
+        .into_iter()
+        .map(|record| Ok(Address {
+            id: record.id,
+            area_id: record.area_id,
+            house_number: record.house_number,
+            position: Point {
+                x: record
+                    .x
+                    .try_into()
+                    .expect("x coordinate bounded by database constraint"),
+                y: record
+                    .y
+                    .try_into()
+                    .expect("y coordinate bounded by database constraint"),
+            },
+            confidence: record.confidence,
+            verified: record.verified != 0,
+            estimated_flats: record.estimated_flats.map(|v| v as u16),
+            circle_color: record
+                .circle_color
+                .map(Color::try_from)
+                .transpose()?,
+            circle_radius: record.circle_radius,
+            assigned_street_id: record.assigned_street_id,
+            _guard: (),
+        }))
+        .collect()
+    }
+
+    async fn get_addresses_within_bbox(&self, min: &Point, max: &Point) -> anyhow::Result<Vec<Address>> {
+        let mut conn = self.state.conn_readonly().await?;
+        sqlx::query!(
+            r#"SELECT
+                id as "id!: i64",
+                area_id as "area_id!: i64",
+                house_number,
+                x,
+                y,
+                confidence,
+                verified,
+                estimated_flats,
+                circle_color,
+                circle_radius as "circle_radius!: u32",
+                street_id as "assigned_street_id"
+            FROM address
+            WHERE area_id = $1 AND deleted_at IS NULL
+            AND x BETWEEN $2 AND $3
+            AND y BETWEEN $4 AND $5
+            ORDER BY id ASC"#,
+            self.area_id,
+            min.x,
+            max.x,
+            min.y,
+            max.y
+        )
+        .fetch_all(&mut **conn)
+        .await?
+        .into_iter()
+        .map(|record| Ok(Address {
+            id: record.id,
+            area_id: record.area_id,
+            house_number: record.house_number,
+            position: Point {
+                x: record
+                    .x
+                    .try_into()
+                    .expect("x coordinate bounded by database constraint"),
+                y: record
+                    .y
+                    .try_into()
+                    .expect("y coordinate bounded by database constraint"),
+            },
+            confidence: record.confidence,
+            verified: record.verified != 0,
+            estimated_flats: record.estimated_flats.map(|v| v as u16),
+            circle_color: record
+                .circle_color
+                .map(Color::try_from)
+                .transpose()?,
+            circle_radius: record.circle_radius,
+            assigned_street_id: record.assigned_street_id,
+            _guard: (),
+        }))
+        .collect()
+    }
+
+    async fn get_addresses_within_radius(&self, center: &Point, radius: u32) -> anyhow::Result<Vec<Address>> {
+        let mut conn = self.state.conn_readonly().await?;
+        let min_x = center.x.saturating_sub(radius);
+        let max_x = center.x.saturating_add(radius);
+        let min_y = center.y.saturating_sub(radius);
+        let max_y = center.y.saturating_add(radius);
+        let radius_squared = (radius as i64) * (radius as i64);
+        // Bounding box prefilter, then the exact distance check
+        sqlx::query!(
+            r#"SELECT
+                id as "id!: i64",
+                area_id as "area_id!: i64",
+                house_number,
+                x,
+                y,
+                confidence,
+                verified,
+                estimated_flats,
+                circle_color,
+                circle_radius as "circle_radius!: u32",
+                street_id as "assigned_street_id"
+            FROM address
+            WHERE area_id = $1 AND deleted_at IS NULL
+            AND x BETWEEN $2 AND $3
+            AND y BETWEEN $4 AND $5
+            AND (x - $6) * (x - $6) + (y - $7) * (y - $7) <= $8
+            ORDER BY id ASC"#,
+            self.area_id,
+            min_x,
+            max_x,
+            min_y,
+            max_y,
+            center.x,
+            center.y,
+            radius_squared
+        )
+        .fetch_all(&mut **conn)
+        .await?
+        .into_iter()
+        .map(|record| Ok(Address {
+            id: record.id,
+            area_id: record.area_id,
+            house_number: record.house_number,
+            position: Point {
+                x: record
+                    .x
+                    .try_into()
+                    .expect("x coordinate bounded by database constraint"),
+                y: record
+                    .y
+                    .try_into()
+                    .expect("y coordinate bounded by database constraint"),
+            },
+            confidence: record.confidence,
+            verified: record.verified != 0,
+            estimated_flats: record.estimated_flats.map(|v| v as u16),
+            circle_color: record
+                .circle_color
+                .map(Color::try_from)
+                .transpose()?,
+            circle_radius: record.circle_radius,
+            assigned_street_id: record.assigned_street_id,
+            _guard: (),
+        }))
+        .collect()
+    }
+
+    async fn neighbors_within(&self, id: i64, distance: u32) -> anyhow::Result<Vec<i64>> {
+        let address = self
+            .get_address_by_id(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Address with id {} not found", id))?;
+        Ok(self
+            .get_addresses_within_radius(&address.position, distance)
+            .await?
+            .into_iter()
+            .map(|other| other.id)
+            .filter(|&other| other != id)
+            .collect())
+    }
+
+    async fn all_duplicate_pairs(&self, distance: u32) -> anyhow::Result<Vec<(i64, i64)>> {
+        let mut conn = self.state.conn_readonly().await?;
+        let points: Vec<(i64, i64, i64)> = sqlx::query!(
+            r#"SELECT id as "id!: i64", x, y FROM address
+            WHERE area_id = $1 AND deleted_at IS NULL"#,
+            self.area_id
+        )
+        .fetch_all(&mut **conn)
+        .await?
+        .into_iter()
+        .map(|record| (record.id, record.x, record.y))
+        .collect();
+        Ok(address::close_pairs(&points, distance))
+    }
+
+    async fn add_address(&self, address: &address::NewAddress) -> anyhow::Result<Address> {
+        self.ensure_in_bounds(&address.position)?;
+        let mut conn = self.state.conn_mut().await?;
+        let estimated_flats = address.estimated_flats.map(|v| v as i64);
+        let circle_color = address.circle_color.map(i64::from);
+        let record = sqlx::query!(
+            r#"INSERT INTO address
+            (area_id, house_number, x, y, confidence, circle_radius, estimated_flats, street_id, circle_color)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING
+                id as "id!: i64",
+                area_id as "area_id!: i64",
+                circle_radius as "circle_radius!: u32",
+                house_number,
+                x,
+                y,
+                confidence,
+                verified,
+                estimated_flats,
+                circle_color,
+                street_id as "assigned_street_id""#,
+            self.area_id,
+            address.house_number,
+            address.position.x,
+            address.position.y,
+            address.confidence,
+            address.circle_radius,
+            estimated_flats,
+            address.assigned_street_id,
+            circle_color
+        )
+        .fetch_one(&mut **conn)
+        .await?;
+        Ok(Address {
+            id: record.id,
+            area_id: record.area_id,
+            house_number: record.house_number,
+            position: Point {
+                x: record
+                    .x
+                    .try_into()
+                    .expect("x coordinate bounded by database constraint"),
+                y: record
+                    .y
+                    .try_into()
+                    .expect("y coordinate bounded by database constraint"),
+            },
+            confidence: record.confidence,
+            verified: record.verified != 0,
+            estimated_flats: record.estimated_flats.map(|v| v as u16),
+            circle_color: record
+                .circle_color
+                .map(Color::try_from)
+                .transpose()?,
+            assigned_street_id: record.assigned_street_id,
+            circle_radius: record.circle_radius,
+            _guard: (),
+        })
+    }
+
+    async fn add_addresses(&self, addresses: &[address::NewAddress]) -> anyhow::Result<Vec<Address>> {
+        for address in addresses {
+            self.ensure_in_bounds(&address.position)?;
+        }
+        let mut conn = self.state.conn_mut().await?;
+        let mut tx = conn.begin().await?;
+        let inserted = insert_addresses(&mut tx, self.area_id, addresses).await?;
+        // Dropping the transaction on an early return rolls everything back
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    async fn set_verified_bulk(&self, ids: &[i64], verified: bool) -> anyhow::Result<usize> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let mut conn = self.state.conn_mut().await?;
+        // The id list is bound as one JSON array so the statement stays static
+        let ids = serde_json::to_string(ids)?;
+        let result = sqlx::query!(
+            r#"UPDATE address SET verified = $1
+            WHERE area_id = $2 AND deleted_at IS NULL
+            AND id IN (SELECT value FROM json_each($3))"#,
+            verified,
+            self.area_id,
+            ids
+        )
+        .execute(&mut **conn)
+        .await?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    async fn estimate_flats_from_radius(&self, small: u32, large: u32) -> anyhow::Result<usize> {
+        if small > large {
+            anyhow::bail!("Small radius {} is larger than large radius {}", small, large);
+        }
+        let mut conn = self.state.conn_mut().await?;
+        let result = sqlx::query!(
+            r#"UPDATE address SET estimated_flats = CASE
+                WHEN circle_radius < $1 THEN 1
+                WHEN circle_radius > $2 THEN 8
+                ELSE 4
+            END
+            WHERE area_id = $3 AND deleted_at IS NULL AND estimated_flats IS NULL"#,
+            small,
+            large,
+            self.area_id
+        )
+        .execute(&mut **conn)
+        .await?;
+        Ok(result.rows_affected() as usize)
+    }
+
+    async fn import_detections(&self, detections: &[HouseNumberDetection]) -> anyhow::Result<Vec<Address>> {
+        let new_addresses = address::from_detections(detections);
+        for address in &new_addresses {
+            self.ensure_in_bounds(&address.position)?;
+        }
+        // Addresses and the state change go in one transaction, so an area never
+        // ends up with half its detections or with addresses but the old state
+        let mut conn = self.state.conn_mut().await?;
+        let mut tx = conn.begin().await?;
+        let addresses = insert_addresses(&mut tx, self.area_id, &new_addresses).await?;
+        let state = i64::from(AreaState::AddressesDetected);
+        sqlx::query!("UPDATE area SET state = $1 WHERE id = $2", state, self.area_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(addresses)
+    }
+
+    async fn update_address(
+        &self,
+        address: &Address,
+        update: &address::AddressUpdate<'_>,
+    ) -> anyhow::Result<Address> {
+        if let Some(position) = &update.position {
+            self.ensure_in_bounds(position)?;
+        }
+        let mut conn = self.state.conn_mut().await?;
+        let estimated_flats = match update.estimated_flats {
+            Some(Some(v)) => Some(v as i64),
+            Some(None) => None,
+            None => address.estimated_flats.map(|v| v as i64),
+        };
+        let circle_color = match update.circle_color {
+            Some(color) => color.map(i64::from),
+            None => address.circle_color.map(i64::from),
+        };
+        let assigned_street_id = match update.street {
+            Some(x) => x.map(|s| s.id),
+            None => address.assigned_street_id,
+        };
+        let x = update.position.as_ref().map(|p| p.x);
+        let y = update.position.as_ref().map(|p| p.y);
+        let record = sqlx::query!(
+            r#"UPDATE address SET
+                house_number = COALESCE($1, house_number),
+                x = COALESCE($2, x),
+                y = COALESCE($3, y),
+                confidence = COALESCE($4, confidence),
+                verified = COALESCE($5, verified),
+                circle_radius = COALESCE($10, circle_radius),
+                circle_color = $11,
+                estimated_flats = $6,
+                street_id = $7
+            WHERE id = $8 AND area_id = $9 AND deleted_at IS NULL
+            RETURNING
+                id as "id!: i64",
+                area_id as "area_id!: i64",
+                house_number,
+                x,
+                y,
+                confidence,
+                verified,
+                estimated_flats,
+                circle_color,
+                street_id as "assigned_street_id",
+                circle_radius as "circle_radius!: u32""#,
+            update.house_number,
+            x,
+            y,
+            update.confidence,
+            update.verified,
+            estimated_flats,
+            assigned_street_id,
+            address.id,
+            self.area_id,
+            update.circle_radius,
+            circle_color,
+        )
+        .fetch_one(&mut **conn)
+        .await?;
+        Ok(Address {
+            id: record.id,
+            area_id: record.area_id,
+            house_number: record.house_number,
+            position: Point {
+                x: record
+                    .x
+                    .try_into()
+                    .expect("x coordinate bounded by database constraint"),
+                y: record
+                    .y
+                    .try_into()
+                    .expect("y coordinate bounded by database constraint"),
+            },
+            confidence: record.confidence,
+            verified: record.verified != 0,
+            estimated_flats: record.estimated_flats.map(|v| v as u16),
+            circle_color: record
+                .circle_color
+                .map(Color::try_from)
+                .transpose()?,
+            assigned_street_id: record.assigned_street_id,
+            circle_radius: record.circle_radius,
+            _guard: (),
+        })
+    }
+
+    async fn import_csv(&self, reader: impl std::io::Read) -> anyhow::Result<Vec<Address>> {
+        let addresses = address::parse_csv(reader)?;
+        self.add_addresses(&addresses).await
+    }
+
+    async fn export_geojson(&self) -> anyhow::Result<String> {
+        let mut conn = self.state.conn_readonly().await?;
+        let georeference = load_georeference(&mut conn, self.area_id).await?;
+        let features: Vec<serde_json::Value> = sqlx::query!(
+            r#"SELECT
+                a.id as "id!: i64",
+                a.house_number,
+                a.x,
+                a.y,
+                a.confidence,
+                a.verified,
+                a.estimated_flats,
+                s.name as "street_name?"
+            FROM address a
+            LEFT JOIN street s ON a.street_id = s.id
+            WHERE a.area_id = $1 AND a.deleted_at IS NULL
+            ORDER BY a.id ASC"#,
+            self.area_id
+        )
+        .fetch_all(&mut **conn)
+        .await?
+        .into_iter()
+        .map(|record| {
+            let mut properties = serde_json::Map::new();
+            properties.insert("house_number".into(), record.house_number.into());
+            if let Some(street_name) = record.street_name {
+                properties.insert("street_name".into(), street_name.into());
+            }
+            properties.insert("confidence".into(), record.confidence.into());
+            properties.insert("verified".into(), (record.verified != 0).into());
+            properties.insert("estimated_flats".into(), record.estimated_flats.into());
+            let coordinates = match &georeference {
+                Some(georeference) => {
+                    let position = Point {
+                        x: record.x.try_into().expect("x coordinate bounded by database constraint"),
+                        y: record.y.try_into().expect("y coordinate bounded by database constraint"),
+                    };
+                    let (lon, lat) = georeference.pixel_to_lonlat(&position);
+                    serde_json::json!([lon, lat])
+                }
+                None => serde_json::json!([record.x, record.y]),
+            };
+            serde_json::json!({
+                "type": "Feature",
+                "id": record.id,
+                "geometry": {
+                    "type": "Point",
+                    "coordinates": coordinates,
+                },
+                "properties": properties,
+            })
+        })
+        .collect();
+        Ok(serde_json::to_string(&serde_json::json!({
+            "type": "FeatureCollection",
+            "features": features,
+        }))?)
+    }
+
+    async fn delete_address(&self, address: Address) -> anyhow::Result<()> {
+        let mut conn = self.state.conn_mut().await?;
+        let mut tx = conn.begin().await?;
+        let deleted_at = OffsetDateTime::now_utc().format(&time::format_description::well_known::Rfc3339)?;
+        sqlx::query!(
+            r#"UPDATE address SET
+                deleted_at = $1,
+                deleted_team_id = (SELECT team_id FROM team_assignment WHERE address_id = $2)
+            WHERE id = $2 AND area_id = $3 AND deleted_at IS NULL"#,
+            deleted_at,
+            address.id,
+            self.area_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"DELETE FROM team_assignment WHERE address_id = $1 AND area_id = $2"#,
+            address.id,
+            self.area_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_deleted_addresses(&self) -> anyhow::Result<Vec<Address>> {
+        let mut conn = self.state.conn_readonly().await?;
+        sqlx::query!(
             r#"SELECT
                 id as "id!: i64",
                 area_id as "area_id!: i64",
                 house_number,
+                circle_radius as "circle_radius!: u32",
                 x,
                 y,
                 confidence,
                 verified,
                 estimated_flats,
-                circle_radius as "circle_radius!: u32",
+                circle_color,
                 street_id as "assigned_street_id"
             FROM address
-            WHERE area_id = $1 AND street_id = $2
+            WHERE area_id = $1 AND deleted_at IS NOT NULL
             ORDER BY id ASC"#,
-            self.area_id,
-            street.id
+            self.area_id
         )
         .fetch_all(&mut **conn)
         .await?
         .into_iter()
-        .map(|record| Address {
+        .map(|record| Ok(Address {
             id: record.id,
             area_id: record.area_id,
             house_number: record.house_number,
+            circle_radius: record.circle_radius,
             position: Point {
                 x: record
                     .x
@@ -549,42 +1898,63 @@ impl AddressRepository for AreaDb {
             confidence: record.confidence,
             verified: record.verified != 0,
             estimated_flats: record.estimated_flats.map(|v| v as u16),
-            circle_radius: record.circle_radius,
+            circle_color: record
+                .circle_color
+                .map(Color::try_from)
+                .transpose()?,
             assigned_street_id: record.assigned_street_id,
             _guard: (),
-        })
-        .collect())
+        }))
+        .collect()
     }
 
-    async fn add_address(&self, address: &address::NewAddress) -> anyhow::Result<Address> {
-        let mut conn = self.state.conn().await?;
-        let estimated_flats = address.estimated_flats.map(|v| v as i64);
+    async fn restore_address(&self, id: i64) -> anyhow::Result<Address> {
+        let mut conn = self.state.conn_mut().await?;
+        let mut tx = conn.begin().await?;
+        let team_id = sqlx::query!(
+            r#"SELECT deleted_team_id FROM address
+            WHERE id = $1 AND area_id = $2 AND deleted_at IS NOT NULL"#,
+            id,
+            self.area_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Deleted address with id {} not found", id))?
+        .deleted_team_id;
+
         let record = sqlx::query!(
-            r#"INSERT INTO address
-            (area_id, house_number, x, y, confidence, circle_radius, estimated_flats, street_id)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            r#"UPDATE address SET deleted_at = NULL, deleted_team_id = NULL
+            WHERE id = $1 AND area_id = $2
             RETURNING
                 id as "id!: i64",
                 area_id as "area_id!: i64",
-                circle_radius as "circle_radius!: u32",
                 house_number,
                 x,
                 y,
                 confidence,
                 verified,
                 estimated_flats,
-                street_id as "assigned_street_id""#,
-            self.area_id,
-            address.house_number,
-            address.position.x,
-            address.position.y,
-            address.confidence,
-            address.circle_radius,
-            estimated_flats,
-            address.assigned_street_id
+                circle_color,
+                street_id as "assigned_street_id",
+                circle_radius as "circle_radius!: u32""#,
+            id,
+            self.area_id
         )
-        .fetch_one(&mut **conn)
+        .fetch_one(&mut *tx)
         .await?;
+
+        if let Some(team_id) = team_id {
+            sqlx::query!(
+                r#"INSERT INTO team_assignment (team_id, address_id, area_id) VALUES ($1, $2, $3)"#,
+                team_id,
+                id,
+                self.area_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
         Ok(Address {
             id: record.id,
             area_id: record.area_id,
@@ -602,40 +1972,77 @@ impl AddressRepository for AreaDb {
             confidence: record.confidence,
             verified: record.verified != 0,
             estimated_flats: record.estimated_flats.map(|v| v as u16),
+            circle_color: record
+                .circle_color
+                .map(Color::try_from)
+                .transpose()?,
             assigned_street_id: record.assigned_street_id,
             circle_radius: record.circle_radius,
             _guard: (),
         })
     }
 
-    async fn update_address(
-        &self,
-        address: &Address,
-        update: &address::AddressUpdate<'_>,
-    ) -> anyhow::Result<Address> {
-        let mut conn = self.state.conn().await?;
-        let estimated_flats = match update.estimated_flats {
-            Some(Some(v)) => Some(v as i64),
-            Some(None) => None,
-            None => address.estimated_flats.map(|v| v as i64),
+    async fn merge_addresses(&self, keep: i64, remove: i64) -> anyhow::Result<Address> {
+        if keep == remove {
+            anyhow::bail!("Cannot merge address {} with itself", keep);
+        }
+        let mut conn = self.state.conn_mut().await?;
+        let mut tx = conn.begin().await?;
+        let mut records = Vec::with_capacity(2);
+        for id in [keep, remove] {
+            let record = sqlx::query!(
+                r#"SELECT
+                    address.area_id as "area_id!: i64",
+                    street_id,
+                    x,
+                    y,
+                    confidence,
+                    verified,
+                    estimated_flats,
+                    team_assignment.team_id as "team_id?: i64"
+                FROM address
+                LEFT JOIN team_assignment ON team_assignment.address_id = address.id
+                WHERE address.id = $1 AND address.deleted_at IS NULL"#,
+                id
+            )
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Address with id {} not found", id))?;
+            if record.area_id != self.area_id {
+                anyhow::bail!(
+                    "Cannot merge address {} from area {} into area {}",
+                    id,
+                    record.area_id,
+                    self.area_id
+                );
+            }
+            records.push(record);
+        }
+        let (kept, removed) = (&records[0], &records[1]);
+
+        let street_id = match (kept.street_id, removed.street_id) {
+            (Some(a), Some(b)) if a != b => {
+                anyhow::bail!("Cannot merge addresses {} and {} on different streets", keep, remove)
+            }
+            (a, b) => a.or(b),
         };
-        let assigned_street_id = match update.street {
-            Some(x) => x.map(|s| s.id),
-            None => address.assigned_street_id,
+        let (x, y) = if kept.verified != 0 {
+            (kept.x, kept.y)
+        } else {
+            ((kept.x + removed.x) / 2, (kept.y + removed.y) / 2)
         };
-        let x = update.position.as_ref().map(|p| p.x);
-        let y = update.position.as_ref().map(|p| p.y);
+        let verified = kept.verified != 0 || removed.verified != 0;
+        let confidence = kept.confidence.max(removed.confidence);
+        let estimated_flats = kept.estimated_flats.or(removed.estimated_flats);
+        let transferred_team_id = if kept.team_id.is_none() { removed.team_id } else { None };
+
+        // Delete first: taking over the street must not clash with the duplicate's house number
+        sqlx::query!(r#"DELETE FROM address WHERE id = $1"#, remove)
+            .execute(&mut *tx)
+            .await?;
         let record = sqlx::query!(
-            r#"UPDATE address SET
-                house_number = COALESCE($1, house_number),
-                x = COALESCE($2, x),
-                y = COALESCE($3, y),
-                confidence = COALESCE($4, confidence),
-                verified = COALESCE($5, verified),
-                circle_radius = COALESCE($10, circle_radius),
-                estimated_flats = $6,
-                street_id = $7
-            WHERE id = $8 AND area_id = $9
+            r#"UPDATE address SET street_id = $1, x = $2, y = $3, confidence = $4, verified = $5, estimated_flats = $6
+            WHERE id = $7
             RETURNING
                 id as "id!: i64",
                 area_id as "area_id!: i64",
@@ -645,21 +2052,31 @@ impl AddressRepository for AreaDb {
                 confidence,
                 verified,
                 estimated_flats,
+                circle_color,
                 street_id as "assigned_street_id",
                 circle_radius as "circle_radius!: u32""#,
-            update.house_number,
+            street_id,
             x,
             y,
-            update.confidence,
-            update.verified,
+            confidence,
+            verified,
             estimated_flats,
-            assigned_street_id,
-            address.id,
-            self.area_id,
-            update.circle_radius,
+            keep
         )
-        .fetch_one(&mut **conn)
+        .fetch_one(&mut *tx)
         .await?;
+        if let Some(team_id) = transferred_team_id {
+            sqlx::query!(
+                r#"INSERT INTO team_assignment (team_id, address_id, area_id) VALUES ($1, $2, $3)"#,
+                team_id,
+                keep,
+                self.area_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
         Ok(Address {
             id: record.id,
             area_id: record.area_id,
@@ -677,28 +2094,31 @@ impl AddressRepository for AreaDb {
             confidence: record.confidence,
             verified: record.verified != 0,
             estimated_flats: record.estimated_flats.map(|v| v as u16),
+            circle_color: record
+                .circle_color
+                .map(Color::try_from)
+                .transpose()?,
             assigned_street_id: record.assigned_street_id,
             circle_radius: record.circle_radius,
             _guard: (),
         })
     }
 
-    async fn delete_address(&self, address: Address) -> anyhow::Result<()> {
-        let mut conn = self.state.conn().await?;
-        sqlx::query!(
-            r#"DELETE FROM address WHERE id = $1 AND area_id = $2"#,
-            address.id,
+    async fn purge_deleted(&self) -> anyhow::Result<usize> {
+        let mut conn = self.state.conn_mut().await?;
+        let result = sqlx::query!(
+            r#"DELETE FROM address WHERE area_id = $1 AND deleted_at IS NOT NULL"#,
             self.area_id
         )
         .execute(&mut **conn)
         .await?;
-        Ok(())
+        Ok(result.rows_affected() as usize)
     }
 }
 
 impl StreetRepository for AreaDb {
     async fn get_streets(&self) -> anyhow::Result<Vec<Street>> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_readonly().await?;
         Ok(sqlx::query!(
             r#"SELECT id as "id!: i64", name, verified FROM street
             WHERE area_id = $1
@@ -717,8 +2137,37 @@ impl StreetRepository for AreaDb {
         .collect())
     }
 
+    async fn search_streets(&self, query: &str) -> anyhow::Result<Vec<Street>> {
+        // LIKE wildcards in the query are matched literally
+        let escaped = query
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let contains = format!("%{escaped}%");
+        let prefix = format!("{escaped}%");
+        let mut conn = self.state.conn_readonly().await?;
+        Ok(sqlx::query!(
+            r#"SELECT id as "id!: i64", name, verified FROM street
+            WHERE area_id = $1 AND name IS NOT NULL AND name LIKE $2 ESCAPE '\'
+            ORDER BY name LIKE $3 ESCAPE '\' DESC, name COLLATE NOCASE ASC, id ASC"#,
+            self.area_id,
+            contains,
+            prefix
+        )
+        .fetch_all(&mut **conn)
+        .await?
+        .into_iter()
+        .map(|record| Street {
+            id: record.id,
+            name: record.name,
+            verified: record.verified != 0,
+            _guard: (),
+        })
+        .collect())
+    }
+
     async fn get_street_by_id(&self, id: i64) -> anyhow::Result<Option<Street>> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_readonly().await?;
         if let Some(record) = sqlx::query!(
             r#"SELECT id as "id!: i64", name, verified FROM street
             WHERE area_id = $1 AND id = $2"#,
@@ -740,7 +2189,7 @@ impl StreetRepository for AreaDb {
     }
 
     async fn add_street(&self) -> anyhow::Result<Street> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_mut().await?;
         let record = sqlx::query!(
             r#"INSERT INTO street (area_id) VALUES ($1)
             RETURNING id as "id!: i64", name, verified"#,
@@ -761,7 +2210,7 @@ impl StreetRepository for AreaDb {
         street: &Street,
         polyline: &[Point],
     ) -> anyhow::Result<()> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_mut().await?;
         let mut tx = conn.begin().await?;
         sqlx::query!(
             r#"DELETE FROM street_polyline_vertices WHERE street_id = $1"#,
@@ -784,7 +2233,7 @@ impl StreetRepository for AreaDb {
     }
 
     async fn get_street_polyline(&self, street: &Street) -> anyhow::Result<Option<StreetPolyline>> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_readonly().await?;
         let records = sqlx::query!(
             r#"SELECT position, x, y FROM street_polyline_vertices
             WHERE street_id = $1
@@ -814,7 +2263,7 @@ impl StreetRepository for AreaDb {
     }
 
     async fn remove_street_polyline(&self, street: &Street) -> anyhow::Result<()> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_mut().await?;
         sqlx::query!(
             r#"DELETE FROM street_polyline_vertices WHERE street_id = $1"#,
             street.id
@@ -829,7 +2278,7 @@ impl StreetRepository for AreaDb {
         street: &Street,
         update: &StreetUpdate,
     ) -> anyhow::Result<Street> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_mut().await?;
         let record = sqlx::query!(
             r#"UPDATE street SET
                 name = COALESCE($1, name),
@@ -851,8 +2300,72 @@ impl StreetRepository for AreaDb {
         })
     }
 
+    async fn order_addresses_along_polyline(&self, street: &Street) -> anyhow::Result<Vec<Address>> {
+        let polyline = self
+            .get_street_polyline(street)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Street with id {} has no polyline", street.id))?;
+        let mut along: Vec<(f64, Address)> = self
+            .get_address_by_street(street)
+            .await?
+            .into_iter()
+            .map(|address| {
+                let arc_length = util::project_onto_polyline(&address.position, &polyline.points)
+                    .map_or(0.0, |projection| projection.arc_length);
+                (arc_length, address)
+            })
+            .collect();
+        // Stable sort: addresses at the same spot stay in id order
+        along.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(along.into_iter().map(|(_, address)| address).collect())
+    }
+
+    async fn autoassign_nearest(&self) -> anyhow::Result<usize> {
+        let mut polylines = Vec::new();
+        for street in self.get_streets().await? {
+            if let Some(polyline) = self.get_street_polyline(&street).await? {
+                polylines.push((street.id, polyline.points));
+            }
+        }
+        if polylines.is_empty() {
+            return Ok(0);
+        }
+
+        let assignments: Vec<(i64, i64)> = self
+            .get_addresses()
+            .await?
+            .into_iter()
+            .filter(|address| address.assigned_street_id.is_none())
+            .filter_map(|address| {
+                polylines
+                    .iter()
+                    .filter_map(|(street_id, points)| {
+                        util::point_to_polyline_distance(&address.position, points)
+                            .map(|distance| (*street_id, distance))
+                    })
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(street_id, _)| (address.id, street_id))
+            })
+            .collect();
+
+        let mut conn = self.state.conn_mut().await?;
+        let mut tx = conn.begin().await?;
+        for (address_id, street_id) in &assignments {
+            sqlx::query!(
+                r#"UPDATE address SET street_id = $1 WHERE id = $2 AND area_id = $3"#,
+                street_id,
+                address_id,
+                self.area_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(assignments.len())
+    }
+
     async fn delete_street(&self, street: Street) -> anyhow::Result<()> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_mut().await?;
         sqlx::query!(
             r#"DELETE FROM street WHERE id = $1 AND area_id = $2"#,
             street.id,
@@ -862,11 +2375,205 @@ impl StreetRepository for AreaDb {
         .await?;
         Ok(())
     }
+
+    async fn merge_streets(&self, a: &Street, b: &Street) -> anyhow::Result<Street> {
+        if a.id == b.id {
+            anyhow::bail!("Cannot merge street {} with itself", a.id);
+        }
+        for street in [a, b] {
+            if self.get_street_by_id(street.id).await?.is_none() {
+                anyhow::bail!("Street with id {} not found", street.id);
+            }
+        }
+        let a_points = self.get_street_polyline(a).await?.map(|p| p.points).unwrap_or_default();
+        let b_points = self.get_street_polyline(b).await?.map(|p| p.points).unwrap_or_default();
+        let joined = util::join_polylines(&a_points, &b_points);
+
+        let mut conn = self.state.conn_mut().await?;
+        let mut tx = conn.begin().await?;
+        // Trashed addresses count too: they keep their house number on the street
+        let clash = sqlx::query!(
+            r#"SELECT house_number FROM address
+            WHERE street_id = $1 AND house_number IN (SELECT house_number FROM address WHERE street_id = $2)
+            LIMIT 1"#,
+            b.id,
+            a.id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(clash) = clash {
+            anyhow::bail!(
+                "Cannot merge streets {} and {}: both have house number {}",
+                a.id,
+                b.id,
+                clash.house_number
+            );
+        }
+
+        sqlx::query!(
+            r#"UPDATE address SET street_id = $1 WHERE street_id = $2 AND area_id = $3"#,
+            a.id,
+            b.id,
+            self.area_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"DELETE FROM street_polyline_vertices WHERE street_id = $1"#,
+            a.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        for (position, point) in joined.iter().enumerate() {
+            let position = position as i64;
+            sqlx::query!(
+                r#"INSERT INTO street_polyline_vertices (street_id, position, x, y) VALUES ($1, $2, $3, $4)"#,
+                a.id,
+                position,
+                point.x,
+                point.y
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // `b`'s name can only move over once `b` is gone
+        let b_name = sqlx::query!(
+            r#"DELETE FROM street WHERE id = $1 AND area_id = $2 RETURNING name"#,
+            b.id,
+            self.area_id
+        )
+        .fetch_one(&mut *tx)
+        .await?
+        .name;
+        let record = sqlx::query!(
+            r#"UPDATE street SET name = COALESCE(name, $1)
+            WHERE id = $2 AND area_id = $3
+            RETURNING id as "id!: i64", name, verified"#,
+            b_name,
+            a.id,
+            self.area_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(Street {
+            id: record.id,
+            name: record.name,
+            verified: record.verified != 0,
+            _guard: (),
+        })
+    }
+
+    async fn split_street(&self, street: &Street, at_vertex: usize) -> anyhow::Result<(Street, Street)> {
+        let points = self
+            .get_street_polyline(street)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Street with id {} has no polyline", street.id))?
+            .points;
+        if at_vertex == 0 || at_vertex + 1 >= points.len() {
+            anyhow::bail!(
+                "Cannot split street {} at vertex {}: only vertices 1 to {} are interior",
+                street.id,
+                at_vertex,
+                points.len().saturating_sub(2)
+            );
+        }
+        let halves = [&points[..=at_vertex], &points[at_vertex..]];
+
+        let mut conn = self.state.conn_mut().await?;
+        let mut tx = conn.begin().await?;
+        let mut new_ids = [0i64; 2];
+        for (new_id, half) in new_ids.iter_mut().zip(halves) {
+            *new_id = sqlx::query!(
+                r#"INSERT INTO street (area_id) VALUES ($1) RETURNING id as "id!: i64""#,
+                self.area_id
+            )
+            .fetch_one(&mut *tx)
+            .await?
+            .id;
+            for (position, point) in half.iter().enumerate() {
+                let position = position as i64;
+                sqlx::query!(
+                    r#"INSERT INTO street_polyline_vertices (street_id, position, x, y) VALUES ($1, $2, $3, $4)"#,
+                    *new_id,
+                    position,
+                    point.x,
+                    point.y
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        let addresses = sqlx::query!(
+            r#"SELECT id as "id!: i64", x, y FROM address WHERE street_id = $1 AND area_id = $2"#,
+            street.id,
+            self.area_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        for address in addresses {
+            let position = Point {
+                x: address.x.try_into().expect("x coordinate bounded by database constraint"),
+                y: address.y.try_into().expect("y coordinate bounded by database constraint"),
+            };
+            let distance = |half: &[Point]| util::point_to_polyline_distance(&position, half).unwrap_or(f64::MAX);
+            // Ties go to the first half
+            let street_id = if distance(halves[1]) < distance(halves[0]) { new_ids[1] } else { new_ids[0] };
+            sqlx::query!(
+                r#"UPDATE address SET street_id = $1 WHERE id = $2"#,
+                street_id,
+                address.id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // The name can only move over once the original street is gone
+        sqlx::query!(
+            r#"DELETE FROM street WHERE id = $1 AND area_id = $2"#,
+            street.id,
+            self.area_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        let first = sqlx::query!(
+            r#"UPDATE street SET name = $1 WHERE id = $2 RETURNING id as "id!: i64", name, verified"#,
+            street.name,
+            new_ids[0]
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        let second = sqlx::query!(
+            r#"SELECT id as "id!: i64", name, verified FROM street WHERE id = $1"#,
+            new_ids[1]
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok((
+            Street {
+                id: first.id,
+                name: first.name,
+                verified: first.verified != 0,
+                _guard: (),
+            },
+            Street {
+                id: second.id,
+                name: second.name,
+                verified: second.verified != 0,
+                _guard: (),
+            },
+        ))
+    }
 }
 
 impl BoundAreaRepository for AreaDb {
     async fn get_area(&self) -> anyhow::Result<Area> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_readonly().await?;
         if let Some(record) = sqlx::query!(
             r#"SELECT id as "id!: i64", name, color, state FROM area WHERE id = $1"#,
             self.area_id
@@ -889,7 +2596,7 @@ impl BoundAreaRepository for AreaDb {
     }
 
     async fn update_area(&self, update: &area::AreaUpdate) -> anyhow::Result<Area> {
-        let mut conn = self.state.conn().await?;
+        let mut conn = self.state.conn_mut().await?;
         let color = update.color.map(i64::from);
         let state = update.state.map(i64::from);
         let record = sqlx::query!(
@@ -917,15 +2624,191 @@ impl BoundAreaRepository for AreaDb {
         })
     }
 
+    async fn advance_state(&self, to: AreaState) -> anyhow::Result<Area> {
+        let current = self.get_area().await?.state;
+        if current.next() != Some(to) && current.previous() != Some(to) {
+            let allowed: Vec<String> = [current.previous(), current.next()]
+                .into_iter()
+                .flatten()
+                .map(|state| format!("{:?}", state))
+                .collect();
+            anyhow::bail!(
+                "Cannot move area {} from {:?} to {:?}; allowed: {}",
+                self.area_id,
+                current,
+                to,
+                allowed.join(", ")
+            );
+        }
+        self.update_area(&area::AreaUpdate {
+            state: Some(to),
+            ..Default::default()
+        })
+        .await
+    }
+
     fn get_image(&self) -> &DynamicImage {
         &self.image
     }
 
-    async fn delete(self) -> anyhow::Result<()> {
-        let mut conn = self.state.conn().await?;
-        sqlx::query!(r#"DELETE FROM area WHERE id = $1"#, self.area_id)
+    async fn replace_image(&mut self, new_path: &Path) -> anyhow::Result<()> {
+        // Copy and decode the new image before taking a connection, so the file IO
+        // doesn't hold up other writers
+        let new_fname = self.state.store_area_image(new_path, false).await?;
+        let image = match self.state.load_area_image(&new_fname).await {
+            Result::Ok(image) => image,
+            Err(e) => {
+                self.state.delete_area_image(&new_fname).await?;
+                return Err(e);
+            }
+        };
+
+        let (old_fname, old_thumbnails, references) = {
+            let mut conn = self.state.conn_mut().await?;
+            let mut tx = conn.begin().await?;
+            let updated = replace_image_rows(&mut tx, self.area_id, &new_fname).await;
+            let (old_fname, old_thumbnails) = match updated {
+                Result::Ok(old) => old,
+                Err(e) => {
+                    drop(tx);
+                    drop(conn);
+                    self.state.delete_area_image(&new_fname).await?;
+                    return Err(e);
+                }
+            };
+            tx.commit().await?;
+
+            let references = sqlx::query!(
+                r#"SELECT COUNT(*) as "count!: i64" FROM area WHERE image_fname = $1"#,
+                old_fname
+            )
+            .fetch_one(&mut **conn)
+            .await?
+            .count;
+            (old_fname, old_thumbnails, references)
+        };
+
+        if references == 0 {
+            self.state.delete_area_image(&old_fname).await?;
+        }
+        for old_thumbnail in old_thumbnails {
+            self.state.delete_thumbnail(&old_thumbnail).await?;
+        }
+        // Detections for the old image can never be hit again
+        self.state.clear_area_detection_cache(self.area_id).await?;
+
+        self.image = image;
+        Ok(())
+    }
+
+    async fn get_georeference(&self) -> anyhow::Result<Option<Georeference>> {
+        let mut conn = self.state.conn_readonly().await?;
+        load_georeference(&mut conn, self.area_id).await
+    }
+
+    async fn set_georeference(&self, georeference: Option<&Georeference>) -> anyhow::Result<()> {
+        let mut conn = self.state.conn_mut().await?;
+        match georeference {
+            Some(georeference) => {
+                sqlx::query!(
+                    r#"INSERT INTO area_georeference (area_id, origin_lat, origin_lon, meters_per_pixel, rotation)
+                    VALUES ($1, $2, $3, $4, $5)
+                    ON CONFLICT (area_id) DO UPDATE SET
+                        origin_lat = excluded.origin_lat,
+                        origin_lon = excluded.origin_lon,
+                        meters_per_pixel = excluded.meters_per_pixel,
+                        rotation = excluded.rotation"#,
+                    self.area_id,
+                    georeference.origin_lat,
+                    georeference.origin_lon,
+                    georeference.meters_per_pixel,
+                    georeference.rotation
+                )
+                .execute(&mut **conn)
+                .await?;
+            }
+            None => {
+                sqlx::query!(r#"DELETE FROM area_georeference WHERE area_id = $1"#, self.area_id)
+                    .execute(&mut **conn)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn get_thumbnail(&self, max_dim: u32) -> anyhow::Result<DynamicImage> {
+        let (width, height) = (self.image.width(), self.image.height());
+        // Sizes at or above the image size all get the full image, so they share one entry
+        let expected_dim = max_dim.min(width.max(height));
+
+        let cached = {
+            let mut conn = self.state.conn_readonly().await?;
+            sqlx::query!(
+                "SELECT thumbnail_fname FROM area_thumbnail WHERE area_id = $1 AND max_dim = $2",
+                self.area_id,
+                expected_dim
+            )
+            .fetch_optional(&mut **conn)
+            .await?
+            .map(|record| record.thumbnail_fname)
+        };
+        if let Some(fname) = &cached {
+            if let Result::Ok(thumbnail) = self.state.load_thumbnail(fname).await {
+                if thumbnail.width().max(thumbnail.height()) == expected_dim {
+                    return Ok(thumbnail);
+                }
+            }
+        }
+
+        // Resizing a large map takes a while, so keep it off the async executor
+        let image = self.image.clone();
+        let thumbnail = tokio::task::spawn_blocking(move || {
+            if expected_dim == width.max(height) {
+                image
+            } else {
+                image.thumbnail(expected_dim, expected_dim)
+            }
+        })
+        .await
+        .context("Thumbnail resizing task failed")?;
+        let new_fname = self.state.store_thumbnail(&thumbnail).await?;
+        {
+            let mut conn = self.state.conn_mut().await?;
+            sqlx::query!(
+                r#"INSERT INTO area_thumbnail (area_id, max_dim, thumbnail_fname) VALUES ($1, $2, $3)
+                ON CONFLICT (area_id, max_dim) DO UPDATE SET thumbnail_fname = excluded.thumbnail_fname"#,
+                self.area_id,
+                expected_dim,
+                new_fname
+            )
             .execute(&mut **conn)
             .await?;
+        }
+        if let Some(old_fname) = cached {
+            self.state.delete_thumbnail(&old_fname).await?;
+        }
+        Ok(thumbnail)
+    }
+
+    async fn delete(self) -> anyhow::Result<()> {
+        let thumbnails = {
+            let mut conn = self.state.conn_mut().await?;
+            let thumbnails = sqlx::query!(
+                "SELECT thumbnail_fname FROM area_thumbnail WHERE area_id = $1",
+                self.area_id
+            )
+            .fetch_all(&mut **conn)
+            .await?;
+            sqlx::query!(r#"DELETE FROM area WHERE id = $1"#, self.area_id)
+                .execute(&mut **conn)
+                .await?;
+            thumbnails
+        };
+        // The rows went with the area; the cached files are not needed anymore either
+        for thumbnail in thumbnails {
+            self.state.delete_thumbnail(&thumbnail.thumbnail_fname).await?;
+        }
+        self.state.clear_area_detection_cache(self.area_id).await?;
         Ok(())
     }
 }
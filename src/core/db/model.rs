@@ -1,10 +1,53 @@
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Point {
     pub x: u32,
     pub y: u32,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+impl Point {
+    pub fn distance_squared(&self, other: &Point) -> f64 {
+        let dx = other.x as f64 - self.x as f64;
+        let dy = other.y as f64 - self.y as f64;
+        dx * dx + dy * dy
+    }
+
+    pub fn distance(&self, other: &Point) -> f64 {
+        self.distance_squared(other).sqrt()
+    }
+}
+
+/// Whether `p` lies inside the polygon `poly` (closed implicitly); points on an edge count as inside.
+/// Polygons with fewer than three vertices contain nothing.
+pub fn polygon_contains(poly: &[Point], p: &Point) -> bool {
+    if poly.len() < 3 {
+        return false;
+    }
+    let (px, py) = (p.x as i64, p.y as i64);
+    let mut inside = false;
+    for (i, a) in poly.iter().enumerate() {
+        let b = &poly[(i + 1) % poly.len()];
+        let (ax, ay, bx, by) = (a.x as i64, a.y as i64, b.x as i64, b.y as i64);
+
+        // On the edge: collinear and within the segment's bounding box
+        let cross = (bx - ax) * (py - ay) - (by - ay) * (px - ax);
+        if cross == 0 && px >= ax.min(bx) && px <= ax.max(bx) && py >= ay.min(by) && py <= ay.max(by) {
+            return true;
+        }
+
+        // Ray casting towards +x; half-open in y so shared vertices count once
+        if (ay > py) != (by > py) {
+            let x_at = ax as f64 + (py - ay) as f64 * (bx - ax) as f64 / (by - ay) as f64;
+            if (px as f64) < x_at {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+// serde is already a required dependency (pipeline metadata, config), so the derive
+// costs nothing extra and lets colors travel in JSON as plain r/g/b fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -33,12 +76,15 @@ impl Color {
     pub const WHITE: Color = Color { r: 255, g: 255, b: 255 };
     pub const BLACK: Color = Color { r: 0, g: 0, b: 0 };
 
+    /// Parse `#RGB`, `#RRGGBB` or `#RRGGBBAA` (alpha is ignored); the `#` is optional
     pub fn from_hex_string(hex: &str) -> Result<Self, anyhow::Error> {
         let hex = hex.trim_start_matches('#');
-        if (hex.len() != 6 && hex.len() != 3) || !hex.chars().all(|c| c.is_digit(16)) {
+        if !matches!(hex.len(), 3 | 6 | 8) || !hex.chars().all(|c| c.is_digit(16)) {
             return Err(anyhow::anyhow!("Invalid hex color string"));
         }
-        if hex.len() == 3 {
+        if hex.len() == 8 {
+            Color::from_hex_string(&hex[0..6])
+        } else if hex.len() == 3 {
             let r = &hex[0..1];
             let g = &hex[1..2];
             let b = &hex[2..3];
@@ -57,4 +103,75 @@ impl Color {
     pub fn to_hex_string(&self) -> String {
         format!("#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
     }
-}
\ No newline at end of file
+
+    /// WCAG relative luminance from 0.0 (black) to 1.0 (white): the sRGB channels are
+    /// linearized before the Rec. 709 weights are applied
+    pub fn luminance(&self) -> f32 {
+        let linear = |c: u8| {
+            let c = c as f32 / 255.0;
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * linear(self.r) + 0.7152 * linear(self.g) + 0.0722 * linear(self.b)
+    }
+
+    /// Black or white, whichever has the higher WCAG contrast ratio with this color
+    pub fn contrasting_text(&self) -> Color {
+        let luminance = self.luminance();
+        // Contrast ratio is (lighter + 0.05) / (darker + 0.05)
+        if (luminance + 0.05) / 0.05 >= 1.05 / (luminance + 0.05) {
+            Color::BLACK
+        } else {
+            Color::WHITE
+        }
+    }
+
+    /// Euclidean distance in RGB space
+    pub fn distance(&self, other: &Color) -> f64 {
+        let dr = self.r as f64 - other.r as f64;
+        let dg = self.g as f64 - other.g as f64;
+        let db = self.b as f64 - other.b as f64;
+        (dr * dr + dg * dg + db * db).sqrt()
+    }
+
+    /// The `AREA_PALETTE` color farthest from its nearest color in `existing`.
+    /// Ties go to the earlier palette entry, so without existing colors this is the first one.
+    pub fn most_distinct(existing: &[Color]) -> Color {
+        let mut best = AREA_PALETTE[0];
+        let mut best_distance = f64::NEG_INFINITY;
+        for candidate in AREA_PALETTE {
+            let distance = existing
+                .iter()
+                .map(|color| candidate.distance(color))
+                .fold(f64::INFINITY, f64::min);
+            if distance > best_distance {
+                best = candidate;
+                best_distance = distance;
+            }
+        }
+        best
+    }
+}
+
+/// Area colors that stay easy to tell apart on the map overlay
+pub const AREA_PALETTE: [Color; 16] = [
+    Color { r: 230, g: 25, b: 75 },
+    Color { r: 60, g: 180, b: 75 },
+    Color { r: 255, g: 225, b: 25 },
+    Color { r: 0, g: 130, b: 200 },
+    Color { r: 245, g: 130, b: 48 },
+    Color { r: 145, g: 30, b: 180 },
+    Color { r: 70, g: 240, b: 240 },
+    Color { r: 240, g: 50, b: 230 },
+    Color { r: 210, g: 245, b: 60 },
+    Color { r: 250, g: 190, b: 212 },
+    Color { r: 0, g: 128, b: 128 },
+    Color { r: 220, g: 190, b: 255 },
+    Color { r: 170, g: 110, b: 40 },
+    Color { r: 128, g: 0, b: 0 },
+    Color { r: 0, g: 0, b: 128 },
+    Color { r: 128, g: 128, b: 0 },
+];
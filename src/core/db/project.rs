@@ -4,15 +4,49 @@ use time::OffsetDateTime;
 
 use crate::core::db::AreaRepository;
 
+/// Metadata keys managed through [`UpdateProjectSettings`]; `set_metadata` refuses them.
+pub const RESERVED_METADATA_KEYS: [&str; 3] = ["name", "created_at", "target_address_count"];
+
 pub struct UpdateProjectSettings {
     pub name: Option<String>,
     pub target_address_count: Option<u64>,
     pub created_at: Option<OffsetDateTime>,
 }
 
+/// Address counts over some set of addresses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AddressStats {
+    pub total: u64,
+    pub verified: u64,
+    pub assigned_to_team: u64,
+    /// Sum of `estimated_flats`; addresses without an estimate count as zero.
+    pub estimated_flats: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AreaStats {
+    pub area_id: i64,
+    pub name: String,
+    pub addresses: AddressStats,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectStats {
+    pub totals: AddressStats,
+    /// One entry per area, including areas without addresses, ordered by id.
+    pub areas: Vec<AreaStats>,
+}
+
 pub trait ProjectRepository: AreaRepository {
     fn get_project_name(&self) -> impl Future<Output = anyhow::Result<String>>;
     fn get_project_created_at(&self) -> impl Future<Output = anyhow::Result<OffsetDateTime>>;
     fn get_target_address_count(&self) -> impl Future<Output = anyhow::Result<u64>>;
     fn set_project_settings(&self, settings: UpdateProjectSettings) -> impl Future<Output = anyhow::Result<()>>;
+    /// Value stored under an arbitrary metadata key, if any.
+    fn get_metadata(&self, key: &str) -> impl Future<Output = anyhow::Result<Option<String>>>;
+    /// Store a value under an arbitrary metadata key, replacing any previous value.
+    /// Fails for [`RESERVED_METADATA_KEYS`], which are set through `set_project_settings`.
+    fn set_metadata(&self, key: &str, value: &str) -> impl Future<Output = anyhow::Result<()>>;
+    /// Address progress counts for the whole project and per area.
+    fn get_statistics(&self) -> impl Future<Output = anyhow::Result<ProjectStats>>;
 }
\ No newline at end of file
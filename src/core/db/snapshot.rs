@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+
+use crate::core::db::{address::Address, area::Area, street::Street, team::Team};
+
+/// Point-in-time copy of every area, address, street and team in a project, keyed by id.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProjectSnapshot {
+    pub areas: BTreeMap<i64, Area>,
+    pub addresses: BTreeMap<i64, Address>,
+    pub streets: BTreeMap<i64, Street>,
+    pub teams: BTreeMap<i64, Team>,
+}
+
+/// Records added, removed or changed between two snapshots of one table.
+/// Changed records are reported as `(before, after)` pairs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordDiff<T> {
+    pub added: Vec<T>,
+    pub removed: Vec<T>,
+    pub changed: Vec<(T, T)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectDiff {
+    pub areas: RecordDiff<Area>,
+    pub addresses: RecordDiff<Address>,
+    pub streets: RecordDiff<Street>,
+    pub teams: RecordDiff<Team>,
+}
+
+impl ProjectSnapshot {
+    /// Compare this snapshot (before) against `other` (after).
+    pub fn diff(&self, other: &ProjectSnapshot) -> ProjectDiff {
+        ProjectDiff {
+            areas: diff_records(&self.areas, &other.areas),
+            addresses: diff_records(&self.addresses, &other.addresses),
+            streets: diff_records(&self.streets, &other.streets),
+            teams: diff_records(&self.teams, &other.teams),
+        }
+    }
+}
+
+impl<T> RecordDiff<T> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl ProjectDiff {
+    pub fn is_empty(&self) -> bool {
+        self.areas.is_empty()
+            && self.addresses.is_empty()
+            && self.streets.is_empty()
+            && self.teams.is_empty()
+    }
+}
+
+fn diff_records<T: Clone + PartialEq>(
+    before: &BTreeMap<i64, T>,
+    after: &BTreeMap<i64, T>,
+) -> RecordDiff<T> {
+    let mut diff = RecordDiff {
+        added: vec![],
+        removed: vec![],
+        changed: vec![],
+    };
+    for (id, old) in before {
+        match after.get(id) {
+            None => diff.removed.push(old.clone()),
+            Some(new) if new != old => diff.changed.push((old.clone(), new.clone())),
+            Some(_) => {}
+        }
+    }
+    for (id, new) in after {
+        if !before.contains_key(id) {
+            diff.added.push(new.clone());
+        }
+    }
+    diff
+}
@@ -1,82 +1,215 @@
-use image::DynamicImage;
+use image::{
+    DynamicImage, ImageFormat,
+    codecs::{
+        jpeg::JpegEncoder,
+        png::{CompressionType, FilterType as PngFilterType, PngEncoder},
+    },
+};
 use sqlx::{
     Connection, Sqlite, Transaction, pool::PoolConnection, sqlite::{
-        SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions, SqliteSynchronous,
+        SqliteConnectOptions, SqliteConnection, SqliteJournalMode, SqlitePool, SqlitePoolOptions,
+        SqliteSynchronous,
     }
 };
 use tempdir::TempDir;
 use tokio::{
     fs as async_fs,
-    sync::{RwLock, RwLockReadGuard},
+    sync::{Mutex, OwnedMutexGuard, RwLock, RwLockReadGuard},
 };
 
 use std::{
     fs::{self, File},
+    io::BufWriter,
     ops::{Deref, DerefMut},
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc, Arc, Weak,
+    },
+    time::Duration,
 };
 use uuid::Uuid;
 use anyhow::Context;
 
+use crate::models::HouseNumberDetection;
+
 // NEW imports for tar + zstd
 use tar::{Archive, Builder};
 use zstd::stream::{read::Decoder as ZstdDecoder, write::Encoder as ZstdEncoder};
 
 const DB_FILE_NAME: &str = "project.db";
 const IMAGE_DIR_NAME: &str = "images";
+const THUMBNAIL_DIR_NAME: &str = "thumbnails";
+const DETECTION_CACHE_DIR_NAME: &str = "detection_cache";
+
+/// How long dropping a project waits for the background save before giving up on it.
+const DROP_SAVE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Maximum number of connections in each of the two pools.
+const MAX_POOL_CONNECTIONS: u32 = 5;
+
+/// The connection pools of an open project, both on the working copy's database file.
+///
+/// Locking: every connection is handed out while holding the `ProjectState::pool`
+/// read lock, and the guard keeps it until the connection is returned. Any number of
+/// readers, writers and transactions share the read lock; only saving and optimizing
+/// take the write lock, which waits for all of them to finish. SQLite itself still
+/// serializes writers, while WAL mode lets readers run alongside a writer, so reads
+/// go through `read` and never queue behind the connections of `write`.
+#[derive(Debug, Clone)]
+struct Pools {
+    write: SqlitePool,
+    read: SqlitePool,
+}
+
+impl Pools {
+    /// Open both pools on `db_file`, creating the database if it does not exist yet.
+    async fn open(db_file: &Path) -> anyhow::Result<Self> {
+        let connect_opts = SqliteConnectOptions::new()
+            .filename(db_file)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .synchronous(SqliteSynchronous::Normal)
+            .foreign_keys(true);
+
+        let write = SqlitePoolOptions::new()
+            .max_connections(MAX_POOL_CONNECTIONS)
+            .connect_with(connect_opts)
+            .await?;
+        // Migrations must have run before read-only connections see the schema
+        sqlx::migrate!("./migrations").run(&write).await?;
+
+        // The journal mode is stored in the file, so read-only connections pick up WAL
+        let read_opts = SqliteConnectOptions::new()
+            .filename(db_file)
+            .read_only(true)
+            .foreign_keys(true);
+        let read = SqlitePoolOptions::new()
+            .max_connections(MAX_POOL_CONNECTIONS)
+            .connect_with(read_opts)
+            .await?;
+        Ok(Self { write, read })
+    }
+
+    async fn close(&self) {
+        self.read.close().await;
+        self.write.close().await;
+    }
+}
 
 pub(super) struct ProjectState {
     project_file: PathBuf,
-    working_dir: TempDir,
-    pool: RwLock<SqlitePool>,
+    /// Only `None` while being dropped, when the save thread takes ownership.
+    working_dir: Option<TempDir>,
+    pool: RwLock<Pools>,
+    /// Set when data changed since the last save.
+    dirty: AtomicBool,
+    /// Detection runs answered from the detection cache since the project was opened.
+    detection_cache_hits: AtomicUsize,
 }
 
 impl std::fmt::Debug for ProjectState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ProjectState")
             .field("project_file", &self.project_file)
-            .field("working_dir", &self.working_dir.path())
+            .field("working_dir", &self.working_dir())
+            .field("dirty", &self.is_dirty())
             .finish()
     }
 }
 
 impl ProjectState {
+    fn working_dir(&self) -> &Path {
+        self.working_dir
+            .as_ref()
+            .expect("working dir is only taken when dropping")
+            .path()
+    }
+
     /// Acquire a pooled connection and hold the pool read lock for the entire lifetime
     /// of the returned guard.
     pub(super) async fn conn(&self) -> anyhow::Result<DbConnGuard<'_>> {
-        let pool_guard = self.pool.read().await;
+        let pool_guard = RwLockReadGuard::map(self.pool.read().await, |pools| &pools.write);
 
         // IMPORTANT: acquire the connection while the read lock is held.
         // The lock will remain held because we store it in DbConnGuard.
         let conn = pool_guard.acquire().await?;
 
         Ok(DbConnGuard {
-            _pool_guard: pool_guard,
-            conn,
+            _pool_guard: Some(pool_guard),
+            conn: DbConn::Pooled(conn),
         })
     }
 
+    /// Like `conn`, but from the read-only pool, for queries that only read.
+    /// Writing through the returned connection fails.
+    pub(super) async fn conn_readonly(&self) -> anyhow::Result<DbConnGuard<'_>> {
+        let pool_guard = RwLockReadGuard::map(self.pool.read().await, |pools| &pools.read);
+        let conn = pool_guard.acquire().await?;
+
+        Ok(DbConnGuard {
+            _pool_guard: Some(pool_guard),
+            conn: DbConn::Pooled(conn),
+        })
+    }
+
+    /// Like `conn`, but for queries that modify the project: marks it dirty so the
+    /// next `save_if_dirty` packs it.
+    pub(super) async fn conn_mut(&self) -> anyhow::Result<DbConnGuard<'_>> {
+        self.mark_dirty();
+        self.conn().await
+    }
+
+    fn mark_dirty(&self) {
+        self.dirty.store(true, Ordering::SeqCst);
+    }
+
+    /// Start a transaction spanning several repository calls.
+    /// The returned pool read lock must outlive the transaction, so saving waits for it.
+    pub(super) async fn begin(
+        &self,
+    ) -> anyhow::Result<(RwLockReadGuard<'_, SqlitePool>, Transaction<'static, Sqlite>)> {
+        self.mark_dirty();
+        let pool_guard = RwLockReadGuard::map(self.pool.read().await, |pools| &pools.write);
+        let tx = pool_guard.begin().await?;
+        Ok((pool_guard, tx))
+    }
+
+    pub(super) fn is_dirty(&self) -> bool {
+        self.dirty.load(Ordering::SeqCst)
+    }
+
     /// Load the image associated with the given area.
     pub(super) async fn load_area_image(
         &self,
         area_image_fname: &str,
     ) -> anyhow::Result<DynamicImage> {
         let area_img_path = self
-            .working_dir
-            .path()
+            .working_dir()
             .join(IMAGE_DIR_NAME)
             .join(area_image_fname);
-        let img = image::open(&area_img_path)
-            .with_context(|| format!("Failed to open area image {:?}", area_img_path))?;
-        Ok(img)
+        decode_image_file(&area_img_path)
+            .await
+            .with_context(|| format!("Failed to open area image {:?}", area_img_path))
     }
 
     /// Save an image for the given area, returning the filename used.
+    ///
+    /// With `reencode` set the image is decoded and written back in a compact
+    /// form instead of being copied verbatim, see [`reencode_image`].
     pub(super) async fn store_area_image<P: AsRef<Path>>(
         &self,
         img_path: P,
+        reencode: bool,
     ) -> anyhow::Result<String> {
-        let images_dir = self.working_dir.path().join(IMAGE_DIR_NAME);
+        let images_dir = self.working_dir().join(IMAGE_DIR_NAME);
+
+        if reencode {
+            let src = img_path.as_ref().to_path_buf();
+            return tokio::task::spawn_blocking(move || reencode_image(&src, &images_dir))
+                .await
+                .context("Image re-encoding task failed")?;
+        }
 
         let img_fname = img_path
             .as_ref()
@@ -95,10 +228,15 @@ impl ProjectState {
         Ok(img_fname)
     }
 
+    /// Copy a stored area image under a new name, returning the filename used.
+    pub(super) async fn copy_area_image(&self, area_image_fname: &str) -> anyhow::Result<String> {
+        let src = self.working_dir().join(IMAGE_DIR_NAME).join(area_image_fname);
+        self.store_area_image(src, false).await
+    }
+
     pub(super) async fn delete_area_image(&self, area_image_fname: &str) -> anyhow::Result<()> {
         let area_img_path = self
-            .working_dir
-            .path()
+            .working_dir()
             .join(IMAGE_DIR_NAME)
             .join(area_image_fname);
         async_fs::remove_file(&area_img_path)
@@ -107,36 +245,126 @@ impl ProjectState {
         Ok(())
     }
 
-    /// Create a tar.zst archive from the working directory.
-    fn save_tar_zstd(&self) -> anyhow::Result<()> {
-        if let Some(parent) = self.project_file.parent() {
-            fs::create_dir_all(parent)?;
-        }
+    pub(super) async fn load_thumbnail(&self, thumbnail_fname: &str) -> anyhow::Result<DynamicImage> {
+        let thumbnail_path = self
+            .working_dir()
+            .join(THUMBNAIL_DIR_NAME)
+            .join(thumbnail_fname);
+        decode_image_file(&thumbnail_path)
+            .await
+            .with_context(|| format!("Failed to open thumbnail {:?}", thumbnail_path))
+    }
 
-        let out = File::create(&self.project_file)
-            .with_context(|| format!("Failed to create project archive {:?}", self.project_file))?;
+    /// Save a thumbnail as PNG, returning the filename used.
+    pub(super) async fn store_thumbnail(&self, thumbnail: &DynamicImage) -> anyhow::Result<String> {
+        let thumbnails_dir = self.working_dir().join(THUMBNAIL_DIR_NAME);
+        async_fs::create_dir_all(&thumbnails_dir)
+            .await
+            .with_context(|| format!("Failed to create thumbnail dir {:?}", thumbnails_dir))?;
+
+        let thumbnail_fname = format!("{}.png", Uuid::new_v4());
+        let dest_path = thumbnails_dir.join(&thumbnail_fname);
+        // PNG encoding is CPU bound, so keep it off the async executor
+        let thumbnail = thumbnail.clone();
+        let bytes = tokio::task::spawn_blocking(move || {
+            let mut bytes = Vec::new();
+            thumbnail.write_to(&mut std::io::Cursor::new(&mut bytes), ImageFormat::Png)?;
+            anyhow::Ok(bytes)
+        })
+        .await
+        .context("Thumbnail encoding task failed")??;
+        async_fs::write(&dest_path, bytes)
+            .await
+            .with_context(|| format!("Failed to save thumbnail {:?}", dest_path))?;
+        Ok(thumbnail_fname)
+    }
 
-        // zstd encoder wrapping the output file
-        let encoder = ZstdEncoder::new(out, 3)
-            .with_context(|| format!("Failed to create zstd encoder for {:?}", self.project_file))?;
+    /// Remove a cached thumbnail; a thumbnail that is already gone is not an error.
+    pub(super) async fn delete_thumbnail(&self, thumbnail_fname: &str) -> anyhow::Result<()> {
+        let thumbnail_path = self
+            .working_dir()
+            .join(THUMBNAIL_DIR_NAME)
+            .join(thumbnail_fname);
+        match async_fs::remove_file(&thumbnail_path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e)
+                .with_context(|| format!("Failed to delete thumbnail {:?}", thumbnail_path)),
+            _ => Ok(()),
+        }
+    }
 
-        // tar builder wrapping the encoder
-        let mut tar = Builder::new(encoder);
+    /// Cache entries are grouped per area, so they can be dropped with the area or its image
+    fn area_detection_cache_dir(&self, area_id: i64) -> PathBuf {
+        self.working_dir()
+            .join(DETECTION_CACHE_DIR_NAME)
+            .join(area_id.to_string())
+    }
 
-        // Add entire working directory
-        tar.append_dir_all(".", self.working_dir.path())
-            .with_context(|| format!("Failed to add {:?} to tar", self.working_dir.path()))?;
+    fn detection_cache_path(&self, area_id: i64, key: &str) -> PathBuf {
+        self.area_detection_cache_dir(area_id).join(format!("{key}.json"))
+    }
 
-        // Finish tar, then finish zstd stream
-        let encoder = tar.into_inner()
-            .with_context(|| format!("Failed to finalize tar for {:?}", self.project_file))?;
+    /// Detections cached for area `area_id` under `key`, or `None` on a cache miss.
+    /// An unreadable cache entry is reported and treated as a miss.
+    pub(super) async fn load_cached_detections(
+        &self,
+        area_id: i64,
+        key: &str,
+    ) -> anyhow::Result<Option<Vec<HouseNumberDetection>>> {
+        let cache_path = self.detection_cache_path(area_id, key);
+        let json = match async_fs::read(&cache_path).await {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read detection cache {:?}", cache_path))
+            }
+        };
+        match serde_json::from_slice(&json) {
+            Ok(detections) => {
+                self.detection_cache_hits.fetch_add(1, Ordering::Relaxed);
+                Ok(Some(detections))
+            }
+            Err(e) => {
+                log::warn!("Ignoring corrupt detection cache {:?}: {}", cache_path, e);
+                Ok(None)
+            }
+        }
+    }
 
-        encoder.finish()
-            .with_context(|| format!("Failed to finalize zstd stream for {:?}", self.project_file))?;
+    /// Cache detections for area `area_id` under `key`. The cache lives in the working dir,
+    /// so it is saved with the project, but storing does not mark the project dirty.
+    pub(super) async fn store_cached_detections(
+        &self,
+        area_id: i64,
+        key: &str,
+        detections: &[HouseNumberDetection],
+    ) -> anyhow::Result<()> {
+        let cache_dir = self.area_detection_cache_dir(area_id);
+        async_fs::create_dir_all(&cache_dir)
+            .await
+            .with_context(|| format!("Failed to create detection cache dir {:?}", cache_dir))?;
 
+        let cache_path = self.detection_cache_path(area_id, key);
+        let json = serde_json::to_vec(detections)?;
+        async_fs::write(&cache_path, json)
+            .await
+            .with_context(|| format!("Failed to write detection cache {:?}", cache_path))?;
         Ok(())
     }
 
+    /// Remove all cached detections; a missing cache is not an error.
+    pub(super) async fn clear_detection_cache(&self) -> anyhow::Result<()> {
+        remove_cache_dir(&self.working_dir().join(DETECTION_CACHE_DIR_NAME)).await
+    }
+
+    /// Remove the cached detections of area `area_id`, e.g. after its image changed.
+    pub(super) async fn clear_area_detection_cache(&self, area_id: i64) -> anyhow::Result<()> {
+        remove_cache_dir(&self.area_detection_cache_dir(area_id)).await
+    }
+
+    pub(super) fn detection_cache_hits(&self) -> usize {
+        self.detection_cache_hits.load(Ordering::Relaxed)
+    }
+
     /// Exclusive close+pack:
     /// - waits for all in-flight read queries (because it takes a WRITE lock)
     /// - checkpoints WAL to ensure project.db is current
@@ -151,38 +379,38 @@ impl ProjectState {
         // this guarantees no queries run while we checkpoint/close/pack.
         let mut pool_guard = self.pool.write().await;
 
-        // Flush WAL into main DB and truncate it
-        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE);")
-            .execute(&*pool_guard)
-            .await?;
-
-        // Release file handles (important on Windows); this is "final".
-        // After this, any DB use will fail unless you re-open a new pool.
-        pool_guard.close().await;
-
-        // Now pack files (db file is stable and handles should be released).
-        // Note: this is synchronous IO; consider spawn_blocking for large projects.
-        self.save_tar_zstd()?;
+        // No writer can hold a connection now, so everything up to here gets packed
+        self.dirty.store(false, Ordering::SeqCst);
+        if let Err(e) = close_and_pack(&pool_guard, self.working_dir(), &self.project_file).await {
+            self.dirty.store(true, Ordering::SeqCst);
+            return Err(e);
+        }
 
-        // Now re-open the pool for any future use.
+        // Now re-open the pools for any future use.
         if reopen {
-            let db_file = self.working_dir.path().join(DB_FILE_NAME);
-            let connect_opts = SqliteConnectOptions::new()
-                .filename(&db_file)
-                .create_if_missing(true)
-                .journal_mode(SqliteJournalMode::Wal)
-                .synchronous(SqliteSynchronous::Normal)
-                .foreign_keys(true);
-
-            let pool = SqlitePoolOptions::new()
-                .max_connections(5)
-                .connect_with(connect_opts)
-                .await?;
-            *pool_guard = pool;
+            let db_file = self.working_dir().join(DB_FILE_NAME);
+            *pool_guard = Pools::open(&db_file).await?;
         }
         Ok(())
     }
 
+    /// Refresh query planner statistics and rebuild the database file without free pages.
+    pub(super) async fn optimize(&self) -> anyhow::Result<()> {
+        // VACUUM fails while other connections are reading, so keep every query out
+        let pool_guard = self.pool.write().await;
+        let mut conn = pool_guard.write.acquire().await?;
+        self.mark_dirty();
+        sqlx::query("PRAGMA optimize;")
+            .execute(&mut *conn)
+            .await
+            .context("Failed to optimize database")?;
+        sqlx::query("VACUUM;")
+            .execute(&mut *conn)
+            .await
+            .context("Failed to vacuum database")?;
+        Ok(())
+    }
+
     pub(super) async fn new<P: AsRef<Path>>(project_file: P) -> anyhow::Result<Self> {
         let project_file = project_file.as_ref().to_path_buf();
 
@@ -251,33 +479,244 @@ impl ProjectState {
             ),
         }
 
-        let connect_opts = SqliteConnectOptions::new()
-            .filename(&db_file)
-            .create_if_missing(true)
-            .journal_mode(SqliteJournalMode::Wal)
-            .synchronous(SqliteSynchronous::Normal)
-            .foreign_keys(true);
-
-        let pool = SqlitePoolOptions::new()
-            .max_connections(5)
-            .connect_with(connect_opts)
-            .await?;
-        sqlx::migrate!("./migrations").run(&pool).await?;
+        let pools = Pools::open(&db_file).await?;
         Ok(Self {
             project_file,
-            working_dir,
-            pool: RwLock::new(pool),
+            working_dir: Some(working_dir),
+            pool: RwLock::new(pools),
+            // Migrations may have changed the database, so start out unsaved
+            dirty: AtomicBool::new(true),
+            detection_cache_hits: AtomicUsize::new(0),
         })
     }
 }
 
+/// Checkpoint the WAL, close the pools and pack the working directory into `project_file`.
+async fn close_and_pack(pools: &Pools, working_dir: &Path, project_file: &Path) -> anyhow::Result<()> {
+    // Flush WAL into main DB and truncate it
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE);")
+        .execute(&pools.write)
+        .await?;
+
+    // Release file handles (important on Windows); this is "final".
+    // After this, any DB use will fail unless you re-open new pools.
+    pools.close().await;
+
+    // Now pack files (db file is stable and handles should be released).
+    // tar+zstd is synchronous IO, so keep it off the async executor.
+    let working_dir = working_dir.to_path_buf();
+    let project_file = project_file.to_path_buf();
+    tokio::task::spawn_blocking(move || save_tar_zstd(&working_dir, &project_file))
+        .await
+        .context("Project packing task failed")?
+}
+
+/// Create a tar.zst archive from the working directory.
+fn save_tar_zstd(working_dir: &Path, project_file: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = project_file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Write next to the project file and rename into place, so a reader never sees a
+    // half-written archive (e.g. when reopening while a save from drop is running).
+    let mut tmp_name = project_file.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".saving");
+    let tmp_file = project_file.with_file_name(tmp_name);
+
+    let result = write_tar_zstd(working_dir, &tmp_file)
+        .and_then(|()| {
+            fs::rename(&tmp_file, project_file)
+                .with_context(|| format!("Failed to move {:?} to {:?}", tmp_file, project_file))
+        });
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_file);
+    }
+    result
+}
+
+fn write_tar_zstd(working_dir: &Path, project_file: &Path) -> anyhow::Result<()> {
+    let out = File::create(project_file)
+        .with_context(|| format!("Failed to create project archive {:?}", project_file))?;
+
+    // zstd encoder wrapping the output file
+    let encoder = ZstdEncoder::new(out, 3)
+        .with_context(|| format!("Failed to create zstd encoder for {:?}", project_file))?;
+
+    // tar builder wrapping the encoder
+    let mut tar = Builder::new(encoder);
+
+    // Add entire working directory
+    tar.append_dir_all(".", working_dir)
+        .with_context(|| format!("Failed to add {:?} to tar", working_dir))?;
+
+    // Finish tar, then finish zstd stream
+    let encoder = tar.into_inner()
+        .with_context(|| format!("Failed to finalize tar for {:?}", project_file))?;
+
+    encoder.finish()
+        .with_context(|| format!("Failed to finalize zstd stream for {:?}", project_file))?;
+
+    Ok(())
+}
+
+/// Transaction shared by all repository handles created inside `ProjectDb::transaction`.
+pub(super) type SharedTransaction = Arc<Mutex<Transaction<'static, Sqlite>>>;
+
+/// Project state as seen by a repository handle.
+///
+/// Handles created inside `ProjectDb::transaction` carry the running transaction,
+/// and every connection they ask for is that transaction's connection.
+#[derive(Clone)]
+pub(super) struct StateRef {
+    state: Arc<ProjectState>,
+    tx: Option<SharedTransaction>,
+}
+
+impl std::fmt::Debug for StateRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StateRef")
+            .field("state", &self.state)
+            .field("in_transaction", &self.tx.is_some())
+            .finish()
+    }
+}
+
+impl StateRef {
+    pub(super) fn new(state: ProjectState) -> Self {
+        Self {
+            state: Arc::new(state),
+            tx: None,
+        }
+    }
+
+    pub(super) fn downgrade(&self) -> Weak<ProjectState> {
+        Arc::downgrade(&self.state)
+    }
+
+    pub(super) fn upgrade(state: &Weak<ProjectState>) -> Option<Self> {
+        state.upgrade().map(|state| Self { state, tx: None })
+    }
+
+    /// Weak handle that keeps neither the project nor a running transaction alive.
+    pub(super) fn to_weak(&self) -> WeakStateRef {
+        WeakStateRef {
+            state: Arc::downgrade(&self.state),
+            tx: self.tx.as_ref().map(Arc::downgrade),
+        }
+    }
+
+    pub(super) fn in_transaction(&self) -> bool {
+        self.tx.is_some()
+    }
+
+    /// The same state, routing all connections through `tx`.
+    pub(super) fn with_transaction(&self, tx: SharedTransaction) -> Self {
+        Self {
+            state: self.state.clone(),
+            tx: Some(tx),
+        }
+    }
+
+    /// Like [`ProjectState::conn`], but inside a transaction this waits for and
+    /// returns the transaction's connection instead of a pooled one.
+    pub(super) async fn conn(&self) -> anyhow::Result<DbConnGuard<'_>> {
+        match &self.tx {
+            Some(tx) => Ok(DbConnGuard {
+                // The transaction already holds the pool read lock
+                _pool_guard: None,
+                conn: DbConn::Transaction(tx.clone().lock_owned().await),
+            }),
+            None => self.state.conn().await,
+        }
+    }
+
+    /// Like [`ProjectState::conn_readonly`]. Inside a transaction reads must see its
+    /// uncommitted changes, so they use the transaction's connection as well.
+    pub(super) async fn conn_readonly(&self) -> anyhow::Result<DbConnGuard<'_>> {
+        match &self.tx {
+            Some(_) => self.conn().await,
+            None => self.state.conn_readonly().await,
+        }
+    }
+
+    pub(super) async fn conn_mut(&self) -> anyhow::Result<DbConnGuard<'_>> {
+        self.state.mark_dirty();
+        self.conn().await
+    }
+
+    /// Saving needs the pool write lock, which the running transaction holds for reading.
+    pub(super) async fn save_project(&self) -> anyhow::Result<()> {
+        if self.in_transaction() {
+            anyhow::bail!("Cannot save the project while a transaction is running");
+        }
+        self.state.save_project().await
+    }
+
+    /// Like saving, optimizing needs the pool write lock.
+    pub(super) async fn optimize(&self) -> anyhow::Result<()> {
+        if self.in_transaction() {
+            anyhow::bail!("Cannot optimize the database while a transaction is running");
+        }
+        self.state.optimize().await
+    }
+}
+
+/// A [`StateRef`] that doesn't keep the project open, see [`StateRef::to_weak`].
+pub(super) struct WeakStateRef {
+    state: Weak<ProjectState>,
+    tx: Option<Weak<Mutex<Transaction<'static, Sqlite>>>>,
+}
+
+impl WeakStateRef {
+    /// `None` once the project was dropped, or the transaction it was taken in has ended.
+    pub(super) fn upgrade(&self) -> Option<StateRef> {
+        let state = self.state.upgrade()?;
+        let tx = match &self.tx {
+            Some(tx) => Some(tx.upgrade()?),
+            None => None,
+        };
+        Some(StateRef { state, tx })
+    }
+}
+
+impl Deref for StateRef {
+    type Target = ProjectState;
+    fn deref(&self) -> &Self::Target {
+        &self.state
+    }
+}
+
+pub enum DbConn {
+    Pooled(PoolConnection<Sqlite>),
+    Transaction(OwnedMutexGuard<Transaction<'static, Sqlite>>),
+}
+
+impl Deref for DbConn {
+    type Target = SqliteConnection;
+    fn deref(&self) -> &Self::Target {
+        match self {
+            DbConn::Pooled(conn) => conn,
+            DbConn::Transaction(tx) => tx,
+        }
+    }
+}
+
+impl DerefMut for DbConn {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            DbConn::Pooled(conn) => conn,
+            DbConn::Transaction(tx) => tx,
+        }
+    }
+}
+
 pub struct DbConnGuard<'a> {
-    _pool_guard: RwLockReadGuard<'a, SqlitePool>,
-    conn: PoolConnection<Sqlite>,
+    _pool_guard: Option<RwLockReadGuard<'a, SqlitePool>>,
+    conn: DbConn,
 }
 
 impl<'a> Deref for DbConnGuard<'a> {
-    type Target = PoolConnection<Sqlite>;
+    type Target = DbConn;
     fn deref(&self) -> &Self::Target {
         &self.conn
     }
@@ -290,30 +729,47 @@ impl<'a> DerefMut for DbConnGuard<'a> {
 }
 
 impl Drop for ProjectState {
+    /// Save-on-drop is best-effort; call `save_project()` explicitly where possible
+    /// so errors can be handled instead of only being logged.
+    ///
+    /// Inside a Tokio runtime the save runs on a detached thread and drop returns
+    /// right away, so the archive may land on disk a little later. Outside a runtime
+    /// drop waits up to `DROP_SAVE_TIMEOUT` for the save to finish.
     fn drop(&mut self) {
-        // Try to save using existing runtime, fall back to creating one if needed
-        let result = if let Ok(handle) = tokio::runtime::Handle::try_current() {
-            // We're in a Tokio runtime context, but we can't block_on from within
-            // a runtime. Spawn a blocking task instead.
-            std::thread::spawn(move || {
-                // This won't work either - we need to just skip save-on-drop in async context
-                // and rely on explicit save() calls
-            });
-            // For now, skip save when already in async context
-            // Users should call save_project() explicitly before dropping
-            Ok(())
-        } else {
-            // No runtime available, create a temporary one for cleanup
-            // This is heavyweight but ensures save-on-drop semantics are preserved
-            match tokio::runtime::Runtime::new() {
-                Ok(rt) => rt.block_on(async { self.internal_close_and_pack(false).await }),
-                Err(e) => Err(e.into()),
-            }
+        let Some(working_dir) = self.working_dir.take() else {
+            return;
         };
+        if !self.is_dirty() {
+            return;
+        }
+        let pool = self.pool.get_mut().clone();
+        let project_file = self.project_file.clone();
+
+        // Blocking on the save is not allowed on an executor thread, so run it on a
+        // thread with its own runtime. The thread owns the working dir, so it stays
+        // around until packing finishes.
+        let (done_tx, done_rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(anyhow::Error::from)
+                .and_then(|rt| rt.block_on(close_and_pack(&pool, working_dir.path(), &project_file)));
+            if let Err(e) = &result {
+                log::warn!("Failed to save project {:?} on drop: {:#}", project_file, e);
+            }
+            let _ = done_tx.send(result);
+        });
+
+        if tokio::runtime::Handle::try_current().is_ok() {
+            return;
+        }
 
-        // Log errors but don't panic in Drop
-        if let Err(e) = result {
-            eprintln!("Warning: Failed to save project on drop: {}", e);
+        if done_rx.recv_timeout(DROP_SAVE_TIMEOUT).is_err() {
+            log::warn!(
+                "Saving project on drop did not finish within {:?}; continuing in background",
+                DROP_SAVE_TIMEOUT
+            );
         }
     }
 }
@@ -322,4 +778,63 @@ impl<'a> DbConnGuard<'a> {
     pub(super) async fn begin_transaction(&'a mut self) -> anyhow::Result<Transaction<'a, Sqlite>> {
         Ok(self.conn.begin().await?)
     }
-}
\ No newline at end of file
+}
+
+/// Remove a detection cache directory; one that is already gone is not an error.
+async fn remove_cache_dir(cache_dir: &Path) -> anyhow::Result<()> {
+    match async_fs::remove_dir_all(cache_dir).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e)
+            .with_context(|| format!("Failed to clear detection cache {:?}", cache_dir)),
+        _ => Ok(()),
+    }
+}
+
+/// Read an image file without blocking the async runtime: the bytes are read asynchronously
+/// and decoded on a blocking thread, so several images can be decoded in parallel.
+async fn decode_image_file(path: &Path) -> anyhow::Result<DynamicImage> {
+    let bytes = async_fs::read(path).await?;
+    tokio::task::spawn_blocking(move || {
+        let img = image::ImageReader::new(std::io::Cursor::new(bytes))
+            .with_guessed_format()?
+            .decode()?;
+        anyhow::Ok(img)
+    })
+    .await
+    .context("Image decoding task failed")?
+}
+
+/// JPEG quality used when re-encoding JPEG sources.
+const REENCODE_JPEG_QUALITY: u8 = 85;
+
+/// Decode `src` and write it into `images_dir` in a compact format, returning the new filename.
+///
+/// JPEG sources are re-saved as JPEG with [`REENCODE_JPEG_QUALITY`]; everything else
+/// becomes a maximally compressed PNG, which keeps dimensions and color type unchanged.
+fn reencode_image(src: &Path, images_dir: &Path) -> anyhow::Result<String> {
+    let reader = image::ImageReader::open(src)
+        .with_context(|| format!("Failed to open area image {:?}", src))?
+        .with_guessed_format()
+        .with_context(|| format!("Failed to detect format of area image {:?}", src))?;
+    let format = reader.format();
+    let img = reader
+        .decode()
+        .with_context(|| format!("Failed to decode area image {:?}", src))?;
+
+    let ext = if format == Some(ImageFormat::Jpeg) { "jpg" } else { "png" };
+    let img_fname = format!("{}.{}", Uuid::new_v4(), ext);
+    let dest_path = images_dir.join(&img_fname);
+    let writer = BufWriter::new(
+        File::create(&dest_path)
+            .with_context(|| format!("Failed to create area image {:?}", dest_path))?,
+    );
+
+    let result = if format == Some(ImageFormat::Jpeg) {
+        let encoder = JpegEncoder::new_with_quality(writer, REENCODE_JPEG_QUALITY);
+        DynamicImage::ImageRgb8(img.to_rgb8()).write_with_encoder(encoder)
+    } else {
+        let encoder = PngEncoder::new_with_quality(writer, CompressionType::Best, PngFilterType::Adaptive);
+        img.write_with_encoder(encoder)
+    };
+    result.with_context(|| format!("Failed to re-encode area image {:?} to {:?}", src, dest_path))?;
+    Ok(img_fname)
+}
@@ -1,8 +1,8 @@
 use std::future::Future;
 
-use crate::core::db::model::Point;
+use crate::core::db::{address::Address, model::Point};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Street {
     pub id: i64,
     pub name: Option<String>,
@@ -24,11 +24,30 @@ pub struct StreetPolyline {
 
 pub trait StreetRepository {
     fn get_streets(&self) -> impl Future<Output = anyhow::Result<Vec<Street>>>;
+    /// Case-insensitive substring search over street names; unnamed streets never match.
+    /// Streets whose name starts with `query` come first, then by name.
+    fn search_streets(&self, query: &str) -> impl Future<Output = anyhow::Result<Vec<Street>>>;
     fn get_street_by_id(&self, id: i64) -> impl Future<Output = anyhow::Result<Option<Street>>>;
     fn add_street(&self) -> impl Future<Output = anyhow::Result<Street>>;
     fn draw_street_polyline(&self, street: &Street, polyline: &[Point]) -> impl Future<Output = anyhow::Result<()>>;
     fn get_street_polyline(&self, street: &Street) -> impl Future<Output = anyhow::Result<Option<StreetPolyline>>>;
     fn remove_street_polyline(&self, street: &Street) -> impl Future<Output = anyhow::Result<()>>;
     fn update_street(&self, street: &Street, update: &StreetUpdate) -> impl Future<Output = anyhow::Result<Street>>;
+    /// The street's addresses in walking order: sorted by where they project onto the polyline,
+    /// from its first vertex to its last. Fails if the street has no polyline.
+    fn order_addresses_along_polyline(&self, street: &Street) -> impl Future<Output = anyhow::Result<Vec<Address>>>;
+    /// Assign every address without a street to the street whose polyline is closest.
+    /// Streets without a polyline are ignored; returns the number of addresses updated.
+    fn autoassign_nearest(&self) -> impl Future<Output = anyhow::Result<usize>>;
     fn delete_street(&self, street: Street) -> impl Future<Output = anyhow::Result<()>>;
+    /// Replace the street by two streets whose polylines end and start at vertex `at_vertex`,
+    /// which must be an interior vertex. Each address (including those in the trash) moves to
+    /// the half its position is nearer to; the first half keeps the street's name. Both halves
+    /// start unverified and the original street is deleted.
+    fn split_street(&self, street: &Street, at_vertex: usize) -> impl Future<Output = anyhow::Result<(Street, Street)>>;
+    /// Fold street `b` into `a` in one transaction: the polylines are joined at their nearest
+    /// endpoints (see `util::join_polylines`), all of `b`'s addresses move to `a` and `b` is
+    /// deleted. `a` keeps its name, or takes `b`'s if it has none. Fails if both streets have
+    /// an address with the same house number.
+    fn merge_streets(&self, a: &Street, b: &Street) -> impl Future<Output = anyhow::Result<Street>>;
 }
\ No newline at end of file
@@ -2,7 +2,7 @@ use std::{collections::HashMap, future::Future};
 
 use crate::core::db::{address::Address, model::Point};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Team {
     pub id: i64,
     pub number: u16,
@@ -55,4 +55,8 @@ pub trait TeamRepository {
         team: &Team,
     ) -> impl Future<Output = anyhow::Result<Option<TeamBounds>>>;
     fn remove_team_bounds(&self, team: &Team) -> impl Future<Output = anyhow::Result<()>>;
+    /// Replace all team assignments with `team_count` geographically compact groups of
+    /// (almost) equal size. Missing teams are created; the first `team_count` teams by id
+    /// are used, numbered top to bottom, then left to right. Extra teams end up empty.
+    fn balance_teams(&self, team_count: usize) -> impl Future<Output = anyhow::Result<()>>;
 }
@@ -0,0 +1,197 @@
+use crate::core::db::model::Point;
+
+/// Result of projecting a point onto a polyline.
+#[derive(Debug, Clone, Copy)]
+pub struct PolylineProjection {
+    /// Arc length from the first vertex to the projected point.
+    pub arc_length: f64,
+    /// Distance from the point to its projection.
+    pub distance: f64,
+}
+
+/// Project `p` onto the segment `a`-`b`.
+///
+/// Returns the segment parameter clamped to `[0, 1]` and the distance from `p`
+/// to the projected point. Degenerate segments project onto `a`.
+pub fn project_onto_segment(p: &Point, a: &Point, b: &Point) -> (f64, f64) {
+    let (px, py) = (p.x as f64, p.y as f64);
+    let (ax, ay) = (a.x as f64, a.y as f64);
+    let (bx, by) = (b.x as f64, b.y as f64);
+    let (dx, dy) = (bx - ax, by - ay);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq == 0.0 {
+        0.0
+    } else {
+        (((px - ax) * dx + (py - ay) * dy) / len_sq).clamp(0.0, 1.0)
+    };
+    let (qx, qy) = (ax + t * dx, ay + t * dy);
+    (t, ((px - qx).powi(2) + (py - qy).powi(2)).sqrt())
+}
+
+/// Distance from `p` to the closest point of the segment `a`-`b`.
+pub fn point_to_segment_distance(p: &Point, a: &Point, b: &Point) -> f64 {
+    project_onto_segment(p, a, b).1
+}
+
+/// Distance from `p` to the closest point of `polyline`, `None` if it is empty.
+pub fn point_to_polyline_distance(p: &Point, polyline: &[Point]) -> Option<f64> {
+    match polyline {
+        [] => None,
+        [single] => Some(point_to_segment_distance(p, single, single)),
+        _ => polyline
+            .windows(2)
+            .map(|w| point_to_segment_distance(p, &w[0], &w[1]))
+            .min_by(f64::total_cmp),
+    }
+}
+
+/// Total length of a polyline.
+pub fn polyline_length(polyline: &[Point]) -> f64 {
+    polyline
+        .windows(2)
+        .map(|w| segment_length(&w[0], &w[1]))
+        .sum()
+}
+
+/// Project `p` onto the closest point of `polyline`.
+///
+/// Returns `None` for an empty polyline. A single vertex is treated as a
+/// zero-length polyline.
+pub fn project_onto_polyline(p: &Point, polyline: &[Point]) -> Option<PolylineProjection> {
+    let first = polyline.first()?;
+    if polyline.len() == 1 {
+        let (_, distance) = project_onto_segment(p, first, first);
+        return Some(PolylineProjection {
+            arc_length: 0.0,
+            distance,
+        });
+    }
+    let mut best: Option<PolylineProjection> = None;
+    let mut offset = 0.0;
+    for w in polyline.windows(2) {
+        let length = segment_length(&w[0], &w[1]);
+        let (t, distance) = project_onto_segment(p, &w[0], &w[1]);
+        if best.is_none_or(|b| distance < b.distance) {
+            best = Some(PolylineProjection {
+                arc_length: offset + t * length,
+                distance,
+            });
+        }
+        offset += length;
+    }
+    best
+}
+
+/// Concatenate two polylines at their closest pair of endpoints.
+///
+/// `b` is appended after or prepended before `a`, reversed where needed, so the result runs
+/// continuously through both; `a` keeps its direction. A vertex shared by the joined endpoints
+/// appears once. If either polyline is empty the other is returned.
+pub fn join_polylines(a: &[Point], b: &[Point]) -> Vec<Point> {
+    let (Some(a_first), Some(a_last), Some(b_first), Some(b_last)) = (a.first(), a.last(), b.first(), b.last())
+    else {
+        return if a.is_empty() { b.to_vec() } else { a.to_vec() };
+    };
+    let reversed = |points: &[Point]| points.iter().rev().copied().collect::<Vec<_>>();
+
+    // (distance, b goes after a, b is reversed), ties resolved in this order
+    let joins = [
+        (a_last.distance(b_first), true, false),
+        (a_last.distance(b_last), true, true),
+        (a_first.distance(b_last), false, false),
+        (a_first.distance(b_first), false, true),
+    ];
+    let (_, after, reverse) = joins
+        .into_iter()
+        .min_by(|x, y| x.0.total_cmp(&y.0))
+        .expect("joins is not empty");
+    let b = if reverse { reversed(b) } else { b.to_vec() };
+
+    let (mut head, tail) = if after { (a.to_vec(), b) } else { (b, a.to_vec()) };
+    let skip = usize::from(head.last() == tail.first());
+    head.extend(tail.into_iter().skip(skip));
+    head
+}
+
+fn segment_length(a: &Point, b: &Point) -> f64 {
+    a.distance(b)
+}
+
+/// Split `points` into `k` spatially compact groups of equal size (sizes differ by at most one).
+///
+/// Runs k-means with a size-constrained assignment step: all point/centroid pairs are taken
+/// closest first, and a point joins a cluster only while the cluster has room. Seeding is
+/// farthest-point from the top-left-most point, so results are deterministic. Returns the
+/// cluster index of each point; clusters are numbered top to bottom, then left to right
+/// by centroid.
+pub fn balanced_clusters(points: &[Point], k: usize) -> Vec<usize> {
+    let n = points.len();
+    if n == 0 || k == 0 {
+        return vec![0; n];
+    }
+    let k = k.min(n);
+    let coords: Vec<(f64, f64)> = points.iter().map(|p| (p.x as f64, p.y as f64)).collect();
+    let dist_sq = |a: (f64, f64), b: (f64, f64)| (a.0 - b.0).powi(2) + (a.1 - b.1).powi(2);
+
+    // Farthest-point seeding
+    let start = (0..n)
+        .min_by_key(|&i| (points[i].y, points[i].x))
+        .expect("points is not empty");
+    let mut centroids = vec![coords[start]];
+    while centroids.len() < k {
+        let nearest = |i: usize| {
+            centroids
+                .iter()
+                .map(|&c| dist_sq(coords[i], c))
+                .fold(f64::MAX, f64::min)
+        };
+        let farthest = (0..n)
+            .max_by(|&a, &b| nearest(a).total_cmp(&nearest(b)).then(b.cmp(&a)))
+            .expect("points is not empty");
+        centroids.push(coords[farthest]);
+    }
+
+    let mut assignment: Vec<usize> = Vec::new();
+    for _ in 0..100 {
+        let mut room: Vec<usize> = (0..k).map(|c| n / k + usize::from(c < n % k)).collect();
+        let mut pairs: Vec<(f64, usize, usize)> = (0..n)
+            .flat_map(|i| (0..k).map(move |c| (i, c)))
+            .map(|(i, c)| (dist_sq(coords[i], centroids[c]), i, c))
+            .collect();
+        pairs.sort_by(|a, b| a.0.total_cmp(&b.0).then((a.1, a.2).cmp(&(b.1, b.2))));
+
+        let mut next = vec![usize::MAX; n];
+        for (_, i, c) in pairs {
+            if next[i] == usize::MAX && room[c] > 0 {
+                next[i] = c;
+                room[c] -= 1;
+            }
+        }
+
+        for (c, centroid) in centroids.iter_mut().enumerate() {
+            let members: Vec<(f64, f64)> = (0..n).filter(|&i| next[i] == c).map(|i| coords[i]).collect();
+            let count = members.len() as f64;
+            *centroid = (
+                members.iter().map(|m| m.0).sum::<f64>() / count,
+                members.iter().map(|m| m.1).sum::<f64>() / count,
+            );
+        }
+
+        let converged = next == assignment;
+        assignment = next;
+        if converged {
+            break;
+        }
+    }
+
+    // Renumber clusters by centroid position
+    let mut order: Vec<usize> = (0..k).collect();
+    order.sort_by(|&a, &b| {
+        centroids[a].1.total_cmp(&centroids[b].1).then(centroids[a].0.total_cmp(&centroids[b].0))
+    });
+    let mut rank = vec![0; k];
+    for (new, &old) in order.iter().enumerate() {
+        rank[old] = new;
+    }
+    assignment.into_iter().map(|c| rank[c]).collect()
+}
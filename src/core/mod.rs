@@ -1 +1,2 @@
-pub mod db;
\ No newline at end of file
+pub mod db;
+pub mod view;
\ No newline at end of file
@@ -0,0 +1,338 @@
+//! GUI view logic that doesn't depend on Dioxus, so it can be tested without a window
+
+use base64::Engine;
+use image::DynamicImage;
+
+use crate::core::db::{
+    Address, AddressRepository, AddressUpdate, AreaRepository, BoundAreaRepository, Color, Point,
+    ProjectRepository, UpdateProjectSettings,
+};
+use crate::models::HouseNumberDetection;
+
+/// Smallest and largest zoom factor of the area viewer
+pub const MIN_ZOOM: f64 = 0.05;
+pub const MAX_ZOOM: f64 = 8.0;
+
+/// Mapping between image pixels and screen pixels of the area viewer:
+/// `screen = pan + zoom * image`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewTransform {
+    pub zoom: f64,
+    pub pan_x: f64,
+    pub pan_y: f64,
+}
+
+impl Default for ViewTransform {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            pan_x: 0.0,
+            pan_y: 0.0,
+        }
+    }
+}
+
+impl ViewTransform {
+    /// Largest zoom showing the whole `image_size` image in `viewport_size`, centered
+    pub fn fit(image_size: (f64, f64), viewport_size: (f64, f64)) -> Self {
+        if image_size.0 <= 0.0 || image_size.1 <= 0.0 {
+            return Self::default();
+        }
+        let zoom = (viewport_size.0 / image_size.0)
+            .min(viewport_size.1 / image_size.1)
+            .clamp(MIN_ZOOM, MAX_ZOOM);
+        Self {
+            zoom,
+            pan_x: (viewport_size.0 - image_size.0 * zoom) / 2.0,
+            pan_y: (viewport_size.1 - image_size.1 * zoom) / 2.0,
+        }
+    }
+
+    pub fn image_to_screen(&self, x: f64, y: f64) -> (f64, f64) {
+        (self.pan_x + self.zoom * x, self.pan_y + self.zoom * y)
+    }
+
+    pub fn screen_to_image(&self, x: f64, y: f64) -> (f64, f64) {
+        ((x - self.pan_x) / self.zoom, (y - self.pan_y) / self.zoom)
+    }
+
+    /// Image pixel under the screen position (`x`, `y`), or `None` outside the `image_size` image
+    pub fn screen_to_image_point(&self, x: f64, y: f64, image_size: (f64, f64)) -> Option<Point> {
+        let (x, y) = self.screen_to_image(x, y);
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let (x, y) = (x.round(), y.round());
+        if x >= image_size.0 || y >= image_size.1 {
+            return None;
+        }
+        Some(Point {
+            x: x as u32,
+            y: y as u32,
+        })
+    }
+
+    pub fn pan_by(&mut self, dx: f64, dy: f64) {
+        self.pan_x += dx;
+        self.pan_y += dy;
+    }
+
+    /// Multiply the zoom by `factor`, clamped to `MIN_ZOOM..=MAX_ZOOM`, keeping the image
+    /// point under the screen position (`x`, `y`) in place
+    pub fn zoom_at(&mut self, x: f64, y: f64, factor: f64) {
+        let (image_x, image_y) = self.screen_to_image(x, y);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.pan_x = x - self.zoom * image_x;
+        self.pan_y = y - self.zoom * image_y;
+    }
+
+    /// Keep the image point at the viewport center centered when the viewport changes size
+    pub fn resize_viewport(&mut self, old_size: (f64, f64), new_size: (f64, f64)) {
+        self.pan_by((new_size.0 - old_size.0) / 2.0, (new_size.1 - old_size.1) / 2.0);
+    }
+}
+
+/// Marker drawn over the area image for one address, in screen pixels relative to the
+/// viewer's top-left corner
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverlayMarker {
+    pub address_id: i64,
+    pub x: f64,
+    pub y: f64,
+    pub radius: f64,
+    pub label: String,
+    pub verified: bool,
+}
+
+/// Overlay markers for `addresses` on an image drawn with `transform`
+/// Marker circles scale with the zoom so they keep covering the printed circles.
+pub fn overlay_markers(addresses: &[Address], transform: &ViewTransform) -> Vec<OverlayMarker> {
+    addresses
+        .iter()
+        .map(|address| {
+            let (x, y) = transform.image_to_screen(address.position.x as f64, address.position.y as f64);
+            OverlayMarker {
+                address_id: address.id,
+                x,
+                y,
+                radius: address.circle_radius as f64 * transform.zoom,
+                label: address.house_number.clone(),
+                verified: address.verified,
+            }
+        })
+        .collect()
+}
+
+/// SVG `points` attribute for the image-space `points` drawn with `transform`
+pub fn svg_points(points: &[Point], transform: &ViewTransform) -> String {
+    points
+        .iter()
+        .map(|p| {
+            let (x, y) = transform.image_to_screen(p.x as f64, p.y as f64);
+            format!("{x},{y}")
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Team boundary being drawn on the area viewer, in image pixels
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PolygonDraft {
+    vertices: Vec<Point>,
+}
+
+impl PolygonDraft {
+    /// Fewest vertices a closed boundary can have
+    pub const MIN_VERTICES: usize = 3;
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    /// Append a vertex; a repeat of the last vertex is ignored, so the clicks of a
+    /// double-click only add one. Returns whether the vertex was added.
+    pub fn add_vertex(&mut self, p: Point) -> bool {
+        if self.vertices.last() == Some(&p) {
+            return false;
+        }
+        self.vertices.push(p);
+        true
+    }
+
+    pub fn remove_last(&mut self) -> Option<Point> {
+        self.vertices.pop()
+    }
+
+    /// Finish the boundary and start a new, empty draft
+    /// Returns `None` and keeps the draft if it has fewer than `MIN_VERTICES` vertices.
+    pub fn close(&mut self) -> Option<Vec<Point>> {
+        if self.vertices.len() < Self::MIN_VERTICES {
+            return None;
+        }
+        Some(std::mem::take(&mut self.vertices))
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+    }
+}
+
+/// `data:` URL of `img` encoded as PNG, for use as an `img` element's `src`
+pub fn image_data_url(img: &DynamicImage) -> anyhow::Result<String> {
+    let mut png = std::io::Cursor::new(Vec::new());
+    img.write_to(&mut png, image::ImageFormat::Png)?;
+    Ok(format!(
+        "data:image/png;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(png.into_inner())
+    ))
+}
+
+/// State of a hex color being typed into the color picker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorInput {
+    Valid(Color),
+    /// Only hex digits so far, but not enough of them yet, e.g. `#ff`
+    Incomplete,
+    /// Can't become a color by typing more, e.g. `zzz`
+    Invalid,
+}
+
+/// Classify the color picker's text as it is being typed; accepts what
+/// `Color::from_hex_string` accepts
+pub fn parse_color_input(input: &str) -> ColorInput {
+    let hex = input.trim().trim_start_matches('#');
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) || hex.len() > 8 {
+        return ColorInput::Invalid;
+    }
+    match Color::from_hex_string(hex) {
+        Ok(color) => ColorInput::Valid(color),
+        Err(_) => ColorInput::Incomplete,
+    }
+}
+
+/// Project metadata key of the color the add-area form starts with
+pub const DEFAULT_AREA_COLOR_KEY: &str = "default_area_color";
+
+/// Values entered in the new-project form, as typed
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewProjectForm {
+    pub name: String,
+    /// Whole number; empty means no target yet
+    pub target_address_count: String,
+    /// `None` leaves the color of each new area to `AreaRepository::suggest_area_color`
+    pub default_area_color: Option<Color>,
+}
+
+impl Default for NewProjectForm {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            target_address_count: String::new(),
+            default_area_color: None,
+        }
+    }
+}
+
+impl NewProjectForm {
+    /// Settings for the new project, stamped with the current time
+    /// Fails with a message for the user if a field is invalid.
+    pub fn settings(&self) -> anyhow::Result<UpdateProjectSettings> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            anyhow::bail!("Project name must not be empty");
+        }
+        let count = self.target_address_count.trim();
+        let target_address_count = if count.is_empty() {
+            0
+        } else {
+            count
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Target address count must be a whole number, got '{}'", count))?
+        };
+        Ok(UpdateProjectSettings {
+            name: Some(name.to_string()),
+            target_address_count: Some(target_address_count),
+            created_at: Some(time::OffsetDateTime::now_utc()),
+        })
+    }
+
+    /// Validate the form and write it to a freshly created `project`
+    pub async fn apply<P: ProjectRepository>(&self, project: &P) -> anyhow::Result<()> {
+        project.set_project_settings(self.settings()?).await?;
+        if let Some(color) = self.default_area_color {
+            project.set_metadata(DEFAULT_AREA_COLOR_KEY, &color.to_hex_string()).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Color the add-area form starts with: the project's chosen color while no area uses it,
+/// otherwise the suggestion most distinct from the existing areas
+pub async fn default_area_color<P: ProjectRepository + AreaRepository>(project: &P) -> anyhow::Result<Color> {
+    if let Some(hex) = project.get_metadata(DEFAULT_AREA_COLOR_KEY).await? {
+        let color = Color::from_hex_string(&hex)?;
+        if !project.get_areas().await?.iter().any(|area| area.color == color) {
+            return Ok(color);
+        }
+    }
+    project.suggest_area_color().await
+}
+
+/// Run `detect` on the area image on a blocking thread and import the detections as
+/// unverified addresses
+/// `detect` is `DetectionPipeline::detect` in the app; returns the new addresses.
+pub async fn detect_addresses<R, F>(area: &R, detect: F) -> anyhow::Result<Vec<Address>>
+where
+    R: AddressRepository + BoundAreaRepository,
+    F: FnOnce(&DynamicImage) -> anyhow::Result<Vec<HouseNumberDetection>> + Send + 'static,
+{
+    let image = area.get_image().clone();
+    let detections = tokio::task::spawn_blocking(move || detect(&image))
+        .await
+        .map_err(|e| anyhow::anyhow!("Detection task failed: {}", e))??;
+    area.import_detections(&detections).await
+}
+
+/// Change made in a row of the address table
+#[derive(Debug, Clone, PartialEq)]
+pub enum AddressEdit {
+    HouseNumber(String),
+    Verified(bool),
+    Delete,
+}
+
+impl AddressEdit {
+    /// Update storing this edit for `address`; `None` for deletes, blank house numbers and
+    /// edits that don't change anything
+    pub fn to_update(&self, address: &Address) -> Option<AddressUpdate<'static>> {
+        match self {
+            AddressEdit::HouseNumber(number) => {
+                let number = number.trim();
+                if number.is_empty() || number == address.house_number {
+                    return None;
+                }
+                Some(AddressUpdate {
+                    house_number: Some(number.to_string()),
+                    ..Default::default()
+                })
+            }
+            AddressEdit::Verified(verified) if *verified != address.verified => Some(AddressUpdate {
+                verified: Some(*verified),
+                ..Default::default()
+            }),
+            AddressEdit::Verified(_) | AddressEdit::Delete => None,
+        }
+    }
+
+    /// Store this edit; returns the address as it is now, `None` once deleted
+    pub async fn apply<R: AddressRepository>(&self, repo: &R, address: &Address) -> anyhow::Result<Option<Address>> {
+        if *self == AddressEdit::Delete {
+            repo.delete_address(address.clone()).await?;
+            return Ok(None);
+        }
+        match self.to_update(address) {
+            Some(update) => repo.update_address(address, &update).await.map(Some),
+            None => Ok(Some(address.clone())),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use image::DynamicImage;
+use image::{DynamicImage, GrayImage};
 use crate::models::Contour;
 
 /// Filter contours to find circular shapes
@@ -32,3 +32,99 @@ pub fn filter_white_circles(
         .cloned()
         .collect()
 }
+
+/// A circle found by the Hough transform
+#[derive(Debug, Clone)]
+pub struct HoughCircle {
+    pub center_x: u32,
+    pub center_y: u32,
+    pub radius: u32,
+    /// Number of edge pixels voting for this circle
+    pub votes: u32,
+    /// Fraction of the circumference covered by edge pixels (0.0-1.0)
+    pub score: f32,
+}
+
+/// Integer offsets of the points on a circle of `radius`, without duplicates
+fn circle_offsets(radius: u32) -> Vec<(i64, i64)> {
+    let r = radius as f32;
+    // Sample finely enough that neighboring samples are at most one pixel apart
+    let steps = (2.0 * std::f32::consts::PI * r).ceil() as usize * 2;
+    let mut offsets: Vec<(i64, i64)> = (0..steps)
+        .map(|i| {
+            let angle = i as f32 / steps as f32 * 2.0 * std::f32::consts::PI;
+            ((r * angle.cos()).round() as i64, (r * angle.sin()).round() as i64)
+        })
+        .collect();
+    offsets.sort_unstable();
+    offsets.dedup();
+    offsets
+}
+
+/// Detect circles in a binary edge image with a basic Hough circle transform
+///
+/// Every radius in `min_radius..=max_radius` gets its own vote pass, so memory stays at
+/// one `width × height` accumulator no matter how large the radius range is; runtime grows
+/// with edge pixels × radii × circumference. A circle is reported when at least
+/// `accumulator_threshold` (0.0-1.0) of its circumference lies on edge pixels. Concentric
+/// or nearby responses (e.g. inner and outer edge of a thick outline) collapse into the
+/// best-scoring circle.
+pub fn hough_circles(
+    edges: &GrayImage,
+    min_radius: u32,
+    max_radius: u32,
+    accumulator_threshold: f32,
+) -> Vec<HoughCircle> {
+    let (width, height) = edges.dimensions();
+    let edge_points: Vec<(i64, i64)> = edges
+        .enumerate_pixels()
+        .filter(|(_, _, p)| p[0] > 0)
+        .map(|(x, y, _)| (x as i64, y as i64))
+        .collect();
+
+    let mut candidates = Vec::new();
+    let mut accumulator = vec![0u32; (width * height) as usize];
+
+    for radius in min_radius.max(1)..=max_radius {
+        let offsets = circle_offsets(radius);
+        accumulator.iter_mut().for_each(|v| *v = 0);
+
+        for &(x, y) in &edge_points {
+            for &(dx, dy) in &offsets {
+                let (cx, cy) = (x + dx, y + dy);
+                if cx >= 0 && cy >= 0 && cx < width as i64 && cy < height as i64 {
+                    accumulator[(cy * width as i64 + cx) as usize] += 1;
+                }
+            }
+        }
+
+        let min_votes = (accumulator_threshold * offsets.len() as f32).ceil().max(1.0) as u32;
+        for (i, &votes) in accumulator.iter().enumerate() {
+            if votes >= min_votes {
+                candidates.push(HoughCircle {
+                    center_x: i as u32 % width,
+                    center_y: i as u32 / width,
+                    radius,
+                    votes,
+                    score: votes as f32 / offsets.len() as f32,
+                });
+            }
+        }
+    }
+
+    // Greedy suppression: best circles first, drop any whose center lies inside a kept one
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let mut circles: Vec<HoughCircle> = Vec::new();
+    for candidate in candidates {
+        let overlaps = circles.iter().any(|c| {
+            let dx = c.center_x as f32 - candidate.center_x as f32;
+            let dy = c.center_y as f32 - candidate.center_y as f32;
+            (dx * dx + dy * dy).sqrt() < c.radius.min(candidate.radius) as f32
+        });
+        if !overlaps {
+            circles.push(candidate);
+        }
+    }
+
+    circles
+}
@@ -1,18 +1,50 @@
-use image::{GrayImage, Luma};
-use imageproc::region_labelling::{connected_components, Connectivity};
+use image::{GrayImage, ImageBuffer, Luma};
+use imageproc::region_labelling::connected_components;
+pub use imageproc::region_labelling::Connectivity;
 use std::collections::HashMap;
 use crate::models::Contour;
 
+type LabelImage = ImageBuffer<Luma<u32>, Vec<u32>>;
+
+/// Moore neighborhood in clockwise order (image coordinates, y pointing down), starting west
+const NEIGHBORS: [(i64, i64); 8] = [
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+];
+
 /// Find contours in binary edge image using connected components
+/// Contours are sorted top to bottom, then left to right (by `min_y`, `min_x`), so runs on
+/// the same image give the same order and debug file names.
 pub fn find_contours(edges: &GrayImage, min_area: u32) -> Vec<Contour> {
+    find_contours_filtered(edges, min_area, None, false, Connectivity::Eight)
+}
+
+/// Like `find_contours`, also dropping oversized and border-touching regions
+/// `max_area` limits the bounding-box area rather than the pixel count, since an outline
+/// spanning the image has few pixels but a huge box. With `reject_border`, regions touching
+/// the image edge (map frames, cut-off shapes) are dropped. `Connectivity::Four` keeps
+/// regions that only touch diagonally apart, e.g. adjacent stickers.
+pub fn find_contours_filtered(
+    edges: &GrayImage,
+    min_area: u32,
+    max_area: Option<u32>,
+    reject_border: bool,
+    connectivity: Connectivity,
+) -> Vec<Contour> {
     // Label connected components (white pixels = edges)
-    let labeled = connected_components(edges, Connectivity::Eight, Luma([0]));
+    let labeled = connected_components(edges, connectivity, Luma([0]));
 
     // Build contours from labeled regions
     let mut regions: HashMap<u32, (u32, u32, u32, u32, u32)> = HashMap::new();
 
     for (x, y, label) in labeled.enumerate_pixels() {
-        let label_val = label[0] as u32;
+        let label_val = label[0];
         if label_val == 0 {
             continue; // Skip background
         }
@@ -28,7 +60,21 @@ pub fn find_contours(edges: &GrayImage, min_area: u32) -> Vec<Contour> {
             .or_insert((x, y, x, y, 1));
     }
 
-    // Convert to Contour structs and filter by minimum area
+    // Convert to Contour structs and filter by minimum area, in a stable order; the label
+    // breaks ties between components sharing a top-left corner
+    let (width, height) = edges.dimensions();
+    let mut regions: Vec<_> = regions.into_iter()
+        .filter(|(_, (_, _, _, _, count))| *count >= min_area)
+        .filter(|(_, (min_x, min_y, max_x, max_y, _))| {
+            let box_area = (max_x - min_x + 1) as u64 * (max_y - min_y + 1) as u64;
+            max_area.is_none_or(|max_area| box_area <= max_area as u64)
+        })
+        .filter(|(_, (min_x, min_y, max_x, max_y, _))| {
+            !reject_border || (*min_x > 0 && *min_y > 0 && *max_x < width - 1 && *max_y < height - 1)
+        })
+        .collect();
+    regions.sort_by_key(|(label, (min_x, min_y, _, _, _))| (*min_y, *min_x, *label));
+
     regions.into_iter()
         .map(|(label, (min_x, min_y, max_x, max_y, count))| {
             Contour {
@@ -38,8 +84,76 @@ pub fn find_contours(edges: &GrayImage, min_area: u32) -> Vec<Contour> {
                 max_x,
                 max_y,
                 pixel_count: count,
+                boundary: Some(trace_labeled_boundary(&labeled, label, min_y)),
             }
         })
-        .filter(|c| c.pixel_count >= min_area)
         .collect()
 }
+
+/// Trace the outer boundary of a connected component with Moore-neighbor tracing
+///
+/// `label` refers to the labelling used by `find_contours` (8-connected components of
+/// non-zero pixels). Returns the boundary pixels in clockwise order without repeating
+/// the start pixel, or an empty Vec if no component has that label.
+pub fn trace_boundary(edges: &GrayImage, label: u32) -> Vec<(u32, u32)> {
+    let labeled = connected_components(edges, Connectivity::Eight, Luma([0]));
+
+    match labeled.enumerate_pixels().find(|(_, _, l)| l[0] == label) {
+        Some((_, min_y, _)) => trace_labeled_boundary(&labeled, label, min_y),
+        None => Vec::new(),
+    }
+}
+
+/// Moore-neighbor tracing starting from the first pixel of `label` in row `min_y`
+fn trace_labeled_boundary(labeled: &LabelImage, label: u32, min_y: u32) -> Vec<(u32, u32)> {
+    let start_x = match (0..labeled.width()).find(|&x| labeled.get_pixel(x, min_y)[0] == label) {
+        Some(x) => x,
+        None => return Vec::new(),
+    };
+    let start = (start_x as i64, min_y as i64);
+
+    let in_region = |(x, y): (i64, i64)| {
+        x >= 0
+            && y >= 0
+            && x < labeled.width() as i64
+            && y < labeled.height() as i64
+            && labeled.get_pixel(x as u32, y as u32)[0] == label
+    };
+
+    // Find the next boundary pixel clockwise from the backtrack direction,
+    // returning it with the new backtrack direction (relative to the new pixel)
+    let next = |current: (i64, i64), backtrack: usize| {
+        (1..=8).find_map(|k| {
+            let dir = (backtrack + k) % 8;
+            let candidate = (current.0 + NEIGHBORS[dir].0, current.1 + NEIGHBORS[dir].1);
+            if !in_region(candidate) {
+                return None;
+            }
+            let prev_dir = (backtrack + k - 1) % 8;
+            let prev = (current.0 + NEIGHBORS[prev_dir].0, current.1 + NEIGHBORS[prev_dir].1);
+            let offset = (prev.0 - candidate.0, prev.1 - candidate.1);
+            let new_backtrack = NEIGHBORS.iter().position(|&n| n == offset)?;
+            Some((candidate, new_backtrack))
+        })
+    };
+
+    // The start is the top-left pixel, so its west neighbor is outside the region
+    let mut boundary = vec![(start.0 as u32, start.1 as u32)];
+    let (second, mut backtrack) = match next(start, 0) {
+        Some(step) => step,
+        None => return boundary, // Isolated pixel
+    };
+    let mut current = second;
+
+    // Stop once we leave the start pixel the same way we did the first time
+    while let Some((following, new_backtrack)) = next(current, backtrack) {
+        if current == start && following == second {
+            break;
+        }
+        boundary.push((current.0 as u32, current.1 as u32));
+        current = following;
+        backtrack = new_backtrack;
+    }
+
+    boundary
+}
@@ -6,6 +6,36 @@ pub mod steps;
 
 use image::DynamicImage;
 use crate::models::{Contour, HouseNumberDetection};
+use crate::pipeline::{verbose_level, verbose_log};
+
+/// Tunable parameters for a detection run
+/// Serialized as part of the detection cache key, so adding a field invalidates old entries.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DetectionParams {
+    pub min_radius: f32,
+    pub max_radius: f32,
+    pub circularity_threshold: f32,
+    pub brightness_threshold: f32,
+}
+
+impl Default for DetectionParams {
+    fn default() -> Self {
+        Self {
+            min_radius: 10.0,
+            max_radius: 200.0,
+            circularity_threshold: 2.0,
+            brightness_threshold: 200.0,
+        }
+    }
+}
+
+/// Run the detection pipeline on an already decoded image
+pub fn run_detections(
+    img: &DynamicImage,
+    params: &DetectionParams,
+) -> anyhow::Result<Vec<HouseNumberDetection>> {
+    DetectionPipeline::from_params(params).detect(img)
+}
 
 /// Main detection pipeline orchestrator
 pub struct DetectionPipeline {
@@ -15,6 +45,17 @@ pub struct DetectionPipeline {
     pub circularity_threshold: f32,
     pub brightness_threshold: f32,
     pub verbose: bool,
+    // Preprocessing parameters
+    pub blur_sigma: f32,
+    pub canny_low: f32,
+    pub canny_high: f32,
+    /// Contours with fewer pixels than this are dropped
+    pub min_contour_area: u32,
+    /// Threads running OCR, each with its own engine
+    pub threads: usize,
+    /// OCR canvas size, see `ocr::ocr_canvas_size`
+    pub ocr_target_size: u32,
+    pub ocr_target_height: Option<u32>,
 }
 
 impl DetectionPipeline {
@@ -25,6 +66,23 @@ impl DetectionPipeline {
             circularity_threshold: 2.0,
             brightness_threshold: 200.0,
             verbose: false,
+            blur_sigma: 1.5,
+            canny_low: 50.0,
+            canny_high: 100.0,
+            min_contour_area: 10,
+            threads: 1,
+            ocr_target_size: ocr::OCR_TARGET_SIZE,
+            ocr_target_height: None,
+        }
+    }
+
+    pub fn from_params(params: &DetectionParams) -> Self {
+        Self {
+            min_radius: params.min_radius,
+            max_radius: params.max_radius,
+            circularity_threshold: params.circularity_threshold,
+            brightness_threshold: params.brightness_threshold,
+            ..Self::new()
         }
     }
 
@@ -33,43 +91,71 @@ impl DetectionPipeline {
         self
     }
 
+    pub fn with_blur(mut self, sigma: f32) -> Self {
+        self.blur_sigma = sigma;
+        self
+    }
+
+    pub fn with_canny(mut self, low_threshold: f32, high_threshold: f32) -> Self {
+        self.canny_low = low_threshold;
+        self.canny_high = high_threshold;
+        self
+    }
+
+    pub fn with_min_contour_area(mut self, min_area: u32) -> Self {
+        self.min_contour_area = min_area;
+        self
+    }
+
+    /// Run OCR on `threads` threads (at least 1); each loads its own OCR engine, so this
+    /// only pays off for images with many circles. Detections keep the serial order.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Preprocess each circle for OCR onto a `target_size` square canvas, or one
+    /// `target_height` tall that widens with the crop, see `ocr::ocr_canvas_size`
+    pub fn with_ocr_canvas(mut self, target_size: u32, target_height: Option<u32>) -> Self {
+        self.ocr_target_size = target_size;
+        self.ocr_target_height = target_height;
+        self
+    }
+
+    /// Preprocessed OCR input for the circle `roi`, as `detect` feeds it to the engine
+    pub fn preprocess_roi(&self, roi: &DynamicImage) -> DynamicImage {
+        ocr::preprocess_roi_for_ocr(roi, self.ocr_target_size, self.ocr_target_height)
+    }
+
     /// Run the full detection pipeline on an image
     pub fn detect(&self, img: &DynamicImage) -> anyhow::Result<Vec<HouseNumberDetection>> {
+        let level = verbose_level(self.verbose);
+
         // Step 1: Preprocess image
-        if self.verbose {
-            println!("\nPreprocessing image...");
-            println!("Converting to grayscale...");
-        }
+        verbose_log!(self.verbose, "Preprocessing image...");
+        verbose_log!(self.verbose, "Converting to grayscale...");
         let gray = preprocessing::to_grayscale(img);
 
-        if self.verbose {
-            println!("Applying Gaussian blur...");
-        }
-        let blurred = preprocessing::apply_blur(&gray, 1.5);
+        verbose_log!(self.verbose, "Applying Gaussian blur...");
+        let blurred = preprocessing::apply_blur(&gray, self.blur_sigma);
 
         // Step 2: Detect edges
-        if self.verbose {
-            println!("\nDetecting edges...");
-        }
-        let edges = preprocessing::detect_edges(&blurred, 50.0, 100.0);
+        verbose_log!(self.verbose, "Detecting edges...");
+        let edges = preprocessing::detect_edges(&blurred, self.canny_low, self.canny_high);
 
         // Step 3: Find contours
-        if self.verbose {
-            println!("\nFinding contours...");
-        }
-        let all_contours = contours::find_contours(&edges, 10);
+        verbose_log!(self.verbose, "Finding contours...");
+        let all_contours = contours::find_contours(&edges, self.min_contour_area);
 
-        if self.verbose {
-            println!("Found {} contours", all_contours.len());
-        }
+        verbose_log!(self.verbose, "Found {} contours", all_contours.len());
 
         // Step 4: Filter for circular shapes
-        if self.verbose {
-            println!("\nFiltering for circular shapes...");
-            println!("Analyzing contours (showing first 10):");
+        verbose_log!(self.verbose, "Filtering for circular shapes...");
+        if log::log_enabled!(level) {
+            verbose_log!(self.verbose, "Analyzing contours (showing first 10):");
             for (i, contour) in all_contours.iter().take(10).enumerate() {
-                println!("  Contour {}: radius={:.1}, circ={:.3}, aspect={:.2}, pixels={}",
-                        i + 1, contour.radius(), contour.circularity(),
+                verbose_log!(self.verbose, "  Contour {}: radius={:.1}, circ={:.3}, aspect={:.2}, pixels={}",
+                        i + 1, contour.radius(), contour.circularity_bbox(),
                         contour.aspect_ratio(), contour.area());
             }
         }
@@ -81,18 +167,16 @@ impl DetectionPipeline {
             self.circularity_threshold,
         );
 
-        if self.verbose {
-            println!("Found {} circular shapes (from {} total contours)",
-                    circular_contours.len(), all_contours.len());
-        }
+        verbose_log!(self.verbose, "Found {} circular shapes (from {} total contours)",
+                circular_contours.len(), all_contours.len());
 
         // Step 5: Filter for white circles
-        if self.verbose {
-            println!("\nFiltering for white circles...");
-            println!("Analyzing brightness (showing first 5):");
+        verbose_log!(self.verbose, "Filtering for white circles...");
+        if log::log_enabled!(level) {
+            verbose_log!(self.verbose, "Analyzing brightness (showing first 5):");
             for (i, circle) in circular_contours.iter().take(5).enumerate() {
                 let brightness = circle.average_brightness(img);
-                println!("  Circle {}: brightness={:.1}/255", i + 1, brightness);
+                verbose_log!(self.verbose, "  Circle {}: brightness={:.1}/255", i + 1, brightness);
             }
         }
 
@@ -102,16 +186,13 @@ impl DetectionPipeline {
             self.brightness_threshold,
         );
 
-        if self.verbose {
-            println!("Found {} white circles (from {} circular shapes)",
-                    white_circles.len(), circular_contours.len());
-
-            if !white_circles.is_empty() {
-                println!("Example white circles:");
-                for (i, circle) in white_circles.iter().take(5).enumerate() {
-                    println!("  Circle {}: radius={:.1}, brightness={:.1}",
-                            i + 1, circle.radius(), circle.average_brightness(img));
-                }
+        verbose_log!(self.verbose, "Found {} white circles (from {} circular shapes)",
+                white_circles.len(), circular_contours.len());
+        if log::log_enabled!(level) && !white_circles.is_empty() {
+            verbose_log!(self.verbose, "Example white circles:");
+            for (i, circle) in white_circles.iter().take(5).enumerate() {
+                verbose_log!(self.verbose, "  Circle {}: radius={:.1}, brightness={:.1}",
+                        i + 1, circle.radius(), circle.average_brightness(img));
             }
         }
 
@@ -120,54 +201,89 @@ impl DetectionPipeline {
             return Ok(Vec::new());
         }
 
-        if self.verbose {
-            println!("\nInitializing OCR engine...");
+        if self.threads > 1 && white_circles.len() > 1 {
+            return self.recognize_parallel(img, &white_circles);
         }
 
+        verbose_log!(self.verbose, "Initializing OCR engine...");
+
         let ocr_engine = ocr::init_ocr_engine()?;
 
-        if self.verbose {
-            println!("OCR engine initialized successfully");
-            println!("\nRunning OCR on {} white circles...", white_circles.len());
-        }
+        verbose_log!(self.verbose, "OCR engine initialized successfully");
+        verbose_log!(self.verbose, "Running OCR on {} white circles...", white_circles.len());
 
         let mut detections = Vec::new();
 
         for (i, circle) in white_circles.iter().enumerate() {
-            if self.verbose {
-                println!("  Processing circle {} of {}...", i + 1, white_circles.len());
-            }
+            verbose_log!(self.verbose, "  Processing circle {} of {}...", i + 1, white_circles.len());
 
             if let Some(roi) = circle.extract_roi(img) {
-                if let Some((text, confidence)) = ocr::recognize_house_number(&ocr_engine, &roi) {
-                    let (x, y) = circle.center();
-                    detections.push(HouseNumberDetection {
-                        number: text.clone(),
-                        x,
-                        y,
-                        confidence,
-                    });
-
-                    if self.verbose {
-                        println!("    Detected: '{}' (confidence: {:.2})", text, confidence);
-                    }
-                } else if self.verbose {
-                    println!("    No text detected");
+                if let Some((text, confidence)) =
+                    ocr::recognize_house_number(&ocr_engine, &roi, self.ocr_target_size, self.ocr_target_height)
+                {
+                    verbose_log!(self.verbose, "    Detected: '{}' (confidence: {:.2})", text, confidence);
+                    detections.push(detection_for(circle, text, confidence));
+                } else {
+                    verbose_log!(self.verbose, "    No text detected");
                 }
-            } else if self.verbose {
-                println!("    Failed to extract ROI");
+            } else {
+                verbose_log!(self.verbose, "    Failed to extract ROI");
             }
         }
 
         Ok(detections)
     }
 
+    /// OCR the circles on `self.threads` scoped threads, each taking a contiguous chunk
+    fn recognize_parallel(&self, img: &DynamicImage, circles: &[Contour]) -> anyhow::Result<Vec<HouseNumberDetection>> {
+        let threads = self.threads.min(circles.len());
+        let chunk_size = circles.len().div_ceil(threads);
+        let (target_size, target_height) = (self.ocr_target_size, self.ocr_target_height);
+
+        verbose_log!(self.verbose, "Running OCR on {} white circles with {} threads...", circles.len(), threads);
+
+        let chunks: Vec<anyhow::Result<Vec<HouseNumberDetection>>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = circles
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        let engine = ocr::init_ocr_engine()?;
+                        Ok(chunk
+                            .iter()
+                            .filter_map(|circle| {
+                                let roi = circle.extract_roi(img)?;
+                                let (text, confidence) =
+                                    ocr::recognize_house_number(&engine, &roi, target_size, target_height)?;
+                                Some(detection_for(circle, text, confidence))
+                            })
+                            .collect())
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("OCR thread panicked"))
+                .collect()
+        });
+
+        let mut detections = Vec::new();
+        for chunk in chunks {
+            detections.extend(chunk?);
+        }
+
+        for detection in &detections {
+            verbose_log!(self.verbose, "    Detected: '{}' (confidence: {:.2})", detection.number, detection.confidence);
+        }
+
+        Ok(detections)
+    }
+
     /// Get all contours from an image (for debugging)
     pub fn get_contours(&self, img: &DynamicImage) -> anyhow::Result<Vec<Contour>> {
         let gray = preprocessing::to_grayscale(img);
-        let blurred = preprocessing::apply_blur(&gray, 1.5);
-        let edges = preprocessing::detect_edges(&blurred, 50.0, 100.0);
-        Ok(contours::find_contours(&edges, 10))
+        let blurred = preprocessing::apply_blur(&gray, self.blur_sigma);
+        let edges = preprocessing::detect_edges(&blurred, self.canny_low, self.canny_high);
+        Ok(contours::find_contours(&edges, self.min_contour_area))
     }
 
     /// Get circular contours from an image (for debugging)
@@ -192,6 +308,17 @@ impl DetectionPipeline {
     }
 }
 
+fn detection_for(circle: &Contour, number: String, confidence: f32) -> HouseNumberDetection {
+    let (x, y) = circle.center();
+    HouseNumberDetection {
+        number,
+        x,
+        y,
+        radius: circle.radius().round() as u32,
+        confidence,
+    }
+}
+
 impl Default for DetectionPipeline {
     fn default() -> Self {
         Self::new()
@@ -199,30 +326,74 @@ impl Default for DetectionPipeline {
 }
 
 /// Build a standard detection pipeline using the composable pipeline system
-pub fn build_standard_pipeline(verbose: bool) -> crate::pipeline::Pipeline {
+/// `sharpen` adds an `UnsharpMaskStep` on the upscaled crops before OCR.
+pub fn build_standard_pipeline(verbose: bool, sharpen: bool) -> crate::pipeline::Pipeline {
+    build_pipeline_with_params(&DetectionParams::default(), verbose, sharpen)
+}
+
+/// Like `build_standard_pipeline`, with the circle filters set from `params`
+pub fn build_pipeline_with_params(
+    params: &DetectionParams,
+    verbose: bool,
+    sharpen: bool,
+) -> crate::pipeline::Pipeline {
     use crate::pipeline::Pipeline;
+    use crate::detection::preprocessing::BorderMode;
+    use crate::detection::contours::Connectivity;
     use crate::detection::steps::*;
     use std::sync::Arc;
 
-    Pipeline::new()
+    let pipeline = Pipeline::new()
         .with_verbose(verbose)
         .add_step(Arc::new(GrayscaleStep))
-        .add_step(Arc::new(BlurStep { sigma: 1.5 }))
+        .add_step(Arc::new(BlurStep {
+            sigma: 1.5,
+            border: BorderMode::Replicate,
+        }))
         .add_step(Arc::new(EdgeDetectionStep {
             low_threshold: 50.0,
             high_threshold: 100.0,
         }))
-        .add_step(Arc::new(ContourDetectionStep { min_area: 10, padding: 10 }))
+        .add_step(Arc::new(ContourDetectionStep {
+            min_area: 10,
+            padding: 10,
+            max_area: None,
+            reject_border: false,
+            connectivity: Connectivity::Eight,
+        }))
         .add_step(Arc::new(CircleFilterStep {
-            min_radius: 10.0,
-            max_radius: 200.0,
-            circularity_threshold: 2.0,
+            min_radius: params.min_radius,
+            max_radius: params.max_radius,
+            circularity_threshold: params.circularity_threshold,
         }))
         .add_step(Arc::new(WhiteCircleFilterStep {
-            brightness_threshold: 200.0,
+            brightness_threshold: Some(params.brightness_threshold),
         }))
         .add_step(Arc::new(BackgroundRemovalStep))
-        .add_step(Arc::new(UpscaleStep { target_size: 100 }))
-        // Sharpening removed - doesn't improve OCR results
-        .add_step(Arc::new(OcrStep::new()))
+        .add_step(Arc::new(UpscaleStep {
+            target_size: 100,
+            target_height: None,
+        }));
+
+    let pipeline = if sharpen {
+        pipeline.add_step(Arc::new(UnsharpMaskStep { sigma: 1.0, amount: 1.0 }))
+    } else {
+        pipeline
+    };
+
+    pipeline.add_step(Arc::new(OcrStep::new()))
+}
+
+/// Turn a recognized pipeline item into a detection in original image coordinates.
+/// Items that carry their contour use its center and radius, which are more precise than
+/// the bounding box; items without OCR text yield `None`.
+pub fn detection_from_item(item: &crate::pipeline::PipelineData) -> Option<HouseNumberDetection> {
+    let detection = item.to_house_number_detection()?;
+    match Contour::from_metadata(&item.metadata) {
+        Ok(contour) => {
+            let (x, y) = contour.center();
+            Some(HouseNumberDetection { x, y, radius: contour.radius().round() as u32, ..detection })
+        }
+        Err(_) => Some(detection),
+    }
 }
@@ -1,29 +1,137 @@
-use image::{DynamicImage, GrayImage, Luma};
+use image::{DynamicImage, GrayImage, Luma, Rgb, RgbImage};
+use imageproc::geometric_transformations::{rotate_about_center, Interpolation};
 pub use ocrs::{OcrEngine, ImageSource};  // Re-export for use in other modules
-use ocrs::OcrEngineParams;
+use ocrs::{OcrEngineParams, TextItem, TextLine};
 use rten::Model;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-/// Initialize OCR engine with models from standard cache location
-pub fn init_ocr_engine() -> anyhow::Result<OcrEngine> {
-    // Try to load models from standard locations
+use crate::pipeline::PipelineError;
+
+const DETECTION_MODEL_FILE: &str = "text-detection.rten";
+const RECOGNITION_MODEL_FILE: &str = "text-recognition.rten";
+
+/// Where the ocrs models are downloaded from with the `download` feature
+#[cfg(feature = "download")]
+const MODEL_BASE_URL: &str = "https://ocrs-models.s3-accelerate.amazonaws.com";
+
+/// Size and SHA-256 digest a downloaded model must have
+#[cfg(feature = "download")]
+#[derive(Debug, Clone, Copy)]
+pub struct ModelPin {
+    pub file: &'static str,
+    pub size: u64,
+    /// Lowercase hex; empty while no digest has been pinned for the file
+    pub sha256: &'static str,
+}
+
+/// Pinned models for `download_model`. Models without a pinned digest are never downloaded;
+/// fill these in from a trusted copy of the files when updating the models.
+#[cfg(feature = "download")]
+pub const MODEL_PINS: [ModelPin; 2] = [
+    ModelPin {
+        file: DETECTION_MODEL_FILE,
+        size: 0,
+        sha256: "",
+    },
+    ModelPin {
+        file: RECOGNITION_MODEL_FILE,
+        size: 0,
+        sha256: "",
+    },
+];
+
+/// Standard ocrs cache location of the OCR models, `~/.cache/ocrs`
+pub fn model_cache_dir() -> anyhow::Result<PathBuf> {
     let home_dir = std::env::var("HOME")
         .or_else(|_| std::env::var("USERPROFILE"))?;
+    Ok(Path::new(&home_dir).join(".cache/ocrs"))
+}
 
-    let cache_dir = Path::new(&home_dir).join(".cache/ocrs");
-    let detection_model_path = cache_dir.join("text-detection.rten");
-    let recognition_model_path = cache_dir.join("text-recognition.rten");
+/// Paths of the detection and recognition models in `model_cache_dir`.
+/// With the `download` feature missing models are downloaded into the cache first;
+/// without it a missing model is a `PipelineError::OcrModelsMissing`.
+pub fn ensure_models() -> anyhow::Result<(PathBuf, PathBuf)> {
+    let cache_dir = model_cache_dir()?;
+    let detection_model_path = cache_dir.join(DETECTION_MODEL_FILE);
+    let recognition_model_path = cache_dir.join(RECOGNITION_MODEL_FILE);
 
-    // Check if models exist
     if !detection_model_path.exists() || !recognition_model_path.exists() {
+        #[cfg(not(feature = "download"))]
+        return Err(PipelineError::OcrModelsMissing {
+            detection: detection_model_path,
+            recognition: recognition_model_path,
+        }
+        .into());
+
+        #[cfg(feature = "download")]
+        for path in [&detection_model_path, &recognition_model_path] {
+            if !path.exists() {
+                download_model(path)?;
+            }
+        }
+    }
+    Ok((detection_model_path, recognition_model_path))
+}
+
+/// Download the model named like `path` into `path`.
+/// The data must match the pinned size and SHA-256 digest in `MODEL_PINS` before anything
+/// is written; it then goes to a `.part` file that is renamed into place, so an interrupted
+/// or tampered download is never mistaken for an installed model.
+#[cfg(feature = "download")]
+fn download_model(path: &Path) -> anyhow::Result<()> {
+    use anyhow::Context;
+    use sha2::{Digest, Sha256};
+
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .expect("model paths end in a file name");
+    let pin = MODEL_PINS
+        .iter()
+        .find(|pin| pin.file == file_name)
+        .expect("every model file has a pin");
+    if pin.sha256.is_empty() {
         anyhow::bail!(
-            "OCR models not found. Please run: ocrs-cli --help (or download models manually)\n\
-             Expected locations:\n  - {}\n  - {}",
-            detection_model_path.display(),
-            recognition_model_path.display()
+            "No pinned SHA-256 for OCR model {}; install it into {:?} by hand",
+            file_name,
+            path.parent().unwrap_or(path)
         );
     }
 
+    let url = format!("{}/{}", MODEL_BASE_URL, file_name);
+    let bytes = reqwest::blocking::get(&url)
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.bytes())
+        .with_context(|| format!("Failed to download OCR model from {}", url))?;
+    if bytes.len() as u64 != pin.size {
+        anyhow::bail!("OCR model from {} has {} bytes, expected {}", url, bytes.len(), pin.size);
+    }
+    let digest = format!("{:x}", Sha256::digest(&bytes));
+    if digest != pin.sha256 {
+        anyhow::bail!("OCR model from {} has SHA-256 {}, expected {}", url, digest, pin.sha256);
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create OCR model cache {:?}", parent))?;
+    }
+    let partial_path = path.with_extension("rten.part");
+    let written = std::fs::write(&partial_path, &bytes)
+        .with_context(|| format!("Failed to write OCR model {:?}", partial_path))
+        .and_then(|()| {
+            std::fs::rename(&partial_path, path)
+                .with_context(|| format!("Failed to move OCR model into place at {:?}", path))
+        });
+    if written.is_err() {
+        let _ = std::fs::remove_file(&partial_path);
+    }
+    written
+}
+
+/// Initialize OCR engine with models from standard cache location, see `ensure_models`
+pub fn init_ocr_engine() -> anyhow::Result<OcrEngine> {
+    let (detection_model_path, recognition_model_path) = ensure_models()?;
+
     // Load models
     let detection_model = Model::load_file(&detection_model_path)?;
     let recognition_model = Model::load_file(&recognition_model_path)?;
@@ -38,9 +146,54 @@ pub fn init_ocr_engine() -> anyhow::Result<OcrEngine> {
     Ok(engine)
 }
 
+/// Default OCR canvas size in pixels
+pub const OCR_TARGET_SIZE: u32 = 100;
+
+/// Canvas dimensions for OCR input cropped to `width`x`height`
+/// Without a `target_height` the canvas is a `target_size` square. With one, the canvas
+/// is `target_height` tall and widens with the crop's aspect ratio, so multi-digit
+/// numbers keep their glyph size instead of being squashed into a square.
+pub fn ocr_canvas_size(width: u32, height: u32, target_size: u32, target_height: Option<u32>) -> (u32, u32) {
+    match target_height {
+        None => (target_size, target_size),
+        Some(target_height) => {
+            let aspect = width as f32 / height.max(1) as f32;
+            let canvas_w = (target_height as f32 * aspect).round() as u32;
+            (canvas_w.max(target_height), target_height)
+        }
+    }
+}
+
+/// Scale image to fit a white canvas while maintaining aspect ratio, centered
+/// Thin crops keep at least 1px in each direction; an empty image gives a blank canvas.
+pub fn fit_to_canvas(gray: &GrayImage, canvas_w: u32, canvas_h: u32) -> GrayImage {
+    let (width, height) = gray.dimensions();
+    let mut canvas = GrayImage::from_pixel(canvas_w, canvas_h, Luma([255u8]));
+    if width == 0 || height == 0 || canvas_w == 0 || canvas_h == 0 {
+        return canvas;
+    }
+
+    // Calculate scaling to fit within the canvas while maintaining aspect ratio
+    let scale = (canvas_w as f32 / width as f32).min(canvas_h as f32 / height as f32);
+    let scaled_w = ((width as f32 * scale) as u32).clamp(1, canvas_w);
+    let scaled_h = ((height as f32 * scale) as u32).clamp(1, canvas_h);
+
+    // Resize with high-quality interpolation
+    let scaled = image::imageops::resize(gray, scaled_w, scaled_h, image::imageops::FilterType::CatmullRom);
+
+    // Center the scaled image in the canvas
+    let offset_x = (canvas_w - scaled_w) / 2;
+    let offset_y = (canvas_h - scaled_h) / 2;
+
+    image::imageops::overlay(&mut canvas, &scaled, offset_x.into(), offset_y.into());
+
+    canvas
+}
+
 /// Preprocess ROI to isolate black text on white background
-/// Strategy: Remove background, crop to content, add uniform border, upscale to 100x100px
-pub fn preprocess_roi_for_ocr(roi: &DynamicImage) -> DynamicImage {
+/// Strategy: Remove background, crop to content, add uniform border, upscale to a
+/// `target_size` square canvas (or `target_height` tall with proportional width)
+pub fn preprocess_roi_for_ocr(roi: &DynamicImage, target_size: u32, target_height: Option<u32>) -> DynamicImage {
     let gray = roi.to_luma8();
     let (width, height) = gray.dimensions();
 
@@ -101,55 +254,303 @@ pub fn preprocess_roi_for_ocr(roi: &DynamicImage) -> DynamicImage {
 
     let cropped = image::imageops::crop_imm(&processed, crop_x, crop_y, crop_w, crop_h).to_image();
 
-    // Upscale to 100x100px while maintaining aspect ratio
-    let target_size = 100u32;
+    // Upscale while maintaining aspect ratio
     let (cropped_w, cropped_h) = cropped.dimensions();
-
-    // Calculate scaling to fit within 100x100 while maintaining aspect ratio
-    let scale = (target_size as f32 / cropped_w as f32).min(target_size as f32 / cropped_h as f32);
-    let scaled_w = (cropped_w as f32 * scale) as u32;
-    let scaled_h = (cropped_h as f32 * scale) as u32;
-
-    // Resize with high-quality interpolation
-    let scaled = image::imageops::resize(&cropped, scaled_w, scaled_h, image::imageops::FilterType::CatmullRom);
-
-    // Center the scaled image in a 100x100 white canvas
-    let mut canvas = GrayImage::from_pixel(target_size, target_size, Luma([255u8]));
-    let offset_x = (target_size - scaled_w) / 2;
-    let offset_y = (target_size - scaled_h) / 2;
-
-    image::imageops::overlay(&mut canvas, &scaled, offset_x.into(), offset_y.into());
+    let (canvas_w, canvas_h) = ocr_canvas_size(cropped_w, cropped_h, target_size, target_height);
+    let canvas = fit_to_canvas(&cropped, canvas_w, canvas_h);
 
     DynamicImage::ImageLuma8(canvas)
 }
 
-/// Recognize house number from a circle ROI
+/// Recognize house number from a circle ROI, preprocessed onto a canvas sized as in
+/// `ocr_canvas_size`
 pub fn recognize_house_number(
     engine: &OcrEngine,
     roi: &DynamicImage,
+    target_size: u32,
+    target_height: Option<u32>,
 ) -> Option<(String, f32)> {
     // Preprocess: remove background and circle outline, leaving only black text on white
-    let preprocessed = preprocess_roi_for_ocr(roi);
+    let preprocessed = preprocess_roi_for_ocr(roi, target_size, target_height);
+
+    let recognized = recognize_text(engine, &preprocessed).and_then(|(text, confidence)| {
+        Some((normalize_house_number(&text)?, confidence))
+    });
+    if recognized.is_some() || roi.width().max(roi.height()) > SINGLE_DIGIT_MAX_ROI {
+        return recognized;
+    }
+
+    // OCR often misses tiny single-digit circles; compare against the digit templates instead
+    let (digit, score) = classify_single_digit(&preprocessed.to_luma8())?;
+    (score >= DIGIT_TEMPLATE_MIN_SCORE).then(|| (digit.to_string(), score))
+}
+
+/// Largest circle ROI side in pixels for which `recognize_house_number` falls back to
+/// template matching when OCR finds no text
+pub const SINGLE_DIGIT_MAX_ROI: u32 = 48;
 
+/// Lowest `classify_single_digit` score accepted as a house number
+pub const DIGIT_TEMPLATE_MIN_SCORE: f32 = 0.6;
+
+/// Digit templates on a 5x7 grid, '#' marks ink
+/// Straight strokes only, so they suit blocky seven-segment and pixel-font digits. Digits
+/// with diagonal or curved strokes (a slanted "7", a round "2") correlate poorly and
+/// usually score below `DIGIT_TEMPLATE_MIN_SCORE`.
+const DIGIT_TEMPLATES: [(char, [&str; 7]); 10] = [
+    ('0', ["#####", "#   #", "#   #", "#   #", "#   #", "#   #", "#####"]),
+    ('1', ["  #  ", "  #  ", "  #  ", "  #  ", "  #  ", "  #  ", "  #  "]),
+    ('2', ["#####", "    #", "    #", "#####", "#    ", "#    ", "#####"]),
+    ('3', ["#####", "    #", "    #", " ####", "    #", "    #", "#####"]),
+    ('4', ["#   #", "#   #", "#   #", "#####", "    #", "    #", "    #"]),
+    ('5', ["#####", "#    ", "#    ", "#####", "    #", "    #", "#####"]),
+    ('6', ["#####", "#    ", "#    ", "#####", "#   #", "#   #", "#####"]),
+    ('7', ["#####", "    #", "    #", "    #", "    #", "    #", "    #"]),
+    ('8', ["#####", "#   #", "#   #", "#####", "#   #", "#   #", "#####"]),
+    ('9', ["#####", "#   #", "#   #", "#####", "    #", "    #", "#####"]),
+];
+
+const GLYPH_GRID_WIDTH: u32 = 5;
+const GLYPH_GRID_HEIGHT: u32 = 7;
+
+/// Classify a dark single digit on a light background by template matching
+/// The ROI is thresholded halfway between its darkest and lightest pixel, cropped to the
+/// ink and averaged onto the template grid; the score is the normalized correlation
+/// (1.0 = identical) with the best matching template. Returns `None` for blank ROIs.
+pub fn classify_single_digit(roi: &GrayImage) -> Option<(char, f32)> {
+    let (min, max) = roi
+        .pixels()
+        .fold((u8::MAX, u8::MIN), |(min, max), p| (min.min(p[0]), max.max(p[0])));
+    if max.saturating_sub(min) < 64 {
+        return None;
+    }
+    let threshold = ((min as u16 + max as u16) / 2) as u8;
+    let grid = glyph_grid(roi.width(), roi.height(), |x, y| roi.get_pixel(x, y)[0] < threshold)?;
+
+    DIGIT_TEMPLATES
+        .iter()
+        .filter_map(|(digit, rows)| {
+            let template = glyph_grid(GLYPH_GRID_WIDTH, GLYPH_GRID_HEIGHT, |x, y| {
+                rows[y as usize].as_bytes()[x as usize] == b'#'
+            })?;
+            Some((*digit, correlation(&grid, &template)))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// Ink coverage of each cell after fitting the ink's bounding box onto the glyph grid
+/// Narrow glyphs (less than 2:5 wide) keep their aspect ratio and are centered, so a
+/// "1" stays a bar instead of filling the grid. Returns `None` without any ink.
+fn glyph_grid(width: u32, height: u32, is_ink: impl Fn(u32, u32) -> bool) -> Option<Vec<f32>> {
+    let mut bounds: Option<(u32, u32, u32, u32)> = None;
+    for y in 0..height {
+        for x in 0..width {
+            if is_ink(x, y) {
+                let (x0, y0, x1, y1) = bounds.unwrap_or((x, y, x, y));
+                bounds = Some((x0.min(x), y0.min(y), x1.max(x), y1.max(y)));
+            }
+        }
+    }
+    let (x0, y0, x1, y1) = bounds?;
+    let (ink_w, ink_h) = ((x1 - x0 + 1) as f32, (y1 - y0 + 1) as f32);
+
+    let cols = if ink_w * 5.0 >= ink_h * 2.0 {
+        GLYPH_GRID_WIDTH
+    } else {
+        ((ink_w * GLYPH_GRID_HEIGHT as f32 / ink_h).round() as u32).clamp(1, GLYPH_GRID_WIDTH)
+    };
+    let offset = (GLYPH_GRID_WIDTH - cols) / 2;
+    let cell_w = ink_w / cols as f32;
+    let cell_h = ink_h / GLYPH_GRID_HEIGHT as f32;
+    let overlap = |a0: f32, a1: f32, b: u32| (a1.min(b as f32 + 1.0) - a0.max(b as f32)).max(0.0);
+
+    let mut grid = vec![0.0; (GLYPH_GRID_WIDTH * GLYPH_GRID_HEIGHT) as usize];
+    for row in 0..GLYPH_GRID_HEIGHT {
+        let sy0 = y0 as f32 + row as f32 * cell_h;
+        let sy1 = sy0 + cell_h;
+        for col in 0..cols {
+            let sx0 = x0 as f32 + col as f32 * cell_w;
+            let sx1 = sx0 + cell_w;
+            let mut covered = 0.0;
+            for y in (sy0.floor() as u32)..(sy1.ceil() as u32).min(y1 + 1) {
+                for x in (sx0.floor() as u32)..(sx1.ceil() as u32).min(x1 + 1) {
+                    if is_ink(x, y) {
+                        covered += overlap(sy0, sy1, y) * overlap(sx0, sx1, x);
+                    }
+                }
+            }
+            grid[(row * GLYPH_GRID_WIDTH + offset + col) as usize] = covered / (cell_w * cell_h);
+        }
+    }
+    Some(grid)
+}
+
+/// Pearson correlation of two equally sized grids, 0.0 if either is uniform
+fn correlation(a: &[f32], b: &[f32]) -> f32 {
+    let mean = |v: &[f32]| v.iter().sum::<f32>() / v.len() as f32;
+    let (mean_a, mean_b) = (mean(a), mean(b));
+    let (mut ab, mut aa, mut bb) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        let (dx, dy) = (x - mean_a, y - mean_b);
+        ab += dx * dy;
+        aa += dx * dx;
+        bb += dy * dy;
+    }
+    if aa == 0.0 || bb == 0.0 {
+        return 0.0;
+    }
+    ab / (aa * bb).sqrt()
+}
+
+/// Clean up raw OCR output into a house number
+/// Whitespace and surrounding punctuation are dropped, then the leading digits are kept
+/// together with an optional single letter suffix ("12a"). Returns `None` if no digits remain.
+pub fn normalize_house_number(raw: &str) -> Option<String> {
+    let compact: String = raw.chars().filter(|c| !c.is_whitespace()).collect();
+    let trimmed = compact.trim_matches(|c: char| !c.is_alphanumeric());
+
+    let digits: String = trimmed.chars().take_while(|c| c.is_ascii_digit()).collect();
+    if digits.is_empty() {
+        return None;
+    }
+
+    match trimmed[digits.len()..].chars().next() {
+        Some(suffix) if suffix.is_alphabetic() => Some(format!("{digits}{suffix}")),
+        _ => Some(digits),
+    }
+}
+
+/// Rotate an image counterclockwise by `degrees`, padding with white so no content is clipped
+/// The canvas grows to the image diagonal before rotating, so corners stay inside.
+pub fn rotate_with_padding(img: &DynamicImage, degrees: f32) -> DynamicImage {
+    if degrees % 360.0 == 0.0 {
+        return img.clone();
+    }
+
+    let rgb = img.to_rgb8();
+    let (width, height) = rgb.dimensions();
+    let diagonal = ((width * width + height * height) as f32).sqrt().ceil() as u32;
+
+    let mut canvas = RgbImage::from_pixel(diagonal, diagonal, Rgb([255u8, 255, 255]));
+    let offset_x = (diagonal - width) / 2;
+    let offset_y = (diagonal - height) / 2;
+    image::imageops::overlay(&mut canvas, &rgb, offset_x.into(), offset_y.into());
+
+    // imageproc rotates clockwise for positive angles
+    let rotated = rotate_about_center(
+        &canvas,
+        -degrees.to_radians(),
+        Interpolation::Bilinear,
+        Rgb([255u8, 255, 255]),
+    );
+
+    DynamicImage::ImageRgb8(rotated)
+}
+
+/// Run OCR on an already preprocessed image, returning the text and its confidence
+/// Digit-only lines on one row are joined left to right (see `join_digit_tokens`).
+/// Otherwise the highest-confidence line containing a digit wins; if no line contains
+/// a digit, the highest-confidence line overall is used.
+pub fn recognize_text(engine: &OcrEngine, img: &DynamicImage) -> Option<(String, f32)> {
     // Convert to RGB8 format for OCR
-    let img = preprocessed.to_rgb8();
+    let img = img.to_rgb8();
 
     // Prepare image for OCR
     let img_source = ImageSource::from_bytes(img.as_raw(), img.dimensions()).ok()?;
     let ocr_input = engine.prepare_input(img_source).ok()?;
 
-    // Run OCR - use simple get_text for straightforward extraction
-    match engine.get_text(&ocr_input) {
-        Ok(text) => {
-            let text = text.trim().to_string();
+    // Use the detailed API to get per-character scores
+    let words = engine.detect_words(&ocr_input).ok()?;
+    let line_rects = engine.find_text_lines(&ocr_input, &words);
+    let lines = engine.recognize_text(&ocr_input, &line_rects).ok()?;
+
+    let tokens: Vec<TextToken> = lines
+        .iter()
+        .flatten()
+        .filter_map(|line| {
+            let text = line.to_string().trim().to_string();
             if text.is_empty() {
-                None
-            } else {
-                // For now, we'll use a default confidence since get_text doesn't provide it
-                // In a future phase, we can use the detailed API for per-character confidence
-                Some((text, 0.9))
+                return None;
             }
-        }
-        Err(_) => None,
+            let rect = line.bounding_rect();
+            Some(TextToken {
+                text,
+                confidence: line_confidence(line),
+                left: rect.left(),
+                top: rect.top(),
+                bottom: rect.bottom(),
+            })
+        })
+        .collect();
+
+    // Widely spaced digits come back as separate lines; read them as one number
+    if let Some(joined) = join_digit_tokens(&tokens) {
+        return Some(joined);
+    }
+
+    let candidates: Vec<(String, f32)> = tokens
+        .into_iter()
+        .map(|token| (token.text, token.confidence))
+        .collect();
+
+    let best = |lines: Vec<(String, f32)>| {
+        lines.into_iter().max_by(|a, b| a.1.total_cmp(&b.1))
+    };
+
+    let (numeric, other): (Vec<_>, Vec<_>) = candidates
+        .into_iter()
+        .partition(|(text, _)| text.chars().any(|c| c.is_ascii_digit()));
+
+    best(numeric).or_else(|| best(other))
+}
+
+/// A recognized text line and where it sits in the OCR input
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextToken {
+    pub text: String,
+    pub confidence: f32,
+    /// Left edge in pixels, used to order tokens
+    pub left: i32,
+    pub top: i32,
+    pub bottom: i32,
+}
+
+/// Join all-digit tokens on the same row as the most confident one, left to right
+/// OCR splits widely spaced digits like "1   2" into separate lines, which would
+/// otherwise be read as just "1". Tokens share a row when their vertical spans overlap
+/// by at least half the shorter one. The joined confidence is the lowest of the parts.
+/// Returns `None` unless at least two tokens were joined.
+pub fn join_digit_tokens(tokens: &[TextToken]) -> Option<(String, f32)> {
+    let digits: Vec<&TextToken> = tokens
+        .iter()
+        .filter(|token| token.text.chars().all(|c| c.is_ascii_digit()))
+        .collect();
+    let anchor = digits.iter().max_by(|a, b| a.confidence.total_cmp(&b.confidence))?;
+
+    let mut row: Vec<&TextToken> = digits
+        .iter()
+        .copied()
+        .filter(|token| {
+            let overlap = token.bottom.min(anchor.bottom) - token.top.max(anchor.top);
+            let shorter = (token.bottom - token.top).min(anchor.bottom - anchor.top);
+            overlap * 2 >= shorter
+        })
+        .collect();
+    if row.len() < 2 {
+        return None;
+    }
+
+    row.sort_by_key(|token| token.left);
+    let text: String = row.iter().map(|token| token.text.as_str()).collect();
+    let confidence = row.iter().map(|token| token.confidence).fold(1.0, f32::min);
+    Some((text, confidence))
+}
+
+/// Average character score of a recognized line, clamped to (0.0, 1.0]
+fn line_confidence(line: &TextLine) -> f32 {
+    let chars = line.chars();
+    if chars.is_empty() {
+        return f32::MIN_POSITIVE;
     }
+    let mean = chars.iter().map(|c| c.score).sum::<f32>() / chars.len() as f32;
+    mean.clamp(f32::MIN_POSITIVE, 1.0)
 }
@@ -1,6 +1,8 @@
-use image::{DynamicImage, GrayImage};
+use image::{DynamicImage, GrayImage, Luma};
 use imageproc::filter::gaussian_blur_f32;
+use imageproc::distance_transform::Norm;
 use imageproc::edges::canny;
+use imageproc::morphology;
 
 /// Convert image to grayscale
 pub fn to_grayscale(img: &DynamicImage) -> GrayImage {
@@ -12,7 +14,299 @@ pub fn apply_blur(img: &GrayImage, sigma: f32) -> GrayImage {
     gaussian_blur_f32(img, sigma)
 }
 
+/// How blurring fills in pixels beyond the image border
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BorderMode {
+    /// Repeat the edge pixel, like `apply_blur`
+    #[default]
+    Replicate,
+    /// Mirror the image at the edge without repeating the edge pixel: `cb|abc|ba`
+    Reflect,
+    /// Treat everything outside as black, which darkens the border
+    Zero,
+}
+
+/// Gaussian blur with explicit border handling
+/// Uses a separable kernel of radius `ceil(3 * sigma)` and rounds to the nearest value, so a
+/// uniform image stays exactly uniform away from `Zero` borders. Non-positive `sigma` returns
+/// the image unchanged.
+pub fn apply_blur_with_border(img: &GrayImage, sigma: f32, border: BorderMode) -> GrayImage {
+    if sigma <= 0.0 || img.width() == 0 || img.height() == 0 {
+        return img.clone();
+    }
+
+    let radius = (3.0 * sigma).ceil() as i64;
+    let weights: Vec<f32> = (-radius..=radius)
+        .map(|offset| (-((offset * offset) as f32) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f32 = weights.iter().sum();
+    let kernel: Vec<f32> = weights.iter().map(|w| w / total).collect();
+
+    let (width, height) = (img.width() as i64, img.height() as i64);
+    // Horizontal pass into floats, then vertical pass back to bytes
+    let mut horizontal = vec![0.0f32; (width * height) as usize];
+    for y in 0..height {
+        for x in 0..width {
+            horizontal[(y * width + x) as usize] = kernel
+                .iter()
+                .zip(-radius..=radius)
+                .filter_map(|(weight, offset)| {
+                    border_index(x + offset, width, border)
+                        .map(|sx| weight * img.get_pixel(sx as u32, y as u32)[0] as f32)
+                })
+                .sum();
+        }
+    }
+    GrayImage::from_fn(img.width(), img.height(), |x, y| {
+        let value: f32 = kernel
+            .iter()
+            .zip(-radius..=radius)
+            .filter_map(|(weight, offset)| {
+                border_index(y as i64 + offset, height, border)
+                    .map(|sy| weight * horizontal[(sy * width + x as i64) as usize])
+            })
+            .sum();
+        Luma([value.round().clamp(0.0, 255.0) as u8])
+    })
+}
+
+/// Index inside `0..len` to read for position `i`, or `None` for a zero pixel
+fn border_index(i: i64, len: i64, border: BorderMode) -> Option<i64> {
+    if (0..len).contains(&i) {
+        return Some(i);
+    }
+    match border {
+        BorderMode::Zero => None,
+        BorderMode::Replicate => Some(i.clamp(0, len - 1)),
+        BorderMode::Reflect => {
+            if len == 1 {
+                return Some(0);
+            }
+            // Reflection repeats with period 2 * (len - 1)
+            let period = 2 * (len - 1);
+            let i = i.rem_euclid(period);
+            Some(if i < len { i } else { period - i })
+        }
+    }
+}
+
+/// Unsharp mask: `orig + amount * (orig - blur)`, clamped to 0-255
+/// The Gaussian blur covers the whole image, so borders are sharpened too.
+pub fn unsharp_mask(img: &GrayImage, sigma: f32, amount: f32) -> GrayImage {
+    let blurred = gaussian_blur_f32(img, sigma);
+    let mut sharpened = img.clone();
+    for (pixel, blur) in sharpened.pixels_mut().zip(blurred.pixels()) {
+        let orig = pixel[0] as f32;
+        let value = orig + amount * (orig - blur[0] as f32);
+        pixel[0] = value.round().clamp(0.0, 255.0) as u8;
+    }
+    sharpened
+}
+
 /// Detect edges using Canny edge detector
 pub fn detect_edges(img: &GrayImage, low_threshold: f32, high_threshold: f32) -> GrayImage {
     canny(img, low_threshold, high_threshold)
 }
+
+/// Morphological operation on a binary image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MorphOp {
+    /// Grow foreground by the kernel
+    Dilate,
+    /// Shrink foreground by the kernel
+    Erode,
+    /// Erode then dilate: removes specks smaller than the kernel
+    Open,
+    /// Dilate then erode: bridges gaps smaller than the kernel
+    Close,
+}
+
+/// Apply a morphological operation with a square `kernel_size` x `kernel_size` structuring element
+/// Even sizes are rounded down to the next odd size; sizes 0 and 1 leave the image unchanged.
+pub fn morph(img: &GrayImage, op: MorphOp, kernel_size: u32) -> GrayImage {
+    // The L-infinity norm makes imageproc's structuring element a square of side 2k + 1
+    let k = (kernel_size / 2).min(u8::MAX as u32) as u8;
+    match op {
+        MorphOp::Dilate => morphology::dilate(img, Norm::LInf, k),
+        MorphOp::Erode => morphology::erode(img, Norm::LInf, k),
+        MorphOp::Open => morphology::open(img, Norm::LInf, k),
+        MorphOp::Close => morphology::close(img, Norm::LInf, k),
+    }
+}
+
+/// Estimate how far horizontal structure in an edge image is rotated, in degrees counterclockwise
+/// Uses a projection profile: edge pixels are projected onto the vertical axis for each candidate
+/// angle within `max_angle`, and the angle giving the sharpest row profile wins.
+/// Returns 0 for an image without edges.
+pub fn estimate_skew(edges: &GrayImage, max_angle: f32) -> f32 {
+    let points: Vec<(f32, f32)> = edges
+        .enumerate_pixels()
+        .filter(|(_, _, pixel)| pixel[0] > 0)
+        .map(|(x, y, _)| (x as f32, y as f32))
+        .collect();
+    if points.is_empty() || max_angle <= 0.0 {
+        return 0.0;
+    }
+
+    let diagonal = ((edges.width() as f32).powi(2) + (edges.height() as f32).powi(2)).sqrt();
+    let profile_score = |angle: f32| -> f64 {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let mut rows = vec![0u64; 2 * diagonal.ceil() as usize + 1];
+        for &(x, y) in &points {
+            // Undo a counterclockwise rotation by `angle`; offset keeps the row index positive
+            let row = (y * cos + x * sin + diagonal).round() as usize;
+            rows[row.min(rows.len() - 1)] += 1;
+        }
+        rows.iter().map(|&count| (count * count) as f64).sum()
+    };
+    let best_angle = |candidates: &mut dyn Iterator<Item = f32>| -> f32 {
+        candidates
+            .map(|angle| (angle, profile_score(angle)))
+            .fold((0.0, f64::MIN), |best, current| if current.1 > best.1 { current } else { best })
+            .0
+    };
+
+    // Coarse search in half degrees, then refine around the best match in tenths
+    let coarse_steps = (max_angle * 2.0).floor() as i32;
+    let coarse = best_angle(&mut (-coarse_steps..=coarse_steps).map(|i| i as f32 * 0.5));
+    best_angle(&mut (-5..=5).map(|i| (coarse + i as f32 * 0.1).clamp(-max_angle, max_angle)))
+}
+
+/// Compute the optimal global threshold of a grayscale image with Otsu's method
+/// Pixels at or below the returned value form the dark class, pixels above it the bright one.
+pub fn otsu_threshold(img: &GrayImage) -> u8 {
+    let mut histogram = [0u64; 256];
+    for pixel in img.pixels() {
+        histogram[pixel[0] as usize] += 1;
+    }
+    otsu_threshold_from_histogram(&histogram)
+}
+
+/// Otsu's method on a precomputed 256-bin histogram
+/// When fewer than two brightness levels are present there is nothing to separate,
+/// so the single level present is returned (0 for an empty histogram).
+/// If several thresholds separate the classes equally well, the middle one is used.
+pub fn otsu_threshold_from_histogram(histogram: &[u64; 256]) -> u8 {
+    let total: u64 = histogram.iter().sum();
+    let levels: Vec<usize> = (0..256).filter(|&i| histogram[i] > 0).collect();
+    if levels.len() < 2 {
+        return levels.first().map(|&level| level as u8).unwrap_or(0);
+    }
+
+    let sum_all: f64 = histogram.iter().enumerate().map(|(i, &h)| i as f64 * h as f64).sum();
+    let mut weight_bg = 0u64;
+    let mut sum_bg = 0.0;
+    let mut best_variance = -1.0;
+    let (mut best_first, mut best_last) = (0usize, 0usize);
+
+    for t in 0..256 {
+        weight_bg += histogram[t];
+        if weight_bg == 0 {
+            continue;
+        }
+        let weight_fg = total - weight_bg;
+        if weight_fg == 0 {
+            break;
+        }
+        sum_bg += t as f64 * histogram[t] as f64;
+
+        let mean_bg = sum_bg / weight_bg as f64;
+        let mean_fg = (sum_all - sum_bg) / weight_fg as f64;
+        let variance = weight_bg as f64 * weight_fg as f64 * (mean_bg - mean_fg).powi(2);
+
+        if variance > best_variance {
+            best_variance = variance;
+            best_first = t;
+            best_last = t;
+        } else if variance == best_variance {
+            best_last = t;
+        }
+    }
+
+    ((best_first + best_last) / 2) as u8
+}
+
+/// Contrast-limited adaptive histogram equalization (CLAHE)
+/// Each `tile_size` tile gets its own equalization mapping with the histogram clipped at
+/// `clip_limit` times the average bin count; the clipped excess is spread over all bins.
+/// Pixels blend the mappings of the four nearest tiles bilinearly. Images smaller than a
+/// tile fall back to plain histogram equalization.
+pub fn clahe(img: &GrayImage, clip_limit: f32, tile_size: u32) -> GrayImage {
+    let (width, height) = img.dimensions();
+    if tile_size == 0 || width < tile_size || height < tile_size {
+        return imageproc::contrast::equalize_histogram(img);
+    }
+
+    let tiles_x = width.div_ceil(tile_size);
+    let tiles_y = height.div_ceil(tile_size);
+
+    // One lookup table per tile
+    let mut mappings = Vec::with_capacity((tiles_x * tiles_y) as usize);
+    for ty in 0..tiles_y {
+        for tx in 0..tiles_x {
+            let x0 = tx * tile_size;
+            let y0 = ty * tile_size;
+            let x1 = (x0 + tile_size).min(width);
+            let y1 = (y0 + tile_size).min(height);
+
+            let mut histogram = [0u32; 256];
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    histogram[img.get_pixel(x, y)[0] as usize] += 1;
+                }
+            }
+            let pixels = (x1 - x0) * (y1 - y0);
+            mappings.push(clipped_equalization(&mut histogram, pixels, clip_limit));
+        }
+    }
+
+    let half = tile_size as f32 / 2.0;
+    let position = |coord: u32, tiles: u32| {
+        // Tile-center coordinates: tile i covers the range around i + 0.5
+        let f = ((coord as f32 - half) / tile_size as f32).max(0.0);
+        let i0 = (f.floor() as u32).min(tiles - 1);
+        let i1 = (i0 + 1).min(tiles - 1);
+        (i0, i1, (f - i0 as f32).clamp(0.0, 1.0))
+    };
+
+    GrayImage::from_fn(width, height, |x, y| {
+        let (tx0, tx1, wx) = position(x, tiles_x);
+        let (ty0, ty1, wy) = position(y, tiles_y);
+        let value = img.get_pixel(x, y)[0] as usize;
+        let map = |tx: u32, ty: u32| mappings[(ty * tiles_x + tx) as usize][value] as f32;
+
+        let top = map(tx0, ty0) * (1.0 - wx) + map(tx1, ty0) * wx;
+        let bottom = map(tx0, ty1) * (1.0 - wx) + map(tx1, ty1) * wx;
+        image::Luma([(top * (1.0 - wy) + bottom * wy).round() as u8])
+    })
+}
+
+/// Clip a tile histogram, redistribute the excess and return the equalization mapping
+fn clipped_equalization(histogram: &mut [u32; 256], pixels: u32, clip_limit: f32) -> [u8; 256] {
+    let limit = ((clip_limit * pixels as f32 / 256.0) as u32).max(1);
+
+    let mut excess = 0;
+    for bin in histogram.iter_mut() {
+        if *bin > limit {
+            excess += *bin - limit;
+            *bin = limit;
+        }
+    }
+
+    // Spread the excess evenly, remainder to the lowest bins
+    let share = excess / 256;
+    let remainder = (excess % 256) as usize;
+    for (i, bin) in histogram.iter_mut().enumerate() {
+        *bin += share + u32::from(i < remainder);
+    }
+
+    let mut mapping = [0u8; 256];
+    let mut cumulative = 0u64;
+    for (i, &count) in histogram.iter().enumerate() {
+        cumulative += count as u64;
+        mapping[i] = (cumulative * 255 / pixels as u64) as u8;
+    }
+    mapping
+}
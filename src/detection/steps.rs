@@ -1,15 +1,18 @@
-use crate::pipeline::{PipelineData, PipelineStep, PipelineContext, BoundingBox, MetadataValue};
-use crate::detection::{preprocessing, contours, ocr};
+use crate::pipeline::{self, verbose_log, PipelineData, PipelineStep, PipelineContext, PipelineError, BoundingBox, MetadataValue};
+use crate::detection::{preprocessing, contours, circles, ocr};
+use crate::core::db::Color;
 use crate::models::Contour;
 use anyhow::Result;
-use image::GenericImageView;
+use image::{GenericImageView, Pixel};
+use imageproc::geometric_transformations::{rotate_about_center, Interpolation};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
 /// Convert image to grayscale
 pub struct GrayscaleStep;
 
 impl PipelineStep for GrayscaleStep {
-    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>> {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
         let mut result = Vec::new();
         for item in data {
             let gray = preprocessing::to_grayscale(&item.image);
@@ -29,17 +32,106 @@ impl PipelineStep for GrayscaleStep {
     }
 }
 
+/// Enhance local contrast with CLAHE so faint circle outlines survive edge detection
+/// Insert between `GrayscaleStep` and `BlurStep`.
+pub struct ContrastEnhanceStep {
+    /// Histogram clip limit as a multiple of the average bin count (typically 2.0-4.0)
+    pub clip_limit: f32,
+    /// Tile edge length in pixels (typically 64)
+    pub tile_size: u32,
+}
+
+impl PipelineStep for ContrastEnhanceStep {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
+        let mut result = Vec::new();
+        for item in data {
+            let gray = item.image.to_luma8();
+            let enhanced = preprocessing::clahe(&gray, self.clip_limit, self.tile_size);
+            let new_item = PipelineData {
+                image: image::DynamicImage::ImageLuma8(enhanced),
+                original: item.original.clone(),
+                bbox: item.bbox.clone(),
+                metadata: item.metadata.clone(),
+            };
+            result.push(new_item);
+        }
+        Ok(result)
+    }
+
+    fn name(&self) -> &str {
+        "Contrast Enhancement"
+    }
+}
+
+/// Largest skew `DeskewStep` corrects, in degrees either way
+pub const MAX_DESKEW_ANGLE: f32 = 15.0;
+
+/// Straighten rotated scans; run right after `GrayscaleStep`
+/// Estimates the skew from the Canny edges of the grayscale image and rotates it back about
+/// its center, keeping the image size. The original is rotated the same way so later crops
+/// line up. Records the applied counterclockwise correction in degrees as "deskew_angle".
+pub struct DeskewStep {
+    /// Search range for the skew angle, capped at `MAX_DESKEW_ANGLE`
+    pub max_angle: f32,
+}
+
+impl PipelineStep for DeskewStep {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
+        let max_angle = self.max_angle.clamp(0.0, MAX_DESKEW_ANGLE);
+        let mut result = Vec::new();
+        for item in data {
+            let gray = item.image.to_luma8();
+            let edges = preprocessing::detect_edges(&gray, 50.0, 100.0);
+            let correction = -preprocessing::estimate_skew(&edges, max_angle);
+
+            let (image, original) = if correction == 0.0 {
+                (image::DynamicImage::ImageLuma8(gray), item.original.clone())
+            } else {
+                // imageproc rotates clockwise for positive angles
+                let theta = -correction.to_radians();
+                let rotated = rotate_about_center(&gray, theta, Interpolation::Bilinear, image::Luma([255u8]));
+                let original = rotate_about_center(
+                    &item.original.to_rgb8(),
+                    theta,
+                    Interpolation::Bilinear,
+                    image::Rgb([255u8, 255, 255]),
+                );
+                (
+                    image::DynamicImage::ImageLuma8(rotated),
+                    Arc::new(image::DynamicImage::ImageRgb8(original)),
+                )
+            };
+
+            let new_item = PipelineData {
+                image,
+                original,
+                bbox: item.bbox.clone(),
+                metadata: item.metadata.clone(),
+            }
+            .with_metadata("deskew_angle", MetadataValue::Float(correction));
+            result.push(new_item);
+        }
+        Ok(result)
+    }
+
+    fn name(&self) -> &str {
+        "Deskew"
+    }
+}
+
 /// Apply Gaussian blur
 pub struct BlurStep {
     pub sigma: f32,
+    /// How pixels beyond the image edge are filled in; `Replicate` avoids false edges at the frame
+    pub border: preprocessing::BorderMode,
 }
 
 impl PipelineStep for BlurStep {
-    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>> {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
         let mut result = Vec::new();
         for item in data {
             let gray = item.image.to_luma8();
-            let blurred = preprocessing::apply_blur(&gray, self.sigma);
+            let blurred = preprocessing::apply_blur_with_border(&gray, self.sigma, self.border);
             let new_item = PipelineData {
                 image: image::DynamicImage::ImageLuma8(blurred),
                 original: item.original.clone(),
@@ -63,7 +155,7 @@ pub struct EdgeDetectionStep {
 }
 
 impl PipelineStep for EdgeDetectionStep {
-    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>> {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
         let mut result = Vec::new();
         for item in data {
             let gray = item.image.to_luma8();
@@ -84,19 +176,61 @@ impl PipelineStep for EdgeDetectionStep {
     }
 }
 
+/// Clean up a binary edge image with a morphological operation; insert after `EdgeDetectionStep`
+/// `Close` bridges small gaps in circle outlines, `Open` removes speckle.
+pub struct MorphologyStep {
+    pub operation: preprocessing::MorphOp,
+    /// Side length of the square structuring element
+    pub kernel_size: u32,
+}
+
+impl PipelineStep for MorphologyStep {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
+        let mut result = Vec::new();
+        for item in data {
+            let gray = item.image.to_luma8();
+            let cleaned = preprocessing::morph(&gray, self.operation, self.kernel_size);
+            let new_item = PipelineData {
+                image: image::DynamicImage::ImageLuma8(cleaned),
+                original: item.original.clone(),
+                bbox: item.bbox.clone(),
+                metadata: item.metadata.clone(),
+            };
+            result.push(new_item);
+        }
+        Ok(result)
+    }
+
+    fn name(&self) -> &str {
+        "Morphology"
+    }
+}
+
 /// Find contours in edge image - splits one image into many regions
 pub struct ContourDetectionStep {
     pub min_area: u32,
     pub padding: u32,
+    /// Largest bounding-box area to keep; `None` keeps regions of any size
+    pub max_area: Option<u32>,
+    /// Drop regions touching the image edge, such as the map frame
+    pub reject_border: bool,
+    /// `Four` separates regions that only touch diagonally
+    pub connectivity: contours::Connectivity,
 }
 
 impl PipelineStep for ContourDetectionStep {
-    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>> {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
         let mut result = Vec::new();
 
         for item in data {
             let gray = item.image.to_luma8();
-            let detected_contours = contours::find_contours(&gray, self.min_area);
+            let detected_contours = contours::find_contours_filtered(
+                &gray,
+                self.min_area,
+                self.max_area,
+                self.reject_border,
+                self.connectivity,
+            );
             let (img_width, img_height) = item.original.as_ref().dimensions();
 
             // Each contour becomes its own PipelineData
@@ -130,14 +264,7 @@ impl PipelineStep for ContourDetectionStep {
                     item.original.clone(),
                     bbox,
                 );
-                contour_data.metadata.insert("contour_min_x".to_string(), MetadataValue::Int(contour.min_x as i32));
-                contour_data.metadata.insert("contour_min_y".to_string(), MetadataValue::Int(contour.min_y as i32));
-                contour_data.metadata.insert("contour_max_x".to_string(), MetadataValue::Int(contour.max_x as i32));
-                contour_data.metadata.insert("contour_max_y".to_string(), MetadataValue::Int(contour.max_y as i32));
-                contour_data.metadata.insert("pixel_count".to_string(), MetadataValue::Int(contour.pixel_count as i32));
-                contour_data.metadata.insert("radius".to_string(), MetadataValue::Float(contour.radius()));
-                contour_data.metadata.insert("circularity".to_string(), MetadataValue::Float(contour.circularity()));
-                contour_data.metadata.insert("aspect_ratio".to_string(), MetadataValue::Float(contour.aspect_ratio()));
+                contour.write_metadata(&mut contour_data.metadata);
 
                 result.push(contour_data);
             }
@@ -159,7 +286,7 @@ pub struct CircleFilterStep {
 }
 
 impl PipelineStep for CircleFilterStep {
-    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>> {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
         let mut result = Vec::new();
 
         for item in data {
@@ -190,45 +317,127 @@ impl PipelineStep for CircleFilterStep {
     }
 }
 
+/// Find circles in the edge image with a Hough transform - splits one image into many regions
+/// Drop-in replacement for `ContourDetectionStep` + `CircleFilterStep`: emits the same
+/// contour metadata (bounding box of the circle) plus `center_x`, `center_y` and `radius`,
+/// and finds circles whose outline is faint or partially occluded.
+/// See `circles::hough_circles` for memory and runtime behavior over the radius range.
+pub struct HoughCircleStep {
+    pub min_radius: f32,
+    pub max_radius: f32,
+    /// Fraction of the circumference that must lie on edge pixels (0.0-1.0, typically 0.4)
+    pub accumulator_threshold: f32,
+    pub padding: u32,
+}
+
+impl PipelineStep for HoughCircleStep {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
+        let mut result = Vec::new();
+
+        for item in data {
+            let gray = item.image.to_luma8();
+            let detected = circles::hough_circles(
+                &gray,
+                self.min_radius.ceil() as u32,
+                self.max_radius.floor() as u32,
+                self.accumulator_threshold,
+            );
+            let (img_width, img_height) = item.original.as_ref().dimensions();
+
+            for circle in detected {
+                let contour = Contour {
+                    label: 0,
+                    min_x: circle.center_x.saturating_sub(circle.radius),
+                    min_y: circle.center_y.saturating_sub(circle.radius),
+                    max_x: (circle.center_x + circle.radius).min(img_width - 1),
+                    max_y: (circle.center_y + circle.radius).min(img_height - 1),
+                    pixel_count: circle.votes,
+                    boundary: None,
+                };
+
+                // Calculate padded bounding box, clamped to image boundaries
+                let padded_x = contour.min_x.saturating_sub(self.padding);
+                let padded_y = contour.min_y.saturating_sub(self.padding);
+                let padded_max_x = (contour.max_x + self.padding).min(img_width - 1);
+                let padded_max_y = (contour.max_y + self.padding).min(img_height - 1);
+
+                let bbox = BoundingBox {
+                    x: padded_x,
+                    y: padded_y,
+                    width: padded_max_x - padded_x + 1,
+                    height: padded_max_y - padded_y + 1,
+                };
+
+                let cropped = item.original.crop_imm(bbox.x, bbox.y, bbox.width, bbox.height);
+
+                let mut circle_data = PipelineData::from_region(
+                    cropped,
+                    item.original.clone(),
+                    bbox,
+                );
+                contour.write_metadata(&mut circle_data.metadata);
+                circle_data.metadata.insert("center_x".to_string(), MetadataValue::Int(circle.center_x as i32));
+                circle_data.metadata.insert("center_y".to_string(), MetadataValue::Int(circle.center_y as i32));
+                circle_data.metadata.insert("radius".to_string(), MetadataValue::Float(circle.radius as f32));
+                circle_data.metadata.insert("hough_score".to_string(), MetadataValue::Float(circle.score));
+                circle_data.metadata.insert("is_circle".to_string(), MetadataValue::Bool(true));
+
+                result.push(circle_data);
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn name(&self) -> &str {
+        "Hough Circle Detection"
+    }
+}
+
 /// Filter circles to keep only white ones
 pub struct WhiteCircleFilterStep {
-    pub brightness_threshold: f32,
+    /// Minimum average brightness (0-255). `None` picks a threshold with Otsu's method
+    /// over the pixels of all candidate circles and keeps the circles brighter than it.
+    /// The executor collects all candidates at this step first, so it uses the same threshold.
+    pub brightness_threshold: Option<f32>,
 }
 
 impl PipelineStep for WhiteCircleFilterStep {
-    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>> {
-        let mut result = Vec::new();
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
+        let mut candidates = Vec::new();
 
         for item in data {
             // Reconstruct contour from metadata to calculate brightness
-            let min_x = item.metadata.get("contour_min_x")
-                .and_then(|v| if let MetadataValue::Int(i) = v { Some(*i as u32) } else { None })
-                .ok_or_else(|| anyhow::anyhow!("Missing contour_min_x"))?;
-            let min_y = item.metadata.get("contour_min_y")
-                .and_then(|v| if let MetadataValue::Int(i) = v { Some(*i as u32) } else { None })
-                .ok_or_else(|| anyhow::anyhow!("Missing contour_min_y"))?;
-            let max_x = item.metadata.get("contour_max_x")
-                .and_then(|v| if let MetadataValue::Int(i) = v { Some(*i as u32) } else { None })
-                .ok_or_else(|| anyhow::anyhow!("Missing contour_max_x"))?;
-            let max_y = item.metadata.get("contour_max_y")
-                .and_then(|v| if let MetadataValue::Int(i) = v { Some(*i as u32) } else { None })
-                .ok_or_else(|| anyhow::anyhow!("Missing contour_max_y"))?;
-            let pixel_count = item.metadata.get("pixel_count")
-                .and_then(|v| if let MetadataValue::Int(i) = v { Some(*i as u32) } else { None })
-                .ok_or_else(|| anyhow::anyhow!("Missing pixel_count"))?;
-
-            let contour = Contour {
-                label: 0, // Not needed for brightness check
-                min_x,
-                min_y,
-                max_x,
-                max_y,
-                pixel_count,
-            };
+            let contour = Contour::from_metadata(&item.metadata)?;
 
+            candidates.push((item, contour));
+        }
+
+        // A fixed threshold is the lowest brightness kept; Otsu's is the brightest dark level
+        let is_white: Box<dyn Fn(f32) -> bool> = match self.brightness_threshold {
+            Some(threshold) => Box::new(move |brightness| brightness >= threshold),
+            None => {
+                // Histogram over the bounding boxes of all candidates in the original image
+                let mut histogram = [0u64; 256];
+                for (item, contour) in &candidates {
+                    let (width, height) = item.original.dimensions();
+                    for y in contour.min_y..=contour.max_y.min(height.saturating_sub(1)) {
+                        for x in contour.min_x..=contour.max_x.min(width.saturating_sub(1)) {
+                            histogram[item.original.get_pixel(x, y).to_luma()[0] as usize] += 1;
+                        }
+                    }
+                }
+                let threshold = preprocessing::otsu_threshold_from_histogram(&histogram) as f32;
+                Box::new(move |brightness| brightness > threshold)
+            }
+        };
+
+        let mut result = Vec::new();
+
+        for (item, contour) in candidates {
             let brightness = contour.average_brightness(&item.original);
 
-            if brightness >= self.brightness_threshold {
+            if is_white(brightness) {
                 let mut new_item = item.clone();
                 new_item.metadata.insert("is_white".to_string(), MetadataValue::Bool(true));
                 new_item.metadata.insert("brightness".to_string(), MetadataValue::Float(brightness));
@@ -242,27 +451,135 @@ impl PipelineStep for WhiteCircleFilterStep {
     fn name(&self) -> &str {
         "White Circle Filtering"
     }
+
+    fn needs_all_items(&self) -> bool {
+        // Otsu's threshold depends on all candidates
+        self.brightness_threshold.is_none()
+    }
+}
+
+/// Filter circles to keep only those close to a given color (e.g. colored sticker dots)
+/// Use instead of `WhiteCircleFilterStep` when house numbers sit on colored markers.
+pub struct ColorCircleFilterStep {
+    pub target: Color,
+    /// Maximum Euclidean distance in RGB (0-441) between the mean circle color and `target`
+    pub tolerance: f32,
+}
+
+impl PipelineStep for ColorCircleFilterStep {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
+        let target = [self.target.r, self.target.g, self.target.b].map(f32::from);
+        let mut result = Vec::new();
+
+        for item in data {
+            let contour = Contour::from_metadata(&item.metadata)?;
+            let mean = contour.average_color(&item.original);
+            let distance = mean
+                .iter()
+                .zip(target)
+                .map(|(m, t)| (m - t) * (m - t))
+                .sum::<f32>()
+                .sqrt();
+
+            if distance <= self.tolerance {
+                let mut new_item = item;
+                new_item.metadata.insert("color_distance".to_string(), MetadataValue::Float(distance));
+                result.push(new_item);
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn name(&self) -> &str {
+        "Color Circle Filtering"
+    }
+}
+
+/// Collapse overlapping detections, keeping the best item of each overlapping cluster
+/// Items are ranked by `brightness`, ties broken by the rounder (lower) `circularity`.
+/// Items without a bounding box pass through unchanged. Needs all candidates in one
+/// `process` call, so `PipelineExecutor` runs it as a barrier step, see `needs_all_items`.
+pub struct NmsStep {
+    /// Boxes overlapping a kept box by more than this IoU are dropped
+    pub iou_threshold: f32,
+}
+
+impl PipelineStep for NmsStep {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
+        let (mut boxed, mut result): (Vec<_>, Vec<_>) = data.into_iter().partition(|item| item.bbox.is_some());
+
+        // Best candidates first
+        boxed.sort_by(|a, b| {
+            let brightness = |item: &PipelineData| item.get_float("brightness").unwrap_or(0.0);
+            let circularity = |item: &PipelineData| item.get_float("circularity").unwrap_or(f32::MAX);
+            brightness(b)
+                .total_cmp(&brightness(a))
+                .then(circularity(a).total_cmp(&circularity(b)))
+        });
+
+        let mut kept: Vec<PipelineData> = Vec::new();
+        for item in boxed {
+            let bbox = item.bbox.as_ref().expect("partitioned on bbox");
+            let overlaps = kept.iter().any(|k| {
+                let kept_bbox = k.bbox.as_ref().expect("partitioned on bbox");
+                pipeline::iou(bbox, kept_bbox) > self.iou_threshold
+            });
+            if !overlaps {
+                kept.push(item);
+            }
+        }
+
+        result.extend(kept);
+        Ok(result)
+    }
+
+    fn name(&self) -> &str {
+        "Non-Maximum Suppression"
+    }
+
+    fn needs_all_items(&self) -> bool {
+        true
+    }
+}
+
+/// Fit the contour's circle inside the item's ROI, in ROI coordinates
+/// Needs the contour metadata and bounding box written by `ContourDetectionStep`.
+fn fit_roi_circle(item: &PipelineData, gray: &image::GrayImage) -> Option<(f32, f32, f32)> {
+    let contour = Contour::from_metadata(&item.metadata).ok()?;
+    let bbox = item.bbox.as_ref()?;
+    let local = Contour {
+        min_x: contour.min_x.checked_sub(bbox.x)?,
+        min_y: contour.min_y.checked_sub(bbox.y)?,
+        max_x: contour.max_x.checked_sub(bbox.x)?,
+        max_y: contour.max_y.checked_sub(bbox.y)?,
+        ..contour
+    };
+    let edges = preprocessing::detect_edges(gray, 50.0, 100.0);
+    local.fit_circle(&edges)
 }
 
 /// Remove background and crop to content (circular mask + brightness filter)
+/// Records the crop within the input image as `bg_crop_x`/`bg_crop_y`/`bg_crop_w`/`bg_crop_h`
+/// and moves `bbox` onto the cropped region, so the digits map back to the original image.
 pub struct BackgroundRemovalStep;
 
 impl PipelineStep for BackgroundRemovalStep {
-    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>> {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
         let mut result = Vec::new();
 
         for item in data {
             let gray = item.image.to_luma8();
             let (width, height) = gray.dimensions();
 
-            // Circle is centered in the ROI (we added 10px padding in ContourDetectionStep)
-            let center_x = width as f32 / 2.0;
-            let center_y = height as f32 / 2.0;
-
-            // Estimate circle radius from bounding box
-            // We added 10px padding, so subtract that to get the actual radius
-            let padding = 10.0;
-            let estimated_radius = ((width.min(height)) as f32 / 2.0) - padding;
+            // Fit the circle outline when the contour is known, otherwise assume the circle
+            // is centered in the ROI (we added 10px padding in ContourDetectionStep)
+            let (center_x, center_y, estimated_radius) = fit_roi_circle(&item, &gray)
+                .unwrap_or_else(|| {
+                    let padding = 10.0;
+                    let radius = ((width.min(height)) as f32 / 2.0) - padding;
+                    (width as f32 / 2.0, height as f32 / 2.0, radius)
+                });
 
             // Shrink less aggressively - only by 2px to avoid cutting off digits
             let inner_radius = estimated_radius - 2.0;
@@ -319,6 +636,24 @@ impl PipelineStep for BackgroundRemovalStep {
 
             let mut new_item = item.clone();
             new_item.image = image::DynamicImage::ImageLuma8(cropped);
+            new_item.bbox = Some(match &item.bbox {
+                Some(bbox) => BoundingBox {
+                    x: bbox.x + crop_x,
+                    y: bbox.y + crop_y,
+                    width: crop_w,
+                    height: crop_h,
+                },
+                None => BoundingBox {
+                    x: crop_x,
+                    y: crop_y,
+                    width: crop_w,
+                    height: crop_h,
+                },
+            });
+            let crop = [("bg_crop_x", crop_x), ("bg_crop_y", crop_y), ("bg_crop_w", crop_w), ("bg_crop_h", crop_h)];
+            for (key, value) in crop {
+                new_item.metadata.insert(key.to_string(), MetadataValue::Int(value as i32));
+            }
             result.push(new_item);
         }
 
@@ -332,31 +667,22 @@ impl PipelineStep for BackgroundRemovalStep {
 
 /// Upscale images to target size while maintaining aspect ratio
 pub struct UpscaleStep {
+    /// Side length of the square canvas, used when `target_height` is unset
     pub target_size: u32,
+    /// Canvas height; the width follows the crop's aspect ratio so wide
+    /// multi-digit crops are not squashed into a square
+    pub target_height: Option<u32>,
 }
 
 impl PipelineStep for UpscaleStep {
-    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>> {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
         let mut result = Vec::new();
 
         for item in data {
             let gray = item.image.to_luma8();
             let (width, height) = gray.dimensions();
-
-            // Calculate scaling to fit within target size while maintaining aspect ratio
-            let scale = (self.target_size as f32 / width as f32).min(self.target_size as f32 / height as f32);
-            let scaled_w = (width as f32 * scale) as u32;
-            let scaled_h = (height as f32 * scale) as u32;
-
-            // Resize with high-quality interpolation
-            let scaled = image::imageops::resize(&gray, scaled_w, scaled_h, image::imageops::FilterType::CatmullRom);
-
-            // Center the scaled image in a target_size x target_size white canvas
-            let mut canvas = image::GrayImage::from_pixel(self.target_size, self.target_size, image::Luma([255u8]));
-            let offset_x = (self.target_size - scaled_w) / 2;
-            let offset_y = (self.target_size - scaled_h) / 2;
-
-            image::imageops::overlay(&mut canvas, &scaled, offset_x.into(), offset_y.into());
+            let (canvas_w, canvas_h) = ocr::ocr_canvas_size(width, height, self.target_size, self.target_height);
+            let canvas = ocr::fit_to_canvas(&gray, canvas_w, canvas_h);
 
             let mut new_item = item.clone();
             new_item.image = image::DynamicImage::ImageLuma8(canvas);
@@ -372,12 +698,13 @@ impl PipelineStep for UpscaleStep {
 }
 
 /// Sharpen images to enhance text edges
+/// Prefer `UnsharpMaskStep`; this kernel amplifies noise and skips the border pixels.
 pub struct SharpenStep {
     pub strength: f32,
 }
 
 impl PipelineStep for SharpenStep {
-    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>> {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
         let mut result = Vec::new();
 
         for item in data {
@@ -431,72 +758,354 @@ impl PipelineStep for SharpenStep {
     }
 }
 
+/// Sharpen images with an unsharp mask (adds back the difference to a blurred copy)
+pub struct UnsharpMaskStep {
+    /// Gaussian blur strength; roughly the width of the edges to enhance
+    pub sigma: f32,
+    /// How much of the detail to add back (0 leaves the image unchanged)
+    pub amount: f32,
+}
+
+impl PipelineStep for UnsharpMaskStep {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
+        let mut result = Vec::new();
+
+        for item in data {
+            let gray = item.image.to_luma8();
+            let sharpened = preprocessing::unsharp_mask(&gray, self.sigma, self.amount);
+
+            let mut new_item = item;
+            new_item.image = image::DynamicImage::ImageLuma8(sharpened);
+            result.push(new_item);
+        }
+
+        Ok(result)
+    }
+
+    fn name(&self) -> &str {
+        "Unsharp Mask"
+    }
+}
+
 /// Run OCR on detected circles
 pub struct OcrStep {
-    // Lazy-initialized OCR engine, initialized once on first use
-    // Using Arc so we can clone the reference and release the mutex lock
-    engine: Mutex<Option<Arc<ocr::OcrEngine>>>,
+    // Lazy-initialized OCR engines, one per worker, created on first use
+    // Using Arc so we can clone the references and release the mutex lock
+    engines: Mutex<Vec<Arc<ocr::OcrEngine>>>,
+    /// Counterclockwise rotations (degrees) to try per item; the most confident numeric
+    /// result wins
+    pub rotations: Vec<f32>,
+    /// Number of items recognized concurrently, each with its own engine
+    pub workers: usize,
 }
 
 impl OcrStep {
     pub fn new() -> Self {
         Self {
-            engine: Mutex::new(None),
+            engines: Mutex::new(Vec::new()),
+            rotations: vec![0.0],
+            workers: 1,
+        }
+    }
+
+    pub fn with_rotations(mut self, rotations: Vec<f32>) -> Self {
+        self.rotations = rotations;
+        self
+    }
+
+    /// Recognize up to `workers` items at once; engines can't run inference concurrently,
+    /// so every worker loads its own. Output order matches input order.
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// Initialize engines until there are at least `count`, reusing earlier ones
+    fn engines(&self, count: usize, context: &PipelineContext) -> Result<Vec<Arc<ocr::OcrEngine>>, PipelineError> {
+        let mut engines = self.engines.lock().unwrap();
+        while engines.len() < count {
+            verbose_log!(context.verbose, "Initializing OCR engine {} of {}...", engines.len() + 1, count);
+            engines.push(Arc::new(ocr::init_ocr_engine()?));
+            verbose_log!(context.verbose, "OCR engine initialized successfully");
         }
+        Ok(engines[..count].to_vec())
+    }
+
+    /// Best normalized house number over all rotations, with its confidence
+    fn recognize(&self, engine: &ocr::OcrEngine, item: &PipelineData) -> Option<(String, f32)> {
+        // Image is already preprocessed (background removed, upscaled)
+        // Try each rotation; non-numeric results are map artifacts, not house numbers
+        self.rotations
+            .iter()
+            .filter_map(|&degrees| {
+                let rotated = ocr::rotate_with_padding(&item.image, degrees);
+                let (text, confidence) = ocr::recognize_text(engine, &rotated)?;
+                Some((ocr::normalize_house_number(&text)?, confidence))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
     }
 }
 
 impl PipelineStep for OcrStep {
-    fn process(&self, data: Vec<PipelineData>, context: &PipelineContext) -> Result<Vec<PipelineData>> {
-        // Initialize OCR engine once on first call, reuse for all subsequent calls
-        // Clone the Arc to release the mutex lock before processing
-        let engine = {
-            let mut engine_guard = self.engine.lock().unwrap();
-            if engine_guard.is_none() {
-                if context.verbose {
-                    println!("Initializing OCR engine...");
-                }
-                *engine_guard = Some(Arc::new(ocr::init_ocr_engine()?));
-                if context.verbose {
-                    println!("OCR engine initialized successfully");
-                }
+    fn process(&self, data: Vec<PipelineData>, context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
+        let total = data.len();
+        if total == 0 {
+            return Ok(Vec::new());
+        }
+        // More workers than items would only load engines that never run
+        let engines = self.engines(self.workers.min(total), context)?;
+
+        // Workers claim items by index and store results in their slot, so order is preserved
+        let next_item = AtomicUsize::new(0);
+        let recognized: Mutex<Vec<Option<(String, f32)>>> = Mutex::new(vec![None; total]);
+        std::thread::scope(|scope| {
+            for engine in &engines {
+                let (data, next_item, recognized) = (&data, &next_item, &recognized);
+                scope.spawn(move || loop {
+                    let i = next_item.fetch_add(1, Ordering::SeqCst);
+                    if i >= total {
+                        break;
+                    }
+                    if total > 5 {
+                        verbose_log!(context.verbose, "  Processing item {} of {}...", i + 1, total);
+                    }
+                    let result = self.recognize(engine, &data[i]);
+                    recognized.lock().unwrap()[i] = result;
+                });
             }
-            engine_guard.as_ref().unwrap().clone()
-        }; // Mutex lock is released here
+        });
 
         let mut result = Vec::new();
-        let total = data.len();
-
-        for (i, item) in data.into_iter().enumerate() {
-            if context.verbose && total > 5 {
-                println!("  Processing item {} of {}...", i + 1, total);
+        for (item, recognized) in data.into_iter().zip(recognized.into_inner().unwrap()) {
+            if let Some((text, confidence)) = recognized {
+                let mut new_item = item;
+                new_item.metadata.insert("ocr_text".to_string(), MetadataValue::String(text));
+                new_item.metadata.insert("ocr_confidence".to_string(), MetadataValue::Float(confidence));
+                result.push(new_item);
             }
+        }
 
-            // Image is already preprocessed (background removed, upscaled)
-            // Convert to RGB8 format for OCR
-            let img = item.image.to_rgb8();
-
-            // Prepare image for OCR
-            if let Ok(img_source) = ocr::ImageSource::from_bytes(img.as_raw(), img.dimensions()) {
-                if let Ok(ocr_input) = engine.prepare_input(img_source) {
-                    // Run OCR
-                    if let Ok(text) = engine.get_text(&ocr_input) {
-                        let text = text.trim().to_string();
-                        if !text.is_empty() {
-                            let mut new_item = item.clone();
-                            new_item.metadata.insert("ocr_text".to_string(), MetadataValue::String(text));
-                            new_item.metadata.insert("ocr_confidence".to_string(), MetadataValue::Float(0.9));
-                            result.push(new_item);
-                        }
-                    }
-                }
+        Ok(result)
+    }
+
+    fn name(&self) -> &str {
+        "OCR Recognition"
+    }
+}
+
+/// Drop OCR results below a confidence threshold
+/// Insert after `OcrStep`; items without `ocr_confidence` were never recognized and are dropped.
+pub struct ConfidenceFilterStep {
+    pub min_confidence: f32,
+}
+
+impl PipelineStep for ConfidenceFilterStep {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
+        Ok(data
+            .into_iter()
+            .filter(|item| {
+                item.get_float("ocr_confidence")
+                    .is_some_and(|confidence| confidence >= self.min_confidence)
+            })
+            .collect())
+    }
+
+    fn name(&self) -> &str {
+        "Confidence Filtering"
+    }
+}
+
+/// Collapse overlapping OCR results, keeping the most confident reading of each cluster
+/// Unlike `NmsStep`, which ranks raw candidates, this runs after `OcrStep`: items are ranked
+/// by `ocr_confidence`, ties broken by the larger circle radius. Items without a bounding
+/// box pass through unchanged. Like `NmsStep`, `PipelineExecutor` runs it as a barrier step.
+pub struct NmsByTextStep {
+    /// Boxes overlapping a kept box by more than this IoU are dropped
+    pub iou_threshold: f32,
+}
+
+/// Circle radius from the contour metadata, or the `radius` written by `HoughCircleStep`
+fn item_radius(item: &PipelineData) -> f32 {
+    Contour::from_metadata(&item.metadata)
+        .map(|contour| contour.radius())
+        .ok()
+        .or_else(|| item.get_float("radius"))
+        .unwrap_or(0.0)
+}
+
+impl PipelineStep for NmsByTextStep {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
+        let (mut boxed, mut result): (Vec<_>, Vec<_>) = data.into_iter().partition(|item| item.bbox.is_some());
+
+        // Most confident readings first
+        boxed.sort_by(|a, b| {
+            let confidence = |item: &PipelineData| item.get_float("ocr_confidence").unwrap_or(0.0);
+            confidence(b)
+                .total_cmp(&confidence(a))
+                .then(item_radius(b).total_cmp(&item_radius(a)))
+        });
+
+        let mut kept: Vec<PipelineData> = Vec::new();
+        for item in boxed {
+            let bbox = item.bbox.as_ref().expect("partitioned on bbox");
+            let overlaps = kept.iter().any(|k| {
+                let kept_bbox = k.bbox.as_ref().expect("partitioned on bbox");
+                pipeline::iou(bbox, kept_bbox) > self.iou_threshold
+            });
+            if !overlaps {
+                kept.push(item);
             }
         }
 
+        result.extend(kept);
         Ok(result)
     }
 
     fn name(&self) -> &str {
-        "OCR Recognition"
+        "Text Non-Maximum Suppression"
+    }
+
+    fn needs_all_items(&self) -> bool {
+        true
+    }
+}
+
+/// `contours::Connectivity` as written in a pipeline config
+#[derive(Debug, Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ConnectivityConfig {
+    Four,
+    #[default]
+    Eight,
+}
+
+impl From<ConnectivityConfig> for contours::Connectivity {
+    fn from(config: ConnectivityConfig) -> Self {
+        match config {
+            ConnectivityConfig::Four => contours::Connectivity::Four,
+            ConnectivityConfig::Eight => contours::Connectivity::Eight,
+        }
     }
 }
+
+/// One `[[steps]]` table of a pipeline config, selected by its `name`
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(tag = "name", rename_all = "snake_case", deny_unknown_fields)]
+enum StepConfig {
+    Grayscale,
+    ContrastEnhance { clip_limit: f32, tile_size: u32 },
+    Deskew { max_angle: f32 },
+    Blur {
+        sigma: f32,
+        #[serde(default)]
+        border: preprocessing::BorderMode,
+    },
+    EdgeDetection { low_threshold: f32, high_threshold: f32 },
+    Morphology { operation: preprocessing::MorphOp, kernel_size: u32 },
+    ContourDetection {
+        min_area: u32,
+        padding: u32,
+        max_area: Option<u32>,
+        #[serde(default)]
+        reject_border: bool,
+        #[serde(default)]
+        connectivity: ConnectivityConfig,
+    },
+    CircleFilter { min_radius: f32, max_radius: f32, circularity_threshold: f32 },
+    HoughCircle { min_radius: f32, max_radius: f32, accumulator_threshold: f32, padding: u32 },
+    WhiteCircleFilter { brightness_threshold: Option<f32> },
+    /// `target` is a hex color such as `"#e53935"`
+    ColorCircleFilter { target: String, tolerance: f32 },
+    Nms { iou_threshold: f32 },
+    BackgroundRemoval,
+    Upscale { target_size: u32, target_height: Option<u32> },
+    Sharpen { strength: f32 },
+    UnsharpMask { sigma: f32, amount: f32 },
+    Ocr { rotations: Option<Vec<f32>>, workers: Option<usize> },
+    ConfidenceFilter { min_confidence: f32 },
+    NmsByText { iou_threshold: f32 },
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PipelineConfig {
+    steps: Vec<StepConfig>,
+}
+
+impl StepConfig {
+    fn build(self) -> Result<Arc<dyn PipelineStep>> {
+        let step: Arc<dyn PipelineStep> = match self {
+            StepConfig::Grayscale => Arc::new(GrayscaleStep),
+            StepConfig::ContrastEnhance { clip_limit, tile_size } => Arc::new(ContrastEnhanceStep { clip_limit, tile_size }),
+            StepConfig::Deskew { max_angle } => Arc::new(DeskewStep { max_angle }),
+            StepConfig::Blur { sigma, border } => Arc::new(BlurStep { sigma, border }),
+            StepConfig::EdgeDetection { low_threshold, high_threshold } => Arc::new(EdgeDetectionStep {
+                low_threshold,
+                high_threshold,
+            }),
+            StepConfig::Morphology { operation, kernel_size } => Arc::new(MorphologyStep { operation, kernel_size }),
+            StepConfig::ContourDetection { min_area, padding, max_area, reject_border, connectivity } => {
+                Arc::new(ContourDetectionStep {
+                    min_area,
+                    padding,
+                    max_area,
+                    reject_border,
+                    connectivity: connectivity.into(),
+                })
+            }
+            StepConfig::CircleFilter { min_radius, max_radius, circularity_threshold } => Arc::new(CircleFilterStep {
+                min_radius,
+                max_radius,
+                circularity_threshold,
+            }),
+            StepConfig::HoughCircle { min_radius, max_radius, accumulator_threshold, padding } => {
+                Arc::new(HoughCircleStep {
+                    min_radius,
+                    max_radius,
+                    accumulator_threshold,
+                    padding,
+                })
+            }
+            StepConfig::WhiteCircleFilter { brightness_threshold } => Arc::new(WhiteCircleFilterStep { brightness_threshold }),
+            StepConfig::ColorCircleFilter { target, tolerance } => Arc::new(ColorCircleFilterStep {
+                target: Color::from_hex_string(&target)
+                    .map_err(|e| anyhow::anyhow!("color_circle_filter target '{}': {}", target, e))?,
+                tolerance,
+            }),
+            StepConfig::Nms { iou_threshold } => Arc::new(NmsStep { iou_threshold }),
+            StepConfig::BackgroundRemoval => Arc::new(BackgroundRemovalStep),
+            StepConfig::Upscale { target_size, target_height } => Arc::new(UpscaleStep { target_size, target_height }),
+            StepConfig::Sharpen { strength } => Arc::new(SharpenStep { strength }),
+            StepConfig::UnsharpMask { sigma, amount } => Arc::new(UnsharpMaskStep { sigma, amount }),
+            StepConfig::Ocr { rotations, workers } => {
+                let mut step = OcrStep::new();
+                if let Some(rotations) = rotations {
+                    step = step.with_rotations(rotations);
+                }
+                if let Some(workers) = workers {
+                    step = step.with_workers(workers);
+                }
+                Arc::new(step)
+            }
+            StepConfig::ConfidenceFilter { min_confidence } => Arc::new(ConfidenceFilterStep { min_confidence }),
+            StepConfig::NmsByText { iou_threshold } => Arc::new(NmsByTextStep { iou_threshold }),
+        };
+        Ok(step)
+    }
+}
+
+/// Build pipeline steps from a TOML config listing them in order
+/// Each `[[steps]]` table selects a step by its snake_case `name` (e.g. `edge_detection` for
+/// `EdgeDetectionStep`) and sets the step's fields; optional fields may be left out.
+/// Unknown step names and fields are rejected.
+///
+/// ```toml
+/// [[steps]]
+/// name = "blur"
+/// sigma = 1.5
+/// ```
+pub fn from_config(config: &str) -> Result<Vec<Arc<dyn PipelineStep>>> {
+    let config: PipelineConfig = toml::from_str(config)
+        .map_err(|e| anyhow::anyhow!("Invalid pipeline config: {}", e))?;
+    config.steps.into_iter().map(StepConfig::build).collect()
+}
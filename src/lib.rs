@@ -3,11 +3,52 @@ pub mod models;
 pub mod pipeline;
 pub mod core;
 
-pub use models::{Contour, HouseNumberDetection};
-pub use detection::DetectionPipeline;
+use anyhow::Context;
+
+pub use models::{Contour, HouseNumberDetection, Moments, MIN_ROI_SIZE};
+pub use detection::{run_detections, DetectionParams, DetectionPipeline};
 pub use pipeline::{
     Pipeline, PipelineData, PipelineStep, PipelineContext,
-    BoundingBox, MetadataValue, WorkItem, PipelineExecutor, DebugConfig
+    BoundingBox, MetadataValue, WorkItem, PipelineExecutor, DebugConfig, FromMetadata, iou,
+    CancelToken, ExecutorStats, PipelineError, ProgressCallback, ProgressEvent, ProgressPhase, StepProfile,
 };
 
 // pub mod core;  // Will be created in Phase 2
+
+/// Run detection on encoded image bytes (e.g. an upload), without a temp file.
+/// The format is guessed from the data when no hint is given.
+pub fn detect_bytes(
+    bytes: &[u8],
+    format: Option<image::ImageFormat>,
+    params: &DetectionParams,
+) -> anyhow::Result<Vec<HouseNumberDetection>> {
+    let img = match format {
+        Some(format) => image::load_from_memory_with_format(bytes, format)?,
+        None => image::load_from_memory(bytes)?,
+    };
+    run_detections(&img, params)
+}
+
+/// Detect house numbers in the image at `path` with the standard composable pipeline.
+/// Detections are in the coordinates of the original image, see
+/// [`detection::detection_from_item`]; items OCR could not read are left out.
+pub fn detect_house_numbers(
+    path: &std::path::Path,
+    params: DetectionParams,
+) -> anyhow::Result<Vec<HouseNumberDetection>> {
+    let img = image::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    detect_house_numbers_in_image(img, &params)
+}
+
+/// [`detect_house_numbers`] on an already decoded image.
+pub fn detect_house_numbers_in_image(
+    img: image::DynamicImage,
+    params: &DetectionParams,
+) -> anyhow::Result<Vec<HouseNumberDetection>> {
+    let mut pipeline = detection::build_pipeline_with_params(params, false, false);
+    Ok(pipeline
+        .run(img)?
+        .iter()
+        .filter_map(detection::detection_from_item)
+        .collect())
+}
@@ -6,9 +6,11 @@ pub mod pipeline;
 pub mod ui;
 
 use crate::ui::{
+    AppError,
     address_detection::AddressDetection,
     home::Home,
     layout::{AreaLayout, ProjectLayout},
+    new_project::NewProject,
     overview::ProjectOverview,
 };
 
@@ -17,6 +19,9 @@ enum Route {
     #[route("/")]
     Home {},
 
+    #[route("/new/:file")]
+    NewProject { file: String },
+
     #[nest("/project/:file")]
         #[layout(ProjectLayout)]
         #[route("/")]
@@ -36,6 +41,9 @@ fn main() {
 
 #[component]
 fn App() -> Element {
+    // Error shown as a banner on the home page, e.g. after a project failed to open
+    use_context_provider(|| Signal::new(None::<AppError>));
+
     rsx! {
         document::Link { rel: "icon", href: FAVICON }
         document::Link { rel: "stylesheet", href: MAIN_CSS }
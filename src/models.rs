@@ -1,6 +1,12 @@
-use image::DynamicImage;
+use image::{DynamicImage, GrayImage};
+use std::collections::HashMap;
+use crate::pipeline::MetadataValue;
 
-#[derive(Debug, Clone)]
+/// Smallest ROI width and height `Contour::extract_roi` returns; anything smaller has no
+/// readable digits
+pub const MIN_ROI_SIZE: u32 = 4;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Contour {
     pub label: u32,
     pub min_x: u32,
@@ -8,9 +14,43 @@ pub struct Contour {
     pub max_x: u32,
     pub max_y: u32,
     pub pixel_count: u32,
+    /// Outer boundary pixels in tracing order, if the contour was traced
+    pub boundary: Option<Vec<(u32, u32)>>,
 }
 
 impl Contour {
+    /// Rebuild a contour from the metadata written by `write_metadata`
+    /// The traced boundary is not stored in metadata, so it comes back as `None`.
+    pub fn from_metadata(meta: &HashMap<String, MetadataValue>) -> anyhow::Result<Contour> {
+        let get = |key: &str| match meta.get(key) {
+            Some(MetadataValue::Int(v)) => Ok(*v as u32),
+            _ => Err(anyhow::anyhow!("Missing {}", key)),
+        };
+
+        Ok(Contour {
+            label: get("contour_label")?,
+            min_x: get("contour_min_x")?,
+            min_y: get("contour_min_y")?,
+            max_x: get("contour_max_x")?,
+            max_y: get("contour_max_y")?,
+            pixel_count: get("pixel_count")?,
+            boundary: None,
+        })
+    }
+
+    /// Store the contour and its derived shape measures in pipeline metadata
+    pub fn write_metadata(&self, meta: &mut HashMap<String, MetadataValue>) {
+        meta.insert("contour_label".to_string(), MetadataValue::Int(self.label as i32));
+        meta.insert("contour_min_x".to_string(), MetadataValue::Int(self.min_x as i32));
+        meta.insert("contour_min_y".to_string(), MetadataValue::Int(self.min_y as i32));
+        meta.insert("contour_max_x".to_string(), MetadataValue::Int(self.max_x as i32));
+        meta.insert("contour_max_y".to_string(), MetadataValue::Int(self.max_y as i32));
+        meta.insert("pixel_count".to_string(), MetadataValue::Int(self.pixel_count as i32));
+        meta.insert("radius".to_string(), MetadataValue::Float(self.radius()));
+        meta.insert("circularity".to_string(), MetadataValue::Float(self.circularity_bbox()));
+        meta.insert("aspect_ratio".to_string(), MetadataValue::Float(self.aspect_ratio()));
+    }
+
     pub fn width(&self) -> u32 {
         self.max_x - self.min_x + 1
     }
@@ -23,13 +63,43 @@ impl Contour {
         self.pixel_count
     }
 
+    /// Perimeter from the traced boundary, falling back to the bounding box
     pub fn perimeter(&self) -> f32 {
+        match &self.boundary {
+            Some(boundary) if !boundary.is_empty() => {
+                // Sum the segment lengths, closing the loop back to the first pixel
+                boundary
+                    .iter()
+                    .zip(boundary.iter().cycle().skip(1))
+                    .map(|(&(x1, y1), &(x2, y2))| {
+                        let dx = x2 as f32 - x1 as f32;
+                        let dy = y2 as f32 - y1 as f32;
+                        (dx * dx + dy * dy).sqrt()
+                    })
+                    .sum()
+            }
+            _ => self.perimeter_bbox(),
+        }
+    }
+
+    pub fn perimeter_bbox(&self) -> f32 {
         // Approximate perimeter from bounding box
         2.0 * (self.width() as f32 + self.height() as f32)
     }
 
+    /// Circularity = 4π × area / perimeter² (1.0 for a perfect circle, ~0.785 for a square)
     pub fn circularity(&self) -> f32 {
         let perimeter = self.perimeter();
+        if perimeter == 0.0 {
+            return 0.0;
+        }
+
+        4.0 * std::f32::consts::PI * self.area() as f32 / (perimeter * perimeter)
+    }
+
+    /// Bounding-box based circularity as used by the circle filter (1.0 and up, lower is rounder)
+    pub fn circularity_bbox(&self) -> f32 {
+        let perimeter = self.perimeter_bbox();
         // Use bounding box area instead of pixel count for better circularity estimate
         let area = (self.width() * self.height()) as f32;
 
@@ -50,8 +120,10 @@ impl Contour {
         w / h
     }
 
+    /// Contours come from edge images (rings, not filled discs), so this keeps using
+    /// the bounding-box circularity
     pub fn is_circular(&self, threshold: f32) -> bool {
-        let circ = self.circularity();
+        let circ = self.circularity_bbox();
         circ >= 0.7 && circ <= threshold
     }
 
@@ -62,6 +134,63 @@ impl Contour {
         (w + h) / 4.0
     }
 
+    /// Least-squares (Kåsa) circle fit over the edge pixels inside the bounding box
+    /// Returns `(center_x, center_y, radius)` in `edges` coordinates, or `None` when the
+    /// points don't determine a circle (fewer than three, or all on one line).
+    /// Edge pixels in the inner half of the box are ignored, since those belong to the
+    /// label inside the circle rather than its outline.
+    pub fn fit_circle(&self, edges: &GrayImage) -> Option<(f32, f32, f32)> {
+        let (box_cx, box_cy) = (
+            (self.min_x + self.max_x) as f64 / 2.0,
+            (self.min_y + self.max_y) as f64 / 2.0,
+        );
+        let inner_radius = self.radius() as f64 / 2.0;
+
+        let mut points = Vec::new();
+        for y in self.min_y..=self.max_y.min(edges.height().saturating_sub(1)) {
+            for x in self.min_x..=self.max_x.min(edges.width().saturating_sub(1)) {
+                let (px, py) = (x as f64, y as f64);
+                if edges.get_pixel(x, y)[0] > 0
+                    && (px - box_cx).hypot(py - box_cy) >= inner_radius
+                {
+                    points.push((px, py));
+                }
+            }
+        }
+        if points.len() < 3 {
+            return None;
+        }
+
+        // Work relative to the centroid so the normal equations reduce to a 2x2 system
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let (mut suu, mut svv, mut suv) = (0.0, 0.0, 0.0);
+        let (mut suuu, mut svvv, mut suvv, mut svuu) = (0.0, 0.0, 0.0, 0.0);
+        for &(x, y) in &points {
+            let (u, v) = (x - mean_x, y - mean_y);
+            suu += u * u;
+            svv += v * v;
+            suv += u * v;
+            suuu += u * u * u;
+            svvv += v * v * v;
+            suvv += u * v * v;
+            svuu += v * u * u;
+        }
+
+        let det = suu * svv - suv * suv;
+        if det.abs() < 1e-9 {
+            return None;
+        }
+        let rhs_u = (suuu + suvv) / 2.0;
+        let rhs_v = (svvv + svuu) / 2.0;
+        let uc = (rhs_u * svv - rhs_v * suv) / det;
+        let vc = (suu * rhs_v - suv * rhs_u) / det;
+        let radius = (uc * uc + vc * vc + (suu + svv) / n).sqrt();
+
+        Some(((uc + mean_x) as f32, (vc + mean_y) as f32, radius as f32))
+    }
+
     pub fn is_reasonable_size(&self, min_radius: f32, max_radius: f32) -> bool {
         let r = self.radius();
         r >= min_radius && r <= max_radius
@@ -101,37 +230,185 @@ impl Contour {
         }
     }
 
+    /// Mean RGB of the pixels in the circle region, same sampling as `average_brightness`
+    pub fn average_color(&self, img: &DynamicImage) -> [f32; 3] {
+        let rgb = img.to_rgb8();
+        let mut sum = [0u64; 3];
+        let mut count: u64 = 0;
+
+        let center_x = (self.min_x + self.max_x) as f32 / 2.0;
+        let center_y = (self.min_y + self.max_y) as f32 / 2.0;
+        let radius = self.radius();
+
+        for y in self.min_y..=self.max_y.min(rgb.height().saturating_sub(1)) {
+            for x in self.min_x..=self.max_x.min(rgb.width().saturating_sub(1)) {
+                let dx = x as f32 - center_x;
+                let dy = y as f32 - center_y;
+                if (dx * dx + dy * dy).sqrt() <= radius {
+                    let pixel = rgb.get_pixel(x, y);
+                    for (channel, value) in sum.iter_mut().zip(pixel.0) {
+                        *channel += value as u64;
+                    }
+                    count += 1;
+                }
+            }
+        }
+
+        if count == 0 {
+            return [0.0; 3];
+        }
+        sum.map(|channel| channel as f32 / count as f32)
+    }
+
     pub fn is_white(&self, img: &DynamicImage, threshold: f32) -> bool {
         self.average_brightness(img) >= threshold
     }
 
-    /// Extract the circle region as a sub-image for OCR
+    /// Extract the circle region as a sub-image for OCR, with 5px of padding
     pub fn extract_roi(&self, img: &DynamicImage) -> Option<DynamicImage> {
-        // Add padding around the bounding box for better OCR
-        let padding = 5;
-        let x = self.min_x.saturating_sub(padding);
-        let y = self.min_y.saturating_sub(padding);
-        let width = (self.width() + 2 * padding).min(img.width() - x);
-        let height = (self.height() + 2 * padding).min(img.height() - y);
+        self.extract_roi_padded(img, 5)
+    }
+
+    /// Extract the bounding box grown by `padding` pixels on each side
+    /// The padded box is clipped to the image; returns `None` if less than `MIN_ROI_SIZE`
+    /// pixels remain in either direction, e.g. for a contour outside the image.
+    pub fn extract_roi_padded(&self, img: &DynamicImage, padding: u32) -> Option<DynamicImage> {
+        let x = self.min_x.saturating_sub(padding).min(img.width());
+        let y = self.min_y.saturating_sub(padding).min(img.height());
+        // Clip each side on its own, so a box at the left or top edge isn't pushed further right or down
+        let right = (self.max_x + 1).saturating_add(padding).min(img.width());
+        let bottom = (self.max_y + 1).saturating_add(padding).min(img.height());
+        let width = right.saturating_sub(x);
+        let height = bottom.saturating_sub(y);
 
         // Ensure valid dimensions
-        if width == 0 || height == 0 {
+        if width < MIN_ROI_SIZE || height < MIN_ROI_SIZE {
             return None;
         }
 
         Some(img.crop_imm(x, y, width, height))
     }
 
+    /// Like `extract_roi_padded`, with padding of `factor` times the radius (rounded), so large
+    /// circles get a wider margin than small ones
+    pub fn extract_roi_scaled(&self, img: &DynamicImage, factor: f32) -> Option<DynamicImage> {
+        let padding = (self.radius() * factor.max(0.0)).round() as u32;
+        self.extract_roi_padded(img, padding)
+    }
+
     /// Get center coordinates
     pub fn center(&self) -> (u32, u32) {
         ((self.min_x + self.max_x) / 2, (self.min_y + self.max_y) / 2)
     }
+
+    /// Mean position of the edge pixels inside the bounding box
+    /// Falls back to the bounding-box center when there are none.
+    pub fn centroid(&self, edges: &GrayImage) -> (f32, f32) {
+        let (mut count, mut sum_x, mut sum_y) = (0u64, 0u64, 0u64);
+        for y in self.min_y..=self.max_y.min(edges.height().saturating_sub(1)) {
+            for x in self.min_x..=self.max_x.min(edges.width().saturating_sub(1)) {
+                if edges.get_pixel(x, y)[0] > 0 {
+                    count += 1;
+                    sum_x += x as u64;
+                    sum_y += y as u64;
+                }
+            }
+        }
+
+        if count == 0 {
+            return (
+                (self.min_x + self.max_x) as f32 / 2.0,
+                (self.min_y + self.max_y) as f32 / 2.0,
+            );
+        }
+        (sum_x as f32 / count as f32, sum_y as f32 / count as f32)
+    }
+
+    /// Image moments of the region
+    /// With a traced boundary each row is filled between its leftmost and rightmost boundary
+    /// pixel; without one the whole bounding box counts as the region.
+    pub fn moments(&self) -> Moments {
+        let spans: Vec<(u32, u32, u32)> = match &self.boundary {
+            Some(boundary) if !boundary.is_empty() => {
+                let mut rows: HashMap<u32, (u32, u32)> = HashMap::new();
+                for &(x, y) in boundary {
+                    let span = rows.entry(y).or_insert((x, x));
+                    span.0 = span.0.min(x);
+                    span.1 = span.1.max(x);
+                }
+                rows.into_iter().map(|(y, (x0, x1))| (y, x0, x1)).collect()
+            }
+            _ => (self.min_y..=self.max_y).map(|y| (y, self.min_x, self.max_x)).collect(),
+        };
+
+        let (mut m00, mut m10, mut m01) = (0.0f64, 0.0f64, 0.0f64);
+        for &(y, x0, x1) in &spans {
+            for x in x0..=x1 {
+                m00 += 1.0;
+                m10 += x as f64;
+                m01 += y as f64;
+            }
+        }
+        if m00 == 0.0 {
+            return Moments::default();
+        }
+
+        let (cx, cy) = (m10 / m00, m01 / m00);
+        let (mut mu20, mut mu02, mut mu11) = (0.0f64, 0.0f64, 0.0f64);
+        for &(y, x0, x1) in &spans {
+            let dy = y as f64 - cy;
+            for x in x0..=x1 {
+                let dx = x as f64 - cx;
+                mu20 += dx * dx;
+                mu02 += dy * dy;
+                mu11 += dx * dy;
+            }
+        }
+
+        Moments {
+            m00: m00 as f32,
+            m10: m10 as f32,
+            m01: m01 as f32,
+            mu20: mu20 as f32,
+            mu02: mu02 as f32,
+            mu11: mu11 as f32,
+        }
+    }
+}
+
+/// Raw and central image moments of a contour region
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Moments {
+    pub m00: f32,
+    pub m10: f32,
+    pub m01: f32,
+    pub mu20: f32,
+    pub mu02: f32,
+    pub mu11: f32,
+}
+
+impl Moments {
+    /// Centroid (m10/m00, m01/m00), or `None` for an empty region
+    pub fn centroid(&self) -> Option<(f32, f32)> {
+        if self.m00 == 0.0 {
+            return None;
+        }
+        Some((self.m10 / self.m00, self.m01 / self.m00))
+    }
+
+    /// Angle of the major axis in radians, in (-π/2, π/2]
+    /// 0 is horizontal; positive angles turn towards +y (downwards in image coordinates).
+    pub fn orientation(&self) -> f32 {
+        0.5 * (2.0 * self.mu11).atan2(self.mu20 - self.mu02)
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct HouseNumberDetection {
     pub number: String,
     pub x: u32,
     pub y: u32,
+    /// Approximate radius of the marker circle in pixels
+    pub radius: u32,
     pub confidence: f32,
 }
@@ -1,8 +1,55 @@
 use image::DynamicImage;
-use std::sync::Arc;
-use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::mpsc::{self, Sender, Receiver};
+use std::time::{Duration, Instant};
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Level of the progress messages of a run: `Info` with `verbose` set, `Debug` otherwise.
+/// The messages are logged either way; `verbose` only raises them to the level a logger
+/// usually shows.
+pub fn verbose_level(verbose: bool) -> log::Level {
+    if verbose {
+        log::Level::Info
+    } else {
+        log::Level::Debug
+    }
+}
+
+/// Log a progress message at `verbose_level($verbose)`
+macro_rules! verbose_log {
+    ($verbose:expr, $($arg:tt)+) => {
+        log::log!($crate::pipeline::verbose_level($verbose), $($arg)+)
+    };
+}
+pub(crate) use verbose_log;
+
+/// Print log records up to `level` to stderr, for the command-line tools and examples.
+/// Applications with their own logger don't need this; if one is already installed it is kept.
+pub fn log_to_stderr(level: log::LevelFilter) {
+    struct StderrLogger;
+
+    impl log::Log for StderrLogger {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.level() <= log::max_level()
+        }
+
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                eprintln!("{}", record.args());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: StderrLogger = StderrLogger;
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level);
+    }
+}
 
 /// Bounding box in the original image
 #[derive(Debug, Clone)]
@@ -13,6 +60,22 @@ pub struct BoundingBox {
     pub height: u32,
 }
 
+/// Intersection over union of two bounding boxes (0.0 = disjoint, 1.0 = identical)
+pub fn iou(a: &BoundingBox, b: &BoundingBox) -> f32 {
+    let left = a.x.max(b.x);
+    let top = a.y.max(b.y);
+    let right = (a.x + a.width).min(b.x + b.width);
+    let bottom = (a.y + a.height).min(b.y + b.height);
+
+    let intersection = right.saturating_sub(left) as f32 * bottom.saturating_sub(top) as f32;
+    let union = (a.width * a.height) as f32 + (b.width * b.height) as f32 - intersection;
+
+    if union == 0.0 {
+        return 0.0;
+    }
+    intersection / union
+}
+
 /// Data that flows through the pipeline
 /// Each PipelineData represents a single image region with associated metadata
 #[derive(Clone)]
@@ -31,7 +94,9 @@ pub struct PipelineData {
 }
 
 /// Metadata value types
-#[derive(Debug, Clone)]
+/// Serialized tagged, e.g. `{"type": "Float", "value": 0.5}`, so the variant survives a round trip
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
 pub enum MetadataValue {
     Bool(bool),
     Float(f32),
@@ -39,6 +104,47 @@ pub enum MetadataValue {
     Int(i32),
 }
 
+/// Types that can be read out of a `MetadataValue`
+pub trait FromMetadata: Sized {
+    /// Returns `None` if `value` holds a different variant
+    fn from_metadata(value: &MetadataValue) -> Option<Self>;
+}
+
+macro_rules! metadata_variant {
+    ($ty:ty, $variant:ident) => {
+        impl FromMetadata for $ty {
+            fn from_metadata(value: &MetadataValue) -> Option<Self> {
+                match value {
+                    MetadataValue::$variant(v) => Some(v.to_owned()),
+                    _ => None,
+                }
+            }
+        }
+
+        impl TryFrom<MetadataValue> for $ty {
+            type Error = anyhow::Error;
+
+            fn try_from(value: MetadataValue) -> Result<Self> {
+                match value {
+                    MetadataValue::$variant(v) => Ok(v),
+                    other => anyhow::bail!("Expected {} metadata, found {:?}", stringify!($variant), other),
+                }
+            }
+        }
+
+        impl From<$ty> for MetadataValue {
+            fn from(value: $ty) -> Self {
+                MetadataValue::$variant(value)
+            }
+        }
+    };
+}
+
+metadata_variant!(bool, Bool);
+metadata_variant!(f32, Float);
+metadata_variant!(String, String);
+metadata_variant!(i32, Int);
+
 impl PipelineData {
     /// Create PipelineData for a full image
     pub fn from_image(image: DynamicImage) -> Self {
@@ -94,6 +200,59 @@ impl PipelineData {
             _ => None,
         }
     }
+
+    /// Get metadata as int
+    pub fn get_int(&self, key: &str) -> Option<i32> {
+        match self.metadata.get(key) {
+            Some(MetadataValue::Int(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// All metadata as a JSON object keyed by metadata name
+    /// Deserialize it as `HashMap<String, MetadataValue>` to get the metadata back
+    pub fn metadata_json(&self) -> serde_json::Value {
+        serde_json::to_value(&self.metadata).expect("metadata values always serialize")
+    }
+
+    /// Write `metadata_json` next to a debug image, replacing its extension with `.json`
+    fn save_metadata_sidecar(&self, image_path: &std::path::Path) -> Result<(), PipelineError> {
+        let json_path = image_path.with_extension("json");
+        let json = serde_json::to_string_pretty(&self.metadata_json()).map_err(std::io::Error::from)?;
+        std::fs::write(&json_path, json).map_err(|e| {
+            std::io::Error::new(e.kind(), format!("Failed to save debug metadata {}: {}", json_path.display(), e))
+        })?;
+        Ok(())
+    }
+
+    /// Get metadata as any type implementing `FromMetadata`
+    /// Returns `None` if the key is absent or holds a different variant.
+    pub fn get_as<T: FromMetadata>(&self, key: &str) -> Option<T> {
+        self.metadata.get(key).and_then(T::from_metadata)
+    }
+
+    /// Center of `bbox` in original image coordinates
+    /// Unaffected by steps that crop, rescale or clean up `image`. `None` for full-image items.
+    pub fn original_center(&self) -> Option<(u32, u32)> {
+        let bbox = self.bbox.as_ref()?;
+        Some((bbox.x + bbox.width / 2, bbox.y + bbox.height / 2))
+    }
+
+    /// Detection for an item the OCR step recognized
+    /// Position comes from `original_center`, the radius from the smaller side of `bbox`.
+    /// `None` without `ocr_text` or `bbox`; a missing `ocr_confidence` counts as 0.
+    pub fn to_house_number_detection(&self) -> Option<crate::models::HouseNumberDetection> {
+        let number = self.get_string("ocr_text")?.to_string();
+        let (x, y) = self.original_center()?;
+        let bbox = self.bbox.as_ref()?;
+        Some(crate::models::HouseNumberDetection {
+            number,
+            x,
+            y,
+            radius: bbox.width.min(bbox.height) / 2,
+            confidence: self.get_float("ocr_confidence").unwrap_or(0.0),
+        })
+    }
 }
 
 /// Debug configuration for pipeline execution
@@ -105,21 +264,240 @@ pub struct DebugConfig {
     pub enabled: bool,
 }
 
+/// Errors returned by pipeline steps and runs
+/// `?` turns it into an `anyhow::Error`; `downcast_ref::<PipelineError>()` gets it back.
+#[derive(Debug, thiserror::Error)]
+pub enum PipelineError {
+    /// The OCR model files are not in the ocrs cache directory
+    #[error(
+        "OCR models not found. Build with the `download` feature to let `ocr::ensure_models` \
+         fetch them, or install them with ocrs-cli (or download models manually)\n\
+         Expected locations:\n  - {}\n  - {}",
+        detection.display(),
+        recognition.display()
+    )]
+    OcrModelsMissing {
+        detection: std::path::PathBuf,
+        recognition: std::path::PathBuf,
+    },
+
+    /// Reading or writing files, e.g. debug outputs, failed
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// An image could not be decoded or encoded
+    #[error(transparent)]
+    Decode(#[from] image::ImageError),
+
+    /// The run was aborted through its `CancelToken`
+    #[error("Pipeline run was cancelled")]
+    Cancelled,
+
+    /// A step failed for any other reason
+    #[error("Step '{name}' failed: {source}")]
+    StepFailed {
+        name: String,
+        #[source]
+        source: anyhow::Error,
+    },
+}
+
+impl PipelineError {
+    /// Attribute an error returned by the step `step_name` to that step
+    /// Cancellation and missing models pass through unchanged, as does an error some
+    /// nested step already claimed.
+    fn in_step(self, step_name: &str) -> Self {
+        match self {
+            PipelineError::Cancelled | PipelineError::OcrModelsMissing { .. } => self,
+            PipelineError::StepFailed { name, source } if name.is_empty() => PipelineError::StepFailed {
+                name: step_name.to_string(),
+                source,
+            },
+            PipelineError::StepFailed { .. } => self,
+            other => PipelineError::StepFailed {
+                name: step_name.to_string(),
+                source: other.into(),
+            },
+        }
+    }
+}
+
+impl From<anyhow::Error> for PipelineError {
+    /// Recovers a `PipelineError`, IO or image error wrapped in `e`; anything else becomes a
+    /// `StepFailed` with an empty name, which the pipeline fills in with the failing step
+    fn from(e: anyhow::Error) -> Self {
+        let e = match e.downcast::<PipelineError>() {
+            Ok(e) => return e,
+            Err(e) => e,
+        };
+        let e = match e.downcast::<std::io::Error>() {
+            Ok(e) => return PipelineError::Io(e),
+            Err(e) => e,
+        };
+        match e.downcast::<image::ImageError>() {
+            Ok(e) => PipelineError::Decode(e),
+            Err(source) => PipelineError::StepFailed {
+                name: String::new(),
+                source,
+            },
+        }
+    }
+}
+
+/// Shared flag for aborting a pipeline run from another thread
+/// Clones refer to the same flag, so keep one and hand another to the pipeline
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation; the pipeline stops before its next step
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Fail with `PipelineError::Cancelled` once cancellation was requested
+    /// Long-running steps can call this between items
+    pub fn check(&self) -> Result<(), PipelineError> {
+        if self.is_cancelled() {
+            return Err(PipelineError::Cancelled);
+        }
+        Ok(())
+    }
+}
+
+/// Whether a progress event is emitted before or after a step processed its items
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressPhase {
+    Started,
+    Finished,
+}
+
+/// Progress report for a single pipeline step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgressEvent {
+    pub step_name: String,
+    /// Zero-based position of the step in the pipeline
+    pub step_index: usize,
+    pub total_steps: usize,
+    /// Input items this step has finished processing so far (never decreases for a step)
+    pub items_processed: usize,
+    pub phase: ProgressPhase,
+}
+
+/// Item counts and timing of one step in a `Pipeline::profile` run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepProfile {
+    pub step_name: String,
+    pub input_count: usize,
+    pub output_count: usize,
+    pub elapsed: Duration,
+}
+
+/// Work done by a `PipelineExecutor` run, to see where the time goes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutorStats {
+    /// Step invocations, one per item per step: the sum of items over the whole funnel
+    pub items_processed: usize,
+    /// Steps that processed at least one item
+    pub steps_run: usize,
+    pub wall_time: Duration,
+    /// Time spent in each step, summed over items and worker threads, by step name
+    pub per_step_time: HashMap<String, Duration>,
+}
+
+impl ExecutorStats {
+    /// Name and total time of the step that took longest
+    pub fn slowest_step(&self) -> Option<(&str, Duration)> {
+        self.per_step_time
+            .iter()
+            .max_by_key(|(_, elapsed)| **elapsed)
+            .map(|(name, elapsed)| (name.as_str(), *elapsed))
+    }
+}
+
+/// Collects `ExecutorStats` from the executor's worker threads
+#[derive(Default)]
+struct StatsCollector {
+    items_processed: AtomicUsize,
+    per_step_time: Mutex<HashMap<String, Duration>>,
+}
+
+impl StatsCollector {
+    /// `items` were processed by the step together, taking `elapsed`
+    fn record(&self, step_name: &str, items: usize, elapsed: Duration) {
+        self.items_processed.fetch_add(items, Ordering::Relaxed);
+        *self.per_step_time.lock().unwrap().entry(step_name.to_string()).or_default() += elapsed;
+    }
+
+    fn finish(self, wall_time: Duration) -> ExecutorStats {
+        let per_step_time = self.per_step_time.into_inner().unwrap();
+        ExecutorStats {
+            items_processed: self.items_processed.into_inner(),
+            steps_run: per_step_time.len(),
+            wall_time,
+            per_step_time,
+        }
+    }
+}
+
+/// Callback receiving progress events; called from worker threads when running in parallel
+pub type ProgressCallback = Arc<dyn Fn(ProgressEvent) + Send + Sync>;
+
 /// Context available to all pipeline steps
 #[derive(Clone)]
 pub struct PipelineContext {
     pub verbose: bool,
     pub debug: Option<DebugConfig>,
+    pub cancel: CancelToken,
+    pub progress: Option<ProgressCallback>,
+}
+
+impl PipelineContext {
+    /// Emit a progress event if a callback is set
+    fn report_progress(
+        &self,
+        step_name: &str,
+        step_index: usize,
+        total_steps: usize,
+        items_processed: usize,
+        phase: ProgressPhase,
+    ) {
+        if let Some(callback) = &self.progress {
+            callback(ProgressEvent {
+                step_name: step_name.to_string(),
+                step_index,
+                total_steps,
+                items_processed,
+                phase,
+            });
+        }
+    }
 }
 
 /// Trait that all pipeline steps must implement
 pub trait PipelineStep: Send + Sync {
     /// Process data and return transformed data
     /// Steps can split data (1 → many), filter (many → fewer), or transform (many → many)
-    fn process(&self, data: Vec<PipelineData>, context: &PipelineContext) -> Result<Vec<PipelineData>>;
+    /// Errors converted from `anyhow::Error` become `StepFailed` naming this step
+    fn process(&self, data: Vec<PipelineData>, context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError>;
 
     /// Human-readable name for this step (used in verbose output)
     fn name(&self) -> &str;
+
+    /// Whether `process` has to see all items that reach this step in one call, e.g. to
+    /// compare them with each other. `Pipeline::run` always passes all items at once; the
+    /// executor holds items back at such a step until no other item can still reach it.
+    fn needs_all_items(&self) -> bool {
+        false
+    }
 }
 
 /// Work item for pipeline execution
@@ -151,6 +529,11 @@ impl WorkItem {
         }
     }
 
+    /// Number of steps in the whole pipeline, done or not
+    fn total_steps(&self) -> usize {
+        self.current_step_index + self.remaining_steps.len()
+    }
+
     /// Check if this work item is complete (no more steps)
     pub fn is_complete(&self) -> bool {
         self.remaining_steps.is_empty()
@@ -167,7 +550,7 @@ impl WorkItem {
     }
 
     /// Save debug output if debug mode is enabled
-    fn save_debug_output(&self, context: &PipelineContext, step_name: &str) -> Result<()> {
+    fn save_debug_output(&self, context: &PipelineContext, step_name: &str) -> Result<(), PipelineError> {
         if let Some(debug_config) = &context.debug {
             if !debug_config.enabled {
                 return Ok(());
@@ -183,19 +566,52 @@ impl WorkItem {
             let filename = self.lineage_filename("png");
             let output_path = step_dir.join(&filename);
 
-            self.data.image.save(&output_path)
-                .map_err(|e| anyhow::anyhow!("Failed to save debug image: {}", e))?;
+            self.data.image.save(&output_path)?;
+            self.data.save_metadata_sidecar(&output_path)?;
 
-            if context.verbose {
-                println!("  Debug: saved {}/{}", step_dir_name, filename);
-            }
+            verbose_log!(context.verbose, "  Debug: saved {}/{}", step_dir_name, filename);
         }
 
         Ok(())
     }
 
+    /// Whether the next step has to see all items at once, see `PipelineStep::needs_all_items`
+    fn waits_for_all(&self) -> bool {
+        self.remaining_steps.first().is_some_and(|step| step.needs_all_items())
+    }
+
+    /// Run the next step on `items`, which all stand before the same step, in one `process` call
+    /// The results can't be traced back to single inputs, so each one starts a new lineage
+    /// numbered by its position in the step's output.
+    fn process_together(items: Vec<WorkItem>, context: &PipelineContext) -> Result<Vec<WorkItem>, PipelineError> {
+        let Some(first) = items.first() else {
+            return Ok(vec![]);
+        };
+        let step = first.remaining_steps[0].clone();
+        let remaining_after = first.remaining_steps[1..].to_vec();
+        let current_step_index = first.current_step_index + 1;
+        let step_name = step.name();
+
+        let data = items.into_iter().map(|item| item.data).collect();
+        let results = step.process(data, context).map_err(|e| e.in_step(step_name))?;
+
+        let mut new_items = Vec::new();
+        for (idx, result_data) in results.into_iter().enumerate() {
+            let new_item = WorkItem {
+                data: result_data,
+                remaining_steps: remaining_after.clone(),
+                current_step_index,
+                lineage: vec![idx + 1],
+            };
+            new_item.save_debug_output(context, step_name)?;
+            new_items.push(new_item);
+        }
+
+        Ok(new_items)
+    }
+
     /// Get the next step and create new work items for the remaining steps
-    pub fn process_next_step(&mut self, context: &PipelineContext) -> Result<Vec<WorkItem>> {
+    pub fn process_next_step(&mut self, context: &PipelineContext) -> Result<Vec<WorkItem>, PipelineError> {
         if self.remaining_steps.is_empty() {
             return Ok(vec![]);
         }
@@ -206,7 +622,9 @@ impl WorkItem {
         let step_name = step.name();
 
         // Process the step (this may split 1 item into many)
-        let results = step.process(vec![self.data.clone()], context)?;
+        let results = step
+            .process(vec![self.data.clone()], context)
+            .map_err(|e| e.in_step(step_name))?;
 
         // Create new work items for each result and assign IDs
         let mut new_items = Vec::new();
@@ -251,18 +669,34 @@ impl PipelineExecutor {
     }
 
     /// Execute the pipeline by processing work items from the channel
-    pub fn execute(&self, initial_items: Vec<WorkItem>) -> Result<Vec<PipelineData>> {
-        // Send all initial work items
+    pub fn execute(&self, initial_items: Vec<WorkItem>) -> Result<Vec<PipelineData>, PipelineError> {
+        self.execute_with_stats(initial_items).map(|(results, _)| results)
+    }
+
+    /// Like `execute`, also reporting how many items each step processed and how long it took
+    pub fn execute_with_stats(
+        &self,
+        initial_items: Vec<WorkItem>,
+    ) -> Result<(Vec<PipelineData>, ExecutorStats), PipelineError> {
+        let start = Instant::now();
+        let stats = StatsCollector::default();
+
+        // Send all initial work items; the executor owns the receiver, so sending cannot fail
         for item in initial_items {
-            self.sender.send(item)
-                .map_err(|e| anyhow::anyhow!("Failed to send work item: {}", e))?;
+            let _ = self.sender.send(item);
         }
 
         let mut completed_results = Vec::new();
         let mut pending_count = 1; // Start with at least 1 item
+        // Items processed so far per step index, for progress events
+        let mut processed: HashMap<usize, usize> = HashMap::new();
+        // Items waiting at a step that needs all items, by step index
+        let mut held: BTreeMap<usize, Vec<WorkItem>> = BTreeMap::new();
 
         // Process work items until queue is empty
-        while pending_count > 0 {
+        while pending_count > 0 || !held.is_empty() {
+            // Items still queued are simply dropped with the executor
+            self.context.cancel.check()?;
             match self.receiver.try_recv() {
                 Ok(mut item) => {
                     pending_count -= 1;
@@ -270,21 +704,45 @@ impl PipelineExecutor {
                     if item.is_complete() {
                         // No more steps - this is a final result
                         completed_results.push(item.data);
+                    } else if item.waits_for_all() {
+                        held.entry(item.current_step_index).or_default().push(item);
                     } else {
                         // Process next step
+                        let (step_index, total_steps) = (item.current_step_index, item.total_steps());
+                        let step_name = item.remaining_steps[0].name().to_string();
+                        let count = processed.entry(step_index).or_insert(0);
+                        self.context.report_progress(&step_name, step_index, total_steps, *count, ProgressPhase::Started);
+                        let step_start = Instant::now();
                         let new_items = item.process_next_step(&self.context)?;
+                        stats.record(&step_name, 1, step_start.elapsed());
+                        *count += 1;
+                        self.context.report_progress(&step_name, step_index, total_steps, *count, ProgressPhase::Finished);
 
                         // Send new work items back to the queue
                         for new_item in new_items {
-                            self.sender.send(new_item)
-                                .map_err(|e| anyhow::anyhow!("Failed to send work item: {}", e))?;
+                            let _ = self.sender.send(new_item);
                             pending_count += 1;
                         }
                     }
                 }
                 Err(mpsc::TryRecvError::Empty) => {
                     if pending_count == 0 {
-                        break;
+                        // Nothing else can reach the held items anymore: release the earliest step
+                        let Some((step_index, items)) = held.pop_first() else {
+                            break;
+                        };
+                        let (total_steps, step_name) = (items[0].total_steps(), items[0].remaining_steps[0].name().to_string());
+                        let count = items.len();
+                        self.context.report_progress(&step_name, step_index, total_steps, 0, ProgressPhase::Started);
+                        let step_start = Instant::now();
+                        let new_items = WorkItem::process_together(items, &self.context)?;
+                        stats.record(&step_name, count, step_start.elapsed());
+                        self.context.report_progress(&step_name, step_index, total_steps, count, ProgressPhase::Finished);
+                        for new_item in new_items {
+                            let _ = self.sender.send(new_item);
+                            pending_count += 1;
+                        }
+                        continue;
                     }
                     // Wait a bit if queue is empty but we expect more items
                     std::thread::yield_now();
@@ -295,13 +753,172 @@ impl PipelineExecutor {
             }
         }
 
-        Ok(completed_results)
+        Ok((completed_results, stats.finish(start.elapsed())))
+    }
+
+    /// Execute the pipeline on `num_threads` worker threads sharing one work queue
+    /// Results are collected in completion order, which differs from `execute`.
+    /// The first step error stops all workers and is returned.
+    pub fn execute_parallel(
+        &self,
+        initial_items: Vec<WorkItem>,
+        num_threads: usize,
+    ) -> Result<Vec<PipelineData>, PipelineError> {
+        self.execute_parallel_with_stats(initial_items, num_threads)
+            .map(|(results, _)| results)
+    }
+
+    /// Like `execute_parallel`, also reporting how many items each step processed and how
+    /// long it took; step times are summed over all workers, so they can exceed `wall_time`
+    pub fn execute_parallel_with_stats(
+        &self,
+        initial_items: Vec<WorkItem>,
+        num_threads: usize,
+    ) -> Result<(Vec<PipelineData>, ExecutorStats), PipelineError> {
+        let start = Instant::now();
+        let stats = StatsCollector::default();
+        let (sender, receiver) = mpsc::channel::<WorkItem>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        // Items queued or being processed; workers stop once this drains to zero
+        let pending = AtomicUsize::new(initial_items.len());
+        let failed = AtomicBool::new(false);
+        let first_error: Mutex<Option<PipelineError>> = Mutex::new(None);
+        let completed_results: Mutex<Vec<PipelineData>> = Mutex::new(Vec::new());
+        // Items processed so far per step index; events are emitted under this lock so
+        // counts reach the callback in order
+        let processed: Mutex<HashMap<usize, usize>> = Mutex::new(HashMap::new());
+        // Items waiting at a step that needs all items, by step index; they stay counted in
+        // `pending` until the step has processed them
+        let held: Mutex<BTreeMap<usize, Vec<WorkItem>>> = Mutex::new(BTreeMap::new());
+
+        // The receiver outlives all workers, so sending cannot fail
+        for item in initial_items {
+            let _ = sender.send(item);
+        }
+
+        // Borrow only the context: the executor itself holds a non-Sync receiver
+        let context = &self.context;
+        std::thread::scope(|scope| {
+            for _ in 0..num_threads.max(1) {
+                let sender = sender.clone();
+                let receiver = receiver.clone();
+                let (pending, failed, first_error, completed_results, processed, stats, held) =
+                    (&pending, &failed, &first_error, &completed_results, &processed, &stats, &held);
+                scope.spawn(move || {
+                    while pending.load(Ordering::SeqCst) > 0 && !failed.load(Ordering::SeqCst) {
+                        if let Err(e) = context.cancel.check() {
+                            first_error.lock().unwrap().get_or_insert(e);
+                            failed.store(true, Ordering::SeqCst);
+                            break;
+                        }
+
+                        // Once every pending item is held, nothing else can reach them anymore:
+                        // this worker releases the earliest step
+                        let release = {
+                            let mut held = held.lock().unwrap();
+                            let held_count: usize = held.values().map(Vec::len).sum();
+                            if held_count > 0 && held_count == pending.load(Ordering::SeqCst) {
+                                held.pop_first()
+                            } else {
+                                None
+                            }
+                        };
+                        if let Some((step_index, items)) = release {
+                            let (total_steps, step_name) = (items[0].total_steps(), items[0].remaining_steps[0].name().to_string());
+                            let count = items.len();
+                            context.report_progress(&step_name, step_index, total_steps, 0, ProgressPhase::Started);
+                            let step_start = Instant::now();
+                            let result = WorkItem::process_together(items, context);
+                            stats.record(&step_name, count, step_start.elapsed());
+                            context.report_progress(&step_name, step_index, total_steps, count, ProgressPhase::Finished);
+                            match result {
+                                Ok(new_items) => {
+                                    pending.fetch_add(new_items.len(), Ordering::SeqCst);
+                                    for new_item in new_items {
+                                        let _ = sender.send(new_item);
+                                    }
+                                }
+                                Err(e) => {
+                                    first_error.lock().unwrap().get_or_insert(e);
+                                    failed.store(true, Ordering::SeqCst);
+                                }
+                            }
+                            pending.fetch_sub(count, Ordering::SeqCst);
+                            continue;
+                        }
+
+                        // Only hold the queue lock while waiting for an item, not while processing it
+                        let next = receiver.lock().unwrap().recv_timeout(Duration::from_millis(10));
+                        let mut item = match next {
+                            Ok(item) => item,
+                            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                        };
+
+                        if item.waits_for_all() {
+                            // Stays pending until released above
+                            held.lock().unwrap().entry(item.current_step_index).or_default().push(item);
+                            continue;
+                        }
+
+                        if item.is_complete() {
+                            // No more steps - this is a final result
+                            completed_results.lock().unwrap().push(item.data);
+                        } else {
+                            let (step_index, total_steps) = (item.current_step_index, item.total_steps());
+                            let step_name = item.remaining_steps[0].name().to_string();
+                            {
+                                let processed = processed.lock().unwrap();
+                                let count = processed.get(&step_index).copied().unwrap_or(0);
+                                context.report_progress(&step_name, step_index, total_steps, count, ProgressPhase::Started);
+                            }
+                            let step_start = Instant::now();
+                            let result = item.process_next_step(context);
+                            stats.record(&step_name, 1, step_start.elapsed());
+                            {
+                                let mut processed = processed.lock().unwrap();
+                                let count = processed.entry(step_index).or_insert(0);
+                                *count += 1;
+                                context.report_progress(&step_name, step_index, total_steps, *count, ProgressPhase::Finished);
+                            }
+                            match result {
+                                Ok(new_items) => {
+                                    // Count children before retiring the parent so pending never hits zero early
+                                    pending.fetch_add(new_items.len(), Ordering::SeqCst);
+                                    for new_item in new_items {
+                                        // The receiver outlives all workers, so sending cannot fail
+                                        let _ = sender.send(new_item);
+                                    }
+                                }
+                                Err(e) => {
+                                    first_error.lock().unwrap().get_or_insert(e);
+                                    failed.store(true, Ordering::SeqCst);
+                                }
+                            }
+                        }
+                        pending.fetch_sub(1, Ordering::SeqCst);
+                    }
+                });
+            }
+        });
+
+        if let Some(e) = first_error.into_inner().unwrap() {
+            return Err(e);
+        }
+        Ok((completed_results.into_inner().unwrap(), stats.finish(start.elapsed())))
     }
 }
 
+/// A pipeline step and whether runs execute it
+struct PipelineEntry {
+    step: Arc<dyn PipelineStep>,
+    enabled: bool,
+}
+
 /// Composable pipeline builder
 pub struct Pipeline {
-    steps: Vec<Arc<dyn PipelineStep>>,
+    steps: Vec<PipelineEntry>,
     context: PipelineContext,
 }
 
@@ -313,6 +930,8 @@ impl Pipeline {
             context: PipelineContext {
                 verbose: false,
                 debug: None,
+                cancel: CancelToken::new(),
+                progress: None,
             },
         }
     }
@@ -323,9 +942,46 @@ impl Pipeline {
         self
     }
 
+    /// Enable debug mode, saving outputs into a fresh `run_YYYYMMDD_HHMMSS` folder inside `output_dir`
+    /// `output_dir` may already contain earlier runs; it is created if missing.
+    /// A `_2`, `_3`, ... suffix keeps runs started within the same second apart.
+    /// The chosen folder is available from `debug_run_dir`.
+    pub fn with_debug(self, output_dir: std::path::PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&output_dir)?;
+
+        let now = time::OffsetDateTime::now_local().unwrap_or_else(|_| time::OffsetDateTime::now_utc());
+        let base_name = format!(
+            "run_{:04}{:02}{:02}_{:02}{:02}{:02}",
+            now.year(),
+            u8::from(now.month()),
+            now.day(),
+            now.hour(),
+            now.minute(),
+            now.second()
+        );
+
+        // create_dir fails if the folder exists, so concurrent runs can't claim the same one
+        let mut suffix = 1;
+        let run_dir = loop {
+            let name = if suffix == 1 { base_name.clone() } else { format!("{}_{}", base_name, suffix) };
+            let candidate = output_dir.join(name);
+            match std::fs::create_dir(&candidate) {
+                Ok(()) => break candidate,
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => suffix += 1,
+                Err(e) => return Err(anyhow::anyhow!(
+                    "Failed to create debug run directory {}: {}",
+                    candidate.display(),
+                    e
+                )),
+            }
+        };
+
+        Ok(self.with_debug_dir(run_dir))
+    }
+
     /// Enable debug mode with output directory
     /// The directory must be empty or non-existent
-    pub fn with_debug(mut self, output_dir: std::path::PathBuf) -> Result<Self> {
+    pub fn with_debug_strict(self, output_dir: std::path::PathBuf) -> Result<Self> {
         // Check if directory exists and is empty
         if output_dir.exists() {
             let entries = std::fs::read_dir(&output_dir)?;
@@ -340,52 +996,107 @@ impl Pipeline {
             std::fs::create_dir_all(&output_dir)?;
         }
 
+        Ok(self.with_debug_dir(output_dir))
+    }
+
+    fn with_debug_dir(mut self, output_dir: std::path::PathBuf) -> Self {
         self.context.debug = Some(DebugConfig {
             output_dir,
             enabled: true,
         });
+        self
+    }
+
+    /// Directory debug outputs are written to, if debug mode is enabled
+    pub fn debug_run_dir(&self) -> Option<&std::path::Path> {
+        self.context.debug.as_ref()
+            .filter(|config| config.enabled)
+            .map(|config| config.output_dir.as_path())
+    }
 
-        Ok(self)
+    /// Use `token` to cancel runs of this pipeline
+    /// Cancelled runs return `PipelineError::Cancelled` and discard partial results
+    pub fn with_cancel_token(mut self, token: CancelToken) -> Self {
+        self.context.cancel = token;
+        self
+    }
+
+    /// Report progress to `callback` before and after each step
+    /// `run` reports once per step; the executors report once per processed item
+    pub fn on_progress(mut self, callback: ProgressCallback) -> Self {
+        self.context.progress = Some(callback);
+        self
     }
 
     /// Add a processing step to the pipeline
     pub fn add_step(mut self, step: Arc<dyn PipelineStep>) -> Self {
-        self.steps.push(step);
+        self.steps.push(PipelineEntry { step, enabled: true });
         self
     }
 
     /// Helper method to add a step from a Box (for convenience)
-    pub fn add_step_boxed(mut self, step: Box<dyn PipelineStep>) -> Self {
-        self.steps.push(Arc::from(step));
-        self
+    pub fn add_step_boxed(self, step: Box<dyn PipelineStep>) -> Self {
+        self.add_step(Arc::from(step))
+    }
+
+    /// Names of all steps in order, enabled or not
+    pub fn step_names(&self) -> Vec<String> {
+        self.steps.iter().map(|entry| entry.step.name().to_string()).collect()
+    }
+
+    /// Enable or disable the step at `index` (as in `step_names`)
+    /// Runs skip disabled steps and hand their input to the next step unchanged.
+    pub fn set_step_enabled(&mut self, index: usize, enabled: bool) -> Result<()> {
+        let step_count = self.steps.len();
+        let entry = self.steps.get_mut(index)
+            .ok_or_else(|| anyhow::anyhow!("Step index {} out of range ({} steps)", index, step_count))?;
+        entry.enabled = enabled;
+        Ok(())
+    }
+
+    pub fn is_step_enabled(&self, index: usize) -> Option<bool> {
+        self.steps.get(index).map(|entry| entry.enabled)
+    }
+
+    /// The steps a run executes, in order
+    fn enabled_steps(&self) -> Vec<Arc<dyn PipelineStep>> {
+        self.steps.iter()
+            .filter(|entry| entry.enabled)
+            .map(|entry| entry.step.clone())
+            .collect()
     }
 
     /// Run the pipeline sequentially on an input image (simple execution)
-    pub fn run(&mut self, input: DynamicImage) -> Result<Vec<PipelineData>> {
+    /// Progress events and debug folders only count the enabled steps.
+    /// A failing step is reported as `PipelineError::StepFailed` carrying its name
+    pub fn run(&mut self, input: DynamicImage) -> Result<Vec<PipelineData>, PipelineError> {
         // Save initial input in debug mode
         if let Some(debug_config) = &self.context.debug {
             if debug_config.enabled {
                 let input_dir = debug_config.output_dir.join("00_input");
                 std::fs::create_dir_all(&input_dir)?;
                 let input_path = input_dir.join("01.png");
-                input.save(&input_path)
-                    .map_err(|e| anyhow::anyhow!("Failed to save debug input: {}", e))?;
-                if self.context.verbose {
-                    println!("  Debug: saved 00_input/01.png");
-                }
+                input.save(&input_path)?;
+                verbose_log!(self.context.verbose, "  Debug: saved 00_input/01.png");
             }
         }
 
         // Start with a single PipelineData containing the full image
         let mut data = vec![PipelineData::from_image(input)];
 
-        for (step_idx, step) in self.steps.iter().enumerate() {
-            if self.context.verbose {
-                println!("Running step: {} (processing {} items)", step.name(), data.len());
-            }
+        let steps = self.enabled_steps();
+        for (step_idx, step) in steps.iter().enumerate() {
+            self.context.cancel.check()?;
+            verbose_log!(self.context.verbose, "Running step: {} (processing {} items)", step.name(), data.len());
 
             let step_name = step.name();
-            data = step.process(data, &self.context)?;
+            let total_steps = steps.len();
+            self.context.report_progress(step_name, step_idx, total_steps, 0, ProgressPhase::Started);
+            let input_count = data.len();
+            data = step.process(data, &self.context).map_err(|e| e.in_step(step_name))?;
+            self.context.report_progress(step_name, step_idx, total_steps, input_count, ProgressPhase::Finished);
+            // A step may have run to completion after cancellation was requested
+            self.context.cancel.check()?;
 
             // Save debug outputs for this step
             if let Some(debug_config) = &self.context.debug {
@@ -398,67 +1109,200 @@ impl Pipeline {
                     for (idx, item) in data.iter().enumerate() {
                         let filename = format!("{:02}.png", idx + 1);
                         let output_path = step_dir.join(&filename);
-                        item.image.save(&output_path)
-                            .map_err(|e| anyhow::anyhow!("Failed to save debug image: {}", e))?;
+                        item.image.save(&output_path)?;
+                        item.save_metadata_sidecar(&output_path)?;
                     }
 
-                    if self.context.verbose {
-                        println!("  Debug: saved {} images to {}/", data.len(), step_dir_name);
-                    }
+                    verbose_log!(self.context.verbose, "  Debug: saved {} images to {}/", data.len(), step_dir_name);
                 }
             }
 
-            if self.context.verbose {
-                println!("  → {} items", data.len());
-            }
+            verbose_log!(self.context.verbose, "  → {} items", data.len());
         }
 
         Ok(data)
     }
 
     /// Run the pipeline using the executor with work queue
-    /// This allows for more sophisticated execution patterns in the future
-    pub fn run_with_executor(&self, input: DynamicImage) -> Result<Vec<PipelineData>> {
+    /// With `num_threads > 1` items are processed in parallel and results come back
+    /// in completion order
+    pub fn run_with_executor(&self, input: DynamicImage, num_threads: usize) -> Result<Vec<PipelineData>, PipelineError> {
+        self.run_with_executor_stats(input, num_threads).map(|(results, _)| results)
+    }
+
+    /// Like `run_with_executor`, also returning per-step timing and item counts, e.g. to find
+    /// the step that dominates a run
+    pub fn run_with_executor_stats(
+        &self,
+        input: DynamicImage,
+        num_threads: usize,
+    ) -> Result<(Vec<PipelineData>, ExecutorStats), PipelineError> {
         // Save initial input in debug mode
         if let Some(debug_config) = &self.context.debug {
             if debug_config.enabled {
                 let input_dir = debug_config.output_dir.join("00_input");
                 std::fs::create_dir_all(&input_dir)?;
                 let input_path = input_dir.join("01.png");
-                input.save(&input_path)
-                    .map_err(|e| anyhow::anyhow!("Failed to save debug input: {}", e))?;
-                if self.context.verbose {
-                    println!("  Debug: saved 00_input/01.png");
-                }
+                input.save(&input_path)?;
+                verbose_log!(self.context.verbose, "  Debug: saved 00_input/01.png");
             }
         }
 
         let initial_data = PipelineData::from_image(input);
-        let initial_item = WorkItem::new(initial_data, self.steps.clone());
+        let initial_item = WorkItem::new(initial_data, self.enabled_steps());
 
         let executor = PipelineExecutor::new(self.context.clone());
-        executor.execute(vec![initial_item])
+        if num_threads > 1 {
+            executor.execute_parallel_with_stats(vec![initial_item], num_threads)
+        } else {
+            executor.execute_with_stats(vec![initial_item])
+        }
+    }
+
+    /// Run the enabled steps like `run` and report how many items each one received and
+    /// emitted and how long it took, to estimate the cost of a full run
+    /// Nothing is written to the debug directory and no progress events are emitted.
+    pub fn profile(&self, input: DynamicImage) -> Result<Vec<StepProfile>, PipelineError> {
+        let mut data = vec![PipelineData::from_image(input)];
+        let mut profiles = Vec::new();
+
+        for step in self.enabled_steps() {
+            self.context.cancel.check()?;
+            let input_count = data.len();
+            let start = Instant::now();
+            data = step.process(data, &self.context).map_err(|e| e.in_step(step.name()))?;
+            profiles.push(StepProfile {
+                step_name: step.name().to_string(),
+                input_count,
+                output_count: data.len(),
+                elapsed: start.elapsed(),
+            });
+        }
+
+        Ok(profiles)
     }
 
     /// Run the pipeline but stop at an intermediate step (useful for debugging)
-    pub fn run_partial(&mut self, input: DynamicImage, num_steps: usize) -> Result<Vec<PipelineData>> {
+    /// `num_steps` counts disabled steps too, so it matches the indices of `step_names`.
+    pub fn run_partial(&mut self, input: DynamicImage, num_steps: usize) -> Result<Vec<PipelineData>, PipelineError> {
         let mut data = vec![PipelineData::from_image(input)];
 
-        for (i, step) in self.steps.iter().enumerate() {
+        for (i, PipelineEntry { step, enabled }) in self.steps.iter().enumerate() {
             if i >= num_steps {
                 break;
             }
-            if self.context.verbose {
-                println!("Running step {}: {} (processing {} items)", i + 1, step.name(), data.len());
-            }
-            data = step.process(data, &self.context)?;
-            if self.context.verbose {
-                println!("  → {} items", data.len());
+            if !enabled {
+                continue;
             }
+            self.context.cancel.check()?;
+            verbose_log!(self.context.verbose, "Running step {}: {} (processing {} items)", i + 1, step.name(), data.len());
+            data = step.process(data, &self.context).map_err(|e| e.in_step(step.name()))?;
+            verbose_log!(self.context.verbose, "  → {} items", data.len());
         }
 
         Ok(data)
     }
+
+    /// Write `index.html` into the debug output directory after a debug run
+    /// Each step folder becomes a column of thumbnails; items link to their parent and
+    /// children by lineage, so the funnel can be followed from the input to the results.
+    /// Lineage links need the executor's file names; `run` only numbers items per step.
+    /// Returns the path of the written report.
+    pub fn write_debug_report(&self) -> Result<std::path::PathBuf> {
+        let debug_config = self.context.debug.as_ref()
+            .filter(|config| config.enabled)
+            .ok_or_else(|| anyhow::anyhow!("Debug mode is not enabled"))?;
+        let output_dir = &debug_config.output_dir;
+
+        // Step folders start with their two-digit index, so sorting by name keeps step order
+        let mut step_dirs: Vec<String> = Vec::new();
+        for entry in std::fs::read_dir(output_dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if entry.file_type()?.is_dir() && name.chars().take(2).all(|c| c.is_ascii_digit()) {
+                step_dirs.push(name);
+            }
+        }
+        step_dirs.sort();
+
+        let mut columns: Vec<(String, Vec<String>)> = Vec::new();
+        for step_dir in step_dirs {
+            let mut stems: Vec<String> = std::fs::read_dir(output_dir.join(&step_dir))?
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "png"))
+                .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+                .collect();
+            stems.sort();
+            columns.push((step_dir, stems));
+        }
+
+        let mut html = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Pipeline debug report</title>\n\
+             <style>\n\
+             body { font-family: sans-serif; }\n\
+             .steps { display: flex; gap: 1em; align-items: flex-start; }\n\
+             .step { min-width: 10em; }\n\
+             figure { margin: 0 0 1em 0; }\n\
+             figure:target { outline: 3px solid orange; }\n\
+             img { max-width: 96px; max-height: 96px; image-rendering: pixelated; }\n\
+             figcaption { font-size: 0.8em; }\n\
+             </style>\n</head>\n<body>\n<h1>Pipeline debug report</h1>\n<div class=\"steps\">\n",
+        );
+        for (step_idx, (step_dir, stems)) in columns.iter().enumerate() {
+            html.push_str(&format!(
+                "<div class=\"step\">\n<h2>{}</h2>\n<p>{} items</p>\n",
+                html_escape(step_dir),
+                stems.len()
+            ));
+            for stem in stems {
+                let id = format!("{}/{}", step_dir, stem);
+                html.push_str(&format!(
+                    "<figure id=\"{id}\">\n<a href=\"{id}.png\"><img src=\"{id}.png\" alt=\"{id}\"></a>\n<figcaption>{stem}",
+                    id = html_escape(&id),
+                    stem = html_escape(stem),
+                ));
+
+                if step_idx > 0 {
+                    let (prev_dir, prev_stems) = &columns[step_idx - 1];
+                    if let Some(parent) = lineage_parent(stem, step_idx == 1)
+                        .filter(|parent| prev_stems.contains(parent))
+                    {
+                        html.push_str(&format!(
+                            "<br>parent: <a href=\"#{0}/{1}\">{1}</a>",
+                            html_escape(prev_dir),
+                            html_escape(&parent)
+                        ));
+                    }
+                }
+                if let Some((next_dir, next_stems)) = columns.get(step_idx + 1) {
+                    let children: Vec<String> = next_stems
+                        .iter()
+                        .filter(|child| lineage_parent(child, step_idx == 0).as_deref() == Some(stem.as_str()))
+                        .map(|child| format!(
+                            "<a href=\"#{0}/{1}\">{1}</a>",
+                            html_escape(next_dir),
+                            html_escape(child)
+                        ))
+                        .collect();
+                    if !children.is_empty() {
+                        html.push_str(&format!("<br>children: {}", children.join(" ")));
+                    }
+                }
+                if output_dir.join(step_dir).join(format!("{}.json", stem)).is_file() {
+                    html.push_str(&format!(" <a href=\"{}.json\">metadata</a>", html_escape(&id)));
+                }
+                html.push_str("</figcaption>\n</figure>\n");
+            }
+            html.push_str("</div>\n");
+        }
+        html.push_str("</div>\n</body>\n</html>\n");
+
+        let report_path = output_dir.join("index.html");
+        std::fs::write(&report_path, html)
+            .map_err(|e| anyhow::anyhow!("Failed to write debug report: {}", e))?;
+        Ok(report_path)
+    }
 }
 
 impl Default for Pipeline {
@@ -466,3 +1310,21 @@ impl Default for Pipeline {
         Self::new()
     }
 }
+
+/// Lineage file stem of the item that produced `stem` (e.g. "01-03" for "01-03-02")
+/// Items of the first step all come from the single input image "01".
+fn lineage_parent(stem: &str, parent_is_input: bool) -> Option<String> {
+    match stem.rsplit_once('-') {
+        Some((parent, _)) => Some(parent.to_string()),
+        None if parent_is_input => Some("01".to_string()),
+        None => None,
+    }
+}
+
+/// Escape text for use in HTML content and attribute values
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -1,14 +1,179 @@
+use std::sync::Arc;
+
 use dioxus::prelude::*;
- 
- /// Address Detection page
 
+use crate::{
+    core::{
+        db::{AddressRepository, AreaDb, BoundAreaRepository, Point, Team, TeamRepository},
+        view::{detect_addresses, image_data_url},
+    },
+    detection::DetectionPipeline,
+    ui::{address_table::AddressTable, area_viewer::AreaViewer, components::Loading},
+};
+
+/// Address Detection page
 #[component]
 pub fn AddressDetection(file: String, area_id: i64) -> Element {
+    let area_db_signal: Signal<Arc<AreaDb>> = use_context();
+    let image_url = use_resource(move || async move {
+        let area_db = area_db_signal.read().clone();
+        let image = area_db.get_image();
+        image_data_url(image)
+            .map(|url| (url, (image.width() as f64, image.height() as f64)))
+            .map_err(|e| e.to_string())
+    });
+    let mut addresses = use_resource(move || async move {
+        let area_db = area_db_signal.read().clone();
+        AddressRepository::get_addresses(area_db.as_ref()).await.map_err(|e| e.to_string())
+    });
+    let mut teams = use_resource(move || async move {
+        let area_db = area_db_signal.read().clone();
+        load_team_bounds(area_db.as_ref()).await.map_err(|e| e.to_string())
+    });
+    // Team whose bounds are being drawn
+    let mut drawing_team: Signal<Option<Team>> = use_signal(|| None);
+    let mut save_error: Signal<Option<String>> = use_signal(|| None);
+
+    let save_bounds = move |boundary: Vec<Point>| {
+        let Some(team) = drawing_team() else {
+            return;
+        };
+        spawn(async move {
+            let area_db = area_db_signal.read().clone();
+            match area_db.set_team_bounds(&team, &boundary).await {
+                Ok(_) => {
+                    save_error.set(None);
+                    drawing_team.set(None);
+                    teams.restart();
+                }
+                Err(e) => save_error.set(Some(e.to_string())),
+            }
+        });
+    };
+
     rsx! {
         div {
             id: "address-detection",
             h1 { "Address Detection" }
             p { "Area ID: {area_id}" }
+            DetectionControls { on_imported: move |_| addresses.restart() }
+            match (image_url.cloned(), addresses.cloned(), teams.cloned()) {
+                (Some(Err(error_msg)), _, _) | (_, Some(Err(error_msg)), _) | (_, _, Some(Err(error_msg))) => rsx! {
+                    div {
+                        class: "error",
+                        h2 { "Error loading area" }
+                        p { "{error_msg}" }
+                    }
+                },
+                (Some(Ok((image_url, image_size))), Some(Ok(area_addresses)), Some(Ok(teams))) => rsx! {
+                    div {
+                        class: "team-bounds",
+                        for (team, boundary) in teams.clone() {
+                            div {
+                                key: "{team.id}",
+                                span {
+                                    "Team {team.number}: "
+                                    if boundary.is_empty() {
+                                        "no bounds"
+                                    } else {
+                                        "{boundary.len()} vertices"
+                                    }
+                                }
+                                if drawing_team().as_ref() == Some(&team) {
+                                    button { onclick: move |_| drawing_team.set(None), "Cancel" }
+                                } else {
+                                    button {
+                                        onclick: move |_| drawing_team.set(Some(team.clone())),
+                                        "Draw bounds"
+                                    }
+                                }
+                            }
+                        }
+                        if drawing_team().is_some() {
+                            p {
+                                "Click to add vertices, backspace removes the last one, double-click closes the boundary."
+                            }
+                        }
+                        if let Some(e) = save_error() {
+                            p { class: "error", "Failed to save bounds: {e}" }
+                        }
+                    }
+                    AreaViewer {
+                        image_url,
+                        image_size,
+                        addresses: area_addresses.clone(),
+                        boundaries: teams
+                            .into_iter()
+                            .map(|(_, boundary)| boundary)
+                            .filter(|boundary| !boundary.is_empty())
+                            .collect::<Vec<_>>(),
+                        on_boundary: drawing_team().map(|_| EventHandler::new(save_bounds)),
+                    }
+                    AddressTable { addresses: area_addresses, on_changed: move |_| addresses.restart() }
+                },
+                _ => rsx! { Loading {} },
+            }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Button running house number detection on the area image and importing the results
+#[component]
+fn DetectionControls(on_imported: EventHandler<()>) -> Element {
+    let area_db_signal: Signal<Arc<AreaDb>> = use_context();
+    let mut running = use_signal(|| false);
+    let mut result: Signal<Option<Result<usize, String>>> = use_signal(|| None);
+
+    rsx! {
+        div {
+            class: "detection-controls",
+            button {
+                disabled: running(),
+                onclick: move |_| async move {
+                    running.set(true);
+                    result.set(None);
+                    let area_db = area_db_signal.read().clone();
+                    let detected =
+                        detect_addresses(area_db.as_ref(), |img| DetectionPipeline::new().detect(img)).await;
+                    running.set(false);
+                    match detected {
+                        Ok(imported) => {
+                            result.set(Some(Ok(imported.len())));
+                            on_imported.call(());
+                        }
+                        Err(e) => result.set(Some(Err(e.to_string()))),
+                    }
+                },
+                "Detect House Numbers"
+            }
+            if running() {
+                Loading {}
+            }
+            match result() {
+                Some(Ok(count)) => rsx! { p { "Imported {count} house numbers" } },
+                Some(Err(error_msg)) => rsx! {
+                    div {
+                        class: "error",
+                        h2 { "Detection failed" }
+                        p { "{error_msg}" }
+                    }
+                },
+                None => rsx! {},
+            }
+        }
+    }
+}
+
+/// Every team of the area with its saved boundary, empty if it has none
+async fn load_team_bounds(area_db: &AreaDb) -> anyhow::Result<Vec<(Team, Vec<Point>)>> {
+    let mut teams = Vec::new();
+    for team in area_db.get_teams().await? {
+        let boundary = area_db
+            .get_team_bounds(&team)
+            .await?
+            .map(|bounds| bounds.boundary)
+            .unwrap_or_default();
+        teams.push((team, boundary));
+    }
+    Ok(teams)
+}
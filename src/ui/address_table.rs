@@ -0,0 +1,107 @@
+use std::{sync::Arc, time::Duration};
+
+use dioxus::prelude::*;
+
+use crate::core::{
+    db::{Address, AreaDb},
+    view::AddressEdit,
+};
+
+/// How long typing has to pause before a house number edit is stored
+const EDIT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Addresses of the area with editable house numbers, a verified checkbox and a delete button
+/// `on_changed` runs after every stored edit, so the caller can reload the addresses.
+#[component]
+pub fn AddressTable(addresses: Vec<Address>, on_changed: EventHandler<()>) -> Element {
+    rsx! {
+        table {
+            class: "address-table",
+            thead {
+                tr {
+                    th { "House number" }
+                    th { "Position" }
+                    th { "Confidence" }
+                    th { "Verified" }
+                    th {}
+                }
+            }
+            tbody {
+                for address in addresses {
+                    AddressRow { key: "{address.id}", address, on_changed }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn AddressRow(address: Address, on_changed: EventHandler<()>) -> Element {
+    let area_db_signal: Signal<Arc<AreaDb>> = use_context();
+    // Controlled input: shows what was typed until the debounced write stores it
+    let mut house_number = use_signal(|| address.house_number.clone());
+    // Bumped on every keystroke so only the last pending write goes through
+    let mut edit_generation = use_signal(|| 0u64);
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+
+    let save = {
+        let address = address.clone();
+        move |edit: AddressEdit| {
+            let address = address.clone();
+            async move {
+                let area_db = area_db_signal.read().clone();
+                match edit.apply(area_db.as_ref(), &address).await {
+                    Ok(_) => {
+                        error.set(None);
+                        on_changed.call(());
+                    }
+                    Err(e) => error.set(Some(e.to_string())),
+                }
+            }
+        }
+    };
+    let save_number = save.clone();
+    let save_verified = save.clone();
+
+    rsx! {
+        tr {
+            class: if address.verified { "verified" } else { "" },
+            td {
+                input {
+                    r#type: "text",
+                    value: "{house_number()}",
+                    oninput: move |event| {
+                        house_number.set(event.value());
+                        let generation = edit_generation() + 1;
+                        edit_generation.set(generation);
+                        let save_number = save_number.clone();
+                        async move {
+                            tokio::time::sleep(EDIT_DEBOUNCE).await;
+                            if edit_generation() == generation {
+                                save_number(AddressEdit::HouseNumber(house_number())).await;
+                            }
+                        }
+                    },
+                }
+                if let Some(e) = error() {
+                    p { class: "error", "{e}" }
+                }
+            }
+            td { "({address.position.x}, {address.position.y})" }
+            td { "{address.confidence:.2}" }
+            td {
+                input {
+                    r#type: "checkbox",
+                    checked: address.verified,
+                    onchange: move |event| save_verified(AddressEdit::Verified(event.checked())),
+                }
+            }
+            td {
+                button {
+                    onclick: move |_| save(AddressEdit::Delete),
+                    "Delete"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use dioxus::prelude::*;
+
+use crate::core::{
+    db::{Address, Point},
+    view::{overlay_markers, svg_points, PolygonDraft, ViewTransform},
+};
+
+/// Zoom change per mouse-wheel notch
+const WHEEL_ZOOM_STEP: f64 = 1.15;
+
+/// Area image with a marker per address, zoomed with the mouse wheel and panned by dragging
+/// Markers show the house number and whether the address is verified. `boundaries` are drawn
+/// as outlines. While `on_boundary` is set, clicks add vertices to a new boundary,
+/// backspace removes the last one and a double-click closes it and hands it over.
+#[component]
+pub fn AreaViewer(
+    image_url: String,
+    image_size: (f64, f64),
+    addresses: Vec<Address>,
+    #[props(default)] boundaries: Vec<Vec<Point>>,
+    on_boundary: Option<EventHandler<Vec<Point>>>,
+) -> Element {
+    let mut transform = use_signal(ViewTransform::default);
+    // Size of the viewer element, known after the first resize event
+    let mut viewport: Signal<Option<(f64, f64)>> = use_signal(|| None);
+    // Last pointer position while dragging
+    let mut drag_from: Signal<Option<(f64, f64)>> = use_signal(|| None);
+    // Whether the pointer moved since the last mouse down, so a drag doesn't add a vertex
+    let mut dragged = use_signal(|| false);
+    let mut draft = use_signal(PolygonDraft::default);
+
+    let drawing = on_boundary.is_some();
+    let current = transform();
+    let markers = overlay_markers(&addresses, &current);
+    let boundary_points: Vec<String> = boundaries.iter().map(|b| svg_points(b, &current)).collect();
+    let draft_points = svg_points(draft.read().vertices(), &current);
+
+    rsx! {
+        div {
+            class: "area-viewer-toolbar",
+            button {
+                disabled: viewport().is_none(),
+                onclick: move |_| {
+                    if let Some(size) = viewport() {
+                        transform.set(ViewTransform::fit(image_size, size));
+                    }
+                },
+                "Fit to window"
+            }
+            span { "{current.zoom * 100.0:.0}%" }
+        }
+        div {
+            class: if drawing { "area-viewer drawing" } else { "area-viewer" },
+            tabindex: 0,
+            onresize: move |event| {
+                let Ok(size) = event.get_content_box_size() else {
+                    return;
+                };
+                let size = (size.width, size.height);
+                match viewport() {
+                    None => transform.set(ViewTransform::fit(image_size, size)),
+                    Some(old_size) => transform.write().resize_viewport(old_size, size),
+                }
+                viewport.set(Some(size));
+            },
+            onwheel: move |event| {
+                event.prevent_default();
+                let point = event.element_coordinates();
+                let factor = if event.delta().strip_units().y < 0.0 {
+                    WHEEL_ZOOM_STEP
+                } else {
+                    1.0 / WHEEL_ZOOM_STEP
+                };
+                transform.write().zoom_at(point.x, point.y, factor);
+            },
+            onmousedown: move |event| {
+                let point = event.client_coordinates();
+                drag_from.set(Some((point.x, point.y)));
+                dragged.set(false);
+            },
+            onmousemove: move |event| {
+                if let Some((from_x, from_y)) = drag_from() {
+                    let point = event.client_coordinates();
+                    if (point.x, point.y) != (from_x, from_y) {
+                        transform.write().pan_by(point.x - from_x, point.y - from_y);
+                        drag_from.set(Some((point.x, point.y)));
+                        dragged.set(true);
+                    }
+                }
+            },
+            onclick: move |event| {
+                if !drawing || dragged() {
+                    return;
+                }
+                let point = event.element_coordinates();
+                if let Some(vertex) = transform().screen_to_image_point(point.x, point.y, image_size) {
+                    draft.write().add_vertex(vertex);
+                }
+            },
+            ondoubleclick: move |_| {
+                if let Some(handler) = on_boundary {
+                    if let Some(boundary) = draft.write().close() {
+                        handler.call(boundary);
+                    }
+                }
+            },
+            onkeydown: move |event| {
+                if drawing && event.key() == Key::Backspace {
+                    event.prevent_default();
+                    draft.write().remove_last();
+                }
+            },
+            onmouseup: move |_| drag_from.set(None),
+            onmouseleave: move |_| drag_from.set(None),
+            img {
+                src: "{image_url}",
+                draggable: false,
+                style: "transform: translate({current.pan_x}px, {current.pan_y}px) scale({current.zoom});",
+            }
+            svg {
+                class: "area-overlay",
+                for points in boundary_points {
+                    polygon { class: "boundary", points: "{points}" }
+                }
+                if drawing {
+                    polyline { class: "boundary draft", points: "{draft_points}" }
+                }
+                for marker in markers {
+                    g {
+                        key: "{marker.address_id}",
+                        circle {
+                            class: if marker.verified { "marker verified" } else { "marker" },
+                            cx: "{marker.x}",
+                            cy: "{marker.y}",
+                            r: "{marker.radius}",
+                        }
+                        text {
+                            x: "{marker.x}",
+                            y: "{marker.y}",
+                            text_anchor: "middle",
+                            dominant_baseline: "central",
+                            "{marker.label}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,9 @@
-use dioxus::{html::g::color, prelude::*};
+use dioxus::prelude::*;
 
-use crate::core::db::Color;
+use crate::core::{
+    db::Color,
+    view::{parse_color_input, ColorInput},
+};
 
 /// A loading spinner component
 #[component]
@@ -18,28 +21,44 @@ pub struct ColorPickerProps {
     pub selected_color: Signal<Color>,
 }
 
+/// Hex color input; the text is left as typed and normalized to `#RRGGBB` on blur.
+/// Incomplete input is only reported once the field loses focus.
 #[component]
 pub fn ColorPicker(mut props: ColorPickerProps) -> Element {
     let mut color_str = use_signal(|| props.selected_color.read().to_hex_string());
-    let computed_color =
-        use_memo(move || Color::from_hex_string(color_str().as_str()).map_err(|e| e.to_string()));
-    use_effect(move || match computed_color() {
-        Ok(col) => {
+    // Whether the field lost focus since the last edit
+    let mut blurred = use_signal(|| false);
+    let computed_color = use_memo(move || parse_color_input(&color_str()));
+    use_effect(move || {
+        if let ColorInput::Valid(col) = computed_color() {
             props.selected_color.set(col);
         }
-        Err(_) => {}
     });
+    let error = match computed_color() {
+        ColorInput::Invalid => Some("Invalid hex color, use #RGB or #RRGGBB"),
+        ColorInput::Incomplete if blurred() => Some("Incomplete hex color, use #RGB or #RRGGBB"),
+        _ => None,
+    };
     rsx! {
         div {
             class: "color-picker",
             input {
-                value: "{color_str().to_ascii_uppercase()}",
-                oninput: move |event| color_str.set(event.value())
+                value: "{color_str}",
+                oninput: move |event| {
+                    color_str.set(event.value());
+                    blurred.set(false);
+                },
+                onblur: move |_| {
+                    if let ColorInput::Valid(col) = computed_color() {
+                        color_str.set(col.to_hex_string());
+                    }
+                    blurred.set(true);
+                },
             }
             span {
                 style: "display: inline-block; width: 24px; height: 24px; border: 1px solid #000; background-color: {props.selected_color.read().to_hex_string()}; margin-left: 8px;",
             }
-            if let Err(e) = computed_color() {
+            if let Some(e) = error {
                 div {
                     class: "error",
                     "{e}"
@@ -1,23 +1,38 @@
 use dioxus::prelude::*;
 use rfd::AsyncFileDialog;
-use crate::Route;
+use crate::{ui::AppError, Route};
 
 /// Home page
 #[component]
 pub fn Home() -> Element {
+    let mut app_error: Signal<Option<AppError>> = use_context();
+
     rsx! {
         div {
             id: "home",
             h1 { "Address Slips" }
+            if let Some(error) = app_error() {
+                div {
+                    class: "error",
+                    h2 { "{error.title}" }
+                    p { "{error.message}" }
+                    button {
+                        onclick: move |_| app_error.set(None),
+                        "Dismiss"
+                    }
+                }
+            }
             p { "Select or create a project to get started." }
             button {
                 onclick: move |_| {
+                    app_error.set(None);
                     spawn(open_path(true));
                 },
                 "Create New Project"
             }
             button {
                 onclick: move |_| {
+                    app_error.set(None);
                     spawn(open_path(false));
                 },
                 "Open Existing Project"
@@ -38,6 +53,10 @@ async fn open_path(create: bool) -> () {
     if let Some(file) = file {
         let path = file.path();
         let encoded_path = urlencoding::encode(path.to_string_lossy().as_ref()).into_owned();
-        navigator().push(Route::ProjectOverview { file: encoded_path });
+        if create {
+            navigator().push(Route::NewProject { file: encoded_path });
+        } else {
+            navigator().push(Route::ProjectOverview { file: encoded_path });
+        }
     }
 }
\ No newline at end of file
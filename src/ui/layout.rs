@@ -1,4 +1,5 @@
 use crate::core::db::{AreaDb, AreaRepository, ProjectDb};
+use crate::ui::AppError;
 use crate::Route;
 use dioxus::prelude::*;
 use std::path::PathBuf;
@@ -9,8 +10,8 @@ use std::sync::Arc;
 pub fn ProjectLayout(file: String) -> Element {
     // Signal to hold the loaded database
     let mut db_signal: Signal<Option<Arc<ProjectDb>>> = use_signal(|| None);
-    // Signal to hold any loading error
-    let mut error_signal: Signal<Option<String>> = use_signal(|| None);
+    // Banner shown on the home page if loading fails
+    let mut app_error: Signal<Option<AppError>> = use_context();
 
     // Provide the signal as context so child components can access it
     use_context_provider(|| db_signal);
@@ -24,34 +25,27 @@ pub fn ProjectLayout(file: String) -> Element {
                 .map(|s| s.into_owned())
                 .unwrap_or(file_path);
             let path = PathBuf::from(decoded);
-            match ProjectDb::new(&path).await {
+            match ProjectDb::open(&path).await {
                 Ok(db) => db_signal.set(Some(Arc::new(db))),
-                Err(e) => error_signal.set(Some(e.to_string())),
+                Err(e) => {
+                    app_error.set(Some(AppError {
+                        title: format!("Could not open {}", path.display()),
+                        message: format!("{:#}", e),
+                    }));
+                    navigator().replace(Route::Home {});
+                }
             }
         }
     });
 
     // Check current state
     let has_db = db_signal.read().is_some();
-    let error = error_signal.read().clone();
 
     if has_db {
         // Database loaded - render provider wrapper with children
         rsx! {
             ProjectContextProvider {}
         }
-    } else if let Some(error_msg) = error {
-        rsx! {
-            div {
-                class: "error",
-                h1 { "Error loading project" }
-                p { "{error_msg}" }
-                Link {
-                    to: Route::Home {},
-                    "Back to Home"
-                }
-            }
-        }
     } else {
         rsx! {
             div {
@@ -91,20 +85,24 @@ pub fn AreaLayout(file: String, area_id: i64) -> Element {
     let mut area_db_signal: Signal<Option<Arc<AreaDb>>> = use_signal(|| None);
     // Signal to hold any loading error
     let mut error_signal: Signal<Option<String>> = use_signal(|| None);
-    let _area_signal = use_resource(move || async move {
+    // Reload whenever another area is selected
+    let _area_signal = use_resource(use_reactive!(|(area_id,)| async move {
+        area_db_signal.set(None);
+        error_signal.set(None);
         let db_c = db_signal.read().clone();
         match db_c.get_area_repo(area_id).await {
             Ok(area_db) => area_db_signal.set(Some(Arc::new(area_db))),
             Err(e) => error_signal.set(Some(e.to_string())),
         }
-    });
+    }));
     // Check current state
     let has_area_db = area_db_signal.read().is_some();
     let error = error_signal.read().clone();
     if has_area_db {
-        // Area database loaded - render provider wrapper with children
+        // Area database loaded - render provider wrapper with children, remounted per area
+        // so its context follows the selection
         rsx! {
-            AreaContextProvider {}
+            AreaContextProvider { key: "{area_id}" }
         }
     } else if let Some(error_msg) = error {
         rsx! {
@@ -113,8 +111,8 @@ pub fn AreaLayout(file: String, area_id: i64) -> Element {
                 h1 { "Error loading area" }
                 p { "{error_msg}" }
                 Link {
-                    to: Route::Home {},
-                    "Back to Home"
+                    to: Route::ProjectOverview { file: file.clone() },
+                    "Back to Project"
                 }
             }
         }
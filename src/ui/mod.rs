@@ -1,5 +1,15 @@
 pub mod components;
 pub mod home;
 pub mod layout;
+pub mod new_project;
 pub mod overview;
 pub mod address_detection;
+pub mod address_table;
+pub mod area_viewer;
+
+/// Message for the error banner on the home page, provided as `Signal<Option<AppError>>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppError {
+    pub title: String,
+    pub message: String,
+}
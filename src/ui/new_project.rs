@@ -0,0 +1,108 @@
+use std::path::PathBuf;
+
+use dioxus::prelude::*;
+
+use crate::{
+    core::{
+        db::{Color, ProjectDb},
+        view::NewProjectForm,
+    },
+    ui::components::ColorPicker,
+    Route,
+};
+
+/// New project form, shown after picking where to save the project
+#[component]
+pub fn NewProject(file: String) -> Element {
+    let mut name = use_signal(String::new);
+    let mut target_address_count = use_signal(String::new);
+    // Without a chosen color every new area gets the most distinct suggestion
+    let mut pick_area_color = use_signal(|| false);
+    let default_area_color = use_signal(|| Color::most_distinct(&[]));
+    let mut create_error: Signal<Option<String>> = use_signal(|| None);
+
+    // URL-decode the path (e.g., %2F -> /, %3A -> :)
+    let path = urlencoding::decode(&file)
+        .map(|s| s.into_owned())
+        .unwrap_or_else(|_| file.clone());
+    let project_path = PathBuf::from(&path);
+
+    rsx! {
+        div {
+            id: "new-project",
+            h1 { "New Project" }
+            p { "File: {path}" }
+            form {
+                onsubmit: move |evt| {
+                    evt.prevent_default();
+                    let form = NewProjectForm {
+                        name: name(),
+                        target_address_count: target_address_count(),
+                        default_area_color: pick_area_color().then(|| default_area_color()),
+                    };
+                    let path = project_path.clone();
+                    let file = file.clone();
+                    async move {
+                        match create_project(path, form).await {
+                            Ok(()) => {
+                                navigator().replace(Route::ProjectOverview { file });
+                            }
+                            Err(e) => create_error.set(Some(e.to_string())),
+                        }
+                    }
+                },
+                label {
+                    "Name"
+                    input {
+                        r#type: "text",
+                        value: "{name()}",
+                        oninput: move |e| name.set(e.value()),
+                    }
+                }
+                label {
+                    "Target address count"
+                    input {
+                        r#type: "number",
+                        min: 0,
+                        value: "{target_address_count()}",
+                        oninput: move |e| target_address_count.set(e.value()),
+                    }
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: pick_area_color(),
+                        onchange: move |event| pick_area_color.set(event.checked()),
+                    }
+                    "Pick a default area color"
+                }
+                if pick_area_color() {
+                    label {
+                        "Default area color"
+                        ColorPicker { selected_color: default_area_color }
+                    }
+                }
+                input { r#type: "submit", value: "Create Project" }
+            }
+            if let Some(error) = create_error() {
+                p {
+                    class: "error",
+                    "{error}"
+                }
+            }
+            Link {
+                to: Route::Home {},
+                "Cancel"
+            }
+        }
+    }
+}
+
+/// Create the project file at `path` with the settings from `form`
+async fn create_project(path: PathBuf, form: NewProjectForm) -> anyhow::Result<()> {
+    // Validate before anything is written to disk
+    form.settings()?;
+    let db = ProjectDb::new(&path).await?;
+    form.apply(&db).await?;
+    db.save_project().await
+}
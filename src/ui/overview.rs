@@ -3,7 +3,7 @@ use std::{path::PathBuf, sync::Arc};
 use dioxus::prelude::*;
 
 use crate::{
-    Route, core::db::{AreaRepository, Color, NewArea, ProjectDb}, ui::components::{ColorPicker, Loading}
+    Route, core::{db::{AreaRepository, Color, NewArea, ProjectDb}, view::default_area_color}, ui::components::{ColorPicker, Loading}
 };
 
 /// Project overview page
@@ -14,7 +14,9 @@ pub fn ProjectOverview(file: String) -> Element {
     //let has_db = db.is_some();
     let mut areas_signal = use_resource(move || async move {
         let db_c = db_signal.read().clone();
-        db_c.get_areas().await.map_err(|e| e.to_string())
+        let areas = db_c.get_areas().await.map_err(|e| e.to_string())?;
+        let default_color = default_area_color(db_c.as_ref()).await.map_err(|e| e.to_string())?;
+        Ok::<_, String>((areas, default_color))
     });
     rsx! {
         div {
@@ -32,19 +34,28 @@ pub fn ProjectOverview(file: String) -> Element {
                         }
                     }
                 },
-                Some(Ok(areas)) => {
+                Some(Ok((areas, default_color))) => {
                     let mut new_area_name = use_signal(|| "".to_string());
-                    let mut new_area_color = use_signal(|| Color::WHITE);
+                    let mut new_area_color = use_signal(|| default_color);
                     let mut new_area_image = use_signal(|| None as Option<PathBuf>);
                     let mut add_error = use_signal(|| None as Option<String>);
                     rsx! {
                         div {
                             h2 { "Areas" }
+                            if areas.is_empty() {
+                                p { "No areas yet" }
+                            }
                             ul {
+                                class: "area-list",
                                 for area in areas {
                                     li {
+                                        key: "{area.id}",
                                         Link {
                                             to: Route::AddressDetection { file: file.clone(), area_id: area.id },
+                                            span {
+                                                class: "area-swatch",
+                                                style: "background-color: {area.color.to_hex_string()};",
+                                            }
                                             "{area.name}"
                                         }
                                     }
@@ -58,12 +69,12 @@ pub fn ProjectOverview(file: String) -> Element {
                                     let new_area_color_val = new_area_color();
                                     let new_area_image_val = new_area_image();
                                     async move {
-                                        if let Err(e) = add_area(db, new_area_name_val, new_area_color_val, new_area_image_val).await {
+                                        if let Err(e) = add_area(db.clone(), new_area_name_val, new_area_color_val, new_area_image_val).await {
                                             add_error.set(Some(e.to_string()));
                                         } else {
-                                            // Clear form on success
+                                            // Clear form on success; the next area gets a color distinct from the new one
                                             new_area_name.set("".to_string());
-                                            new_area_color.set(Color::WHITE);
+                                            new_area_color.set(default_area_color(db.as_ref()).await.unwrap_or(default_color));
                                             new_area_image.set(None);
                                             add_error.set(None);
                                             areas_signal.restart();
@@ -112,8 +123,9 @@ async fn add_area(db: Arc<ProjectDb>, name: String, color: Color, image: Option<
     let image_path = image.ok_or_else(|| anyhow::anyhow!("Image path is required"))?;
     let new_area = NewArea {
         name,
-        color,
+        color: Some(color),
         image_path,
+        reencode: false,
     };
     db.add_area(new_area).await?;
     Ok(())
@@ -1,11 +1,15 @@
 use addrslips::core::db::{Color, NewAddress, NewArea, Point, ProjectDb};
 use image::{ImageBuffer, Rgb};
+use std::path::{Path, PathBuf};
 use tempfile::NamedTempFile;
 
-/// Creates a 100x100 red test image and returns the temp file.
+/// Side length of the square test image; addresses in tests must lie inside it.
+pub const TEST_IMAGE_SIZE: u32 = 100;
+
+/// Creates a `TEST_IMAGE_SIZE` square red test image and returns the temp file.
 /// The file will be automatically cleaned up when dropped.
 pub fn create_test_image() -> NamedTempFile {
-    let img = ImageBuffer::from_fn(100, 100, |_, _| Rgb([255u8, 0u8, 0u8]));
+    let img = ImageBuffer::from_fn(TEST_IMAGE_SIZE, TEST_IMAGE_SIZE, |_, _| Rgb([255u8, 0u8, 0u8]));
     let file = tempfile::Builder::new()
         .suffix(".png")
         .tempfile()
@@ -32,8 +36,9 @@ pub fn make_new_area(name: &str, color: Color) -> (NewArea, NamedTempFile) {
     let img_file = create_test_image();
     let new_area = NewArea {
         name: name.to_string(),
-        color,
+        color: Some(color),
         image_path: img_file.path().to_path_buf(),
+        reencode: false,
     };
     (new_area, img_file)
 }
@@ -52,5 +57,23 @@ pub fn make_test_address(house_number: &str, x: u32, y: u32) -> NewAddress {
         estimated_flats: Some(4),
         circle_radius: 10,
         assigned_street_id: None,
+        circle_color: None,
+    }
+}
+
+/// Paths (relative to the project root) and sizes of all files in a saved project archive.
+pub fn archive_entries(project_path: &Path) -> anyhow::Result<Vec<(PathBuf, u64)>> {
+    let decoder = zstd::stream::read::Decoder::new(std::fs::File::open(project_path)?)?;
+    let mut archive = tar::Archive::new(decoder);
+    let mut entries = Vec::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?;
+        let path = path.strip_prefix(".").unwrap_or(&path).to_path_buf();
+        entries.push((path, entry.header().size()?));
     }
+    Ok(entries)
 }
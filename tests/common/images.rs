@@ -0,0 +1,145 @@
+use image::{DynamicImage, Rgb, RgbImage};
+use imageproc::drawing::{draw_filled_circle_mut, draw_filled_rect_mut, draw_hollow_circle_mut};
+use imageproc::filter::box_filter;
+use imageproc::rect::Rect;
+
+/// Turquoise map background, as in the Aktivisti exports.
+pub const MAP_BACKGROUND: Rgb<u8> = Rgb([64, 200, 190]);
+pub const INK: Rgb<u8> = Rgb([20, 20, 20]);
+pub const PAPER: Rgb<u8> = Rgb([255, 255, 255]);
+
+/// A house-number marker drawn onto a synthetic map.
+#[derive(Debug, Clone, Copy)]
+pub struct Marker {
+    pub x: i32,
+    pub y: i32,
+    pub radius: i32,
+    pub number: &'static str,
+}
+
+/// Creates a map with white, dark-outlined circles carrying house numbers.
+pub fn synthetic_map(width: u32, height: u32, markers: &[Marker]) -> DynamicImage {
+    let mut img = RgbImage::from_pixel(width, height, MAP_BACKGROUND);
+    for marker in markers {
+        draw_marker(&mut img, marker);
+    }
+    DynamicImage::ImageRgb8(img)
+}
+
+/// Draws a single marker: white disc, 2px dark outline, number centered inside.
+pub fn draw_marker(img: &mut RgbImage, marker: &Marker) {
+    draw_filled_circle_mut(img, (marker.x, marker.y), marker.radius, PAPER);
+    draw_hollow_circle_mut(img, (marker.x, marker.y), marker.radius, INK);
+    draw_hollow_circle_mut(img, (marker.x, marker.y), marker.radius - 1, INK);
+    let glyph_height = (marker.radius as u32).max(8);
+    draw_number(img, marker.number, marker.x, marker.y, glyph_height);
+}
+
+/// Renders `text` in black seven-segment digits on a white canvas with a margin.
+pub fn number_image(text: &str, glyph_height: u32) -> DynamicImage {
+    let glyph_width = glyph_height / 2;
+    let spacing = (glyph_height / 4).max(2);
+    let margin = glyph_height / 2;
+    let count = text.chars().count() as u32;
+    let width = count * glyph_width + count.saturating_sub(1) * spacing + 2 * margin;
+    let height = glyph_height + 2 * margin;
+    let mut img = RgbImage::from_pixel(width, height, PAPER);
+    draw_number(
+        &mut img,
+        text,
+        (width / 2) as i32,
+        (height / 2) as i32,
+        glyph_height,
+    );
+    DynamicImage::ImageRgb8(img)
+}
+
+/// Draws `text` centered on (`cx`, `cy`). Non-digit characters leave a gap.
+pub fn draw_number(img: &mut RgbImage, text: &str, cx: i32, cy: i32, glyph_height: u32) {
+    let glyph_width = glyph_height / 2;
+    let spacing = (glyph_height / 4).max(2);
+    let count = text.chars().count() as u32;
+    let total_width = count * glyph_width + count.saturating_sub(1) * spacing;
+    let mut x = cx - total_width as i32 / 2;
+    let y = cy - glyph_height as i32 / 2;
+    for c in text.chars() {
+        draw_digit(img, c, x, y, glyph_width, glyph_height);
+        x += (glyph_width + spacing) as i32;
+    }
+}
+
+/// Draws one seven-segment digit with its top-left corner at (`x`, `y`).
+pub fn draw_digit(img: &mut RgbImage, digit: char, x: i32, y: i32, width: u32, height: u32) {
+    // Segments in the usual a..g order: top, top-right, bottom-right, bottom,
+    // bottom-left, top-left, middle.
+    let segments: &[u8] = match digit {
+        '0' => b"abcdef",
+        '1' => b"bc",
+        '2' => b"abdeg",
+        '3' => b"abcdg",
+        '4' => b"bcfg",
+        '5' => b"acdfg",
+        '6' => b"acdefg",
+        '7' => b"abc",
+        '8' => b"abcdefg",
+        '9' => b"abcdfg",
+        _ => b"",
+    };
+    let t = (height / 7).max(2);
+    let half = height / 2;
+    for &segment in segments {
+        let rect = match segment {
+            b'a' => Rect::at(x, y).of_size(width, t),
+            b'b' => Rect::at(x + (width - t) as i32, y).of_size(t, half),
+            b'c' => Rect::at(x + (width - t) as i32, y + half as i32).of_size(t, height - half),
+            b'd' => Rect::at(x, y + (height - t) as i32).of_size(width, t),
+            b'e' => Rect::at(x, y + half as i32).of_size(t, height - half),
+            b'f' => Rect::at(x, y).of_size(t, half),
+            _ => Rect::at(x, y + (half - t / 2) as i32).of_size(width, t),
+        };
+        draw_filled_rect_mut(img, rect, INK);
+    }
+}
+
+/// Renders one digit of a 3x5 pixel font (with a serifed "1") on a white canvas.
+/// Each font pixel becomes a `cell_w` x `cell_h` block; `soften` box-blurs the edges.
+pub fn pixel_font_image(digit: char, cell_w: u32, cell_h: u32, soften: bool) -> DynamicImage {
+    let rows: [&str; 5] = match digit {
+        '0' => ["###", "# #", "# #", "# #", "###"],
+        '1' => [" # ", "## ", " # ", " # ", "###"],
+        '2' => ["###", "  #", "###", "#  ", "###"],
+        '3' => ["###", "  #", " ##", "  #", "###"],
+        '4' => ["# #", "# #", "###", "  #", "  #"],
+        '5' => ["###", "#  ", "###", "  #", "###"],
+        '6' => ["###", "#  ", "###", "# #", "###"],
+        '7' => ["###", "  #", "  #", "  #", "  #"],
+        '8' => ["###", "# #", "###", "# #", "###"],
+        '9' => ["###", "# #", "###", "  #", "###"],
+        _ => ["   "; 5],
+    };
+    let margin = 6;
+    let mut img = RgbImage::from_pixel(3 * cell_w + 2 * margin, 5 * cell_h + 2 * margin, PAPER);
+    for (row, line) in rows.iter().enumerate() {
+        for (col, c) in line.chars().enumerate() {
+            if c == '#' {
+                let x = (margin + col as u32 * cell_w) as i32;
+                let y = (margin + row as u32 * cell_h) as i32;
+                draw_filled_rect_mut(&mut img, Rect::at(x, y).of_size(cell_w, cell_h), INK);
+            }
+        }
+    }
+    let img = DynamicImage::ImageRgb8(img);
+    if soften {
+        DynamicImage::ImageLuma8(box_filter(&img.to_luma8(), 1, 1))
+    } else {
+        img
+    }
+}
+
+/// Encodes an image as PNG bytes.
+pub fn png_bytes(img: &DynamicImage) -> Vec<u8> {
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    img.write_to(&mut bytes, image::ImageFormat::Png)
+        .expect("Failed to encode PNG");
+    bytes.into_inner()
+}
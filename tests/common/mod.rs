@@ -1,5 +1,7 @@
 mod fixtures;
+mod images;
 pub use fixtures::*;
+pub use images::*;
 
 // Re-export commonly used types from addrslips for tests
 pub use addrslips::core::db::{
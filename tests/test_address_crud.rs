@@ -5,10 +5,20 @@
 //! - Querying addresses by ID and by street
 //! - Updating address fields (verified flag, estimated flats)
 //! - Deleting addresses
+//! - Restoring deleted addresses with their street and team, and purging the trash
+//! - Re-adding a house number whose address was deleted
+//! - Merging duplicate addresses
+//! - Verifying many addresses in one call
+//! - Estimating flats from the marker radius
+//! - Streaming addresses page by page without keeping the project open
+//! - Batch insertion in a single transaction
+//! - Street lookups agreeing between single and batch insertion
+//! - Circle colors surviving save and reopen
 
 mod common;
 
 use common::*;
+use futures::TryStreamExt;
 
 #[tokio::test]
 async fn test_add_address_without_street() -> anyhow::Result<()> {
@@ -18,14 +28,14 @@ async fn test_add_address_without_street() -> anyhow::Result<()> {
     let area_repo = project.add_area(new_area).await?;
 
     // 2. Add address with assigned_street_id = None
-    let new_address = make_test_address("42", 100, 200);
+    let new_address = make_test_address("42", 10, 20);
     let address = AddressRepository::add_address(&area_repo, &new_address).await?;
 
     // 3. Verify address created with correct properties
     assert!(address.id > 0);
     assert_eq!(address.house_number, "42");
-    assert_eq!(address.position.x, 100);
-    assert_eq!(address.position.y, 200);
+    assert_eq!(address.position.x, 10);
+    assert_eq!(address.position.y, 20);
     assert_eq!(address.confidence, 0.95);
     assert_eq!(address.verified, false);
     assert_eq!(address.estimated_flats, Some(4));
@@ -43,7 +53,7 @@ async fn test_add_address_with_street() -> anyhow::Result<()> {
     let street = area_repo.add_street().await?;
 
     // 2. Add address with assigned_street_id = Some(street.id)
-    let mut new_address = make_test_address("123", 300, 400);
+    let mut new_address = make_test_address("123", 30, 40);
     new_address.assigned_street_id = Some(street.id);
     let address = AddressRepository::add_address(&area_repo, &new_address).await?;
 
@@ -65,7 +75,7 @@ async fn test_update_address_verified_flag() -> anyhow::Result<()> {
     let (project, _temp_dir) = create_test_project().await;
     let (new_area, _img_file) = make_new_area("Test Area", TEST_GREEN);
     let area_repo = project.add_area(new_area).await?;
-    let new_address = make_test_address("99", 500, 600);
+    let new_address = make_test_address("99", 50, 60);
     let address = AddressRepository::add_address(&area_repo, &new_address).await?;
 
     assert_eq!(address.verified, false);
@@ -96,7 +106,7 @@ async fn test_delete_address() -> anyhow::Result<()> {
     let (project, _temp_dir): (ProjectDb, _) = create_test_project().await;
     let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
     let area_repo: AreaDb = project.add_area(new_area).await?;
-    let new_address = make_test_address("77", 700, 800);
+    let new_address = make_test_address("77", 70, 80);
     let address: Address = AddressRepository::add_address(&area_repo, &new_address).await?;
     let address_id = address.id;
 
@@ -116,3 +126,403 @@ async fn test_delete_address() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_add_addresses_batch_in_order() -> anyhow::Result<()> {
+    // 1. Create area
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+
+    // 2. Insert 50 addresses in one batch
+    let batch: Vec<NewAddress> = (0..50)
+        .map(|i| make_test_address(&i.to_string(), i * 2, i))
+        .collect();
+    let inserted = AddressRepository::add_addresses(&area_repo, &batch).await?;
+
+    // 3. Verify all were stored, returned in input order
+    assert_eq!(inserted.len(), 50);
+    for (i, address) in inserted.iter().enumerate() {
+        assert_eq!(address.house_number, i.to_string());
+    }
+    assert_eq!(AddressRepository::get_addresses(&area_repo).await?, inserted);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_add_addresses_rolls_back_on_error() -> anyhow::Result<()> {
+    // 1. Create area
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+
+    // 2. Build 50 addresses, the 26th violating the estimated_flats constraint
+    let mut batch: Vec<NewAddress> = (0..50)
+        .map(|i| make_test_address(&i.to_string(), i * 2, i))
+        .collect();
+    batch[25].estimated_flats = Some(0);
+
+    // 3. Verify the batch fails and nothing was stored
+    let result = AddressRepository::add_addresses(&area_repo, &batch).await;
+    assert!(result.is_err());
+    assert!(AddressRepository::get_addresses(&area_repo).await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_street_lookup_consistent_after_insert() -> anyhow::Result<()> {
+    // 1. Create two areas, each with one street
+    let (project, _temp_dir) = create_test_project().await;
+    let (single_area, _img_a) = make_new_area("Single", TEST_RED);
+    let (batch_area, _img_b) = make_new_area("Batch", TEST_BLUE);
+    let single_repo = project.add_area(single_area).await?;
+    let batch_repo = project.add_area(batch_area).await?;
+    let single_street = single_repo.add_street().await?;
+    let batch_street = batch_repo.add_street().await?;
+
+    // 2. Insert the same addresses one by one and as a batch
+    let addresses = |street_id: i64| -> Vec<NewAddress> {
+        ["1", "2", "3"]
+            .iter()
+            .enumerate()
+            .map(|(i, number)| NewAddress {
+                // Only every other address is on the street
+                assigned_street_id: (i % 2 == 0).then_some(street_id),
+                ..make_test_address(number, i as u32 * 10, 0)
+            })
+            .collect()
+    };
+    for address in addresses(single_street.id) {
+        AddressRepository::add_address(&single_repo, &address).await?;
+    }
+    AddressRepository::add_addresses(&batch_repo, &addresses(batch_street.id)).await?;
+
+    // 3. Verify the street lookup sees exactly the assigned addresses in both cases
+    let on_street = |found: Vec<Address>| -> Vec<String> {
+        found.into_iter().map(|a| a.house_number).collect()
+    };
+    let single = on_street(AddressRepository::get_address_by_street(&single_repo, &single_street).await?);
+    let batch = on_street(AddressRepository::get_address_by_street(&batch_repo, &batch_street).await?);
+    assert_eq!(single, vec!["1", "3"]);
+    assert_eq!(single, batch);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_soft_delete_and_restore() -> anyhow::Result<()> {
+    // 1. Add an address on a street and assign it to a team
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let street = area_repo.add_street().await?;
+    let mut new_address = make_test_address("5", 60, 80);
+    new_address.assigned_street_id = Some(street.id);
+    let address = AddressRepository::add_address(&area_repo, &new_address).await?;
+    let team = area_repo.add_team().await?;
+    TeamRepository::add_address(&area_repo, &team, &address).await?;
+
+    // 2. Delete moves it to the trash and detaches it from the team
+    area_repo.delete_address(address.clone()).await?;
+    assert!(area_repo.get_addresses().await?.is_empty());
+    assert!(area_repo.get_address_by_street(&street).await?.is_empty());
+    assert!(area_repo.get_team_addresses(&team).await?.is_empty());
+    assert_eq!(area_repo.get_deleted_addresses().await?, vec![address.clone()]);
+
+    // 3. Restore brings it back with its street and team
+    let restored = area_repo.restore_address(address.id).await?;
+    assert_eq!(restored, address);
+    assert_eq!(restored.assigned_street_id, Some(street.id));
+    assert_eq!(area_repo.get_addresses().await?, vec![address.clone()]);
+    let team_addresses = area_repo.get_team_addresses(&team).await?;
+    assert_eq!(team_addresses.len(), 1);
+    assert_eq!(team_addresses[0].address_id, address.id);
+    assert!(area_repo.get_deleted_addresses().await?.is_empty());
+
+    // 4. Restoring an address that is not in the trash fails
+    assert!(area_repo.restore_address(address.id).await.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_readd_deleted_house_number() -> anyhow::Result<()> {
+    // 1. Add an address on a street and delete it
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let street = area_repo.add_street().await?;
+    let mut new_address = make_test_address("5", 60, 80);
+    new_address.assigned_street_id = Some(street.id);
+    let deleted = AddressRepository::add_address(&area_repo, &new_address).await?;
+    area_repo.delete_address(deleted.clone()).await?;
+
+    // 2. The same number can be added to the street again
+    let readded = AddressRepository::add_address(&area_repo, &new_address).await?;
+    assert_ne!(readded.id, deleted.id);
+    assert_eq!(area_repo.get_address_by_street(&street).await?, vec![readded.clone()]);
+
+    // 3. A second live address with that number is still rejected
+    assert!(AddressRepository::add_address(&area_repo, &new_address).await.is_err());
+
+    // 4. So is restoring the deleted one while the number is taken
+    assert!(area_repo.restore_address(deleted.id).await.is_err());
+    assert_eq!(area_repo.get_deleted_addresses().await?, vec![deleted]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_merge_addresses() -> anyhow::Result<()> {
+    // 1. A detected address and its unverified duplicate, which is on a team and has a flat estimate
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let street = area_repo.add_street().await?;
+    let mut kept = make_test_address("7", 70, 40);
+    kept.assigned_street_id = Some(street.id);
+    kept.confidence = 0.6;
+    let kept = AddressRepository::add_address(&area_repo, &kept).await?;
+    let mut duplicate = make_test_address("7a", 80, 50);
+    duplicate.confidence = 0.9;
+    duplicate.estimated_flats = Some(4);
+    let duplicate = AddressRepository::add_address(&area_repo, &duplicate).await?;
+    let team = area_repo.add_team().await?;
+    TeamRepository::add_address(&area_repo, &team, &duplicate).await?;
+
+    // 2. Merging keeps the house number, averages the position and takes over the rest
+    let merged = area_repo.merge_addresses(kept.id, duplicate.id).await?;
+    assert_eq!(merged.id, kept.id);
+    assert_eq!(merged.house_number, "7");
+    assert_eq!(merged.position, Point { x: 75, y: 45 });
+    assert_eq!(merged.assigned_street_id, Some(street.id));
+    assert_eq!(merged.confidence, 0.9);
+    assert_eq!(merged.estimated_flats, Some(4));
+    assert_eq!(area_repo.get_addresses().await?, vec![merged.clone()]);
+    let team_addresses = area_repo.get_team_addresses(&team).await?;
+    assert_eq!(team_addresses.len(), 1);
+    assert_eq!(team_addresses[0].address_id, kept.id);
+
+    // 3. The duplicate is gone for good, not in the trash
+    assert_eq!(area_repo.get_address_by_id(duplicate.id).await?, None);
+    assert!(area_repo.get_deleted_addresses().await?.is_empty());
+    assert!(area_repo.merge_addresses(kept.id, duplicate.id).await.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_merge_addresses_rejects_mismatches() -> anyhow::Result<()> {
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let (other_area, _other_img_file) = make_new_area("Other Area", TEST_BLUE);
+    let other_repo = project.add_area(other_area).await?;
+
+    // 1. Addresses on different streets
+    let first_street = area_repo.add_street().await?;
+    let second_street = area_repo.add_street().await?;
+    let mut a = make_test_address("1", 10, 10);
+    a.assigned_street_id = Some(first_street.id);
+    let a = AddressRepository::add_address(&area_repo, &a).await?;
+    let mut b = make_test_address("1", 12, 10);
+    b.assigned_street_id = Some(second_street.id);
+    let b = AddressRepository::add_address(&area_repo, &b).await?;
+    let err = area_repo.merge_addresses(a.id, b.id).await.unwrap_err();
+    assert!(err.to_string().contains("different streets"), "Unexpected error: {err}");
+
+    // 2. An address from another area
+    let foreign = AddressRepository::add_address(&other_repo, &make_test_address("1", 10, 10)).await?;
+    let err = area_repo.merge_addresses(a.id, foreign.id).await.unwrap_err();
+    assert!(err.to_string().contains("from area"), "Unexpected error: {err}");
+
+    // 3. Nothing changed
+    assert_eq!(area_repo.get_addresses().await?, vec![a, b]);
+    assert_eq!(other_repo.get_addresses().await?, vec![foreign]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_purge_deleted() -> anyhow::Result<()> {
+    // 1. Add two addresses and delete one
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let kept = AddressRepository::add_address(&area_repo, &make_test_address("1", 10, 10)).await?;
+    let deleted = AddressRepository::add_address(&area_repo, &make_test_address("2", 20, 20)).await?;
+    let deleted_id = deleted.id;
+    area_repo.delete_address(deleted).await?;
+
+    // 2. Purge removes only the deleted one, for good
+    assert_eq!(area_repo.purge_deleted().await?, 1);
+    assert!(area_repo.get_deleted_addresses().await?.is_empty());
+    assert!(area_repo.restore_address(deleted_id).await.is_err());
+    assert_eq!(area_repo.get_addresses().await?, vec![kept]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_set_verified_bulk() -> anyhow::Result<()> {
+    // 1. Add ten unverified addresses
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let batch: Vec<NewAddress> = (0..10).map(|i| make_test_address(&i.to_string(), 10 * i, 10)).collect();
+    let addresses = area_repo.add_addresses(&batch).await?;
+
+    // 2. Verify every other one in one call
+    let ids: Vec<i64> = addresses.iter().step_by(2).map(|a| a.id).collect();
+    assert_eq!(area_repo.set_verified_bulk(&ids, true).await?, 5);
+
+    // 3. Exactly those five are verified
+    let verified: Vec<i64> = area_repo
+        .get_addresses()
+        .await?
+        .into_iter()
+        .filter(|a| a.verified)
+        .map(|a| a.id)
+        .collect();
+    assert_eq!(verified, ids);
+
+    // 4. An empty list is a no-op
+    assert_eq!(area_repo.set_verified_bulk(&[], false).await?, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_estimate_flats_from_radius() -> anyhow::Result<()> {
+    // 1. Add three addresses without estimates and one with a manual value
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let mut batch = Vec::new();
+    for (number, radius) in [("1", 8), ("2", 15), ("3", 30)] {
+        let mut new_address = make_test_address(number, 10, 10);
+        new_address.circle_radius = radius;
+        new_address.estimated_flats = None;
+        batch.push(new_address);
+    }
+    let mut manual = make_test_address("4", 20, 20);
+    manual.circle_radius = 30;
+    manual.estimated_flats = Some(2);
+    batch.push(manual);
+    area_repo.add_addresses(&batch).await?;
+
+    // 2. Estimate with small = 10, large = 20
+    assert_eq!(area_repo.estimate_flats_from_radius(10, 20).await?, 3);
+
+    // 3. Radii map to 1 / 4 / 8 flats; the manual value is kept
+    let flats: Vec<Option<u16>> = area_repo
+        .get_addresses()
+        .await?
+        .into_iter()
+        .map(|a| a.estimated_flats)
+        .collect();
+    assert_eq!(flats, vec![Some(1), Some(4), Some(8), Some(2)]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_addresses_stream_matches_get_addresses() -> anyhow::Result<()> {
+    // 1. Add more addresses than the stream buffers at once, and trash one
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let batch: Vec<NewAddress> = (0..150).map(|i| make_test_address(&i.to_string(), i % 100, (2 * i) % 100)).collect();
+    let mut addresses = area_repo.add_addresses(&batch).await?;
+    area_repo.delete_address(addresses.remove(7)).await?;
+
+    // 2. Collect the stream
+    let streamed: Vec<Address> = area_repo.addresses_stream().try_collect().await?;
+
+    // 3. Verify it matches the eager query
+    assert_eq!(streamed, area_repo.get_addresses().await?);
+    assert_eq!(streamed, addresses);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_addresses_stream_does_not_keep_project_open() -> anyhow::Result<()> {
+    // 1. Start streaming more addresses than fit in one page
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let batch: Vec<NewAddress> = (0..100).map(|i| make_test_address(&i.to_string(), i, i)).collect();
+    area_repo.add_addresses(&batch).await?;
+    let mut stream = Box::pin(area_repo.addresses_stream());
+    assert_eq!(stream.try_next().await?.expect("first address").house_number, "0");
+
+    // 2. Dropping the project closes it even though the stream is still alive
+    drop(area_repo);
+    drop(project);
+
+    // 3. The buffered page drains, then the stream reports the closed project and ends
+    let mut streamed = 1;
+    let err = loop {
+        match stream.try_next().await {
+            Ok(Some(_)) => streamed += 1,
+            Ok(None) => panic!("stream ended without reporting the closed project"),
+            Err(e) => break e,
+        }
+    };
+    assert!(streamed < 100, "all {streamed} addresses streamed from a closed project");
+    assert!(err.to_string().contains("closed"), "unexpected error: {err}");
+    assert!(stream.try_next().await?.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_circle_color_round_trips_through_save() -> anyhow::Result<()> {
+    // 1. Create area with a blue-circled address and an uncolored one
+    let (project, temp_dir) = create_test_project().await;
+    let project_path = temp_dir.path().join("test.addrslips");
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let area_id = area_repo.get_area().await?.id;
+    let blue = AddressRepository::add_address(
+        &area_repo,
+        &NewAddress {
+            circle_color: Some(TEST_BLUE),
+            ..make_test_address("1", 10, 10)
+        },
+    )
+    .await?;
+    let plain = AddressRepository::add_address(&area_repo, &make_test_address("2", 20, 20)).await?;
+    assert_eq!(blue.circle_color, Some(TEST_BLUE));
+    assert_eq!(plain.circle_color, None);
+
+    // 2. Save and reopen the project
+    project.save_project().await?;
+    drop(area_repo);
+    drop(project);
+    let project = ProjectDb::open(&project_path).await?;
+    let area_repo = project.get_area_repo(area_id).await?;
+
+    // 3. Both colors came back unchanged
+    let blue = area_repo.get_address_by_id(blue.id).await?.expect("address saved");
+    let plain = area_repo.get_address_by_id(plain.id).await?.expect("address saved");
+    assert_eq!(blue.circle_color, Some(TEST_BLUE));
+    assert_eq!(plain.circle_color, None);
+
+    // 4. Updating can clear the color again
+    let cleared = area_repo
+        .update_address(
+            &blue,
+            &AddressUpdate {
+                circle_color: Some(None),
+                ..Default::default()
+            },
+        )
+        .await?;
+    assert_eq!(cleared.circle_color, None);
+
+    Ok(())
+}
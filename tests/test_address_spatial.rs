@@ -0,0 +1,161 @@
+//! Integration tests for spatial address queries.
+//!
+//! Tests cover:
+//! - Addresses inside a bounding box
+//! - Addresses within a radius of a point
+//! - Neighbours of an address and close pairs matching a brute-force reference
+
+mod common;
+
+use std::collections::BTreeSet;
+
+use common::*;
+
+/// 10x10 grid with 10px spacing, house number "x,y"
+async fn grid_area(project: &ProjectDb) -> anyhow::Result<AreaDb> {
+    let (new_area, _img_file) = make_new_area("Grid", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let batch: Vec<NewAddress> = (0..10)
+        .flat_map(|gx| (0..10).map(move |gy| (gx * 10, gy * 10)))
+        .map(|(x, y)| make_test_address(&format!("{x},{y}"), x, y))
+        .collect();
+    AddressRepository::add_addresses(&area_repo, &batch).await?;
+    Ok(area_repo)
+}
+
+fn numbers(addresses: &[Address]) -> BTreeSet<String> {
+    addresses.iter().map(|a| a.house_number.clone()).collect()
+}
+
+#[tokio::test]
+async fn test_addresses_within_bbox() -> anyhow::Result<()> {
+    // 1. Create grid
+    let (project, _temp_dir) = create_test_project().await;
+    let area_repo = grid_area(&project).await?;
+
+    // 2. Query a box covering x 15..=40, y 20..=30
+    let found = AddressRepository::get_addresses_within_bbox(
+        &area_repo,
+        &Point { x: 15, y: 20 },
+        &Point { x: 40, y: 30 },
+    )
+    .await?;
+
+    // 3. Verify exactly the grid points inside the box (edges inclusive)
+    let expected: BTreeSet<String> = [20, 30, 40]
+        .iter()
+        .flat_map(|x| [20, 30].map(|y| format!("{x},{y}")))
+        .collect();
+    assert_eq!(numbers(&found), expected);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_addresses_within_radius() -> anyhow::Result<()> {
+    // 1. Create grid
+    let (project, _temp_dir) = create_test_project().await;
+    let area_repo = grid_area(&project).await?;
+
+    // 2. Query radius 10 around (50, 50)
+    let found =
+        AddressRepository::get_addresses_within_radius(&area_repo, &Point { x: 50, y: 50 }, 10).await?;
+
+    // 3. Verify the center and its four direct neighbors, not the diagonals (~14px away)
+    let expected: BTreeSet<String> = ["50,50", "40,50", "60,50", "50,40", "50,60"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    assert_eq!(numbers(&found), expected);
+
+    Ok(())
+}
+
+/// Clusters of close addresses around a few centers plus scattered ones, from a fixed seed
+async fn clustered_area(project: &ProjectDb) -> anyhow::Result<AreaDb> {
+    let (new_area, _img_file) = make_new_area("Clusters", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let mut seed: u32 = 12345;
+    let mut next = |range: u32| {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        (seed >> 16) % range
+    };
+    let mut batch = Vec::new();
+    for (cx, cy) in [(20, 20), (70, 30), (40, 80)] {
+        for _ in 0..12 {
+            batch.push((cx + next(13) - 6, cy + next(13) - 6));
+        }
+    }
+    for _ in 0..20 {
+        batch.push((next(100), next(100)));
+    }
+    let batch: Vec<NewAddress> = batch
+        .iter()
+        .enumerate()
+        .map(|(i, &(x, y))| make_test_address(&i.to_string(), x, y))
+        .collect();
+    AddressRepository::add_addresses(&area_repo, &batch).await?;
+    Ok(area_repo)
+}
+
+/// Every pair of addresses within `distance` of each other, checked one by one
+fn brute_force_pairs(addresses: &[Address], distance: u32) -> Vec<(i64, i64)> {
+    let mut pairs = Vec::new();
+    for a in addresses {
+        for b in addresses {
+            let dx = a.position.x.abs_diff(b.position.x) as u64;
+            let dy = a.position.y.abs_diff(b.position.y) as u64;
+            if a.id < b.id && dx * dx + dy * dy <= (distance as u64).pow(2) {
+                pairs.push((a.id, b.id));
+            }
+        }
+    }
+    pairs.sort();
+    pairs
+}
+
+#[tokio::test]
+async fn test_all_duplicate_pairs_match_brute_force() -> anyhow::Result<()> {
+    // 1. Create clustered addresses
+    let (project, _temp_dir) = create_test_project().await;
+    let area_repo = clustered_area(&project).await?;
+    let addresses = AddressRepository::get_addresses(&area_repo).await?;
+
+    // 2. Compare with the O(n²) reference at several distances, including 0
+    for distance in [0, 1, 3, 5, 10] {
+        let pairs = AddressRepository::all_duplicate_pairs(&area_repo, distance).await?;
+        assert_eq!(pairs, brute_force_pairs(&addresses, distance), "distance {distance}");
+    }
+    assert!(!AddressRepository::all_duplicate_pairs(&area_repo, 5).await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_neighbors_within() -> anyhow::Result<()> {
+    // 1. Create clustered addresses
+    let (project, _temp_dir) = create_test_project().await;
+    let area_repo = clustered_area(&project).await?;
+    let addresses = AddressRepository::get_addresses(&area_repo).await?;
+
+    // 2. The neighbours of each address are its partners in the close pairs
+    let pairs = brute_force_pairs(&addresses, 5);
+    for address in &addresses {
+        let expected: Vec<i64> = addresses
+            .iter()
+            .map(|other| other.id)
+            .filter(|&other| pairs.contains(&(address.id.min(other), address.id.max(other))))
+            .collect();
+        let neighbors = AddressRepository::neighbors_within(&area_repo, address.id, 5).await?;
+        assert_eq!(neighbors, expected, "neighbours of address {}", address.id);
+    }
+
+    // 3. Deleted addresses are neither neighbours nor looked up
+    let (first, second) = pairs[0];
+    let removed = AddressRepository::get_address_by_id(&area_repo, second).await?.unwrap();
+    area_repo.delete_address(removed).await?;
+    assert!(!AddressRepository::neighbors_within(&area_repo, first, 5).await?.contains(&second));
+    assert!(AddressRepository::neighbors_within(&area_repo, second, 5).await.is_err());
+
+    Ok(())
+}
@@ -4,13 +4,24 @@
 //! - Creating areas with images
 //! - Retrieving areas by ID and listing all areas
 //! - Updating area metadata (state)
+//! - Validated workflow state transitions
 //! - Deleting areas
 //! - Area persistence through save/load cycles
+//! - Save-on-drop inside an async runtime
+//! - Saving projects with large images
+//! - Loading several large area images concurrently
+//! - Skipping saves when nothing changed
+//! - Replacing an area's image
+//! - Re-encoding stored area images
+//! - Cached area thumbnails, one per size
+//! - Duplicating an area with its addresses and streets
+//! - Suggesting distinct colors for new areas, and using them when no color is given
 
 mod common;
 
 // Import traits to bring methods into scope
-use addrslips::core::db::{AreaRepository, BoundAreaRepository};
+use addrslips::core::db::{AreaRepository, BoundAreaRepository, AREA_PALETTE};
+use image::GenericImageView;
 
 use common::*;
 
@@ -68,6 +79,30 @@ async fn test_update_area_state() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_advance_state_validates_transitions() -> anyhow::Result<()> {
+    // 1. Create area in Imported state
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_BLUE);
+    let area_repo = project.add_area(new_area).await?;
+
+    // 2. Jumping straight to Complete is rejected and leaves the state alone
+    let err = area_repo.advance_state(AreaState::Complete).await.unwrap_err();
+    assert!(err.to_string().contains("Complete"), "Unexpected error: {err}");
+    assert_eq!(area_repo.get_area().await?.state, AreaState::Imported);
+
+    // 3. One step forward and back is allowed
+    let area = area_repo.advance_state(AreaState::AddressesDetected).await?;
+    assert_eq!(area.state, AreaState::AddressesDetected);
+    let area = area_repo.advance_state(AreaState::Imported).await?;
+    assert_eq!(area.state, AreaState::Imported);
+
+    // 4. Staying in place is not a transition
+    assert!(area_repo.advance_state(AreaState::Imported).await.is_err());
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_delete_area() -> anyhow::Result<()> {
     // 1. Create area
@@ -89,7 +124,8 @@ async fn test_delete_area() -> anyhow::Result<()> {
 
     // Attempting to get deleted area should fail
     let result: anyhow::Result<AreaDb> = project.get_area_repo(area_id).await;
-    assert!(result.is_err(), "Getting deleted area should fail");
+    let error = result.err().expect("Getting deleted area should fail");
+    assert_eq!(error.to_string(), format!("Area with id {} not found", area_id));
 
     Ok(())
 }
@@ -110,7 +146,7 @@ async fn test_area_persists_after_save() -> anyhow::Result<()> {
         assert_eq!(areas.len(), 1);
         assert_eq!(areas[0].name, "Persistent Area");
 
-        // Explicitly save before dropping (required in async context)
+        // Explicitly save before dropping (preferred over save-on-drop)
         project.save_project().await?;
     } // Drop project
 
@@ -128,9 +164,460 @@ async fn test_area_persists_after_save() -> anyhow::Result<()> {
         // 4. Verify image is still accessible
         let area_repo: AreaDb = project.get_area_repo(areas[0].id).await?;
         let image = area_repo.get_image();
-        assert_eq!(image.width(), 100);
-        assert_eq!(image.height(), 100);
+        assert_eq!(image.width(), TEST_IMAGE_SIZE);
+        assert_eq!(image.height(), TEST_IMAGE_SIZE);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_area_persists_after_drop_in_runtime() -> anyhow::Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let project_path = temp_dir.path().join("drop_test.addrslips");
+
+    // 1. Create project and add area, then drop without saving
+    let created_at;
+    {
+        let project: ProjectDb = ProjectDb::new(&project_path).await?;
+        created_at = std::fs::metadata(&project_path)?.modified()?;
+        let (new_area, _img_file) = make_new_area("Dropped Area", TEST_GREEN);
+        let area_repo: AreaDb = project.add_area(new_area).await?;
+        drop(area_repo);
+    } // Drop project inside the Tokio runtime; this must not wait for the save
+
+    // 2. Saving on drop is best-effort and runs in the background; wait for the archive
+    // to be replaced (it is renamed into place, so it is never seen half-written)
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(30);
+    while std::fs::metadata(&project_path)?.modified()? == created_at {
+        assert!(std::time::Instant::now() < deadline, "project was not saved after drop");
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
     }
 
+    // 3. Reopen project and verify the area was saved on drop
+    let project: ProjectDb = ProjectDb::new(&project_path).await?;
+    let areas: Vec<Area> = project.get_areas().await?;
+    assert_eq!(areas.len(), 1);
+    assert_eq!(areas[0].name, "Dropped Area");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_concurrent_large_image_loads() -> anyhow::Result<()> {
+    let (project, temp_dir) = create_test_project().await;
+
+    // 1. Add four areas with distinct noisy 1500x1500 PNGs
+    let mut sources = Vec::new();
+    let mut area_ids = Vec::new();
+    for i in 0..4u32 {
+        let mut seed: u32 = 12345 + i;
+        let noise = image::RgbImage::from_fn(1500, 1500, |_, _| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let [r, g, b, _] = seed.to_le_bytes();
+            image::Rgb([r, g, b])
+        });
+        let img_path = temp_dir.path().join(format!("large_{}.png", i));
+        noise.save(&img_path)?;
+        let area_repo = project
+            .add_area(NewArea {
+                name: format!("Large Area {}", i),
+                color: Some(TEST_BLUE),
+                image_path: img_path,
+                reencode: false,
+            })
+            .await?;
+        area_ids.push(area_repo.get_area().await?.id);
+        sources.push(noise);
+    }
+
+    // 2. Load them one after the other
+    let start = std::time::Instant::now();
+    for &id in &area_ids {
+        project.get_area_repo(id).await?;
+    }
+    let sequential = start.elapsed();
+
+    // 3. Load them all at once; every image decodes correctly
+    let start = std::time::Instant::now();
+    let repos = futures::future::join_all(area_ids.iter().map(|&id| project.get_area_repo(id))).await;
+    let parallel = start.elapsed();
+    for (repo, source) in repos.into_iter().zip(&sources) {
+        assert_eq!(&repo?.get_image().to_rgb8(), source);
+    }
+
+    // 4. With more than one core the decodes overlap instead of queueing up
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    if cores > 1 {
+        assert!(
+            parallel < sequential,
+            "concurrent loads took {:?}, sequential loads {:?}",
+            parallel,
+            sequential
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_save_project_with_large_image() -> anyhow::Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let project_path = temp_dir.path().join("large_test.addrslips");
+
+    // 1. Write a noisy 1500x1500 PNG (several MB, barely compressible)
+    let mut seed: u32 = 12345;
+    let noise = image::RgbImage::from_fn(1500, 1500, |_, _| {
+        seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        let [r, g, b, _] = seed.to_le_bytes();
+        image::Rgb([r, g, b])
+    });
+    let img_path = temp_dir.path().join("large.png");
+    noise.save(&img_path)?;
+    assert!(std::fs::metadata(&img_path)?.len() > 2_000_000);
+
+    // 2. Add it as an area and save
+    {
+        let project: ProjectDb = ProjectDb::new(&project_path).await?;
+        project
+            .add_area(NewArea {
+                name: "Large Area".to_string(),
+                color: Some(TEST_BLUE),
+                image_path: img_path.clone(),
+                reencode: false,
+            })
+            .await?;
+        project.save_project().await?;
+    }
+
+    // 3. Verify the archive reopens with the full image
+    let project: ProjectDb = ProjectDb::new(&project_path).await?;
+    let areas: Vec<Area> = project.get_areas().await?;
+    assert_eq!(areas.len(), 1);
+    let area_repo: AreaDb = project.get_area_repo(areas[0].id).await?;
+    assert_eq!(area_repo.get_image().width(), 1500);
+    assert_eq!(area_repo.get_image().height(), 1500);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_save_if_dirty_skips_unchanged_project() -> anyhow::Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let project_path = temp_dir.path().join("dirty_test.addrslips");
+    let project: ProjectDb = ProjectDb::new(&project_path).await?;
+
+    // 1. A fresh project has unsaved changes (migrations)
+    assert!(project.save_if_dirty().await?);
+    let first_write = std::fs::metadata(&project_path)?.modified()?;
+
+    // 2. Nothing changed since, so the archive is left alone
+    assert!(!project.save_if_dirty().await?);
+    assert_eq!(std::fs::metadata(&project_path)?.modified()?, first_write);
+
+    // 3. A mutation makes the next call save again
+    let (new_area, _img_file) = make_new_area("Dirty Area", TEST_RED);
+    project.add_area(new_area).await?;
+    assert!(project.save_if_dirty().await?);
+    assert!(!project.save_if_dirty().await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_replace_image_persists() -> anyhow::Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let project_path = temp_dir.path().join("replace_test.addrslips");
+
+    // 1. Create area with the test image and one address
+    let area_id = {
+        let project: ProjectDb = ProjectDb::new(&project_path).await?;
+        let (new_area, _img_file) = make_new_area("Rescanned Area", TEST_RED);
+        let mut area_repo: AreaDb = project.add_area(new_area).await?;
+        AddressRepository::add_address(&area_repo, &make_test_address("1", 10, 10)).await?;
+        assert_eq!(area_repo.get_image().dimensions(), (TEST_IMAGE_SIZE, TEST_IMAGE_SIZE));
+
+        // 2. Replace it with a 200x150 scan
+        let new_scan = temp_dir.path().join("rescan.png");
+        image::RgbImage::from_pixel(200, 150, image::Rgb([0, 0, 255])).save(&new_scan)?;
+        area_repo.replace_image(&new_scan).await?;
+        assert_eq!(area_repo.get_image().dimensions(), (200, 150));
+
+        project.save_project().await?;
+        area_repo.get_area().await?.id
+    };
+
+    // 3. Reopen and verify the new image and the existing address
+    let project: ProjectDb = ProjectDb::new(&project_path).await?;
+    let area_repo: AreaDb = project.get_area_repo(area_id).await?;
+    assert_eq!(area_repo.get_image().dimensions(), (200, 150));
+    assert_eq!(AddressRepository::get_addresses(&area_repo).await?.len(), 1);
+
+    Ok(())
+}
+
+/// Names and sizes of the area images stored in a saved project archive.
+fn stored_images(project_path: &std::path::Path) -> anyhow::Result<Vec<(String, u64)>> {
+    Ok(archive_entries(project_path)?
+        .into_iter()
+        .filter(|(path, _)| path.parent().is_some_and(|dir| dir.ends_with("images")))
+        .map(|(path, size)| (path.file_name().unwrap().to_string_lossy().into_owned(), size))
+        .collect())
+}
+
+/// Smooth gradient that compresses well as PNG but not as raw pixels.
+fn gradient_image(width: u32, height: u32) -> image::RgbImage {
+    image::RgbImage::from_fn(width, height, |x, y| {
+        image::Rgb([(x % 256) as u8, (y % 256) as u8, ((x + y) / 8 % 256) as u8])
+    })
+}
+
+#[tokio::test]
+async fn test_reencode_lossless_image_as_png() -> anyhow::Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let project_path = temp_dir.path().join("reencode_png.addrslips");
+
+    // 1. Write an uncompressed BMP
+    let source = gradient_image(1200, 900);
+    let img_path = temp_dir.path().join("scan.bmp");
+    source.save(&img_path)?;
+    let source_size = std::fs::metadata(&img_path)?.len();
+
+    // 2. Add it re-encoded and save
+    {
+        let project: ProjectDb = ProjectDb::new(&project_path).await?;
+        project
+            .add_area(NewArea {
+                name: "Re-encoded Area".to_string(),
+                color: Some(TEST_GREEN),
+                image_path: img_path.clone(),
+                reencode: true,
+            })
+            .await?;
+        project.save_project().await?;
+    }
+
+    // 3. The stored file is a smaller PNG
+    let images = stored_images(&project_path)?;
+    assert_eq!(images.len(), 1);
+    assert!(images[0].0.ends_with(".png"));
+    assert!(images[0].1 < source_size);
+
+    // 4. Dimensions, color type and pixels are unchanged
+    let project: ProjectDb = ProjectDb::new(&project_path).await?;
+    let areas: Vec<Area> = project.get_areas().await?;
+    let area_repo: AreaDb = project.get_area_repo(areas[0].id).await?;
+    let stored = area_repo.get_image();
+    assert_eq!(stored.color(), image::ColorType::Rgb8);
+    assert_eq!(stored.to_rgb8(), source);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reencode_jpeg_image() -> anyhow::Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let project_path = temp_dir.path().join("reencode_jpeg.addrslips");
+
+    // 1. Write a maximum quality JPEG
+    let source = gradient_image(1200, 900);
+    let img_path = temp_dir.path().join("scan.jpg");
+    let writer = std::io::BufWriter::new(std::fs::File::create(&img_path)?);
+    source.write_with_encoder(image::codecs::jpeg::JpegEncoder::new_with_quality(writer, 100))?;
+    let source_size = std::fs::metadata(&img_path)?.len();
+
+    // 2. Add it re-encoded and save
+    {
+        let project: ProjectDb = ProjectDb::new(&project_path).await?;
+        project
+            .add_area(NewArea {
+                name: "Re-encoded Area".to_string(),
+                color: Some(TEST_GREEN),
+                image_path: img_path.clone(),
+                reencode: true,
+            })
+            .await?;
+        project.save_project().await?;
+    }
+
+    // 3. The stored file is a smaller JPEG
+    let images = stored_images(&project_path)?;
+    assert_eq!(images.len(), 1);
+    assert!(images[0].0.ends_with(".jpg"));
+    assert!(images[0].1 < source_size);
+
+    // 4. Pixels match within JPEG tolerance
+    let project: ProjectDb = ProjectDb::new(&project_path).await?;
+    let areas: Vec<Area> = project.get_areas().await?;
+    let area_repo: AreaDb = project.get_area_repo(areas[0].id).await?;
+    let stored = area_repo.get_image().to_rgb8();
+    assert_eq!(stored.dimensions(), source.dimensions());
+    let total_diff: u64 = stored
+        .as_raw()
+        .iter()
+        .zip(source.as_raw())
+        .map(|(a, b)| u64::from(a.abs_diff(*b)))
+        .sum();
+    let mean_diff = total_diff as f64 / source.as_raw().len() as f64;
+    assert!(mean_diff < 4.0, "mean channel difference {mean_diff} too large");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_thumbnail_is_cached() -> anyhow::Result<()> {
+    let (project, _dir) = create_test_project().await;
+
+    // 1. Create area with the test image and start from a clean project
+    let (new_area, _img_file) = make_new_area("Thumbnail Area", TEST_RED);
+    let area_repo: AreaDb = project.add_area(new_area).await?;
+    project.save_project().await?;
+
+    // 2. The first request generates and stores the thumbnail
+    let thumbnail = area_repo.get_thumbnail(64).await?;
+    assert_eq!(thumbnail.width().max(thumbnail.height()), 64);
+    assert!(project.save_if_dirty().await?);
+
+    // 3. The second request is served from the cache without touching the project
+    let cached = area_repo.get_thumbnail(64).await?;
+    assert_eq!(cached.dimensions(), thumbnail.dimensions());
+    assert!(!project.save_if_dirty().await?);
+
+    // 4. A different size gets its own entry and leaves the first one in place
+    let small = area_repo.get_thumbnail(32).await?;
+    assert_eq!(small.width().max(small.height()), 32);
+    assert!(project.save_if_dirty().await?);
+    let cached = area_repo.get_thumbnail(64).await?;
+    assert_eq!(cached.dimensions(), thumbnail.dimensions());
+    let cached_small = area_repo.get_thumbnail(32).await?;
+    assert_eq!(cached_small.dimensions(), small.dimensions());
+    assert!(!project.save_if_dirty().await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_duplicate_area_is_independent() -> anyhow::Result<()> {
+    // 1. Create an area with a drawn street and two addresses on it
+    let (project, temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Original", TEST_RED);
+    let original = project.add_area(new_area).await?;
+    let street = original.add_street().await?;
+    let polyline = vec![Point { x: 0, y: 50 }, Point { x: 100, y: 50 }];
+    original.draw_street_polyline(&street, &polyline).await?;
+    for (number, x) in [("1", 20), ("3", 60)] {
+        let mut new_address = make_test_address(number, x, 45);
+        new_address.assigned_street_id = Some(street.id);
+        AddressRepository::add_address(&original, &new_address).await?;
+    }
+    original.advance_state(AreaState::AddressesDetected).await?;
+
+    // 2. Duplicate it
+    let original_id = original.get_area().await?.id;
+    let duplicate = project.duplicate_area(original_id, "Experiment").await?;
+    let duplicate_area = duplicate.get_area().await?;
+    assert_ne!(duplicate_area.id, original_id);
+    assert_eq!(duplicate_area.name, "Experiment");
+    assert_eq!(duplicate_area.color, TEST_RED);
+    assert_eq!(duplicate_area.state, AreaState::Imported);
+    assert_eq!(duplicate.get_image().dimensions(), original.get_image().dimensions());
+
+    // 3. The copy has its own street and addresses pointing at it
+    let duplicate_streets = duplicate.get_streets().await?;
+    assert_eq!(duplicate_streets.len(), 1);
+    assert_ne!(duplicate_streets[0].id, street.id);
+    assert_eq!(duplicate.get_street_polyline(&duplicate_streets[0]).await?.unwrap().points, polyline);
+    let duplicate_addresses = AddressRepository::get_addresses(&duplicate).await?;
+    let original_addresses = AddressRepository::get_addresses(&original).await?;
+    assert_eq!(duplicate_addresses.len(), 2);
+    for (copy, source) in duplicate_addresses.iter().zip(&original_addresses) {
+        assert_ne!(copy.id, source.id);
+        assert_eq!(copy.house_number, source.house_number);
+        assert_eq!(copy.position, source.position);
+        assert_eq!(copy.assigned_street_id, Some(duplicate_streets[0].id));
+    }
+
+    // 4. Editing the copy leaves the original alone
+    duplicate
+        .update_address(
+            &duplicate_addresses[0],
+            &AddressUpdate {
+                house_number: Some("1a".to_string()),
+                ..Default::default()
+            },
+        )
+        .await?;
+    duplicate
+        .update_street(
+            &duplicate_streets[0],
+            &StreetUpdate {
+                name: Some("Renamed".to_string()),
+                ..Default::default()
+            },
+        )
+        .await?;
+    duplicate.remove_street_polyline(&duplicate_streets[0]).await?;
+    assert_eq!(AddressRepository::get_addresses(&original).await?, original_addresses);
+    assert_eq!(original.get_streets().await?, vec![street.clone()]);
+    assert_eq!(original.get_street_polyline(&street).await?.unwrap().points, polyline);
+    assert_eq!(original.get_area().await?.state, AreaState::AddressesDetected);
+
+    // 5. Both images end up in the archive
+    project.save_project().await?;
+    assert_eq!(stored_images(&temp_dir.path().join("test.addrslips"))?.len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_suggested_area_colors_are_distinct() -> anyhow::Result<()> {
+    // 1. Without areas the first palette color is suggested
+    let (project, _temp_dir) = create_test_project().await;
+    assert_eq!(project.suggest_area_color().await?, AREA_PALETTE[0]);
+
+    // 2. Create areas with the suggested colors
+    let mut colors = Vec::new();
+    for i in 0..8 {
+        let color = project.suggest_area_color().await?;
+        let (new_area, _img_file) = make_new_area(&format!("Area {}", i), color);
+        project.add_area(new_area).await?;
+        colors.push(color);
+    }
+
+    // 3. All colors are pairwise far apart
+    for (i, a) in colors.iter().enumerate() {
+        for b in &colors[..i] {
+            assert!(a.distance(b) > 100.0, "{} and {} are too similar", a.to_hex_string(), b.to_hex_string());
+        }
+    }
+
+    // 4. The suggestion avoids colors chosen by hand as well
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Red Area", AREA_PALETTE[0]);
+    project.add_area(new_area).await?;
+    assert_ne!(project.suggest_area_color().await?, AREA_PALETTE[0]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_area_without_color_gets_suggestion() -> anyhow::Result<()> {
+    // 1. Add one area by hand with the first palette color
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _first_img) = make_new_area("Red Area", AREA_PALETTE[0]);
+    project.add_area(new_area).await?;
+
+    // 2. An area without a color gets the suggestion at the time it is added
+    let suggested = project.suggest_area_color().await?;
+    let (new_area, _second_img) = make_new_area("Second Area", TEST_RED);
+    let area_repo = project
+        .add_area(NewArea {
+            color: None,
+            ..new_area
+        })
+        .await?;
+    assert_eq!(area_repo.get_area().await?.color, suggested);
+    assert_ne!(suggested, AREA_PALETTE[0]);
+
     Ok(())
 }
@@ -0,0 +1,138 @@
+//! Integration tests for the `detect` command-line tool.
+//!
+//! Tests cover:
+//! - Writing JSON and CSV detections for a map without markers
+//! - Rejecting unreadable images
+//! - Running the steps of a pipeline config
+//! - JSON records carrying every detection field
+
+mod common;
+
+use addrslips::HouseNumberDetection;
+use std::path::Path;
+use std::process::Command;
+
+use common::*;
+
+/// Runs the `detect` binary on `image` and returns its exit status and stderr
+fn run_detect(image: &Path, args: &[&str]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_detect"))
+        .arg(image)
+        .args(args)
+        .output()
+        .expect("Failed to run detect");
+    (output.status.success(), String::from_utf8_lossy(&output.stderr).into_owned())
+}
+
+#[test]
+fn test_detect_writes_json_and_csv() -> anyhow::Result<()> {
+    // 1. A map without markers never needs the OCR models
+    let dir = tempfile::TempDir::new()?;
+    let image_path = dir.path().join("map.png");
+    std::fs::write(&image_path, png_bytes(&synthetic_map(200, 150, &[])))?;
+
+    // 2. JSON is an empty array of detections
+    let json_path = dir.path().join("detections.json");
+    let (ok, stderr) = run_detect(
+        &image_path,
+        &["--output-format", "json", "--detections-out", json_path.to_str().unwrap()],
+    );
+    assert!(ok, "detect failed: {stderr}");
+    let detections: Vec<HouseNumberDetection> = serde_json::from_str(&std::fs::read_to_string(&json_path)?)?;
+    assert!(detections.is_empty());
+
+    // 3. CSV has just the header
+    let csv_path = dir.path().join("detections.csv");
+    let (ok, stderr) = run_detect(
+        &image_path,
+        &["--output-format", "csv", "--detections-out", csv_path.to_str().unwrap()],
+    );
+    assert!(ok, "detect failed: {stderr}");
+    assert_eq!(std::fs::read_to_string(&csv_path)?, "number,x,y,radius,confidence\n");
+
+    Ok(())
+}
+
+#[test]
+fn test_detect_rejects_missing_image() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let (ok, stderr) = run_detect(&dir.path().join("missing.png"), &["--output-format", "json"]);
+    assert!(!ok);
+    assert!(stderr.contains("missing.png"), "Unexpected error: {stderr}");
+    Ok(())
+}
+
+#[test]
+fn test_detect_with_pipeline_config() -> anyhow::Result<()> {
+    // 1. Circle detection without OCR yields no house numbers, even with markers present
+    let dir = tempfile::TempDir::new()?;
+    let image_path = dir.path().join("map.png");
+    let map = synthetic_map(200, 150, &[Marker { x: 60, y: 60, radius: 22, number: "12" }]);
+    std::fs::write(&image_path, png_bytes(&map))?;
+    let config_path = dir.path().join("pipeline.toml");
+    std::fs::write(
+        &config_path,
+        "[[steps]]\nname = \"grayscale\"\n\n[[steps]]\nname = \"blur\"\nsigma = 1.5\n",
+    )?;
+
+    let json_path = dir.path().join("detections.json");
+    let (ok, stderr) = run_detect(
+        &image_path,
+        &[
+            "--pipeline-config",
+            config_path.to_str().unwrap(),
+            "--output-format",
+            "json",
+            "--detections-out",
+            json_path.to_str().unwrap(),
+        ],
+    );
+    assert!(ok, "detect failed: {stderr}");
+    let detections: Vec<HouseNumberDetection> = serde_json::from_str(&std::fs::read_to_string(&json_path)?)?;
+    assert!(detections.is_empty());
+
+    // 2. A config naming an unknown step fails with the step's name
+    std::fs::write(&config_path, "[[steps]]\nname = \"magic\"\n")?;
+    let (ok, stderr) = run_detect(&image_path, &["--pipeline-config", config_path.to_str().unwrap()]);
+    assert!(!ok);
+    assert!(stderr.contains("magic"), "Unexpected error: {stderr}");
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "requires OCR models in ~/.cache/ocrs"]
+fn test_detect_json_fields() -> anyhow::Result<()> {
+    // 1. Map with two markers
+    let dir = tempfile::TempDir::new()?;
+    let image_path = dir.path().join("map.png");
+    let map = synthetic_map(
+        300,
+        200,
+        &[
+            Marker { x: 60, y: 60, radius: 22, number: "12" },
+            Marker { x: 200, y: 120, radius: 22, number: "7" },
+        ],
+    );
+    std::fs::write(&image_path, png_bytes(&map))?;
+
+    // 2. Every record has number, position, radius and confidence
+    let json_path = dir.path().join("detections.json");
+    let (ok, stderr) = run_detect(
+        &image_path,
+        &["--output-format", "json", "--detections-out", json_path.to_str().unwrap()],
+    );
+    assert!(ok, "detect failed: {stderr}");
+    let doc: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&json_path)?)?;
+    let records = doc.as_array().expect("JSON output is an array");
+    assert!(!records.is_empty(), "Expected at least one detection");
+    for record in records {
+        assert!(record["number"].is_string(), "{record}");
+        assert!(record["x"].as_u64().is_some_and(|x| x < 300), "{record}");
+        assert!(record["y"].as_u64().is_some_and(|y| y < 200), "{record}");
+        assert!(record["radius"].as_u64().is_some(), "{record}");
+        assert!(record["confidence"].is_number(), "{record}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! Integration tests for the area color model.
+//!
+//! Tests cover:
+//! - Hex parsing in short, long and alpha forms
+//! - WCAG relative luminance
+//! - Choosing readable text colors
+//! - Serde round trip
+
+mod common;
+
+use common::*;
+
+#[test]
+fn test_from_hex_string_forms() -> anyhow::Result<()> {
+    assert_eq!(Color::from_hex_string("#F00")?, TEST_RED);
+    assert_eq!(Color::from_hex_string("#ff0000")?, TEST_RED);
+    assert_eq!(Color::from_hex_string("#FF0000")?, TEST_RED);
+    // Alpha is ignored
+    assert_eq!(Color::from_hex_string("#ff000080")?, TEST_RED);
+    assert_eq!(Color::from_hex_string("0000FFff")?, TEST_BLUE);
+
+    assert!(Color::from_hex_string("#ff00").is_err());
+    assert!(Color::from_hex_string("#ff00000").is_err());
+    assert!(Color::from_hex_string("#gg0000").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_luminance() {
+    assert!((TEST_RED.luminance() - 0.2126).abs() < 1e-4);
+    assert!((Color::WHITE.luminance() - 1.0).abs() < 1e-4);
+    assert_eq!(Color::BLACK.luminance(), 0.0);
+    // Mid gray is linearized well below the naive 0.5
+    let gray = Color { r: 128, g: 128, b: 128 };
+    assert!((gray.luminance() - 0.2159).abs() < 1e-3);
+}
+
+#[test]
+fn test_contrasting_text() -> anyhow::Result<()> {
+    assert_eq!(Color::from_hex_string("#1A237E")?.contrasting_text(), Color::WHITE);
+    // Pure red contrasts more with black (5.25:1) than with white (4:1)
+    assert_eq!(TEST_RED.contrasting_text(), Color::BLACK);
+    assert_eq!(Color::from_hex_string("#555555")?.contrasting_text(), Color::WHITE);
+    assert_eq!(Color::from_hex_string("#777777")?.contrasting_text(), Color::BLACK);
+    assert_eq!(Color::from_hex_string("#FFEB3B")?.contrasting_text(), Color::BLACK);
+    assert_eq!(Color::WHITE.contrasting_text(), Color::BLACK);
+
+    Ok(())
+}
+
+#[test]
+fn test_color_serde_round_trip() -> anyhow::Result<()> {
+    let json = serde_json::to_string(&TEST_GREEN)?;
+    assert_eq!(serde_json::from_str::<Color>(&json)?, TEST_GREEN);
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+//! Integration tests for concurrent database access.
+//!
+//! Tests cover:
+//! - Many concurrent `get_areas` calls completing without deadlock
+//! - Reads running alongside writes and a save
+//! - Reads inside a transaction seeing its uncommitted changes
+
+mod common;
+
+use common::*;
+use futures::future::join_all;
+use std::time::Duration;
+
+/// Far more concurrent readers than pooled connections.
+const CONCURRENT_READS: usize = 64;
+
+/// Generous upper bound; a deadlock never finishes at all.
+const DEADLOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[tokio::test]
+async fn test_concurrent_get_areas() -> anyhow::Result<()> {
+    // 1. Create a few areas
+    let (project, _temp_dir) = create_test_project().await;
+    let mut _img_files = Vec::new();
+    for name in ["North", "South", "East"] {
+        let (new_area, img_file) = make_new_area(name, TEST_RED);
+        project.add_area(new_area).await?;
+        _img_files.push(img_file);
+    }
+
+    // 2. Read the areas from many tasks at once
+    let reads = join_all((0..CONCURRENT_READS).map(|_| project.get_areas()));
+    let results = tokio::time::timeout(DEADLOCK_TIMEOUT, reads)
+        .await
+        .expect("concurrent reads deadlocked");
+
+    // 3. Every read succeeded and saw all areas
+    for result in results {
+        assert_eq!(result?.len(), 3);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reads_alongside_writes_and_save() -> anyhow::Result<()> {
+    // 1. Create area
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_id = project.add_area(new_area).await?.get_area().await?.id;
+    let area_repo = project.get_area_repo(area_id).await?;
+
+    // 2. Interleave reads with inserts and a save
+    let reads = join_all((0..CONCURRENT_READS).map(|_| project.get_areas()));
+    let writes = join_all((0..10u32).map(|i| {
+        let address = make_test_address(&(i + 1).to_string(), 10 * i, 10 * i);
+        let area_repo = &area_repo;
+        async move { AddressRepository::add_address(area_repo, &address).await }
+    }));
+    let save = project.save_project();
+    let (reads, writes, save) = tokio::time::timeout(DEADLOCK_TIMEOUT, async {
+        futures::join!(reads, writes, save)
+    })
+    .await
+    .expect("reads, writes and save deadlocked");
+
+    // 3. Everything succeeded and all inserts are visible afterwards
+    for result in reads {
+        assert_eq!(result?.len(), 1);
+    }
+    for result in writes {
+        result?;
+    }
+    save?;
+    assert_eq!(AddressRepository::get_addresses(&area_repo).await?.len(), 10);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reads_in_transaction_see_uncommitted_rows() -> anyhow::Result<()> {
+    // 1. Create area
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_id = project.add_area(new_area).await?.get_area().await?.id;
+
+    // 2. Insert and read back within the same transaction
+    let seen = project
+        .transaction(|tx| async move {
+            let area_repo = tx.get_area_repo(area_id).await?;
+            AddressRepository::add_address(&area_repo, &make_test_address("1", 10, 10)).await?;
+            Ok(AddressRepository::get_addresses(&area_repo).await?.len())
+        })
+        .await?;
+    assert_eq!(seen, 1);
+
+    Ok(())
+}
@@ -3,6 +3,7 @@
 //! Tests cover:
 //! - Team and address must be in same area (foreign key constraint)
 //! - Each address can only belong to one team (unique constraint)
+//! - Address positions must lie inside the area image unless the check is turned off
 
 mod common;
 
@@ -19,7 +20,7 @@ async fn test_team_assignment_enforces_same_area() -> anyhow::Result<()> {
     let area1_repo = project.add_area(new_area1).await?;
     let street1 = area1_repo.add_street().await?;
 
-    let mut new_address1 = make_test_address("42", 100, 200);
+    let mut new_address1 = make_test_address("42", 10, 20);
     new_address1.assigned_street_id = Some(street1.id);
     let address1: Address = AddressRepository::add_address(&area1_repo, &new_address1).await?;
 
@@ -57,7 +58,7 @@ async fn test_address_can_only_belong_to_one_team() -> anyhow::Result<()> {
     let area_repo: AreaDb = project.add_area(new_area).await?;
     let street: Street = area_repo.add_street().await?;
 
-    let mut new_address = make_test_address("99", 300, 400);
+    let mut new_address = make_test_address("99", 30, 40);
     new_address.assigned_street_id = Some(street.id);
     let address: Address = AddressRepository::add_address(&area_repo, &new_address).await?;
 
@@ -90,3 +91,34 @@ async fn test_address_can_only_belong_to_one_team() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_address_outside_image_is_rejected() -> anyhow::Result<()> {
+    // 1. Create area with the 100x100 test image
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+
+    // 2. Single and batch inserts far outside the image fail and store nothing
+    let outside = make_test_address("1", 10000, 10000);
+    let err = AddressRepository::add_address(&area_repo, &outside).await.unwrap_err();
+    assert!(err.to_string().contains("(10000, 10000)"), "unexpected error: {err}");
+    let batch = [make_test_address("2", 10, 10), make_test_address("3", 100, 50)];
+    assert!(AddressRepository::add_addresses(&area_repo, &batch).await.is_err());
+    assert!(AddressRepository::get_addresses(&area_repo).await?.is_empty());
+
+    // 3. Moving an address off the image fails too
+    let inside = AddressRepository::add_address(&area_repo, &make_test_address("4", 99, 99)).await?;
+    let update = AddressUpdate {
+        position: Some(Point { x: 50, y: 100 }),
+        ..Default::default()
+    };
+    assert!(area_repo.update_address(&inside, &update).await.is_err());
+
+    // 4. Without the check the address is stored as given
+    let area_repo = area_repo.with_bounds_check(false);
+    let stored = AddressRepository::add_address(&area_repo, &outside).await?;
+    assert_eq!(stored.position, Point { x: 10000, y: 10000 });
+
+    Ok(())
+}
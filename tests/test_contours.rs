@@ -0,0 +1,343 @@
+//! Integration tests for contour extraction.
+//!
+//! Tests cover:
+//! - Boundary tracing of a filled shape
+//! - Circularity from the traced boundary for a circle and a square
+//! - Round-tripping a contour through pipeline metadata
+//! - Least-squares circle fit on a rasterized outline
+//! - Centroid and moment-based orientation of an elongated blob
+//! - ROI extraction for contours at or beyond the image border
+//! - Fixed and radius-proportional ROI padding
+//! - ROI padding clipped per side at the top-left corner
+//! - Stable top-to-bottom, left-to-right contour order
+//! - Dropping oversized and border-touching regions
+//! - Four- versus eight-connected labelling of diagonally touching blobs
+
+use addrslips::detection::contours::{find_contours, find_contours_filtered, trace_boundary, Connectivity};
+use addrslips::{Contour, MIN_ROI_SIZE};
+use image::{DynamicImage, GrayImage, Luma};
+use std::collections::HashMap;
+
+fn filled_circle(radius: i64) -> GrayImage {
+    let center = radius + 5;
+    let size = (2 * center + 1) as u32;
+    GrayImage::from_fn(size, size, |x, y| {
+        let dx = x as i64 - center;
+        let dy = y as i64 - center;
+        if dx * dx + dy * dy <= radius * radius { Luma([255]) } else { Luma([0]) }
+    })
+}
+
+/// One-pixel outline of a circle with a non-integer center, like the edge map of a marker
+fn circle_outline(cx: f32, cy: f32, radius: f32) -> GrayImage {
+    GrayImage::from_fn(80, 80, |x, y| {
+        let distance = (x as f32 - cx).hypot(y as f32 - cy);
+        if (distance - radius).abs() < 0.5 { Luma([255]) } else { Luma([0]) }
+    })
+}
+
+fn filled_square(side: u32) -> GrayImage {
+    GrayImage::from_fn(side + 20, side + 20, |x, y| {
+        if (10..10 + side).contains(&x) && (10..10 + side).contains(&y) { Luma([255]) } else { Luma([0]) }
+    })
+}
+
+#[test]
+fn test_trace_boundary_of_square() {
+    let img = filled_square(5);
+    let contours = find_contours(&img, 1);
+    assert_eq!(contours.len(), 1);
+
+    let boundary = trace_boundary(&img, contours[0].label);
+    // 5x5 square has 16 outer pixels, starting at the top-left corner
+    assert_eq!(boundary.len(), 16);
+    assert_eq!(boundary[0], (10, 10));
+    assert_eq!(contours[0].boundary.as_ref(), Some(&boundary));
+}
+
+#[test]
+fn test_circularity_of_circle() {
+    let contours = find_contours(&filled_circle(30), 1);
+    assert_eq!(contours.len(), 1);
+
+    let circularity = contours[0].circularity();
+    assert!(
+        (circularity - 1.0).abs() < 0.15,
+        "Circle circularity should be close to 1.0, got {circularity}"
+    );
+}
+
+#[test]
+fn test_circularity_of_square() {
+    let contours = find_contours(&filled_square(60), 1);
+    assert_eq!(contours.len(), 1);
+
+    let circularity = contours[0].circularity();
+    assert!(
+        (circularity - std::f32::consts::FRAC_PI_4).abs() < 0.05,
+        "Square circularity should be close to 0.785, got {circularity}"
+    );
+}
+
+#[test]
+fn test_fit_circle_on_outline() {
+    let edges = circle_outline(37.4, 41.7, 24.0);
+    let contours = find_contours(&edges, 1);
+    assert_eq!(contours.len(), 1);
+
+    let (cx, cy, r) = contours[0].fit_circle(&edges).expect("outline should fit a circle");
+    assert!((cx - 37.4).abs() < 1.0, "Center x should be ~37.4, got {cx}");
+    assert!((cy - 41.7).abs() < 1.0, "Center y should be ~41.7, got {cy}");
+    assert!((r - 24.0).abs() < 1.0, "Radius should be ~24.0, got {r}");
+}
+
+#[test]
+fn test_fit_circle_needs_points() {
+    let edges = GrayImage::new(20, 20);
+    let contour = Contour {
+        label: 1,
+        min_x: 2,
+        min_y: 2,
+        max_x: 17,
+        max_y: 17,
+        pixel_count: 0,
+        boundary: None,
+    };
+    assert_eq!(contour.fit_circle(&edges), None);
+}
+
+#[test]
+fn test_moments_of_horizontal_blob() {
+    // 41x9 filled bar spanning x 10..=50, y 20..=28
+    let img = GrayImage::from_fn(70, 50, |x, y| {
+        if (10..=50).contains(&x) && (20..=28).contains(&y) { Luma([255]) } else { Luma([0]) }
+    });
+    let contours = find_contours(&img, 1);
+    assert_eq!(contours.len(), 1);
+    let contour = &contours[0];
+
+    let moments = contour.moments();
+    assert_eq!(moments.m00, (41 * 9) as f32);
+    assert!(moments.mu20 > moments.mu02, "Horizontal blob should spread along x");
+
+    let orientation = moments.orientation();
+    assert!(orientation.abs() < 0.05, "Orientation should be ~0, got {orientation}");
+
+    let (mx, my) = moments.centroid().expect("non-empty region");
+    assert!((mx - 30.0).abs() < 0.5 && (my - 24.0).abs() < 0.5, "Moment centroid off: ({mx}, {my})");
+
+    let (cx, cy) = contour.centroid(&img);
+    assert!((cx - 30.0).abs() < 0.5 && (cy - 24.0).abs() < 0.5, "Edge centroid off: ({cx}, {cy})");
+}
+
+#[test]
+fn test_contour_metadata_round_trip() -> anyhow::Result<()> {
+    let contour = Contour {
+        label: 17,
+        min_x: 12,
+        min_y: 30,
+        max_x: 52,
+        max_y: 71,
+        pixel_count: 240,
+        boundary: None,
+    };
+
+    let mut meta = HashMap::new();
+    contour.write_metadata(&mut meta);
+    assert_eq!(Contour::from_metadata(&meta)?, contour);
+
+    Ok(())
+}
+
+#[test]
+fn test_contour_from_metadata_names_missing_key() {
+    let mut meta = HashMap::new();
+    filled_square_contour().write_metadata(&mut meta);
+    meta.remove("contour_max_x");
+
+    let err = Contour::from_metadata(&meta).unwrap_err();
+    assert!(err.to_string().contains("contour_max_x"), "Unexpected error: {err}");
+}
+
+fn filled_square_contour() -> Contour {
+    find_contours(&filled_square(10), 1).remove(0)
+}
+
+fn bbox_contour(min_x: u32, min_y: u32, max_x: u32, max_y: u32) -> Contour {
+    Contour {
+        label: 1,
+        min_x,
+        min_y,
+        max_x,
+        max_y,
+        pixel_count: (max_x - min_x + 1) * (max_y - min_y + 1),
+        boundary: None,
+    }
+}
+
+/// Asserts the ROI is either absent or a non-empty crop inside the image
+fn assert_roi_valid(contour: &Contour, img: &DynamicImage) -> Option<(u32, u32)> {
+    let roi = contour.extract_roi(img)?;
+    assert!(roi.width() >= MIN_ROI_SIZE && roi.height() >= MIN_ROI_SIZE);
+    assert!(roi.width() <= img.width() && roi.height() <= img.height());
+    Some((roi.width(), roi.height()))
+}
+
+#[test]
+fn test_extract_roi_at_image_corners() {
+    let img = DynamicImage::new_rgb8(40, 30);
+
+    // Single-pixel contours in the corners still get the padding that fits
+    assert_eq!(assert_roi_valid(&bbox_contour(0, 0, 0, 0), &img), Some((11, 11)));
+    assert_eq!(assert_roi_valid(&bbox_contour(39, 29, 39, 29), &img), Some((6, 6)));
+
+    // A contour touching the bottom-right corner is clipped, not overflowing
+    assert_eq!(assert_roi_valid(&bbox_contour(30, 20, 39, 29), &img), Some((15, 15)));
+}
+
+#[test]
+fn test_extract_roi_outside_image_is_none() {
+    let img = DynamicImage::new_rgb8(40, 30);
+
+    // Entirely outside, e.g. a contour from a larger image
+    assert!(bbox_contour(100, 100, 120, 120).extract_roi(&img).is_none());
+    assert!(bbox_contour(50, 0, 60, 10).extract_roi(&img).is_none());
+
+    // Just past the edge leaves a sliver thinner than the minimum
+    assert!(assert_roi_valid(&bbox_contour(42, 10, 50, 20), &img).is_none());
+}
+
+#[test]
+fn test_extract_roi_padding_variants() {
+    let img = DynamicImage::new_rgb8(200, 150);
+    // 21x21 box, radius 10.5
+    let contour = bbox_contour(90, 60, 110, 80);
+
+    // 1. Fixed padding grows each side; the default is 5px
+    let size = |roi: Option<DynamicImage>| roi.map(|roi| (roi.width(), roi.height()));
+    assert_eq!(size(contour.extract_roi(&img)), Some((31, 31)));
+    assert_eq!(size(contour.extract_roi_padded(&img, 0)), Some((21, 21)));
+    assert_eq!(size(contour.extract_roi_padded(&img, 12)), Some((45, 45)));
+
+    // 2. Scaled padding follows the radius: 10.5 * 0.5 rounds to 5, 10.5 * 2 is 21
+    assert_eq!(size(contour.extract_roi_scaled(&img, 0.5)), Some((31, 31)));
+    assert_eq!(size(contour.extract_roi_scaled(&img, 2.0)), Some((63, 63)));
+    assert_eq!(size(contour.extract_roi_scaled(&img, -1.0)), Some((21, 21)));
+
+    // 3. Both variants clip at the image border
+    let corner = bbox_contour(190, 140, 199, 149);
+    assert_eq!(size(corner.extract_roi_padded(&img, 30)), Some((40, 40)));
+    assert_eq!(size(corner.extract_roi_scaled(&img, 3.0)), Some((25, 25)));
+    let outside = bbox_contour(400, 10, 420, 30);
+    assert!(outside.extract_roi_padded(&img, 100).is_none());
+    assert!(outside.extract_roi_scaled(&img, 10.0).is_none());
+}
+
+#[test]
+fn test_extract_roi_padding_at_top_left_corner() {
+    // Mark the 21x21 box touching the top-left corner
+    let mut img = image::RgbImage::new(200, 150);
+    for y in 0..=20 {
+        for x in 0..=20 {
+            img.put_pixel(x, y, image::Rgb([255, 255, 255]));
+        }
+    }
+    let img = DynamicImage::ImageRgb8(img);
+    let contour = bbox_contour(0, 0, 20, 20);
+
+    // 1. Only the right and bottom sides get padding; the box stays at the ROI origin
+    let roi = contour.extract_roi_padded(&img, 10).expect("roi inside the image").to_rgb8();
+    assert_eq!(roi.dimensions(), (31, 31));
+    assert_eq!(roi.get_pixel(20, 20), &image::Rgb([255, 255, 255]));
+    assert_eq!(roi.get_pixel(21, 21), &image::Rgb([0, 0, 0]));
+
+    // 2. Huge padding clips to the image instead of overflowing
+    let roi = contour.extract_roi_padded(&img, u32::MAX).expect("roi inside the image");
+    assert_eq!((roi.width(), roi.height()), (200, 150));
+}
+
+#[test]
+fn test_find_contours_order_is_stable() {
+    // Ring outlines scattered over the image, several sharing a row
+    let centers = [(70.0, 20.0), (20.0, 20.0), (45.0, 60.0), (120.0, 60.0), (20.0, 100.0), (95.0, 100.0)];
+    let edges = GrayImage::from_fn(150, 130, |x, y| {
+        let on_ring = centers.iter().any(|&(cx, cy): &(f32, f32)| {
+            ((x as f32 - cx).hypot(y as f32 - cy) - 12.0).abs() < 0.5
+        });
+        if on_ring { Luma([255]) } else { Luma([0]) }
+    });
+
+    let first = find_contours(&edges, 10);
+    assert_eq!(first.len(), centers.len());
+    for _ in 0..5 {
+        let again = find_contours(&edges, 10);
+        let corners = |contours: &[Contour]| -> Vec<(u32, u32, u32)> {
+            contours.iter().map(|c| (c.min_x, c.min_y, c.label)).collect()
+        };
+        assert_eq!(corners(&again), corners(&first));
+    }
+
+    // Sorted top to bottom, then left to right
+    let keys: Vec<(u32, u32)> = first.iter().map(|c| (c.min_y, c.min_x)).collect();
+    let mut sorted = keys.clone();
+    sorted.sort();
+    assert_eq!(keys, sorted);
+    assert!(keys[0].1 < keys[1].1, "Rings in the first row should be left to right: {keys:?}");
+}
+
+/// Edge image with a small ring and a rectangle outline `inset` pixels inside the image border
+fn ring_and_frame(inset: u32) -> GrayImage {
+    let (width, height) = (200u32, 150u32);
+    GrayImage::from_fn(width, height, |x, y| {
+        let on_ring = ((x as f32 - 60.0).hypot(y as f32 - 70.0) - 15.0).abs() < 0.5;
+        let inside = (inset..width - inset).contains(&x) && (inset..height - inset).contains(&y);
+        let on_frame = inside && (x == inset || y == inset || x == width - 1 - inset || y == height - 1 - inset);
+        if on_ring || on_frame { Luma([255]) } else { Luma([0]) }
+    })
+}
+
+#[test]
+fn test_max_area_drops_image_spanning_regions() {
+    let edges = ring_and_frame(3);
+
+    // 1. Without a limit both the ring and the frame are found
+    assert_eq!(find_contours(&edges, 10).len(), 2);
+
+    // 2. The frame's box covers most of the image and is dropped; the ring survives
+    let contours = find_contours_filtered(&edges, 10, Some(100 * 100), false, Connectivity::Eight);
+    assert_eq!(contours.len(), 1);
+    let ring = &contours[0];
+    assert!((44..=46).contains(&ring.min_x) && (54..=56).contains(&ring.min_y), "Unexpected ring {ring:?}");
+}
+
+#[test]
+fn test_reject_border_drops_frame() {
+    // A frame drawn on the outermost pixels touches the border; the ring doesn't
+    let edges = ring_and_frame(0);
+    assert_eq!(find_contours_filtered(&edges, 10, None, false, Connectivity::Eight).len(), 2);
+    let contours = find_contours_filtered(&edges, 10, None, true, Connectivity::Eight);
+    assert_eq!(contours.len(), 1);
+    assert!(contours[0].max_x < 100, "Expected only the ring, got {:?}", contours[0]);
+
+    // An inset frame isn't touching the border
+    assert_eq!(find_contours_filtered(&ring_and_frame(3), 10, None, true, Connectivity::Eight).len(), 2);
+}
+
+#[test]
+fn test_connectivity_separates_diagonal_blobs() {
+    // Two 10x10 squares touching only at one corner pixel
+    let edges = GrayImage::from_fn(40, 40, |x, y| {
+        let first = (5..15).contains(&x) && (5..15).contains(&y);
+        let second = (15..25).contains(&x) && (15..25).contains(&y);
+        if first || second { Luma([255]) } else { Luma([0]) }
+    });
+
+    let eight = find_contours_filtered(&edges, 10, None, false, Connectivity::Eight);
+    assert_eq!(eight.len(), 1, "Eight-connectivity should join the squares: {eight:?}");
+    assert_eq!(eight[0].pixel_count, 200);
+
+    let four = find_contours_filtered(&edges, 10, None, false, Connectivity::Four);
+    assert_eq!(four.len(), 2, "Four-connectivity should keep the squares apart: {four:?}");
+    assert_eq!((four[0].min_x, four[0].min_y), (5, 5));
+    assert_eq!((four[1].min_x, four[1].min_y), (15, 15));
+}
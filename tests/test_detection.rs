@@ -0,0 +1,148 @@
+//! Integration tests for the public detection entry points.
+//!
+//! Tests cover:
+//! - Detecting house numbers from encoded image bytes
+//! - Detecting house numbers from an image file with original-image coordinates
+//! - Multi-threaded OCR matching the serial detection list
+//! - Custom preprocessing parameters changing the contours found
+
+mod common;
+
+use addrslips::{detect_bytes, detect_house_numbers, DetectionParams, DetectionPipeline, HouseNumberDetection};
+use image::ImageFormat;
+
+use common::*;
+
+#[test]
+fn test_detect_bytes_on_empty_map() -> anyhow::Result<()> {
+    // A map without markers never reaches OCR and yields no detections
+    let map = synthetic_map(200, 150, &[]);
+    let bytes = png_bytes(&map);
+
+    let detections = detect_bytes(&bytes, Some(ImageFormat::Png), &DetectionParams::default())?;
+    assert!(detections.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_detect_bytes_rejects_garbage() {
+    let result = detect_bytes(b"not an image", None, &DetectionParams::default());
+    assert!(result.is_err(), "Undecodable bytes should be rejected");
+}
+
+#[test]
+#[ignore = "requires OCR models in ~/.cache/ocrs"]
+fn test_detect_bytes_on_synthetic_map() -> anyhow::Result<()> {
+    let map = synthetic_map(
+        300,
+        200,
+        &[
+            Marker { x: 60, y: 60, radius: 22, number: "12" },
+            Marker { x: 200, y: 120, radius: 22, number: "7" },
+        ],
+    );
+    let bytes = png_bytes(&map);
+
+    // No format hint: PNG is guessed from the magic bytes
+    let detections = detect_bytes(&bytes, None, &DetectionParams::default())?;
+    assert!(!detections.is_empty(), "Expected at least one detection");
+    for detection in &detections {
+        assert!(detection.x < 300 && detection.y < 200);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_detect_house_numbers_on_empty_map() -> anyhow::Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("map.png");
+    synthetic_map(200, 150, &[]).save(&path)?;
+
+    // 1. No markers, no detections
+    assert!(detect_house_numbers(&path, DetectionParams::default())?.is_empty());
+
+    // 2. A missing file is reported with its path
+    let missing = dir.path().join("missing.png");
+    let err = detect_house_numbers(&missing, DetectionParams::default()).unwrap_err();
+    assert!(err.to_string().contains("missing.png"), "unexpected error: {err}");
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "requires OCR models in ~/.cache/ocrs"]
+fn test_detect_house_numbers_maps_back_to_markers() -> anyhow::Result<()> {
+    let markers = [
+        Marker { x: 60, y: 60, radius: 22, number: "12" },
+        Marker { x: 200, y: 120, radius: 22, number: "7" },
+    ];
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("map.png");
+    synthetic_map(300, 200, &markers).save(&path)?;
+
+    // Every detection sits on one of the markers in full-image coordinates
+    let detections = detect_house_numbers(&path, DetectionParams::default())?;
+    assert!(!detections.is_empty(), "Expected at least one detection");
+    for detection in &detections {
+        let marker = markers
+            .iter()
+            .find(|m| m.x.abs_diff(detection.x as i32) <= 5 && m.y.abs_diff(detection.y as i32) <= 5)
+            .unwrap_or_else(|| panic!("detection {:?} is not on a marker", detection));
+        assert!((detection.radius as i32).abs_diff(marker.radius) <= 5, "radius of {:?}", detection);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "requires OCR models in ~/.cache/ocrs"]
+fn test_parallel_detection_matches_serial() -> anyhow::Result<()> {
+    let map = synthetic_map(
+        420,
+        260,
+        &[
+            Marker { x: 60, y: 60, radius: 22, number: "12" },
+            Marker { x: 200, y: 60, radius: 22, number: "7" },
+            Marker { x: 340, y: 60, radius: 22, number: "31" },
+            Marker { x: 60, y: 190, radius: 22, number: "4" },
+            Marker { x: 200, y: 190, radius: 22, number: "58" },
+            Marker { x: 340, y: 190, radius: 22, number: "9" },
+        ],
+    );
+
+    let key = |d: &HouseNumberDetection| (d.x, d.y, d.number.clone());
+    let mut serial: Vec<_> = DetectionPipeline::new().detect(&map)?.iter().map(key).collect();
+    let mut parallel: Vec<_> = DetectionPipeline::new().with_threads(3).detect(&map)?.iter().map(key).collect();
+    serial.sort();
+    parallel.sort();
+
+    assert!(!serial.is_empty(), "Expected at least one detection");
+    assert_eq!(parallel, serial);
+
+    Ok(())
+}
+
+#[test]
+fn test_custom_canny_changes_contours() -> anyhow::Result<()> {
+    let map = synthetic_map(
+        300,
+        200,
+        &[
+            Marker { x: 60, y: 60, radius: 22, number: "12" },
+            Marker { x: 200, y: 120, radius: 22, number: "7" },
+        ],
+    );
+
+    // Defaults find the marker outlines
+    let default_contours = DetectionPipeline::new().get_contours(&map)?;
+    assert!(!default_contours.is_empty());
+
+    // Thresholds above any Sobel magnitude leave no edges to trace
+    let strict_contours = DetectionPipeline::new().with_canny(5000.0, 6000.0).get_contours(&map)?;
+    assert_ne!(strict_contours.len(), default_contours.len());
+    assert!(strict_contours.is_empty());
+
+    Ok(())
+}
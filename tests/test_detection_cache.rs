@@ -0,0 +1,108 @@
+//! Integration tests for caching detection results in the project.
+//!
+//! Tests cover:
+//! - A second run with the same image and parameters coming from the cache
+//! - Changed parameters and a cleared cache running detection again
+//! - The cache surviving a save and reopen
+//! - Replacing the area image dropping its cached results
+
+mod common;
+
+use addrslips::DetectionParams;
+use common::*;
+
+#[tokio::test]
+async fn test_second_detection_comes_from_cache() -> anyhow::Result<()> {
+    // 1. Create area; the plain test image has no markers, so OCR is never needed
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let params = DetectionParams::default();
+
+    // 2. The first run misses the cache
+    let first = area_repo.detect_house_numbers(&params).await?;
+    assert_eq!(project.detection_cache_hits(), 0);
+
+    // 3. The second run with the same parameters is a hit with the same result
+    let second = area_repo.detect_house_numbers(&params).await?;
+    assert_eq!(project.detection_cache_hits(), 1);
+    assert_eq!(second, first);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_changed_params_and_cleared_cache_miss() -> anyhow::Result<()> {
+    // 1. Create area and fill the cache
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let params = DetectionParams::default();
+    area_repo.detect_house_numbers(&params).await?;
+
+    // 2. Different parameters are a separate entry
+    let stricter = DetectionParams {
+        brightness_threshold: 220.0,
+        ..DetectionParams::default()
+    };
+    area_repo.detect_house_numbers(&stricter).await?;
+    assert_eq!(project.detection_cache_hits(), 0);
+
+    // 3. Clearing the cache forces a fresh run
+    project.clear_detection_cache().await?;
+    area_repo.detect_house_numbers(&params).await?;
+    assert_eq!(project.detection_cache_hits(), 0);
+
+    // 4. Clearing an already empty cache is fine
+    project.clear_detection_cache().await?;
+    project.clear_detection_cache().await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_detection_cache_survives_reopen() -> anyhow::Result<()> {
+    // 1. Create area and fill the cache
+    let (project, temp_dir) = create_test_project().await;
+    let project_path = temp_dir.path().join("test.addrslips");
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let area_id = area_repo.get_area().await?.id;
+    let params = DetectionParams::default();
+    area_repo.detect_house_numbers(&params).await?;
+
+    // 2. Save and reopen the project
+    project.save_project().await?;
+    drop(area_repo);
+    drop(project);
+    let project = ProjectDb::open(&project_path).await?;
+    let area_repo = project.get_area_repo(area_id).await?;
+
+    // 3. The cached result is still there
+    area_repo.detect_house_numbers(&params).await?;
+    assert_eq!(project.detection_cache_hits(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_replaced_image_drops_cached_detections() -> anyhow::Result<()> {
+    // 1. Create area and fill the cache
+    let (project, temp_dir) = create_test_project().await;
+    let (new_area, img_file) = make_new_area("Test Area", TEST_RED);
+    let mut area_repo = project.add_area(new_area).await?;
+    let params = DetectionParams::default();
+    area_repo.detect_house_numbers(&params).await?;
+
+    // 2. Replace the image, then put the original back
+    let other_scan = temp_dir.path().join("other.png");
+    image::RgbImage::from_pixel(50, 50, image::Rgb([0, 0, 255])).save(&other_scan)?;
+    area_repo.replace_image(&other_scan).await?;
+    area_repo.replace_image(img_file.path()).await?;
+
+    // 3. The entry for the original image went with the first replacement
+    area_repo.detect_house_numbers(&params).await?;
+    assert_eq!(project.detection_cache_hits(), 0);
+
+    Ok(())
+}
@@ -0,0 +1,106 @@
+//! Integration tests for the address undo/redo history.
+//!
+//! Tests cover:
+//! - Undoing and redoing an address update
+//! - Undoing a delete by restoring the address from the trash
+//! - New edits clearing the redo stack
+//! - Capping the history depth
+
+mod common;
+
+use addrslips::core::db::EditHistory;
+use common::*;
+
+#[tokio::test]
+async fn test_undo_redo_update() -> anyhow::Result<()> {
+    // 1. Create area with one address
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let original = AddressRepository::add_address(&area_repo, &make_test_address("12", 10, 20)).await?;
+
+    // 2. Update through the history
+    let mut history = EditHistory::default();
+    let update = AddressUpdate {
+        house_number: Some("12a".to_string()),
+        position: Some(Point { x: 11, y: 25 }),
+        verified: Some(true),
+        ..Default::default()
+    };
+    let updated = history.update_address(&area_repo, &original, &update).await?;
+
+    // 3. Undo restores the original values
+    assert!(history.undo(&area_repo).await?);
+    assert_eq!(AddressRepository::get_address_by_id(&area_repo, original.id).await?, Some(original.clone()));
+    assert!(!history.can_undo());
+
+    // 4. Redo brings the update back
+    assert!(history.redo(&area_repo).await?);
+    assert_eq!(AddressRepository::get_address_by_id(&area_repo, original.id).await?, Some(updated));
+    assert!(!history.can_redo());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_undo_delete_restores() -> anyhow::Result<()> {
+    // 1. Create area with one address and delete it through the history
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let address = AddressRepository::add_address(&area_repo, &make_test_address("7", 50, 60)).await?;
+    let mut history = EditHistory::default();
+    history.delete_address(&area_repo, address.clone()).await?;
+    assert!(AddressRepository::get_addresses(&area_repo).await?.is_empty());
+
+    // 2. Undo restores the same address from the trash
+    history.undo(&area_repo).await?;
+    assert_eq!(AddressRepository::get_addresses(&area_repo).await?, vec![address]);
+
+    // 3. Redo deletes it again
+    history.redo(&area_repo).await?;
+    assert!(AddressRepository::get_addresses(&area_repo).await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_new_edit_clears_redo() -> anyhow::Result<()> {
+    // 1. Add and undo
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let mut history = EditHistory::default();
+    history.add_address(&area_repo, &make_test_address("1", 10, 10)).await?;
+    history.undo(&area_repo).await?;
+    assert!(history.can_redo());
+
+    // 2. A new edit drops the undone one
+    history.add_address(&area_repo, &make_test_address("2", 20, 20)).await?;
+    assert!(!history.can_redo());
+    assert!(!history.redo(&area_repo).await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_history_depth_is_capped() -> anyhow::Result<()> {
+    // 1. Record three inserts with room for two
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let mut history = EditHistory::new(2);
+    for (i, number) in ["1", "2", "3"].into_iter().enumerate() {
+        history.add_address(&area_repo, &make_test_address(number, 10 * i as u32, 10)).await?;
+    }
+
+    // 2. Only the two most recent can be undone
+    assert!(history.undo(&area_repo).await?);
+    assert!(history.undo(&area_repo).await?);
+    assert!(!history.undo(&area_repo).await?);
+    let remaining = AddressRepository::get_addresses(&area_repo).await?;
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].house_number, "1");
+
+    Ok(())
+}
@@ -0,0 +1,136 @@
+//! Integration tests for importing and exporting area addresses.
+//!
+//! Tests cover:
+//! - GeoJSON export of addresses with and without streets
+//! - CSV import in one transaction
+//! - CSV errors naming the offending line
+//! - Importing pipeline detections and advancing the area state
+
+mod common;
+
+use addrslips::HouseNumberDetection;
+use common::*;
+
+#[tokio::test]
+async fn test_export_geojson() -> anyhow::Result<()> {
+    // 1. Create area with a named street
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let street = area_repo.add_street().await?;
+    let street = area_repo
+        .update_street(
+            &street,
+            &StreetUpdate {
+                name: Some("Hauptstraße".to_string()),
+                verified: None,
+            },
+        )
+        .await?;
+
+    // 2. Add one address on the street and two without
+    let mut on_street = make_test_address("12", 50, 60);
+    on_street.assigned_street_id = Some(street.id);
+    AddressRepository::add_address(&area_repo, &on_street).await?;
+    AddressRepository::add_address(&area_repo, &make_test_address("3", 10, 20)).await?;
+    AddressRepository::add_address(&area_repo, &make_test_address("5", 30, 40)).await?;
+
+    // 3. Export and parse back
+    let geojson = AddressRepository::export_geojson(&area_repo).await?;
+    let doc: serde_json::Value = serde_json::from_str(&geojson)?;
+
+    // 4. Verify structure and properties
+    assert_eq!(doc["type"], "FeatureCollection");
+    let features = doc["features"].as_array().expect("features array");
+    assert_eq!(features.len(), 3);
+
+    let first = &features[0];
+    assert_eq!(first["geometry"]["type"], "Point");
+    assert_eq!(first["geometry"]["coordinates"], serde_json::json!([50, 60]));
+    assert_eq!(first["properties"]["house_number"], "12");
+    assert_eq!(first["properties"]["street_name"], "Hauptstraße");
+    assert_eq!(first["properties"]["estimated_flats"], 4);
+    assert!(features[1]["properties"].get("street_name").is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_import_csv() -> anyhow::Result<()> {
+    // 1. Create area
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+
+    // 2. Import a small CSV
+    let csv = "house_number,x,y,confidence,estimated_flats\n\
+               12,50,60,0.9,4\n\
+               12a,55,60,0.75,\n";
+    let imported = AddressRepository::import_csv(&area_repo, csv.as_bytes()).await?;
+
+    // 3. Verify the created addresses
+    assert_eq!(imported.len(), 2);
+    assert_eq!(imported[0].house_number, "12");
+    assert_eq!(imported[0].position, Point { x: 50, y: 60 });
+    assert_eq!(imported[0].confidence, 0.9);
+    assert_eq!(imported[0].estimated_flats, Some(4));
+    assert_eq!(imported[1].house_number, "12a");
+    assert_eq!(imported[1].estimated_flats, None);
+    assert_eq!(AddressRepository::get_addresses(&area_repo).await?, imported);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_import_csv_reports_bad_line() -> anyhow::Result<()> {
+    // 1. Create area
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+
+    // 2. Import a CSV whose third line has a bad coordinate
+    let csv = "house_number,x,y,confidence,estimated_flats\n1,10,10,0.9,\n2,ten,10,0.9,\n";
+    let err = AddressRepository::import_csv(&area_repo, csv.as_bytes())
+        .await
+        .unwrap_err();
+
+    // 3. Verify the error names the line and nothing was inserted
+    assert!(err.to_string().contains("line 3"), "Unexpected error: {err}");
+    assert!(AddressRepository::get_addresses(&area_repo).await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_import_detections() -> anyhow::Result<()> {
+    // 1. Create area
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    assert_eq!(area_repo.get_area().await?.state, AreaState::Imported);
+
+    // 2. Import a stubbed detection list
+    let detections = vec![
+        HouseNumberDetection { number: "7".to_string(), x: 40, y: 60, radius: 18, confidence: 0.75 },
+        HouseNumberDetection { number: "9b".to_string(), x: 90, y: 64, radius: 21, confidence: 0.5 },
+    ];
+    let imported = AddressRepository::import_detections(&area_repo, &detections).await?;
+
+    // 3. Verify the addresses match the detections
+    assert_eq!(imported.len(), 2);
+    for (address, detection) in imported.iter().zip(&detections) {
+        assert_eq!(address.house_number, detection.number);
+        assert_eq!(address.position, Point { x: detection.x, y: detection.y });
+        assert_eq!(address.circle_radius, detection.radius);
+        assert_eq!(address.confidence, detection.confidence as f64);
+        assert_eq!(address.estimated_flats, None);
+        assert_eq!(address.assigned_street_id, None);
+        assert!(!address.verified);
+    }
+    assert_eq!(AddressRepository::get_addresses(&area_repo).await?, imported);
+
+    // 4. Verify the area moved on
+    assert_eq!(area_repo.get_area().await?.state, AreaState::AddressesDetected);
+
+    Ok(())
+}
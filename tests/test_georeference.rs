@@ -0,0 +1,111 @@
+//! Integration tests for georeferencing map scans.
+//!
+//! Tests cover:
+//! - Round-tripping pixel -> lon/lat -> pixel for a rotated reference
+//! - Scale and orientation of an unrotated reference
+//! - Persisting a georeference per area, and copying it with a duplicated area
+//! - GeoJSON export in lon/lat once a georeference is set
+
+mod common;
+
+use addrslips::core::db::Georeference;
+use common::*;
+
+fn rotated_reference() -> Georeference {
+    Georeference {
+        origin_lat: 52.52,
+        origin_lon: 13.405,
+        meters_per_pixel: 0.5,
+        rotation: 23.0,
+    }
+}
+
+#[test]
+fn test_pixel_lonlat_round_trip_rotated() {
+    let georeference = rotated_reference();
+    for p in [Point { x: 0, y: 0 }, Point { x: 1200, y: 40 }, Point { x: 357, y: 2890 }] {
+        let (lon, lat) = georeference.pixel_to_lonlat(&p);
+        let (x, y) = georeference.lonlat_to_pixel(lon, lat);
+        assert!((x - p.x as f64).abs() < 1e-6, "x {x} should round-trip to {}", p.x);
+        assert!((y - p.y as f64).abs() < 1e-6, "y {y} should round-trip to {}", p.y);
+    }
+}
+
+#[test]
+fn test_unrotated_reference_points_north_up() {
+    let georeference = Georeference {
+        rotation: 0.0,
+        ..rotated_reference()
+    };
+
+    // 2000px right at 0.5 m/px is 1 km east; 2000px down is 1 km south
+    let (lon, lat) = georeference.pixel_to_lonlat(&Point { x: 2000, y: 0 });
+    assert!((lat - 52.52).abs() < 1e-9);
+    assert!(lon > 13.405);
+    let (lon, lat) = georeference.pixel_to_lonlat(&Point { x: 0, y: 2000 });
+    assert!((lon - 13.405).abs() < 1e-9);
+    assert!((52.52 - lat - 1000.0 / 111_195.0).abs() < 1e-6, "1 km south should be ~0.009°, got {lat}");
+}
+
+#[tokio::test]
+async fn test_georeference_persisted_and_used_by_export() -> anyhow::Result<()> {
+    // 1. Create area with one address; no georeference yet
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let address = AddressRepository::add_address(&area_repo, &make_test_address("12", 50, 60)).await?;
+    assert_eq!(area_repo.get_georeference().await?, None);
+
+    // 2. Store a georeference
+    let georeference = rotated_reference();
+    area_repo.set_georeference(Some(&georeference)).await?;
+    assert_eq!(area_repo.get_georeference().await?, Some(georeference));
+
+    // 3. Export uses lon/lat
+    let doc: serde_json::Value = serde_json::from_str(&AddressRepository::export_geojson(&area_repo).await?)?;
+    let (lon, lat) = georeference.pixel_to_lonlat(&address.position);
+    let coordinates = &doc["features"][0]["geometry"]["coordinates"];
+    assert!((coordinates[0].as_f64().unwrap() - lon).abs() < 1e-9, "Unexpected lon {coordinates}");
+    assert!((coordinates[1].as_f64().unwrap() - lat).abs() < 1e-9, "Unexpected lat {coordinates}");
+
+    // 4. Clearing it falls back to pixel coordinates
+    area_repo.set_georeference(None).await?;
+    let doc: serde_json::Value = serde_json::from_str(&AddressRepository::export_geojson(&area_repo).await?)?;
+    assert_eq!(doc["features"][0]["geometry"]["coordinates"], serde_json::json!([50, 60]));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_georeference_is_per_area() -> anyhow::Result<()> {
+    // 1. Create two areas and georeference only the first
+    let (project, _temp_dir) = create_test_project().await;
+    let (first_area, _first_img) = make_new_area("First Area", TEST_RED);
+    let (second_area, _second_img) = make_new_area("Second Area", TEST_BLUE);
+    let first_repo = project.add_area(first_area).await?;
+    let second_repo = project.add_area(second_area).await?;
+    let georeference = rotated_reference();
+    first_repo.set_georeference(Some(&georeference)).await?;
+
+    // 2. The second area still exports pixel coordinates
+    assert_eq!(second_repo.get_georeference().await?, None);
+    AddressRepository::add_address(&second_repo, &make_test_address("7", 10, 20)).await?;
+    let doc: serde_json::Value = serde_json::from_str(&AddressRepository::export_geojson(&second_repo).await?)?;
+    assert_eq!(doc["features"][0]["geometry"]["coordinates"], serde_json::json!([10, 20]));
+
+    // 3. A duplicate shares the image, so it keeps the georeference
+    let first_id = first_repo.get_area().await?.id;
+    let copy_repo = project.duplicate_area(first_id, "Copy").await?;
+    assert_eq!(copy_repo.get_georeference().await?, Some(georeference));
+
+    // 4. Updating one area leaves the other alone
+    let moved = Georeference {
+        rotation: 0.0,
+        ..georeference
+    };
+    copy_repo.set_georeference(Some(&moved)).await?;
+    assert_eq!(copy_repo.get_georeference().await?, Some(moved));
+    assert_eq!(first_repo.get_georeference().await?, Some(georeference));
+
+    Ok(())
+}
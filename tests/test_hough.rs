@@ -0,0 +1,53 @@
+//! Integration tests for Hough circle detection.
+//!
+//! Tests cover:
+//! - Finding the centers of drawn circles on a synthetic map
+
+mod common;
+
+use addrslips::detection::preprocessing::BorderMode;
+use addrslips::detection::steps::*;
+use addrslips::Pipeline;
+
+use common::*;
+
+#[test]
+fn test_hough_finds_three_circles() -> anyhow::Result<()> {
+    let markers = [
+        Marker { x: 70, y: 70, radius: 22, number: "4" },
+        Marker { x: 220, y: 110, radius: 25, number: "18" },
+        Marker { x: 320, y: 230, radius: 20, number: "9" },
+    ];
+    let img = synthetic_map(400, 300, &markers);
+
+    let mut pipeline = Pipeline::new()
+        .add_step_boxed(Box::new(GrayscaleStep))
+        .add_step_boxed(Box::new(BlurStep {
+            sigma: 1.5,
+            border: BorderMode::Replicate,
+        }))
+        .add_step_boxed(Box::new(EdgeDetectionStep {
+            low_threshold: 50.0,
+            high_threshold: 100.0,
+        }))
+        .add_step_boxed(Box::new(HoughCircleStep {
+            min_radius: 15.0,
+            max_radius: 30.0,
+            accumulator_threshold: 0.4,
+            padding: 10,
+        }));
+
+    let results = pipeline.run(img)?;
+    assert_eq!(results.len(), 3, "Expected one detection per marker");
+
+    for marker in &markers {
+        let found = results.iter().any(|item| {
+            let dx = item.get_int("center_x").expect("center_x") - marker.x;
+            let dy = item.get_int("center_y").expect("center_y") - marker.y;
+            dx.abs() <= 3 && dy.abs() <= 3
+        });
+        assert!(found, "No circle found near ({}, {})", marker.x, marker.y);
+    }
+
+    Ok(())
+}
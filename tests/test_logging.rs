@@ -0,0 +1,79 @@
+//! Integration tests for pipeline progress logging.
+//!
+//! Tests cover:
+//! - Step progress being logged at debug level by default
+//! - `with_verbose(true)` raising the same messages to info level
+//!
+//! The capturing logger is global, so nothing else lives in this file.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use addrslips::detection::preprocessing::BorderMode;
+use addrslips::detection::steps::{BlurStep, GrayscaleStep};
+use addrslips::Pipeline;
+use common::*;
+
+/// Records every message logged by the library.
+struct CapturingLogger {
+    records: Mutex<Vec<(log::Level, String)>>,
+}
+
+impl log::Log for CapturingLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.target().starts_with("addrslips")
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            self.records.lock().unwrap().push((record.level(), record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CapturingLogger = CapturingLogger {
+    records: Mutex::new(Vec::new()),
+};
+
+/// Run a two-step pipeline and return the messages it logged.
+fn run_logged(verbose: bool) -> anyhow::Result<Vec<(log::Level, String)>> {
+    LOGGER.records.lock().unwrap().clear();
+    let mut pipeline = Pipeline::new()
+        .with_verbose(verbose)
+        .add_step(Arc::new(GrayscaleStep))
+        .add_step(Arc::new(BlurStep {
+            sigma: 1.5,
+            border: BorderMode::Replicate,
+        }));
+    pipeline.run(synthetic_map(200, 150, &[]))?;
+    Ok(std::mem::take(&mut *LOGGER.records.lock().unwrap()))
+}
+
+#[test]
+fn test_pipeline_progress_is_logged() -> anyhow::Result<()> {
+    log::set_logger(&LOGGER).expect("no other logger in this test binary");
+    log::set_max_level(log::LevelFilter::Trace);
+
+    // 1. Without verbose, every step is logged at debug level
+    let records = run_logged(false)?;
+    let expected = [
+        "Running step: Grayscale Conversion (processing 1 items)",
+        "  → 1 items",
+        "Running step: Gaussian Blur (processing 1 items)",
+        "  → 1 items",
+    ];
+    let messages: Vec<&str> = records.iter().map(|(_, message)| message.as_str()).collect();
+    assert_eq!(messages, expected);
+    assert!(records.iter().all(|(level, _)| *level == log::Level::Debug));
+
+    // 2. Verbose runs log the same messages at info level
+    let records = run_logged(true)?;
+    let messages: Vec<&str> = records.iter().map(|(_, message)| message.as_str()).collect();
+    assert_eq!(messages, expected);
+    assert!(records.iter().all(|(level, _)| *level == log::Level::Info));
+
+    Ok(())
+}
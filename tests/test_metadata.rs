@@ -0,0 +1,118 @@
+//! Integration tests for pipeline metadata accessors.
+//!
+//! Tests cover:
+//! - Typed getters for present, wrong-type and absent keys
+//! - Generic `get_as` and `TryFrom` conversions
+//! - JSON round trips and debug sidecar files
+//! - Mapping recognized regions back to original-image detections
+
+mod common;
+
+use addrslips::detection::steps::GrayscaleStep;
+use addrslips::{BoundingBox, HouseNumberDetection, MetadataValue, Pipeline, PipelineData};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use common::*;
+
+fn item() -> PipelineData {
+    PipelineData::from_image(number_image("1", 10))
+        .with_metadata("count", MetadataValue::Int(7))
+        .with_metadata("brightness", MetadataValue::Float(231.5))
+        .with_metadata("ocr_text", MetadataValue::String("12a".to_string()))
+        .with_metadata("is_circle", MetadataValue::Bool(true))
+}
+
+#[test]
+fn test_get_int_present() {
+    assert_eq!(item().get_int("count"), Some(7));
+}
+
+#[test]
+fn test_get_int_wrong_type_is_none() {
+    assert_eq!(item().get_int("brightness"), None);
+    assert_eq!(item().get_float("count"), None);
+}
+
+#[test]
+fn test_get_absent_key_is_none() {
+    let item = item();
+    assert_eq!(item.get_int("missing"), None);
+    assert_eq!(item.get_as::<bool>("missing"), None);
+}
+
+#[test]
+fn test_get_as_each_type() {
+    let item = item();
+    assert_eq!(item.get_as::<i32>("count"), Some(7));
+    assert_eq!(item.get_as::<f32>("brightness"), Some(231.5));
+    assert_eq!(item.get_as::<String>("ocr_text"), Some("12a".to_string()));
+    assert_eq!(item.get_as::<bool>("is_circle"), Some(true));
+    assert_eq!(item.get_as::<String>("count"), None);
+}
+
+#[test]
+fn test_try_from_metadata_value() {
+    assert_eq!(i32::try_from(MetadataValue::Int(3)).unwrap(), 3);
+    assert!(bool::try_from(MetadataValue::Int(3)).is_err());
+    assert!(matches!(MetadataValue::from(2.5f32), MetadataValue::Float(v) if v == 2.5));
+}
+
+#[test]
+fn test_metadata_json_round_trip() -> anyhow::Result<()> {
+    let item = item();
+    let json = item.metadata_json();
+    assert_eq!(json["count"], serde_json::json!({ "type": "Int", "value": 7 }));
+
+    let restored: HashMap<String, MetadataValue> = serde_json::from_value(json)?;
+    assert_eq!(restored, item.metadata);
+    Ok(())
+}
+
+#[test]
+fn test_debug_mode_writes_metadata_sidecars() -> anyhow::Result<()> {
+    let debug_dir = tempfile::TempDir::new()?;
+    let mut pipeline = Pipeline::new()
+        .with_debug(debug_dir.path().to_path_buf())?
+        .add_step_boxed(Box::new(GrayscaleStep));
+    pipeline.run(number_image("1", 10))?;
+
+    let run_dir = pipeline.debug_run_dir().expect("debug mode is enabled");
+    let step_dir = run_dir.join("01_grayscale_conversion");
+    assert!(step_dir.join("01.png").is_file());
+    let sidecar: HashMap<String, MetadataValue> =
+        serde_json::from_str(&std::fs::read_to_string(step_dir.join("01.json"))?)?;
+    assert!(sidecar.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_region_maps_back_to_original_detection() {
+    // 1. Region at (100, 40) in a 300x200 map, upscaled to 90x60 by later steps
+    let original = Arc::new(image::DynamicImage::new_luma8(300, 200));
+    let bbox = BoundingBox { x: 100, y: 40, width: 30, height: 20 };
+    let region = PipelineData::from_region(image::DynamicImage::new_luma8(90, 60), original, bbox);
+    assert_eq!(region.original_center(), Some((115, 50)));
+
+    // 2. Without OCR text there is no detection
+    assert_eq!(region.to_house_number_detection(), None);
+
+    // 3. OCR results become a detection centered on the original region
+    let recognized = region
+        .with_metadata("ocr_text", MetadataValue::String("17b".to_string()))
+        .with_metadata("ocr_confidence", MetadataValue::Float(0.875));
+    assert_eq!(
+        recognized.to_house_number_detection(),
+        Some(HouseNumberDetection {
+            number: "17b".to_string(),
+            x: 115,
+            y: 50,
+            radius: 10,
+            confidence: 0.875,
+        })
+    );
+
+    // 4. Full-image items have no position in the original
+    assert_eq!(item().original_center(), None);
+    assert_eq!(item().to_house_number_detection(), None);
+}
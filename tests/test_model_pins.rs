@@ -0,0 +1,22 @@
+//! Integration tests for the pinned OCR model downloads.
+//!
+//! Tests cover:
+//! - Every model having a pinned size and a well-formed SHA-256 digest
+
+#![cfg(feature = "download")]
+
+use addrslips::detection::ocr::MODEL_PINS;
+
+#[test]
+fn test_every_model_is_pinned() {
+    for pin in MODEL_PINS {
+        assert!(pin.size > 0, "{} has no pinned size", pin.file);
+        assert_eq!(pin.sha256.len(), 64, "{} digest should be 64 hex characters", pin.file);
+        assert!(
+            pin.sha256.chars().all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)),
+            "{} digest should be lowercase hex, got {}",
+            pin.file,
+            pin.sha256
+        );
+    }
+}
@@ -0,0 +1,192 @@
+//! Integration tests for OCR post-processing.
+//!
+//! Tests cover:
+//! - Normalizing raw OCR text into house numbers
+//! - Template matching of single digits, including a differently drawn pixel font
+//! - Joining widely spaced digits into one house number
+//! - Proportional OCR canvases keeping multi-digit glyphs larger than the square canvas
+//! - Choosing the OCR canvas size on `DetectionPipeline`
+
+mod common;
+
+use addrslips::detection::ocr::{
+    classify_single_digit, fit_to_canvas, init_ocr_engine, join_digit_tokens, normalize_house_number,
+    ocr_canvas_size, recognize_text, TextToken, DIGIT_TEMPLATE_MIN_SCORE, OCR_TARGET_SIZE,
+};
+use addrslips::DetectionPipeline;
+use common::*;
+
+#[test]
+fn test_normalize_plain_number() {
+    assert_eq!(normalize_house_number("12"), Some("12".to_string()));
+}
+
+#[test]
+fn test_normalize_strips_whitespace() {
+    assert_eq!(normalize_house_number(" 7 "), Some("7".to_string()));
+}
+
+#[test]
+fn test_normalize_keeps_letter_suffix() {
+    assert_eq!(normalize_house_number("12a"), Some("12a".to_string()));
+}
+
+#[test]
+fn test_normalize_rejects_letters_only() {
+    assert_eq!(normalize_house_number("O"), None);
+}
+
+#[test]
+fn test_normalize_strips_punctuation() {
+    assert_eq!(normalize_house_number("!3."), Some("3".to_string()));
+}
+
+#[test]
+fn test_classify_single_digits() {
+    for glyph_height in [16, 24, 32] {
+        for digit in '0'..='9' {
+            let roi = number_image(&digit.to_string(), glyph_height).to_luma8();
+            let (classified, score) = classify_single_digit(&roi).expect("digit should be classified");
+            assert_eq!(classified, digit, "{glyph_height}px '{digit}' classified as '{classified}'");
+            assert!(
+                score >= DIGIT_TEMPLATE_MIN_SCORE,
+                "{glyph_height}px '{digit}' scored only {score}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_classify_pixel_font_digits() {
+    // A font drawn unlike both the templates and `number_image`: 3 font pixels wide, a
+    // serifed "1", at several block sizes, with crisp and with blurred edges
+    for (cell_w, cell_h) in [(3, 4), (4, 5), (6, 8)] {
+        for soften in [false, true] {
+            for digit in '0'..='9' {
+                let roi = pixel_font_image(digit, cell_w, cell_h, soften).to_luma8();
+                let (classified, score) = classify_single_digit(&roi).expect("digit should be classified");
+                assert_eq!(
+                    classified, digit,
+                    "{cell_w}x{cell_h} (soften: {soften}) '{digit}' classified as '{classified}' ({score})"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_classify_blank_roi_is_none() {
+    let blank = image::GrayImage::from_pixel(30, 30, image::Luma([255u8]));
+    assert_eq!(classify_single_digit(&blank), None);
+}
+
+fn token(text: &str, confidence: f32, left: i32, top: i32) -> TextToken {
+    TextToken {
+        text: text.to_string(),
+        confidence,
+        left,
+        top,
+        bottom: top + 20,
+    }
+}
+
+#[test]
+fn test_join_digit_tokens_orders_by_position() {
+    let tokens = [token("2", 0.9, 60, 10), token("1", 0.8, 10, 12)];
+    assert_eq!(join_digit_tokens(&tokens), Some(("12".to_string(), 0.8)));
+}
+
+#[test]
+fn test_join_digit_tokens_needs_two_tokens_on_one_row() {
+    // A single token is left to the regular candidate selection
+    assert_eq!(join_digit_tokens(&[token("12", 0.9, 10, 10)]), None);
+
+    // Stacked lines are not one number
+    let stacked = [token("1", 0.9, 10, 10), token("2", 0.8, 10, 40)];
+    assert_eq!(join_digit_tokens(&stacked), None);
+}
+
+#[test]
+fn test_join_digit_tokens_skips_non_digit_tokens() {
+    let tokens = [token("1", 0.9, 10, 10), token("a", 0.95, 40, 10), token("2", 0.7, 70, 10)];
+    assert_eq!(join_digit_tokens(&tokens), Some(("12".to_string(), 0.7)));
+}
+
+#[test]
+#[ignore = "requires OCR models in ~/.cache/ocrs"]
+fn test_recognize_text_joins_spaced_digits() -> anyhow::Result<()> {
+    let engine = init_ocr_engine()?;
+    // Spaces leave gaps wide enough for OCR to report separate lines
+    let img = number_image("1    2", 30);
+
+    let (text, _) = recognize_text(&engine, &img).expect("digits should be recognized");
+    assert_eq!(text, "12");
+
+    Ok(())
+}
+
+/// Width and height of the dark pixels' bounding box
+fn ink_size(img: &image::GrayImage) -> (u32, u32) {
+    let ink: Vec<(u32, u32)> = img
+        .enumerate_pixels()
+        .filter(|(_, _, pixel)| pixel[0] < 128)
+        .map(|(x, y, _)| (x, y))
+        .collect();
+    let span = |coords: Vec<u32>| coords.iter().max().unwrap() - coords.iter().min().unwrap() + 1;
+    (span(ink.iter().map(|p| p.0).collect()), span(ink.iter().map(|p| p.1).collect()))
+}
+
+#[test]
+fn test_proportional_canvas_keeps_glyphs_larger() {
+    // 60x40 crop with three 10x20 digits
+    let roi = number_image("123", 20).to_luma8();
+    let (width, height) = roi.dimensions();
+    assert_eq!((width, height), (60, 40));
+
+    // 1. The square canvas ignores the aspect ratio; the proportional one widens with it
+    assert_eq!(ocr_canvas_size(width, height, OCR_TARGET_SIZE, None), (100, 100));
+    assert_eq!(ocr_canvas_size(width, height, OCR_TARGET_SIZE, Some(100)), (150, 100));
+    // Crops taller than wide never get a canvas narrower than it is tall
+    assert_eq!(ocr_canvas_size(20, 60, OCR_TARGET_SIZE, Some(100)), (100, 100));
+
+    // 2. The square canvas scales the crop by 100 / 60, the proportional one by 100 / 40,
+    //    so the digits come out 1.5 times as large
+    let square = fit_to_canvas(&roi, 100, 100);
+    let proportional = fit_to_canvas(&roi, 150, 100);
+    let (square_w, square_h) = ink_size(&square);
+    let (proportional_w, proportional_h) = ink_size(&proportional);
+    let growth = proportional_h as f32 / square_h as f32;
+    assert!((growth - 1.5).abs() < 0.1, "glyphs grew {growth}x ({square_h}px to {proportional_h}px)");
+
+    // 3. Neither canvas distorts the digits
+    let square_aspect = square_w as f32 / square_h as f32;
+    let proportional_aspect = proportional_w as f32 / proportional_h as f32;
+    assert!(
+        (square_aspect - proportional_aspect).abs() < 0.1,
+        "ink aspect {square_aspect} on the square canvas, {proportional_aspect} on the proportional one"
+    );
+}
+
+#[test]
+fn test_detection_pipeline_ocr_canvas_size() {
+    // 1. A 60x60 circle ROI with a wide 30x10 blob of ink in the middle
+    let mut roi = image::GrayImage::from_pixel(60, 60, image::Luma([255]));
+    for x in 15..45 {
+        for y in 25..35 {
+            roi.put_pixel(x, y, image::Luma([0]));
+        }
+    }
+    let roi = image::DynamicImage::ImageLuma8(roi);
+
+    // 2. By default the ink lands on the 100x100 canvas
+    let default = DetectionPipeline::new().preprocess_roi(&roi);
+    assert_eq!((default.width(), default.height()), (OCR_TARGET_SIZE, OCR_TARGET_SIZE));
+
+    // 3. A different square size changes the canvas
+    let larger = DetectionPipeline::new().with_ocr_canvas(160, None).preprocess_roi(&roi);
+    assert_eq!((larger.width(), larger.height()), (160, 160));
+
+    // 4. A target height widens the canvas with the 40x20 crop
+    let proportional = DetectionPipeline::new().with_ocr_canvas(160, Some(64)).preprocess_roi(&roi);
+    assert_eq!((proportional.width(), proportional.height()), (128, 64));
+}
@@ -0,0 +1,39 @@
+//! Integration tests for locating the OCR models.
+//!
+//! Tests cover:
+//! - Missing models without the `download` feature failing with an actionable error
+//!
+//! `HOME` is redirected for the whole test binary, so nothing else lives in this file.
+
+#![cfg(not(feature = "download"))]
+
+use addrslips::detection::ocr::{ensure_models, init_ocr_engine};
+use addrslips::PipelineError;
+
+#[test]
+fn test_missing_models_error_points_at_ensure_models() -> anyhow::Result<()> {
+    // 1. Point the model cache at an empty home directory
+    let home = tempfile::TempDir::new()?;
+    std::env::set_var("HOME", home.path());
+    let cache_dir = home.path().join(".cache/ocrs");
+
+    // 2. ensure_models reports both expected paths instead of touching the network
+    let err = ensure_models().unwrap_err();
+    match err.downcast_ref::<PipelineError>() {
+        Some(PipelineError::OcrModelsMissing { detection, recognition }) => {
+            assert_eq!(detection, &cache_dir.join("text-detection.rten"));
+            assert_eq!(recognition, &cache_dir.join("text-recognition.rten"));
+        }
+        other => panic!("expected OcrModelsMissing, got {:?}", other),
+    }
+    let message = err.to_string();
+    assert!(message.contains("ensure_models"), "unexpected error: {message}");
+    assert!(message.contains("`download` feature"), "unexpected error: {message}");
+    assert!(!cache_dir.exists(), "nothing should be written without the download feature");
+
+    // 3. Creating an engine fails the same way
+    let err = init_ocr_engine().err().expect("engine needs the models");
+    assert!(err.to_string().contains("ensure_models"), "unexpected error: {err}");
+
+    Ok(())
+}
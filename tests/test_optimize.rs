@@ -0,0 +1,59 @@
+//! Integration tests for database optimization.
+//!
+//! Tests cover:
+//! - Reclaiming free pages left behind by deletions
+//! - Data surviving optimization and a save/load cycle
+
+mod common;
+
+use common::*;
+
+/// Size of `project.db` inside a saved project archive.
+fn packed_db_size(project_path: &std::path::Path) -> anyhow::Result<u64> {
+    archive_entries(project_path)?
+        .into_iter()
+        .find(|(path, _)| path.as_os_str() == "project.db")
+        .map(|(_, size)| size)
+        .ok_or_else(|| anyhow::anyhow!("project.db missing from archive"))
+}
+
+#[tokio::test]
+async fn test_optimize_shrinks_database() -> anyhow::Result<()> {
+    let (project, temp_dir) = create_test_project().await;
+    let project_path = temp_dir.path().join("test.addrslips");
+
+    // 1. Insert many addresses and delete all but one
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let batch: Vec<NewAddress> = (0..2000)
+        .map(|i| make_test_address(&format!("{i}-{}", "x".repeat(100)), i % TEST_IMAGE_SIZE, i % TEST_IMAGE_SIZE))
+        .collect();
+    let mut addresses = area_repo.add_addresses(&batch).await?;
+    let kept = addresses.pop().expect("batch is not empty");
+    for address in addresses {
+        area_repo.delete_address(address).await?;
+    }
+    area_repo.purge_deleted().await?;
+
+    // 2. Save without optimizing; the free pages are packed too
+    project.save_project().await?;
+    let before = packed_db_size(&project_path)?;
+
+    // 3. Optimize and save again
+    project.optimize().await?;
+    project.save_project().await?;
+    let after = packed_db_size(&project_path)?;
+    assert!(after < before, "expected {after} < {before}");
+
+    // 4. The remaining address is still there after reopening
+    drop(area_repo);
+    drop(project);
+    let project = ProjectDb::new(&project_path).await?;
+    let areas = project.get_areas().await?;
+    let area_repo = project.get_area_repo(areas[0].id).await?;
+    let remaining = AddressRepository::get_addresses(&area_repo).await?;
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].house_number, kept.house_number);
+
+    Ok(())
+}
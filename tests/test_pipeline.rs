@@ -0,0 +1,518 @@
+//! Integration tests for pipeline execution.
+//!
+//! Tests cover:
+//! - Parallel executor producing the same results as sequential execution
+//! - Executors passing all items at once to steps that need them, e.g. Otsu's white filter
+//! - Cancelling a run through its cancel token
+//! - Step failures reported as `StepFailed` naming the step
+//! - Disabling and re-enabling steps without rebuilding the pipeline
+//! - Profiling item counts per step
+//! - Executor stats: funnel totals and the slowest step
+//! - Progress events for every step
+//! - HTML lineage report for debug runs
+//! - Separate debug folders for repeated runs
+
+mod common;
+
+use addrslips::detection::contours::Connectivity;
+use addrslips::detection::preprocessing::BorderMode;
+use addrslips::detection::steps::*;
+use addrslips::{
+    CancelToken, Pipeline, PipelineContext, PipelineData, PipelineError, PipelineStep, ProgressEvent,
+    ProgressPhase, StepProfile,
+};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use common::*;
+
+/// Circle detection without OCR, so tests don't need the OCR models.
+fn circle_pipeline() -> Pipeline {
+    Pipeline::new()
+        .add_step_boxed(Box::new(GrayscaleStep))
+        .add_step_boxed(Box::new(BlurStep {
+            sigma: 1.5,
+            border: BorderMode::Replicate,
+        }))
+        .add_step_boxed(Box::new(EdgeDetectionStep {
+            low_threshold: 50.0,
+            high_threshold: 100.0,
+        }))
+        .add_step_boxed(Box::new(ContourDetectionStep {
+            min_area: 10,
+            padding: 10,
+            max_area: None,
+            reject_border: false,
+            connectivity: Connectivity::Eight,
+        }))
+        .add_step_boxed(Box::new(CircleFilterStep {
+            min_radius: 10.0,
+            max_radius: 200.0,
+            circularity_threshold: 2.0,
+        }))
+        .add_step_boxed(Box::new(WhiteCircleFilterStep {
+            brightness_threshold: Some(200.0),
+        }))
+}
+
+fn test_map() -> image::DynamicImage {
+    synthetic_map(
+        400,
+        300,
+        &[
+            Marker { x: 60, y: 60, radius: 20, number: "1" },
+            Marker { x: 200, y: 80, radius: 24, number: "12" },
+            Marker { x: 320, y: 200, radius: 22, number: "7" },
+            Marker { x: 120, y: 230, radius: 18, number: "3" },
+        ],
+    )
+}
+
+/// Bounding boxes of the results, sorted so execution order doesn't matter.
+fn sorted_boxes(results: &[PipelineData]) -> Vec<(u32, u32, u32, u32)> {
+    let mut boxes: Vec<_> = results
+        .iter()
+        .map(|item| {
+            let bbox = item.bbox.as_ref().expect("Circles carry a bounding box");
+            (bbox.x, bbox.y, bbox.width, bbox.height)
+        })
+        .collect();
+    boxes.sort();
+    boxes
+}
+
+#[test]
+fn test_parallel_executor_matches_sequential() -> anyhow::Result<()> {
+    let pipeline = circle_pipeline();
+    let img = test_map();
+
+    let sequential = pipeline.run_with_executor(img.clone(), 1)?;
+    let parallel = pipeline.run_with_executor(img, 4)?;
+
+    assert!(!sequential.is_empty(), "Expected the markers to be detected");
+    assert_eq!(sorted_boxes(&sequential), sorted_boxes(&parallel));
+
+    Ok(())
+}
+
+/// Passes data through, recording how many items each `process` call received.
+struct BatchRecorder(Arc<Mutex<Vec<usize>>>);
+
+impl PipelineStep for BatchRecorder {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
+        self.0.lock().unwrap().push(data.len());
+        Ok(data)
+    }
+
+    fn name(&self) -> &str {
+        "Batch Recorder"
+    }
+
+    fn needs_all_items(&self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn test_executor_passes_all_items_to_collecting_step() -> anyhow::Result<()> {
+    // 1. The sequential run hands all circles to the step in one call
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let pipeline = circle_pipeline().add_step_boxed(Box::new(BatchRecorder(batches.clone())));
+    let expected = pipeline.run(test_map())?.len();
+    assert!(expected > 1, "Expected several markers to be detected");
+    assert_eq!(*batches.lock().unwrap(), vec![expected]);
+
+    // 2. So do both executors, although they otherwise pass items one at a time
+    for num_threads in [1, 4] {
+        batches.lock().unwrap().clear();
+        let results = pipeline.run_with_executor(test_map(), num_threads)?;
+        assert_eq!(results.len(), expected);
+        assert_eq!(*batches.lock().unwrap(), vec![expected], "{num_threads} threads");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_otsu_white_filter_matches_in_executor() -> anyhow::Result<()> {
+    // 1. Circle detection with Otsu's threshold instead of a fixed one
+    let pipeline = circle_pipeline().add_step_boxed(Box::new(WhiteCircleFilterStep {
+        brightness_threshold: None,
+    }));
+    let expected = sorted_boxes(&pipeline.run(test_map())?);
+
+    // 2. The executors pick the threshold over the same candidates, so they keep the same circles
+    for num_threads in [1, 4] {
+        let results = pipeline.run_with_executor(test_map(), num_threads)?;
+        assert_eq!(sorted_boxes(&results), expected, "{num_threads} threads");
+    }
+
+    Ok(())
+}
+
+/// Passes data through and cancels the run.
+struct CancelStep(CancelToken);
+
+impl PipelineStep for CancelStep {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
+        self.0.cancel();
+        Ok(data)
+    }
+
+    fn name(&self) -> &str {
+        "Cancel"
+    }
+}
+
+/// Sleeps long enough that running it would make the test noticeably slow.
+struct SlowStep;
+
+impl PipelineStep for SlowStep {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
+        std::thread::sleep(Duration::from_secs(5));
+        Ok(data)
+    }
+
+    fn name(&self) -> &str {
+        "Slow"
+    }
+}
+
+fn cancelling_pipeline() -> Pipeline {
+    let token = CancelToken::new();
+    Pipeline::new()
+        .with_cancel_token(token.clone())
+        .add_step_boxed(Box::new(CancelStep(token)))
+        .add_step_boxed(Box::new(SlowStep))
+}
+
+fn assert_cancelled(result: Result<Vec<PipelineData>, PipelineError>) {
+    let err = result.err().expect("Cancelled run must fail");
+    assert!(matches!(err, PipelineError::Cancelled), "Unexpected error: {err}");
+}
+
+#[test]
+fn test_cancel_after_first_step() {
+    // 1. Sequential run stops before the slow step
+    let start = Instant::now();
+    assert_cancelled(cancelling_pipeline().run(test_map()));
+    assert!(start.elapsed() < Duration::from_secs(1));
+
+    // 2. So do the executors
+    for num_threads in [1, 4] {
+        let start = Instant::now();
+        assert_cancelled(cancelling_pipeline().run_with_executor(test_map(), num_threads));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
+
+/// Fails every time with a plain `anyhow` error.
+struct FailingStep;
+
+impl PipelineStep for FailingStep {
+    fn process(&self, _data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
+        Err(anyhow::anyhow!("no circles today").into())
+    }
+
+    fn name(&self) -> &str {
+        "Failing"
+    }
+}
+
+fn assert_failed_in_step(result: Result<Vec<PipelineData>, PipelineError>) {
+    match result {
+        Err(PipelineError::StepFailed { name, source }) => {
+            assert_eq!(name, "Failing");
+            assert_eq!(source.to_string(), "no circles today");
+        }
+        other => panic!("Expected StepFailed, got {:?}", other.map(|data| data.len())),
+    }
+}
+
+#[test]
+fn test_failing_step_reported_by_name() {
+    let failing_pipeline = || circle_pipeline().add_step_boxed(Box::new(FailingStep));
+
+    // 1. Sequential run
+    assert_failed_in_step(failing_pipeline().run(test_map()));
+
+    // 2. Both executors
+    for num_threads in [1, 4] {
+        assert_failed_in_step(failing_pipeline().run_with_executor(test_map(), num_threads));
+    }
+
+    // 3. The anyhow conversion keeps the structured error reachable
+    let err: anyhow::Error = failing_pipeline().run(test_map()).unwrap_err().into();
+    assert!(err.to_string().contains("'Failing'"));
+    assert!(matches!(err.downcast_ref::<PipelineError>(), Some(PipelineError::StepFailed { .. })));
+}
+
+#[test]
+fn test_disabled_step_is_skipped() -> anyhow::Result<()> {
+    // 1. Contours with every step enabled
+    let mut pipeline = Pipeline::new()
+        .add_step_boxed(Box::new(GrayscaleStep))
+        .add_step_boxed(Box::new(BlurStep {
+            sigma: 1.5,
+            border: BorderMode::Replicate,
+        }))
+        .add_step_boxed(Box::new(EdgeDetectionStep {
+            low_threshold: 50.0,
+            high_threshold: 100.0,
+        }))
+        .add_step_boxed(Box::new(ContourDetectionStep {
+            min_area: 10,
+            padding: 10,
+            max_area: None,
+            reject_border: false,
+            connectivity: Connectivity::Eight,
+        }));
+    assert_eq!(
+        pipeline.step_names(),
+        ["Grayscale Conversion", "Gaussian Blur", "Edge Detection", "Contour Detection"]
+    );
+    let blurred = pipeline.run(test_map())?.len();
+
+    // 2. Without blur the raw edges yield a different contour count
+    pipeline.set_step_enabled(1, false)?;
+    assert_eq!(pipeline.is_step_enabled(1), Some(false));
+    let unblurred = pipeline.run(test_map())?.len();
+    assert_ne!(unblurred, blurred);
+    assert_eq!(pipeline.run_with_executor(test_map(), 4)?.len(), unblurred);
+
+    // 3. Re-enabling restores the original result
+    pipeline.set_step_enabled(1, true)?;
+    assert_eq!(pipeline.run(test_map())?.len(), blurred);
+
+    // 4. Unknown indices are rejected
+    assert!(pipeline.set_step_enabled(4, false).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_profile_counts_items_per_step() -> anyhow::Result<()> {
+    // 1. Profile the circle pipeline on the four-marker map
+    let profiles = circle_pipeline().profile(test_map())?;
+    assert_eq!(profiles.len(), 6);
+    let profile = |name: &str| -> &StepProfile {
+        profiles.iter().find(|profile| profile.step_name == name).expect("step is profiled")
+    };
+
+    // 2. Image steps map the single input image 1 -> 1
+    for name in ["Grayscale Conversion", "Gaussian Blur", "Edge Detection"] {
+        assert_eq!((profile(name).input_count, profile(name).output_count), (1, 1), "{name}");
+    }
+
+    // 3. Contour detection expands 1 -> N, the filters shrink N -> M
+    let contours = profile("Contour Detection");
+    assert_eq!(contours.input_count, 1);
+    assert!(contours.output_count > 1);
+    let circles = profile("Circle Filtering");
+    assert_eq!(circles.input_count, contours.output_count);
+    assert!(circles.output_count < circles.input_count);
+    let white = profile("White Circle Filtering");
+    assert_eq!(white.input_count, circles.output_count);
+    assert!(white.output_count <= white.input_count);
+    assert!(white.output_count > 0, "Expected the markers to be detected");
+
+    // 4. Counts chain from step to step
+    for pair in profiles.windows(2) {
+        assert_eq!(pair[0].output_count, pair[1].input_count);
+    }
+
+    Ok(())
+}
+
+/// Passes items through unchanged, taking a while for each, like OCR does
+struct SlowStep;
+
+impl PipelineStep for SlowStep {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
+        std::thread::sleep(Duration::from_millis(20) * data.len() as u32);
+        Ok(data)
+    }
+
+    fn name(&self) -> &str {
+        "Slow Recognition"
+    }
+}
+
+#[test]
+fn test_executor_stats_find_slowest_step() -> anyhow::Result<()> {
+    let pipeline = circle_pipeline().add_step(Arc::new(SlowStep));
+    let funnel_total: usize = pipeline.profile(test_map())?.iter().map(|profile| profile.input_count).sum();
+
+    for num_threads in [1, 4] {
+        let (results, stats) = pipeline.run_with_executor_stats(test_map(), num_threads)?;
+        assert!(!results.is_empty(), "Expected the markers to be detected");
+
+        // 1. Every item passing through every step is counted once
+        assert_eq!(stats.items_processed, funnel_total, "{num_threads} threads");
+        assert_eq!(stats.steps_run, 7);
+        assert_eq!(stats.per_step_time.len(), 7);
+
+        // 2. The slow step dominates, once per detected marker
+        let (slowest, elapsed) = stats.slowest_step().expect("steps ran");
+        assert_eq!(slowest, "Slow Recognition");
+        assert!(elapsed >= Duration::from_millis(20) * results.len() as u32);
+        assert!(stats.wall_time > Duration::ZERO);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "requires OCR models in ~/.cache/ocrs"]
+fn test_executor_stats_ocr_dominates_standard_pipeline() -> anyhow::Result<()> {
+    let pipeline = addrslips::detection::build_standard_pipeline(false, false);
+    let funnel_total: usize = pipeline.profile(test_map())?.iter().map(|profile| profile.input_count).sum();
+
+    let (_, stats) = pipeline.run_with_executor_stats(test_map(), 4)?;
+    assert_eq!(stats.items_processed, funnel_total);
+    assert_eq!(stats.slowest_step().map(|(name, _)| name), Some("OCR Recognition"));
+
+    Ok(())
+}
+
+/// Runs `pipeline` through `run_pipeline` and returns the progress events it emitted.
+fn collect_progress(
+    pipeline: Pipeline,
+    run_pipeline: impl FnOnce(Pipeline) -> Result<Vec<PipelineData>, PipelineError>,
+) -> anyhow::Result<Vec<ProgressEvent>> {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    let pipeline = pipeline.on_progress(Arc::new(move |event| sink.lock().unwrap().push(event)));
+    run_pipeline(pipeline)?;
+    let events = events.lock().unwrap().clone();
+    Ok(events)
+}
+
+/// Asserts that each step's item count never decreases.
+fn assert_monotonic_per_step(events: &[ProgressEvent]) {
+    for step_index in 0..events[0].total_steps {
+        let counts: Vec<usize> = events
+            .iter()
+            .filter(|event| event.step_index == step_index)
+            .map(|event| event.items_processed)
+            .collect();
+        assert!(!counts.is_empty(), "no events for step {step_index}");
+        assert!(counts.windows(2).all(|pair| pair[0] <= pair[1]), "step {step_index}: {counts:?}");
+    }
+}
+
+#[test]
+fn test_progress_events_sequential() -> anyhow::Result<()> {
+    // 1. Run sequentially while collecting events
+    let events = collect_progress(circle_pipeline(), |mut pipeline| pipeline.run(test_map()))?;
+
+    // 2. Every step reports a start and a finish, in order
+    let sequence: Vec<(&str, ProgressPhase)> = events
+        .iter()
+        .map(|event| (event.step_name.as_str(), event.phase))
+        .collect();
+    let mut expected = Vec::new();
+    for name in [
+        "Grayscale Conversion",
+        "Gaussian Blur",
+        "Edge Detection",
+        "Contour Detection",
+        "Circle Filtering",
+        "White Circle Filtering",
+    ] {
+        expected.push((name, ProgressPhase::Started));
+        expected.push((name, ProgressPhase::Finished));
+    }
+    assert_eq!(sequence, expected);
+    assert!(events.iter().all(|event| event.total_steps == 6));
+    assert_monotonic_per_step(&events);
+
+    // 3. Finishing the contour step reports the single edge image it consumed
+    assert_eq!(events[7].items_processed, 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_progress_events_executor() -> anyhow::Result<()> {
+    for num_threads in [1, 4] {
+        // 1. Run through the executor while collecting events
+        let events = collect_progress(circle_pipeline(), |pipeline| {
+            pipeline.run_with_executor(test_map(), num_threads)
+        })?;
+
+        // 2. Each step starts as often as it finishes and counts never decrease
+        for step_index in 0..6 {
+            let phases = |phase| {
+                events
+                    .iter()
+                    .filter(|event| event.step_index == step_index && event.phase == phase)
+                    .count()
+            };
+            assert_eq!(phases(ProgressPhase::Started), phases(ProgressPhase::Finished));
+        }
+        assert_monotonic_per_step(&events);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_debug_report_links_step_folders() -> anyhow::Result<()> {
+    // 1. Run in debug mode through the executor, which names files by lineage
+    let debug_dir = tempfile::TempDir::new()?;
+    let pipeline = circle_pipeline().with_debug(debug_dir.path().to_path_buf())?;
+    pipeline.run_with_executor(test_map(), 1)?;
+
+    // 2. Write the report
+    let run_dir = pipeline.debug_run_dir().expect("debug mode is enabled");
+    let report_path = pipeline.write_debug_report()?;
+    assert_eq!(report_path, run_dir.join("index.html"));
+    let html = std::fs::read_to_string(&report_path)?;
+
+    // 3. Every step folder is referenced with relative paths
+    let mut step_dirs = 0;
+    for entry in std::fs::read_dir(run_dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            assert!(html.contains(&format!("<h2>{name}</h2>")), "missing step {name}");
+            step_dirs += 1;
+        }
+    }
+    assert_eq!(step_dirs, 7);
+    assert!(html.contains("src=\"00_input/01.png\""));
+    assert!(!html.contains(&debug_dir.path().to_string_lossy().into_owned()));
+
+    // 4. Items are linked to their parents
+    assert!(html.contains("parent: <a href=\"#00_input/01\">01</a>"));
+    assert!(html.contains("children: "));
+
+    Ok(())
+}
+
+#[test]
+fn test_debug_runs_get_separate_folders() -> anyhow::Result<()> {
+    // 1. Run twice into the same root, most likely within the same second
+    let debug_root = tempfile::TempDir::new()?;
+    let mut first = circle_pipeline().with_debug(debug_root.path().to_path_buf())?;
+    first.run(test_map())?;
+    let mut second = circle_pipeline().with_debug(debug_root.path().to_path_buf())?;
+    second.run(test_map())?;
+
+    // 2. Each run wrote into its own folder inside the root
+    let first_dir = first.debug_run_dir().expect("debug mode is enabled");
+    let second_dir = second.debug_run_dir().expect("debug mode is enabled");
+    assert_ne!(first_dir, second_dir);
+    for run_dir in [first_dir, second_dir] {
+        assert_eq!(run_dir.parent(), Some(debug_root.path()));
+        assert!(run_dir.file_name().unwrap().to_string_lossy().starts_with("run_"));
+        assert!(run_dir.join("00_input").join("01.png").is_file());
+    }
+    assert_eq!(std::fs::read_dir(debug_root.path())?.count(), 2);
+
+    // 3. The strict variant still refuses a non-empty directory
+    assert!(circle_pipeline().with_debug_strict(debug_root.path().to_path_buf()).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+//! Integration tests for building pipelines from TOML configs.
+//!
+//! Tests cover:
+//! - Building the steps of a sample config in order
+//! - Optional step fields falling back to their defaults
+//! - Clear errors for unknown steps, unknown fields and bad colors
+
+use addrslips::detection::steps::from_config;
+use addrslips::Pipeline;
+
+const SAMPLE_CONFIG: &str = r##"
+[[steps]]
+name = "grayscale"
+
+[[steps]]
+name = "blur"
+sigma = 2.0
+
+[[steps]]
+name = "edge_detection"
+low_threshold = 40.0
+high_threshold = 90.0
+
+[[steps]]
+name = "morphology"
+operation = "close"
+kernel_size = 3
+
+[[steps]]
+name = "contour_detection"
+min_area = 25
+padding = 10
+connectivity = "four"
+
+[[steps]]
+name = "circle_filter"
+min_radius = 15.0
+max_radius = 150.0
+circularity_threshold = 1.5
+
+[[steps]]
+name = "color_circle_filter"
+target = "#ffffff"
+tolerance = 40.0
+
+[[steps]]
+name = "background_removal"
+
+[[steps]]
+name = "upscale"
+target_size = 100
+
+[[steps]]
+name = "ocr"
+rotations = [0.0, -15.0, 15.0]
+
+[[steps]]
+name = "confidence_filter"
+min_confidence = 0.5
+
+[[steps]]
+name = "nms_by_text"
+iou_threshold = 0.5
+"##;
+
+#[test]
+fn test_sample_config_builds_steps_in_order() -> anyhow::Result<()> {
+    let pipeline = from_config(SAMPLE_CONFIG)?
+        .into_iter()
+        .fold(Pipeline::new(), |pipeline, step| pipeline.add_step(step));
+
+    assert_eq!(
+        pipeline.step_names(),
+        [
+            "Grayscale Conversion",
+            "Gaussian Blur",
+            "Edge Detection",
+            "Morphology",
+            "Contour Detection",
+            "Circle Filtering",
+            "Color Circle Filtering",
+            "Background Removal",
+            "Upscale",
+            "OCR Recognition",
+            "Confidence Filtering",
+            "Text Non-Maximum Suppression",
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_empty_config_has_no_steps() -> anyhow::Result<()> {
+    assert!(from_config("steps = []")?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_unknown_step_is_rejected() {
+    let err = from_config("[[steps]]\nname = \"sharpen_more\"\n").err().expect("unknown step must fail");
+    let message = err.to_string();
+    assert!(message.contains("sharpen_more"), "Unexpected error: {message}");
+}
+
+#[test]
+fn test_unknown_field_and_bad_color_are_rejected() {
+    // Misspelled parameter
+    let err = from_config("[[steps]]\nname = \"blur\"\nsigam = 1.5\n").err().expect("unknown field must fail");
+    assert!(err.to_string().contains("sigam"), "Unexpected error: {err}");
+
+    // Missing required parameter
+    assert!(from_config("[[steps]]\nname = \"blur\"\n").is_err());
+
+    // Unparseable color
+    let err = from_config("[[steps]]\nname = \"color_circle_filter\"\ntarget = \"red\"\ntolerance = 10.0\n")
+        .err()
+        .expect("bad color must fail");
+    assert!(err.to_string().contains("red"), "Unexpected error: {err}");
+}
@@ -0,0 +1,50 @@
+//! Integration tests for the point geometry helpers.
+//!
+//! Tests cover:
+//! - Euclidean and squared distance between points
+//! - Point-in-polygon for points inside, outside and on the edge of a square
+//! - Degenerate polygons containing nothing
+
+mod common;
+
+use addrslips::core::db::polygon_contains;
+use common::*;
+
+fn square() -> Vec<Point> {
+    vec![
+        Point { x: 10, y: 10 },
+        Point { x: 20, y: 10 },
+        Point { x: 20, y: 20 },
+        Point { x: 10, y: 20 },
+    ]
+}
+
+#[test]
+fn test_point_distance() {
+    let a = Point { x: 1, y: 2 };
+    let b = Point { x: 4, y: 6 };
+    assert_eq!(a.distance_squared(&b), 25.0);
+    assert_eq!(a.distance(&b), 5.0);
+    assert_eq!(b.distance(&a), 5.0);
+    assert_eq!(a.distance(&a), 0.0);
+}
+
+#[test]
+fn test_polygon_contains_square() {
+    let square = square();
+    assert!(polygon_contains(&square, &Point { x: 15, y: 15 }));
+    assert!(!polygon_contains(&square, &Point { x: 25, y: 15 }));
+    assert!(!polygon_contains(&square, &Point { x: 5, y: 10 }));
+    // Edges and corners count as inside
+    assert!(polygon_contains(&square, &Point { x: 20, y: 15 }));
+    assert!(polygon_contains(&square, &Point { x: 15, y: 10 }));
+    assert!(polygon_contains(&square, &Point { x: 10, y: 20 }));
+}
+
+#[test]
+fn test_polygon_contains_degenerate() {
+    let p = Point { x: 0, y: 0 };
+    assert!(!polygon_contains(&[], &p));
+    assert!(!polygon_contains(&[p], &p));
+    assert!(!polygon_contains(&[p, Point { x: 5, y: 0 }], &p));
+}
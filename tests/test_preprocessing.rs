@@ -0,0 +1,85 @@
+//! Integration tests for image preprocessing helpers.
+//!
+//! Tests cover:
+//! - Otsu thresholding separating a bimodal image
+//! - Otsu thresholding on a uniform image
+//! - CLAHE widening the dynamic range of low-contrast images
+//! - Blur border modes on a uniform image
+
+use addrslips::detection::preprocessing::{apply_blur_with_border, clahe, otsu_threshold, BorderMode};
+use image::{GrayImage, Luma};
+
+#[test]
+fn test_otsu_threshold_separates_bimodal_image() {
+    // Left half dark (40), right half bright (220)
+    let img = GrayImage::from_fn(100, 50, |x, _| if x < 50 { Luma([40]) } else { Luma([220]) });
+
+    let threshold = otsu_threshold(&img);
+    assert!(
+        threshold > 40 && threshold < 220,
+        "Threshold {threshold} should lie between the two peaks"
+    );
+}
+
+#[test]
+fn test_otsu_threshold_uniform_image_returns_level() {
+    let img = GrayImage::from_pixel(20, 20, Luma([128]));
+    assert_eq!(otsu_threshold(&img), 128);
+}
+
+fn dynamic_range(img: &GrayImage) -> u8 {
+    let min = img.pixels().map(|p| p[0]).min().unwrap_or(0);
+    let max = img.pixels().map(|p| p[0]).max().unwrap_or(0);
+    max - min
+}
+
+/// Horizontal gradient squeezed into 110..=140
+fn low_contrast_gradient(width: u32, height: u32) -> GrayImage {
+    GrayImage::from_fn(width, height, |x, _| Luma([110 + (x * 30 / (width - 1)) as u8]))
+}
+
+#[test]
+fn test_clahe_widens_dynamic_range() {
+    let img = low_contrast_gradient(128, 64);
+    let enhanced = clahe(&img, 3.0, 32);
+
+    assert!(
+        dynamic_range(&enhanced) > dynamic_range(&img),
+        "Range should widen: {} -> {}",
+        dynamic_range(&img),
+        dynamic_range(&enhanced)
+    );
+}
+
+#[test]
+fn test_clahe_smaller_than_tile_falls_back_to_equalization() {
+    let img = low_contrast_gradient(20, 10);
+    let enhanced = clahe(&img, 3.0, 64);
+
+    assert_eq!(enhanced.dimensions(), img.dimensions());
+    assert!(dynamic_range(&enhanced) > dynamic_range(&img));
+}
+
+#[test]
+fn test_blur_border_modes_on_uniform_image() {
+    let img = GrayImage::from_pixel(40, 30, Luma([128]));
+
+    // 1. Replicate and reflect keep a uniform image uniform, border columns included
+    for border in [BorderMode::Replicate, BorderMode::Reflect] {
+        let blurred = apply_blur_with_border(&img, 2.0, border);
+        for y in 0..30 {
+            for x in [0, 1, 20, 38, 39] {
+                assert_eq!(blurred.get_pixel(x, y)[0], 128, "{border:?} at ({x}, {y})");
+            }
+        }
+    }
+
+    // 2. Zero darkens the border columns, most at the corner, and leaves the middle alone
+    let blurred = apply_blur_with_border(&img, 2.0, BorderMode::Zero);
+    assert_eq!(blurred.get_pixel(20, 15)[0], 128);
+    for y in 0..30 {
+        assert!(blurred.get_pixel(0, y)[0] < 128, "Left border at y {y}");
+        assert!(blurred.get_pixel(39, y)[0] < 128, "Right border at y {y}");
+    }
+    assert!(blurred.get_pixel(0, 0)[0] < blurred.get_pixel(0, 15)[0]);
+}
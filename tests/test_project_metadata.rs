@@ -0,0 +1,58 @@
+//! Integration tests for arbitrary project metadata.
+//!
+//! Tests cover:
+//! - Setting, overwriting and reading custom keys
+//! - Custom keys surviving save/reopen
+//! - Rejecting writes to reserved keys
+
+mod common;
+
+use addrslips::core::db::{ProjectRepository, UpdateProjectSettings, RESERVED_METADATA_KEYS};
+
+use common::*;
+
+#[tokio::test]
+async fn test_custom_metadata_persists() -> anyhow::Result<()> {
+    let (project, temp_dir) = create_test_project().await;
+    let project_path = temp_dir.path().join("test.addrslips");
+
+    // 1. Unknown keys read as None
+    assert_eq!(project.get_metadata("last_opened_area").await?, None);
+
+    // 2. Set and overwrite a custom key
+    project.set_metadata("last_opened_area", "1").await?;
+    project.set_metadata("last_opened_area", "2").await?;
+    assert_eq!(project.get_metadata("last_opened_area").await?.as_deref(), Some("2"));
+
+    // 3. Save, reopen and read it back
+    project.save_project().await?;
+    drop(project);
+    let project = ProjectDb::new(&project_path).await?;
+    assert_eq!(project.get_metadata("last_opened_area").await?.as_deref(), Some("2"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reserved_metadata_keys_rejected() -> anyhow::Result<()> {
+    let (project, _temp_dir) = create_test_project().await;
+    project
+        .set_project_settings(UpdateProjectSettings {
+            name: Some("Campaign".to_string()),
+            target_address_count: None,
+            created_at: None,
+        })
+        .await?;
+
+    // 1. Every reserved key is refused
+    for key in RESERVED_METADATA_KEYS {
+        let err = project.set_metadata(key, "overwritten").await.unwrap_err();
+        assert!(err.to_string().contains("reserved"));
+    }
+
+    // 2. The reserved values are untouched but still readable
+    assert_eq!(project.get_project_name().await?, "Campaign");
+    assert_eq!(project.get_metadata("name").await?.as_deref(), Some("Campaign"));
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+//! Integration tests for opening existing project files.
+//!
+//! Tests cover:
+//! - Reopening a saved project
+//! - Missing files being reported instead of created
+//! - Corrupt files being reported as errors
+
+mod common;
+
+use common::*;
+
+#[tokio::test]
+async fn test_open_saved_project() -> anyhow::Result<()> {
+    // 1. Create and save a project with one area
+    let (project, temp_dir) = create_test_project().await;
+    let project_path = temp_dir.path().join("test.addrslips");
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    project.add_area(new_area).await?;
+    project.save_project().await?;
+    drop(project);
+
+    // 2. Opening it finds the area again
+    let project = ProjectDb::open(&project_path).await?;
+    assert_eq!(project.get_areas().await?.len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_open_missing_project_fails() -> anyhow::Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+    let project_path = temp_dir.path().join("missing.addrslips");
+
+    let err = ProjectDb::open(&project_path).await.unwrap_err();
+    assert!(err.to_string().contains("does not exist"), "Unexpected error: {err}");
+    assert!(!project_path.exists(), "Opening must not create the file");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_open_corrupt_project_fails() -> anyhow::Result<()> {
+    let temp_dir = tempfile::TempDir::new()?;
+
+    // Not a zstd stream at all
+    let garbage_path = temp_dir.path().join("garbage.addrslips");
+    std::fs::write(&garbage_path, b"this is not a project archive")?;
+    assert!(ProjectDb::open(&garbage_path).await.is_err());
+
+    // Truncated archive of a real project
+    let (project, project_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    project.add_area(new_area).await?;
+    project.save_project().await?;
+    drop(project);
+    let bytes = std::fs::read(project_dir.path().join("test.addrslips"))?;
+    let truncated_path = temp_dir.path().join("truncated.addrslips");
+    std::fs::write(&truncated_path, &bytes[..bytes.len() / 2])?;
+    assert!(ProjectDb::open(&truncated_path).await.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+//! Integration tests for project snapshots.
+//!
+//! Tests cover:
+//! - Diffing snapshots taken before and after a single write
+
+mod common;
+
+// Import traits to bring methods into scope
+use addrslips::core::db::{AddressRepository, AreaRepository};
+
+use common::*;
+
+#[tokio::test]
+async fn test_snapshot_diff_reports_single_addition() -> anyhow::Result<()> {
+    // 1. Create area with one address
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    AddressRepository::add_address(&area_repo, &make_test_address("1", 10, 10)).await?;
+
+    // 2. Snapshot, add an address, snapshot again
+    let before = project.snapshot().await?;
+    let added = AddressRepository::add_address(&area_repo, &make_test_address("2", 20, 20)).await?;
+    let after = project.snapshot().await?;
+
+    // 3. Verify exactly one address was added and nothing else changed
+    let diff = before.diff(&after);
+    assert_eq!(diff.addresses.added, vec![added]);
+    assert!(diff.addresses.removed.is_empty());
+    assert!(diff.addresses.changed.is_empty());
+    assert!(diff.areas.is_empty());
+    assert!(diff.streets.is_empty());
+    assert!(diff.teams.is_empty());
+
+    // 4. Identical snapshots have an empty diff
+    assert!(after.diff(&after).is_empty());
+
+    Ok(())
+}
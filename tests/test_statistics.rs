@@ -0,0 +1,75 @@
+//! Integration tests for project-wide address statistics.
+//!
+//! Tests cover:
+//! - Totals across areas
+//! - Per-area verified, team-assigned and estimated flat counts
+//! - Areas without addresses
+
+mod common;
+
+use addrslips::core::db::{AddressStats, ProjectRepository};
+
+use common::*;
+
+#[tokio::test]
+async fn test_statistics_across_areas() -> anyhow::Result<()> {
+    let (project, _temp_dir) = create_test_project().await;
+
+    // 1. First area: three addresses, one verified, two assigned to a team
+    let (new_area, _img_file) = make_new_area("North", TEST_RED);
+    let north = project.add_area(new_area).await?;
+    let mut addresses = Vec::new();
+    for (number, flats) in [("1", Some(4)), ("2", Some(2)), ("3", None)] {
+        let mut new_address = make_test_address(number, 10, 10);
+        new_address.estimated_flats = flats;
+        addresses.push(AddressRepository::add_address(&north, &new_address).await?);
+    }
+    let update = AddressUpdate {
+        verified: Some(true),
+        ..Default::default()
+    };
+    north.update_address(&addresses[0], &update).await?;
+    let team = north.add_team().await?;
+    TeamRepository::add_address(&north, &team, &addresses[0]).await?;
+    TeamRepository::add_address(&north, &team, &addresses[1]).await?;
+
+    // 2. Second area: one verified address without team
+    let (new_area, _img_file2) = make_new_area("South", TEST_BLUE);
+    let south = project.add_area(new_area).await?;
+    let address = AddressRepository::add_address(&south, &make_test_address("7", 20, 20)).await?;
+    south.update_address(&address, &update).await?;
+
+    // 3. Third area stays empty
+    let (new_area, _img_file3) = make_new_area("Empty", TEST_GREEN);
+    project.add_area(new_area).await?;
+
+    // 4. Verify the aggregates
+    let stats = project.get_statistics().await?;
+    assert_eq!(
+        stats.totals,
+        AddressStats {
+            total: 4,
+            verified: 2,
+            assigned_to_team: 2,
+            estimated_flats: 10,
+        }
+    );
+    assert_eq!(stats.areas.len(), 3);
+    assert_eq!(stats.areas[0].name, "North");
+    assert_eq!(
+        stats.areas[0].addresses,
+        AddressStats {
+            total: 3,
+            verified: 1,
+            assigned_to_team: 2,
+            estimated_flats: 6,
+        }
+    );
+    assert_eq!(stats.areas[1].name, "South");
+    assert_eq!(stats.areas[1].addresses.total, 1);
+    assert_eq!(stats.areas[1].addresses.verified, 1);
+    assert_eq!(stats.areas[1].addresses.estimated_flats, 4);
+    assert_eq!(stats.areas[2].addresses, AddressStats::default());
+
+    Ok(())
+}
@@ -0,0 +1,578 @@
+//! Integration tests for individual pipeline steps.
+//!
+//! Tests cover:
+//! - Upscaling crops to square and proportional OCR canvases
+//! - Upscaling 1px-thin crops without losing or misplacing them
+//! - OCR reporting a real per-detection confidence
+//! - Rotating OCR input with white padding and retrying rotated labels
+//! - Non-maximum suppression of overlapping detections, also in executor runs
+//! - Keeping the most confident of overlapping OCR results
+//! - Deskewing rotated scans
+//! - Morphological cleanup of edge images
+//! - Multi-worker OCR matching single-worker results
+//! - Filtering circles by color
+//! - Unsharp masking a step edge
+//! - Dropping low-confidence OCR results
+//! - Mapping background-removed digits back to the original image
+
+mod common;
+
+use addrslips::detection::ocr::rotate_with_padding;
+use addrslips::detection::preprocessing::MorphOp;
+use addrslips::detection::steps::*;
+use addrslips::{
+    BoundingBox, CancelToken, Contour, MetadataValue, Pipeline, PipelineContext, PipelineData, PipelineError,
+    PipelineStep,
+};
+use image::GenericImageView;
+
+use common::*;
+
+fn context() -> PipelineContext {
+    PipelineContext {
+        verbose: false,
+        debug: None,
+        cancel: CancelToken::new(),
+        progress: None,
+    }
+}
+
+#[test]
+fn test_upscale_square_canvas_by_default() -> anyhow::Result<()> {
+    let wide = PipelineData::from_image(number_image("123", 20));
+    let step = UpscaleStep {
+        target_size: 100,
+        target_height: None,
+    };
+
+    let result = step.process(vec![wide], &context())?;
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].image.width(), 100);
+    assert_eq!(result[0].image.height(), 100);
+
+    Ok(())
+}
+
+#[test]
+fn test_upscale_wide_crop_gets_wide_canvas() -> anyhow::Result<()> {
+    // Three digits side by side are clearly wider than tall
+    let wide = PipelineData::from_image(number_image("123", 20));
+    let step = UpscaleStep {
+        target_size: 100,
+        target_height: Some(100),
+    };
+
+    let result = step.process(vec![wide], &context())?;
+    assert_eq!(result[0].image.height(), 100);
+    assert!(
+        result[0].image.width() > result[0].image.height(),
+        "Wide crop should get a wider-than-tall canvas, got {}x{}",
+        result[0].image.width(),
+        result[0].image.height()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_upscale_tiny_crops_stay_centered() -> anyhow::Result<()> {
+    let step = UpscaleStep {
+        target_size: 100,
+        target_height: None,
+    };
+
+    // 1x500 scales to less than a pixel wide and must still show up
+    for (width, height) in [(1, 1), (1, 50), (50, 1), (1, 500)] {
+        let crop = image::DynamicImage::ImageLuma8(image::GrayImage::new(width, height));
+        let result = step.process(vec![PipelineData::from_image(crop)], &context())?;
+        let canvas = result[0].image.to_luma8();
+        assert_eq!(canvas.dimensions(), (100, 100), "{width}x{height}");
+
+        // The dark content's bounding box is centered on the canvas
+        let dark: Vec<(u32, u32)> = canvas
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel[0] < 128)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert!(!dark.is_empty(), "{width}x{height} crop vanished");
+        let center = |coords: Vec<u32>| {
+            (*coords.iter().min().unwrap() + *coords.iter().max().unwrap()) as f32 / 2.0
+        };
+        let center_x = center(dark.iter().map(|p| p.0).collect());
+        let center_y = center(dark.iter().map(|p| p.1).collect());
+        assert!((center_x - 49.5).abs() <= 1.0, "{width}x{height}: center x {center_x}");
+        assert!((center_y - 49.5).abs() <= 1.0, "{width}x{height}: center y {center_y}");
+    }
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "requires OCR models in ~/.cache/ocrs"]
+fn test_upscale_proportional_canvas_reads_three_digits() -> anyhow::Result<()> {
+    let ocr = OcrStep::new();
+    let proportional = UpscaleStep {
+        target_size: 100,
+        target_height: Some(100),
+    };
+
+    let input = PipelineData::from_image(number_image("123", 20));
+    let upscaled = proportional.process(vec![input], &context())?;
+    let recognized = ocr.process(upscaled, &context())?;
+
+    assert_eq!(recognized.len(), 1);
+    assert_eq!(recognized[0].get_string("ocr_text"), Some("123"));
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "requires OCR models in ~/.cache/ocrs"]
+fn test_ocr_reports_real_confidence() -> anyhow::Result<()> {
+    let ocr = OcrStep::new();
+    let upscale = UpscaleStep {
+        target_size: 100,
+        target_height: Some(100),
+    };
+
+    let input = PipelineData::from_image(number_image("42", 20));
+    let recognized = ocr.process(upscale.process(vec![input], &context())?, &context())?;
+
+    assert_eq!(recognized.len(), 1);
+    let confidence = recognized[0]
+        .get_float("ocr_confidence")
+        .expect("OCR results carry a confidence");
+    assert!(confidence > 0.0 && confidence <= 1.0, "Confidence {confidence} out of range");
+    assert_ne!(confidence, 0.9, "Confidence should not be the old hardcoded value");
+
+    Ok(())
+}
+
+/// Bounding box size and pixel count of the dark (ink) pixels
+fn ink_extent(img: &image::RgbImage) -> (u32, u32, usize) {
+    let ink: Vec<(u32, u32)> = img
+        .enumerate_pixels()
+        .filter(|(_, _, pixel)| pixel.0[0] < 128)
+        .map(|(x, y, _)| (x, y))
+        .collect();
+    let width = ink.iter().map(|p| p.0).max().unwrap() - ink.iter().map(|p| p.0).min().unwrap() + 1;
+    let height = ink.iter().map(|p| p.1).max().unwrap() - ink.iter().map(|p| p.1).min().unwrap() + 1;
+    (width, height, ink.len())
+}
+
+#[test]
+fn test_rotation_pads_with_white() {
+    // 45x40, so the result depends on which side is which
+    let img = number_image("23", 20);
+    let (ink_w, ink_h, ink_count) = ink_extent(&img.to_rgb8());
+    assert_ne!(img.width(), img.height());
+
+    // 1. The canvas is a square of the diagonal, ceil(sqrt(45² + 40²)) = 61, padded with white
+    let rotated = rotate_with_padding(&img, 45.0).to_rgb8();
+    assert_eq!(rotated.dimensions(), (61, 61));
+    for (x, y) in [(0, 0), (rotated.width() - 1, 0), (0, rotated.height() - 1), (60, 60)] {
+        assert_eq!(rotated.get_pixel(x, y).0, [255, 255, 255], "Corner ({x}, {y}) should be white");
+    }
+
+    // 2. No ink is clipped
+    let (_, _, rotated_count) = ink_extent(&rotated);
+    let ratio = rotated_count as f32 / ink_count as f32;
+    assert!((0.8..1.25).contains(&ratio), "ink changed from {ink_count} to {rotated_count} pixels");
+
+    // 3. A quarter turn swaps the ink's width and height
+    let quarter = rotate_with_padding(&img, 90.0).to_rgb8();
+    assert_eq!(quarter.dimensions(), (61, 61));
+    let (quarter_w, quarter_h, _) = ink_extent(&quarter);
+    assert!(quarter_w.abs_diff(ink_h) <= 1, "ink width {quarter_w}, expected {ink_h}");
+    assert!(quarter_h.abs_diff(ink_w) <= 1, "ink height {quarter_h}, expected {ink_w}");
+}
+
+#[test]
+#[ignore = "requires OCR models in ~/.cache/ocrs"]
+fn test_ocr_recognizes_rotated_label() -> anyhow::Result<()> {
+    // Digits rotated 90° clockwise, undone by a 90° counterclockwise retry
+    let rotated = number_image("23", 20).rotate90();
+    let ocr = OcrStep::new().with_rotations(vec![0.0, 90.0]);
+
+    let recognized = ocr.process(vec![PipelineData::from_image(rotated)], &context())?;
+
+    assert_eq!(recognized.len(), 1);
+    assert_eq!(recognized[0].get_string("ocr_text"), Some("23"));
+
+    Ok(())
+}
+
+#[test]
+fn test_nms_collapses_overlapping_boxes() -> anyhow::Result<()> {
+    let original = std::sync::Arc::new(number_image("1", 20));
+    let region = |x: u32, brightness: f32| {
+        PipelineData::from_region(
+            (*original).clone(),
+            original.clone(),
+            BoundingBox { x, y: 0, width: 100, height: 100 },
+        )
+        .with_metadata("brightness", MetadataValue::Float(brightness))
+    };
+
+    // Shifted by 10px: IoU = 9000 / 11000 ≈ 0.82
+    let dim = region(0, 210.0);
+    let bright = region(10, 240.0);
+    assert!(addrslips::iou(dim.bbox.as_ref().unwrap(), bright.bbox.as_ref().unwrap()) > 0.8);
+
+    let step = NmsStep { iou_threshold: 0.5 };
+    let result = step.process(vec![dim, bright], &context())?;
+
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].get_float("brightness"), Some(240.0));
+
+    Ok(())
+}
+
+/// Splits the input into 100x100 regions at the given x offsets with the given brightness.
+struct RegionsStep(Vec<(u32, f32)>);
+
+impl PipelineStep for RegionsStep {
+    fn process(&self, data: Vec<PipelineData>, _context: &PipelineContext) -> Result<Vec<PipelineData>, PipelineError> {
+        let mut result = Vec::new();
+        for item in data {
+            for &(x, brightness) in &self.0 {
+                let region = PipelineData::from_region(
+                    item.image.clone(),
+                    item.original.clone(),
+                    BoundingBox { x, y: 0, width: 100, height: 100 },
+                );
+                result.push(region.with_metadata("brightness", MetadataValue::Float(brightness)));
+            }
+        }
+        Ok(result)
+    }
+
+    fn name(&self) -> &str {
+        "Regions"
+    }
+}
+
+#[test]
+fn test_nms_collapses_overlapping_boxes_in_executor() -> anyhow::Result<()> {
+    // 1. Two overlapping regions and a separate one, each reaching NMS as its own work item
+    let pipeline = Pipeline::new()
+        .add_step_boxed(Box::new(RegionsStep(vec![(0, 210.0), (10, 240.0), (300, 220.0)])))
+        .add_step_boxed(Box::new(NmsStep { iou_threshold: 0.5 }));
+
+    // 2. Both executors drop the dimmer of the overlapping pair, like a sequential run
+    for num_threads in [1, 4] {
+        let result = pipeline.run_with_executor(number_image("1", 20), num_threads)?;
+        let mut brightness: Vec<f32> = result.iter().filter_map(|item| item.get_float("brightness")).collect();
+        brightness.sort_by(f32::total_cmp);
+        assert_eq!(brightness, vec![220.0, 240.0], "{num_threads} threads");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_nms_by_text_keeps_most_confident_reading() -> anyhow::Result<()> {
+    let original = std::sync::Arc::new(number_image("12", 20));
+    let reading = |x: u32, text: &str, confidence: f32| {
+        PipelineData::from_region(
+            (*original).clone(),
+            original.clone(),
+            BoundingBox { x, y: 0, width: 100, height: 100 },
+        )
+        .with_metadata("ocr_text", MetadataValue::String(text.to_string()))
+        .with_metadata("ocr_confidence", MetadataValue::Float(confidence))
+    };
+
+    // 1. Two overlapping readings of one circle, plus a separate circle
+    let unsure = reading(0, "17", 0.6);
+    let sure = reading(10, "12", 0.9);
+    let elsewhere = reading(300, "5", 0.4);
+
+    // 2. Only the confident reading of the overlapping pair survives
+    let step = NmsByTextStep { iou_threshold: 0.5 };
+    let result = step.process(vec![unsure, sure, elsewhere], &context())?;
+
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0].get_string("ocr_text"), Some("12"));
+    assert_eq!(result[0].get_float("ocr_confidence"), Some(0.9));
+    assert_eq!(result[1].get_string("ocr_text"), Some("5"));
+
+    Ok(())
+}
+
+#[test]
+fn test_nms_by_text_breaks_ties_by_radius() -> anyhow::Result<()> {
+    let original = std::sync::Arc::new(number_image("1", 20));
+    let reading = |x: u32, radius: f32| {
+        PipelineData::from_region(
+            (*original).clone(),
+            original.clone(),
+            BoundingBox { x, y: 0, width: 100, height: 100 },
+        )
+        .with_metadata("ocr_confidence", MetadataValue::Float(0.8))
+        .with_metadata("radius", MetadataValue::Float(radius))
+    };
+
+    let step = NmsByTextStep { iou_threshold: 0.5 };
+    let result = step.process(vec![reading(0, 20.0), reading(10, 30.0)], &context())?;
+
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].get_float("radius"), Some(30.0));
+
+    Ok(())
+}
+
+/// Runs `DeskewStep` on a grayscale version of `img` and returns the recorded correction.
+fn deskew_angle(img: &image::DynamicImage) -> anyhow::Result<f32> {
+    let gray = image::DynamicImage::ImageLuma8(img.to_luma8());
+    let results = DeskewStep { max_angle: MAX_DESKEW_ANGLE }
+        .process(vec![PipelineData::from_image(gray)], &context())?;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].image.dimensions(), img.dimensions());
+    Ok(results[0].get_float("deskew_angle").expect("deskew angle recorded"))
+}
+
+#[test]
+fn test_deskew_detects_rotation() -> anyhow::Result<()> {
+    let text = number_image("1234567890", 30);
+
+    // 1. A straight image needs no correction
+    assert!(deskew_angle(&text)?.abs() < 1.0);
+
+    // 2. Text rotated 5 degrees counterclockwise is rotated back
+    let rotated = rotate_with_padding(&text, 5.0);
+    let angle = deskew_angle(&rotated)?;
+    assert!((angle + 5.0).abs() < 1.0, "detected correction {angle}");
+
+    Ok(())
+}
+
+/// Runs `MorphologyStep` with a 3x3 kernel on a binary image.
+fn morph_step(edges: image::GrayImage, operation: MorphOp) -> anyhow::Result<image::GrayImage> {
+    let step = MorphologyStep { operation, kernel_size: 3 };
+    let input = PipelineData::from_image(image::DynamicImage::ImageLuma8(edges));
+    let results = step.process(vec![input], &context())?;
+    assert_eq!(results.len(), 1);
+    Ok(results[0].image.to_luma8())
+}
+
+#[test]
+fn test_morphology_open_removes_speckle() -> anyhow::Result<()> {
+    // A single-pixel speck next to a thick line
+    let mut edges = image::GrayImage::new(30, 30);
+    for x in 5..25 {
+        for y in 10..15 {
+            edges.put_pixel(x, y, image::Luma([255]));
+        }
+    }
+    edges.put_pixel(20, 25, image::Luma([255]));
+
+    let opened = morph_step(edges, MorphOp::Open)?;
+    assert_eq!(opened.get_pixel(20, 25)[0], 0);
+    assert_eq!(opened.get_pixel(15, 12)[0], 255);
+    Ok(())
+}
+
+#[test]
+fn test_morphology_close_bridges_gap() -> anyhow::Result<()> {
+    // A horizontal line with a one pixel gap at x = 15
+    let mut edges = image::GrayImage::new(30, 30);
+    for x in (5..25).filter(|&x| x != 15) {
+        edges.put_pixel(x, 15, image::Luma([255]));
+    }
+
+    let closed = morph_step(edges, MorphOp::Close)?;
+    assert_eq!(closed.get_pixel(15, 15)[0], 255);
+    // The line doesn't grow thicker
+    assert_eq!(closed.get_pixel(15, 14)[0], 0);
+    assert_eq!(closed.get_pixel(15, 16)[0], 0);
+    Ok(())
+}
+
+#[test]
+#[ignore = "requires OCR models in ~/.cache/ocrs"]
+fn test_ocr_workers_match_single_worker() -> anyhow::Result<()> {
+    let upscale = UpscaleStep {
+        target_size: 100,
+        target_height: Some(100),
+    };
+    let numbers = ["1", "23", "456", "7", "89", "10", "112"];
+    let inputs: Vec<PipelineData> = numbers
+        .iter()
+        .map(|number| PipelineData::from_image(number_image(number, 20)))
+        .collect();
+    let upscaled = upscale.process(inputs, &context())?;
+
+    let texts = |results: Vec<PipelineData>| -> Vec<String> {
+        results
+            .iter()
+            .map(|item| item.get_string("ocr_text").unwrap_or_default().to_string())
+            .collect()
+    };
+    let single = texts(OcrStep::new().process(upscaled.clone(), &context())?);
+    // More workers than items are capped to the item count
+    let multi = texts(OcrStep::new().with_workers(16).process(upscaled, &context())?);
+
+    assert_eq!(single.len(), numbers.len());
+    assert_eq!(single, multi);
+    Ok(())
+}
+
+/// Item for the disc of radius 15 centered at `(cx, 30)` in `original`, as `ContourDetectionStep` emits it
+fn disc_item(original: &image::DynamicImage, cx: u32) -> PipelineData {
+    let contour = Contour {
+        label: 1,
+        min_x: cx - 15,
+        min_y: 15,
+        max_x: cx + 15,
+        max_y: 45,
+        pixel_count: 700,
+        boundary: None,
+    };
+    let mut item = PipelineData::from_image(original.clone());
+    contour.write_metadata(&mut item.metadata);
+    item
+}
+
+#[test]
+fn test_color_filter_keeps_matching_circle() -> anyhow::Result<()> {
+    // Red disc on the left, white disc on the right, gray background
+    let original = image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(120, 60, |x, y| {
+        let dist = |cx: f32| (x as f32 - cx).hypot(y as f32 - 30.0);
+        if dist(30.0) <= 15.0 {
+            image::Rgb([TEST_RED.r, TEST_RED.g, TEST_RED.b])
+        } else if dist(90.0) <= 15.0 {
+            image::Rgb([255, 255, 255])
+        } else {
+            image::Rgb([128, 128, 128])
+        }
+    }));
+
+    let step = ColorCircleFilterStep {
+        target: TEST_RED,
+        tolerance: 60.0,
+    };
+    let result = step.process(vec![disc_item(&original, 30), disc_item(&original, 90)], &context())?;
+
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].metadata.get("contour_min_x"), Some(&MetadataValue::Int(15)));
+    assert!(matches!(result[0].metadata.get("color_distance"), Some(MetadataValue::Float(d)) if *d < 60.0));
+
+    Ok(())
+}
+
+fn sharpen_step_edge(dark: u8, bright: u8) -> anyhow::Result<image::GrayImage> {
+    let edge = image::GrayImage::from_fn(100, 20, |x, _| image::Luma([if x < 50 { dark } else { bright }]));
+    let step = UnsharpMaskStep { sigma: 2.0, amount: 1.0 };
+    let result = step.process(vec![PipelineData::from_image(image::DynamicImage::ImageLuma8(edge))], &context())?;
+    Ok(result[0].image.to_luma8())
+}
+
+#[test]
+fn test_unsharp_mask_increases_edge_contrast() -> anyhow::Result<()> {
+    let sharpened = sharpen_step_edge(60, 180)?;
+
+    // Darker just before the edge, brighter just after, flat far away
+    let before = sharpened.get_pixel(49, 10)[0];
+    let after = sharpened.get_pixel(50, 10)[0];
+    assert!(before < 60 && after > 180, "Edge should gain contrast, got {before} / {after}");
+    assert_eq!(sharpened.get_pixel(5, 10)[0], 60);
+    assert_eq!(sharpened.get_pixel(95, 10)[0], 180);
+
+    Ok(())
+}
+
+#[test]
+fn test_unsharp_mask_clamps_without_wrapping() -> anyhow::Result<()> {
+    // A full-range edge would overshoot both ends; it must clamp rather than wrap around
+    let sharpened = sharpen_step_edge(0, 255)?;
+    for x in 0..100 {
+        let expected = if x < 50 { 0 } else { 255 };
+        assert_eq!(sharpened.get_pixel(x, 10)[0], expected, "Pixel {x} wrapped around");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_confidence_filter_drops_low_confidence() -> anyhow::Result<()> {
+    let blank = image::DynamicImage::new_luma8(10, 10);
+    let mut items: Vec<PipelineData> = [0.3, 0.6, 0.95]
+        .into_iter()
+        .map(|confidence| {
+            let mut item = PipelineData::from_image(blank.clone());
+            item.metadata.insert("ocr_confidence".to_string(), MetadataValue::Float(confidence));
+            item
+        })
+        .collect();
+    // Never reached OCR
+    items.push(PipelineData::from_image(blank));
+
+    let step = ConfidenceFilterStep { min_confidence: 0.5 };
+    let result = step.process(items, &context())?;
+
+    let kept: Vec<f32> = result.iter().filter_map(|item| item.get_float("ocr_confidence")).collect();
+    assert_eq!(result.len(), 2);
+    assert_eq!(kept, vec![0.6, 0.95]);
+
+    Ok(())
+}
+
+#[test]
+fn test_background_removal_records_crop() -> anyhow::Result<()> {
+    // 1. A marker ROI as ContourDetectionStep cuts it: outline contour plus 10px padding
+    let original = synthetic_map(120, 120, &[Marker { x: 60, y: 60, radius: 22, number: "8" }]);
+    let contour = Contour {
+        label: 1,
+        min_x: 38,
+        min_y: 38,
+        max_x: 82,
+        max_y: 82,
+        pixel_count: 280,
+        boundary: None,
+    };
+    let roi_bbox = BoundingBox { x: 28, y: 28, width: 65, height: 65 };
+    let roi = original.crop_imm(28, 28, 65, 65);
+    let mut item = PipelineData::from_region(roi, std::sync::Arc::new(original.clone()), roi_bbox);
+    contour.write_metadata(&mut item.metadata);
+
+    // 2. Remove the background
+    let result = BackgroundRemovalStep.process(vec![item], &context())?;
+    assert_eq!(result.len(), 1);
+    let cleaned = &result[0];
+    let crop_x = cleaned.get_int("bg_crop_x").expect("crop x recorded") as u32;
+    let crop_y = cleaned.get_int("bg_crop_y").expect("crop y recorded") as u32;
+    let bbox = cleaned.bbox.as_ref().expect("bbox kept");
+    assert_eq!((bbox.x, bbox.y), (28 + crop_x, 28 + crop_y));
+    assert_eq!(cleaned.get_int("bg_crop_w"), Some(bbox.width as i32));
+    assert_eq!(cleaned.get_int("bg_crop_h"), Some(bbox.height as i32));
+    assert_eq!((cleaned.image.width(), cleaned.image.height()), (bbox.width, bbox.height));
+
+    // 3. Digit pixels shifted by the crop land on the digit in the original
+    let dark_bounds = |pixels: Vec<(u32, u32)>| {
+        let xs = pixels.iter().map(|p| p.0);
+        let ys = pixels.iter().map(|p| p.1);
+        (xs.clone().min().unwrap(), ys.clone().min().unwrap(), xs.max().unwrap(), ys.max().unwrap())
+    };
+    let cleaned_gray = cleaned.image.to_luma8();
+    let (min_x, min_y, max_x, max_y) = dark_bounds(
+        cleaned_gray
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel[0] < 150)
+            .map(|(x, y, _)| (bbox.x + x, bbox.y + y))
+            .collect(),
+    );
+    let original_gray = original.to_luma8();
+    let expected = dark_bounds(
+        original_gray
+            .enumerate_pixels()
+            .filter(|(x, y, pixel)| pixel[0] < 150 && (*x as f32 - 60.0).hypot(*y as f32 - 60.0) < 15.0)
+            .map(|(x, y, _)| (x, y))
+            .collect(),
+    );
+    for (got, want) in [(min_x, expected.0), (min_y, expected.1), (max_x, expected.2), (max_y, expected.3)] {
+        assert!(got.abs_diff(want) <= 1, "Digit bounds {:?} vs original {:?}", (min_x, min_y, max_x, max_y), expected);
+    }
+
+    Ok(())
+}
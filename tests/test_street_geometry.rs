@@ -0,0 +1,270 @@
+//! Integration tests for street polyline geometry.
+//!
+//! Tests cover:
+//! - Address density histograms along a street polyline
+//! - Assigning unassigned addresses to the nearest street
+//! - Ordering a street's addresses along its polyline
+//! - Splitting a street at a vertex
+//! - Joining polylines and merging street fragments
+
+mod common;
+
+// Import traits to bring methods into scope
+use addrslips::core::db::{util::join_polylines, AddressRepository, AreaRepository, StreetRepository};
+
+use common::*;
+
+#[tokio::test]
+async fn test_street_density_skewed_towards_start() -> anyhow::Result<()> {
+    // 1. Create area and a straight street from (0, 50) to (100, 50)
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let street = area_repo.add_street().await?;
+    area_repo
+        .draw_street_polyline(&street, &[Point { x: 0, y: 50 }, Point { x: 100, y: 50 }])
+        .await?;
+
+    // 2. Cluster four addresses near the start and one near the end
+    for (number, x) in [("1", 2), ("3", 8), ("5", 12), ("7", 18), ("9", 95)] {
+        let mut new_address = make_test_address(number, x, 45);
+        new_address.assigned_street_id = Some(street.id);
+        AddressRepository::add_address(&area_repo, &new_address).await?;
+    }
+
+    // 3. Verify the histogram is skewed towards the first segment
+    let density = area_repo.street_density(&street, 5).await?;
+    assert_eq!(density, vec![4, 0, 0, 0, 1]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_street_density_requires_polyline() -> anyhow::Result<()> {
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_BLUE);
+    let area_repo = project.add_area(new_area).await?;
+    let street = area_repo.add_street().await?;
+
+    let result = area_repo.street_density(&street, 4).await;
+    assert!(result.is_err(), "Street without polyline should be rejected");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_autoassign_nearest_street() -> anyhow::Result<()> {
+    // 1. Create area with two parallel streets at y = 40 and y = 80
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let north = area_repo.add_street().await?;
+    let south = area_repo.add_street().await?;
+    area_repo
+        .draw_street_polyline(&north, &[Point { x: 0, y: 40 }, Point { x: 90, y: 40 }])
+        .await?;
+    area_repo
+        .draw_street_polyline(&south, &[Point { x: 0, y: 80 }, Point { x: 90, y: 80 }])
+        .await?;
+    // A street without polyline must be ignored
+    area_repo.add_street().await?;
+
+    // 2. Add unassigned addresses, plus one already on the south street
+    let near_north = AddressRepository::add_address(&area_repo, &make_test_address("1", 20, 45)).await?;
+    let near_south = AddressRepository::add_address(&area_repo, &make_test_address("2", 60, 75)).await?;
+    let mut preassigned = make_test_address("3", 20, 35);
+    preassigned.assigned_street_id = Some(south.id);
+    let preassigned = AddressRepository::add_address(&area_repo, &preassigned).await?;
+
+    // 3. Verify only the two unassigned addresses were updated
+    assert_eq!(area_repo.autoassign_nearest().await?, 2);
+
+    for (address, expected) in [(near_north, north.id), (near_south, south.id), (preassigned, south.id)] {
+        let reloaded = AddressRepository::get_address_by_id(&area_repo, address.id)
+            .await?
+            .expect("address still exists");
+        assert_eq!(reloaded.assigned_street_id, Some(expected));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_autoassign_without_polylines_skips() -> anyhow::Result<()> {
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    area_repo.add_street().await?;
+    AddressRepository::add_address(&area_repo, &make_test_address("1", 20, 45)).await?;
+
+    assert_eq!(area_repo.autoassign_nearest().await?, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_order_addresses_along_polyline() -> anyhow::Result<()> {
+    // 1. Create area and a straight street from (0, 50) to (100, 50)
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let street = area_repo.add_street().await?;
+    area_repo
+        .draw_street_polyline(&street, &[Point { x: 0, y: 50 }, Point { x: 100, y: 50 }])
+        .await?;
+
+    // 2. Scatter addresses on both sides, inserted out of order
+    for (number, x, y) in [("8", 75, 70), ("2", 10, 30), ("6", 55, 35), ("4", 30, 72), ("10", 95, 28)] {
+        let mut new_address = make_test_address(number, x, y);
+        new_address.assigned_street_id = Some(street.id);
+        AddressRepository::add_address(&area_repo, &new_address).await?;
+    }
+
+    // 3. Verify they come back left to right
+    let ordered: Vec<String> = area_repo
+        .order_addresses_along_polyline(&street)
+        .await?
+        .into_iter()
+        .map(|address| address.house_number)
+        .collect();
+    assert_eq!(ordered, vec!["2", "4", "6", "8", "10"]);
+
+    Ok(())
+}
+
+/// House numbers of the street's addresses, by id.
+async fn house_numbers(area_repo: &AreaDb, street: &Street) -> anyhow::Result<Vec<String>> {
+    Ok(area_repo
+        .get_address_by_street(street)
+        .await?
+        .into_iter()
+        .map(|address| address.house_number)
+        .collect())
+}
+
+#[tokio::test]
+async fn test_split_street_assigns_addresses_to_nearer_half() -> anyhow::Result<()> {
+    // 1. A verified, named U-shaped street: along the top, down the right side, back along the bottom
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let street = area_repo.add_street().await?;
+    let street = area_repo
+        .update_street(
+            &street,
+            &StreetUpdate {
+                name: Some("Ringstraße".to_string()),
+                verified: Some(true),
+            },
+        )
+        .await?;
+    let polyline = [
+        Point { x: 0, y: 0 },
+        Point { x: 100, y: 0 },
+        Point { x: 100, y: 100 },
+        Point { x: 0, y: 100 },
+    ];
+    area_repo.draw_street_polyline(&street, &polyline).await?;
+    for (number, x, y) in [("1", 50, 5), ("3", 95, 40), ("5", 50, 95), ("7", 10, 90)] {
+        let mut new_address = make_test_address(number, x, y);
+        new_address.assigned_street_id = Some(street.id);
+        AddressRepository::add_address(&area_repo, &new_address).await?;
+    }
+
+    // 2. Only interior vertices are accepted
+    assert!(area_repo.split_street(&street, 0).await.is_err());
+    assert!(area_repo.split_street(&street, 3).await.is_err());
+
+    // 3. Split at the bottom-right corner
+    let (first, second) = area_repo.split_street(&street, 2).await?;
+    assert_eq!(first.name.as_deref(), Some("Ringstraße"));
+    assert_eq!(second.name, None);
+    assert!(!first.verified && !second.verified);
+    assert_eq!(area_repo.get_street_polyline(&first).await?.unwrap().points, polyline[..=2].to_vec());
+    assert_eq!(area_repo.get_street_polyline(&second).await?.unwrap().points, polyline[2..].to_vec());
+    assert_eq!(area_repo.get_street_by_id(street.id).await?, None);
+
+    // 4. Addresses along the top and right side stay on the first half, the bottom ones move
+    assert_eq!(house_numbers(&area_repo, &first).await?, ["1", "3"]);
+    assert_eq!(house_numbers(&area_repo, &second).await?, ["5", "7"]);
+
+    Ok(())
+}
+
+#[test]
+fn test_join_polylines_at_nearest_endpoints() {
+    let p = |x, y| Point { x, y };
+    let a = [p(50, 0), p(100, 0)];
+
+    // b continues after a
+    assert_eq!(join_polylines(&a, &[p(110, 0), p(150, 0)]), [p(50, 0), p(100, 0), p(110, 0), p(150, 0)]);
+    // b ends at a's start and shares the vertex
+    assert_eq!(join_polylines(&a, &[p(0, 0), p(50, 0)]), [p(0, 0), p(50, 0), p(100, 0)]);
+    // b points away from a's start, so it is reversed in front of a
+    assert_eq!(join_polylines(&a, &[p(40, 0), p(0, 0)]), [p(0, 0), p(40, 0), p(50, 0), p(100, 0)]);
+    // Nothing to join
+    assert_eq!(join_polylines(&[], &a), a);
+}
+
+#[tokio::test]
+async fn test_merge_streets_joins_fragments() -> anyhow::Result<()> {
+    // 1. Two collinear fragments of one road: `a` unnamed from x=50 to 100, `b` named from x=40 back to 0
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let a = area_repo.add_street().await?;
+    area_repo
+        .draw_street_polyline(&a, &[Point { x: 50, y: 50 }, Point { x: 100, y: 50 }])
+        .await?;
+    let b = area_repo.add_street().await?;
+    let b = area_repo
+        .update_street(
+            &b,
+            &StreetUpdate {
+                name: Some("Lindenweg".to_string()),
+                ..Default::default()
+            },
+        )
+        .await?;
+    area_repo
+        .draw_street_polyline(&b, &[Point { x: 40, y: 50 }, Point { x: 0, y: 50 }])
+        .await?;
+    for (number, x, street) in [("2", 60, &a), ("4", 90, &a), ("1", 5, &b), ("3", 30, &b)] {
+        let mut new_address = make_test_address(number, x, 45);
+        new_address.assigned_street_id = Some(street.id);
+        AddressRepository::add_address(&area_repo, &new_address).await?;
+    }
+
+    // 2. Merge: `a` takes over the name, the addresses and a continuous polyline
+    let merged = area_repo.merge_streets(&a, &b).await?;
+    assert_eq!(merged.id, a.id);
+    assert_eq!(merged.name.as_deref(), Some("Lindenweg"));
+    let points = area_repo.get_street_polyline(&merged).await?.unwrap().points;
+    let xs: Vec<u32> = points.iter().map(|p| p.x).collect();
+    assert_eq!(xs, [0, 40, 50, 100]);
+    assert!(points.iter().all(|p| p.y == 50));
+    assert_eq!(house_numbers(&area_repo, &merged).await?, ["2", "4", "1", "3"]);
+    assert_eq!(area_repo.get_street_by_id(b.id).await?, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_merge_streets_rejects_duplicate_house_numbers() -> anyhow::Result<()> {
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let a = area_repo.add_street().await?;
+    let b = area_repo.add_street().await?;
+    for (x, street) in [(10, &a), (80, &b)] {
+        let mut new_address = make_test_address("1", x, 45);
+        new_address.assigned_street_id = Some(street.id);
+        AddressRepository::add_address(&area_repo, &new_address).await?;
+    }
+
+    let err = area_repo.merge_streets(&a, &b).await.unwrap_err();
+    assert!(err.to_string().contains("house number 1"), "Unexpected error: {err}");
+    assert_eq!(area_repo.get_streets().await?.len(), 2);
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+//! Integration tests for searching streets by name.
+//!
+//! Tests cover:
+//! - Case-insensitive partial matches ordered by prefix
+//! - Excluding unnamed streets
+//! - Treating LIKE wildcards in the query literally
+
+mod common;
+
+use common::*;
+
+/// Adds a street with the given name.
+async fn add_named_street(area_repo: &AreaDb, name: &str) -> anyhow::Result<Street> {
+    let street = area_repo.add_street().await?;
+    area_repo
+        .update_street(
+            &street,
+            &StreetUpdate {
+                name: Some(name.to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+}
+
+#[tokio::test]
+async fn test_search_streets_prefix_first() -> anyhow::Result<()> {
+    // 1. Create area with named streets and one unnamed street
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    add_named_street(&area_repo, "Main St").await?;
+    add_named_street(&area_repo, "Oak Rd").await?;
+    add_named_street(&area_repo, "Main Ave").await?;
+    add_named_street(&area_repo, "Old Main Rd").await?;
+    area_repo.add_street().await?;
+
+    // 2. Search case-insensitively
+    let names = |streets: Vec<Street>| streets.into_iter().filter_map(|s| s.name).collect::<Vec<_>>();
+    assert_eq!(
+        names(area_repo.search_streets("main").await?),
+        vec!["Main Ave", "Main St", "Old Main Rd"]
+    );
+    assert_eq!(names(area_repo.search_streets("OAK").await?), vec!["Oak Rd"]);
+
+    // 3. Unnamed streets never match, not even the empty query
+    assert_eq!(area_repo.search_streets("").await?.len(), 4);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_streets_escapes_wildcards() -> anyhow::Result<()> {
+    // 1. Create area with streets with and without a percent sign
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    add_named_street(&area_repo, "Main St").await?;
+    add_named_street(&area_repo, "100% Lane").await?;
+
+    // 2. Wildcards only match themselves
+    assert!(area_repo.search_streets("M_in").await?.is_empty());
+    let found = area_repo.search_streets("0%").await?;
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].name.as_deref(), Some("100% Lane"));
+
+    Ok(())
+}
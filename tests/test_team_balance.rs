@@ -0,0 +1,97 @@
+//! Integration tests for balancing addresses across teams.
+//!
+//! Tests cover:
+//! - Creating missing teams and splitting addresses into equal, compact groups
+//! - Replacing existing assignments
+
+mod common;
+
+use common::*;
+
+/// 40 addresses on a slightly jittered 8x5 grid with 12px spacing
+async fn add_grid_addresses(area_repo: &AreaDb) -> anyhow::Result<Vec<Address>> {
+    let batch: Vec<NewAddress> = (0..40u32)
+        .map(|i| {
+            let (col, row) = (i % 8, i / 8);
+            make_test_address(&i.to_string(), 5 + 12 * col + (i * 7) % 5, 5 + 12 * row + (i * 3) % 4)
+        })
+        .collect();
+    area_repo.add_addresses(&batch).await
+}
+
+#[tokio::test]
+async fn test_balance_teams_even_and_compact() -> anyhow::Result<()> {
+    // 1. Create area with 40 addresses and no teams
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let addresses = add_grid_addresses(&area_repo).await?;
+
+    // 2. Balance across four teams
+    area_repo.balance_teams(4).await?;
+    let teams = area_repo.get_teams().await?;
+    assert_eq!(teams.len(), 4);
+
+    // 3. Every team gets 9-11 addresses and every address has a team
+    let assignments = area_repo.get_team_addresses_all().await?;
+    let mut centroids = Vec::new();
+    let mut assigned = 0;
+    for team in &teams {
+        let members = assignments.get(&team.id).cloned().unwrap_or_default();
+        assert!(
+            (9..=11).contains(&members.len()),
+            "Team {} got {} addresses",
+            team.number,
+            members.len()
+        );
+        assigned += members.len();
+
+        let positions: Vec<Point> = members
+            .iter()
+            .map(|member| addresses.iter().find(|a| a.id == member.address_id).unwrap().position)
+            .collect();
+        let count = positions.len() as f64;
+        centroids.push((
+            positions.iter().map(|p| p.x as f64).sum::<f64>() / count,
+            positions.iter().map(|p| p.y as f64).sum::<f64>() / count,
+        ));
+    }
+    assert_eq!(assigned, addresses.len());
+
+    // 4. Groups are compact: centroids lie well apart compared to the 12px grid spacing
+    for (i, a) in centroids.iter().enumerate() {
+        for b in &centroids[i + 1..] {
+            let distance = ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt();
+            assert!(distance > 18.0, "Team centroids {a:?} and {b:?} are only {distance:.1}px apart");
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_balance_teams_replaces_assignments() -> anyhow::Result<()> {
+    // 1. Assign everything to one team by hand
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_BLUE);
+    let area_repo = project.add_area(new_area).await?;
+    let addresses = add_grid_addresses(&area_repo).await?;
+    let first = area_repo.add_team().await?;
+    for address in &addresses {
+        TeamRepository::add_address(&area_repo, &first, address).await?;
+    }
+
+    // 2. Balancing over two teams reuses the existing one and adds one more
+    area_repo.balance_teams(2).await?;
+    let teams = area_repo.get_teams().await?;
+    assert_eq!(teams.len(), 2);
+    assert_eq!(teams[0], first);
+    for team in &teams {
+        assert_eq!(area_repo.get_team_addresses(team).await?.len(), 20);
+    }
+
+    // 3. Zero teams is rejected
+    assert!(area_repo.balance_teams(0).await.is_err());
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+//! Integration tests for transactions spanning several repository calls.
+//!
+//! Tests cover:
+//! - Committing inserts made through a transaction-bound handle
+//! - Rolling back all inserts when the closure fails
+//! - Using a batch insert (nested transaction) inside a transaction
+
+mod common;
+
+use common::*;
+
+#[tokio::test]
+async fn test_transaction_commits_on_ok() -> anyhow::Result<()> {
+    // 1. Create area
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_id = project.add_area(new_area).await?.get_area().await?.id;
+
+    // 2. Add a street and an address on it in one transaction
+    let street_id = project
+        .transaction(|tx| async move {
+            let area_repo = tx.get_area_repo(area_id).await?;
+            let street = area_repo.add_street().await?;
+            let mut address = make_test_address("1", 10, 10);
+            address.assigned_street_id = Some(street.id);
+            AddressRepository::add_address(&area_repo, &address).await?;
+            Ok(street.id)
+        })
+        .await?;
+
+    // 3. Both rows are visible outside the transaction
+    let area_repo = project.get_area_repo(area_id).await?;
+    let street = area_repo.get_street_by_id(street_id).await?.expect("street committed");
+    assert_eq!(area_repo.get_address_by_street(&street).await?.len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transaction_rolls_back_on_err() -> anyhow::Result<()> {
+    // 1. Create area
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_id = project.add_area(new_area).await?.get_area().await?.id;
+
+    // 2. Insert two addresses, then fail
+    let result: anyhow::Result<()> = project
+        .transaction(|tx| async move {
+            let area_repo = tx.get_area_repo(area_id).await?;
+            AddressRepository::add_address(&area_repo, &make_test_address("1", 10, 10)).await?;
+            AddressRepository::add_address(&area_repo, &make_test_address("2", 20, 20)).await?;
+            anyhow::bail!("forced error")
+        })
+        .await;
+    assert!(result.is_err());
+
+    // 3. Neither insert survived
+    let area_repo = project.get_area_repo(area_id).await?;
+    assert!(AddressRepository::get_addresses(&area_repo).await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transaction_rolls_back_batch_insert() -> anyhow::Result<()> {
+    // 1. Create area
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_id = project.add_area(new_area).await?.get_area().await?.id;
+
+    // 2. A committed batch insert inside the transaction is still undone by a later error
+    let result: anyhow::Result<()> = project
+        .transaction(|tx| async move {
+            let area_repo = tx.get_area_repo(area_id).await?;
+            let batch = [make_test_address("1", 10, 10), make_test_address("2", 20, 20)];
+            assert_eq!(area_repo.add_addresses(&batch).await?.len(), 2);
+            anyhow::bail!("forced error")
+        })
+        .await;
+    assert!(result.is_err());
+
+    // 3. The batch was rolled back with the outer transaction
+    let area_repo = project.get_area_repo(area_id).await?;
+    assert!(AddressRepository::get_addresses(&area_repo).await?.is_empty());
+
+    Ok(())
+}
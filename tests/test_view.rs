@@ -0,0 +1,354 @@
+//! Integration tests for the GUI view logic.
+//!
+//! Tests cover:
+//! - Overlay marker positions for addresses with and without panning and zoom
+//! - Screen <-> image coordinate mapping at several zoom/pan combinations
+//! - Zooming around the cursor, zoom clamping, fit to window and resizing
+//! - Accumulating, undoing and closing team boundary vertices
+//! - Validating the new-project form and writing it to the project, with or without a color
+//! - Importing detections from the detection button's handler
+//! - Address table edits: verifying, renaming and deleting
+//! - Classifying color picker input while typing
+//! - Encoding the area image as a data URL
+
+mod common;
+
+use addrslips::core::db::{ProjectRepository, AREA_PALETTE};
+use addrslips::core::view::{
+    default_area_color, detect_addresses, image_data_url, parse_color_input, AddressEdit, ColorInput, overlay_markers, svg_points, NewProjectForm, PolygonDraft,
+    ViewTransform, DEFAULT_AREA_COLOR_KEY, MAX_ZOOM, MIN_ZOOM,
+};
+use addrslips::HouseNumberDetection;
+use base64::Engine;
+use common::*;
+
+#[tokio::test]
+async fn test_overlay_markers_follow_addresses_and_transform() -> anyhow::Result<()> {
+    // 1. Create area with two addresses, one verified
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let first = AddressRepository::add_address(&area_repo, &make_test_address("12", 60, 80)).await?;
+    let second = AddressRepository::add_address(&area_repo, &make_test_address("7a", 40, 15)).await?;
+    let update = AddressUpdate {
+        verified: Some(true),
+        ..Default::default()
+    };
+    let second = area_repo.update_address(&second, &update).await?;
+    let addresses = vec![first.clone(), second.clone()];
+
+    // 2. Without panning markers sit on the image coordinates
+    let markers = overlay_markers(&addresses, &ViewTransform::default());
+    assert_eq!(markers.len(), 2);
+    assert_eq!((markers[0].x, markers[0].y, markers[0].radius), (60.0, 80.0, 10.0));
+    assert_eq!(markers[0].label, "12");
+    assert_eq!(markers[0].address_id, first.id);
+    assert!(!markers[0].verified);
+    assert_eq!((markers[1].x, markers[1].y), (40.0, 15.0));
+    assert!(markers[1].verified);
+
+    // 3. Panning shifts every marker by the same offset
+    let panned = ViewTransform {
+        zoom: 1.0,
+        pan_x: 25.5,
+        pan_y: -30.0,
+    };
+    let panned = overlay_markers(&addresses, &panned);
+    assert_eq!((panned[0].x, panned[0].y), (85.5, 50.0));
+    assert_eq!((panned[1].x, panned[1].y), (65.5, -15.0));
+    assert_eq!(panned[1].radius, markers[1].radius);
+
+    // 4. Zooming scales positions and radii
+    let zoomed = ViewTransform {
+        zoom: 2.0,
+        pan_x: 10.0,
+        pan_y: 0.0,
+    };
+    let zoomed = overlay_markers(&addresses, &zoomed);
+    assert_eq!((zoomed[0].x, zoomed[0].y, zoomed[0].radius), (130.0, 160.0, 20.0));
+
+    Ok(())
+}
+
+fn assert_close(actual: (f64, f64), expected: (f64, f64)) {
+    assert!(
+        (actual.0 - expected.0).abs() < 1e-9 && (actual.1 - expected.1).abs() < 1e-9,
+        "Expected {expected:?}, got {actual:?}"
+    );
+}
+
+#[test]
+fn test_transform_maps_between_screen_and_image() {
+    let cases = [(1.0, 0.0, 0.0), (2.0, 0.0, 0.0), (0.5, 30.0, -12.5), (3.25, -400.0, 250.0)];
+    for (zoom, pan_x, pan_y) in cases {
+        let transform = ViewTransform { zoom, pan_x, pan_y };
+        for (x, y) in [(0.0, 0.0), (100.0, 200.0), (1234.5, 17.25)] {
+            let screen = transform.image_to_screen(x, y);
+            assert_close(screen, (pan_x + zoom * x, pan_y + zoom * y));
+            assert_close(transform.screen_to_image(screen.0, screen.1), (x, y));
+        }
+    }
+}
+
+#[test]
+fn test_zoom_keeps_point_under_cursor() {
+    // 1. Zooming in keeps the image point under the cursor in place
+    let mut transform = ViewTransform {
+        zoom: 1.0,
+        pan_x: 20.0,
+        pan_y: 10.0,
+    };
+    let cursor = (150.0, 90.0);
+    let under_cursor = transform.screen_to_image(cursor.0, cursor.1);
+    transform.zoom_at(cursor.0, cursor.1, 2.0);
+    assert_eq!(transform.zoom, 2.0);
+    assert_close(transform.image_to_screen(under_cursor.0, under_cursor.1), cursor);
+
+    // 2. Zoom is clamped in both directions, still anchored at the cursor
+    transform.zoom_at(cursor.0, cursor.1, 1000.0);
+    assert_eq!(transform.zoom, MAX_ZOOM);
+    assert_close(transform.image_to_screen(under_cursor.0, under_cursor.1), cursor);
+    transform.zoom_at(cursor.0, cursor.1, 1e-6);
+    assert_eq!(transform.zoom, MIN_ZOOM);
+    assert_close(transform.image_to_screen(under_cursor.0, under_cursor.1), cursor);
+}
+
+#[test]
+fn test_fit_and_resize_keep_image_centered() {
+    // 1. A wide image fits the viewport width and is centered vertically
+    let mut transform = ViewTransform::fit((2000.0, 1000.0), (800.0, 600.0));
+    assert_eq!(transform.zoom, 0.4);
+    assert_close(transform.image_to_screen(0.0, 0.0), (0.0, 100.0));
+    assert_close(transform.image_to_screen(1000.0, 500.0), (400.0, 300.0));
+
+    // 2. Growing the viewport keeps the image center in the viewport center
+    transform.resize_viewport((800.0, 600.0), (1000.0, 700.0));
+    assert_close(transform.image_to_screen(1000.0, 500.0), (500.0, 350.0));
+
+    // 3. Degenerate images fall back to the identity transform
+    assert_eq!(ViewTransform::fit((0.0, 0.0), (800.0, 600.0)), ViewTransform::default());
+}
+
+#[test]
+fn test_polygon_draft_click_undo_close() {
+    // 1. Clicks append vertices; the repeated click of a double-click is ignored
+    let mut draft = PolygonDraft::default();
+    assert!(draft.add_vertex(Point { x: 10, y: 10 }));
+    assert!(draft.add_vertex(Point { x: 50, y: 10 }));
+    assert!(!draft.add_vertex(Point { x: 50, y: 10 }));
+    assert_eq!(draft.vertices().len(), 2);
+
+    // 2. Two vertices can't be closed; the draft is kept
+    assert_eq!(draft.close(), None);
+    assert_eq!(draft.vertices().len(), 2);
+
+    // 3. Undo removes the last vertex
+    assert_eq!(draft.remove_last(), Some(Point { x: 50, y: 10 }));
+    assert!(draft.add_vertex(Point { x: 60, y: 20 }));
+    assert!(draft.add_vertex(Point { x: 30, y: 70 }));
+
+    // 4. Closing hands over the vertices in click order and starts over
+    let boundary = draft.close().expect("three vertices close");
+    assert_eq!(
+        boundary,
+        vec![Point { x: 10, y: 10 }, Point { x: 60, y: 20 }, Point { x: 30, y: 70 }]
+    );
+    assert!(draft.vertices().is_empty());
+    assert_eq!(draft.remove_last(), None);
+}
+
+#[test]
+fn test_boundary_points_follow_transform() {
+    let transform = ViewTransform {
+        zoom: 2.0,
+        pan_x: 5.0,
+        pan_y: -10.0,
+    };
+    assert_eq!(svg_points(&[Point { x: 10, y: 10 }, Point { x: 20, y: 30 }], &transform), "25,10 45,50");
+    assert_eq!(svg_points(&[], &transform), "");
+
+    // Clicks map back to image pixels; clicks left of or above the image are dropped
+    let image_size = (100.0, 80.0);
+    assert_eq!(transform.screen_to_image_point(45.0, 50.0, image_size), Some(Point { x: 20, y: 30 }));
+    assert_eq!(transform.screen_to_image_point(0.0, 50.0, image_size), None);
+}
+
+#[test]
+fn test_clicks_past_the_image_edge_are_dropped() {
+    let transform = ViewTransform {
+        zoom: 2.0,
+        pan_x: 5.0,
+        pan_y: -10.0,
+    };
+    let image_size = (100.0, 80.0);
+
+    // The last pixel row and column are still inside
+    assert_eq!(transform.screen_to_image_point(203.0, 148.0, image_size), Some(Point { x: 99, y: 79 }));
+    // One pixel further right or down is outside
+    assert_eq!(transform.screen_to_image_point(205.0, 50.0, image_size), None);
+    assert_eq!(transform.screen_to_image_point(45.0, 150.0, image_size), None);
+    assert_eq!(transform.screen_to_image_point(1000.0, 1000.0, image_size), None);
+}
+
+#[tokio::test]
+async fn test_new_project_form_writes_settings() -> anyhow::Result<()> {
+    let (project, _temp_dir) = create_test_project().await;
+    assert_eq!(default_area_color(&project).await?, AREA_PALETTE[0]);
+
+    // 1. Blank names and non-numeric targets are rejected without touching the project
+    let mut form = NewProjectForm {
+        name: "   ".to_string(),
+        target_address_count: "250".to_string(),
+        default_area_color: Some(TEST_RED),
+    };
+    assert!(form.apply(&project).await.unwrap_err().to_string().contains("name"));
+    form.name = "Campaign North".to_string();
+    form.target_address_count = "lots".to_string();
+    assert!(form.apply(&project).await.unwrap_err().to_string().contains("whole number"));
+    assert_eq!(project.get_metadata("name").await?, None);
+
+    // 2. Valid values end up in the project settings
+    form.name = "  Campaign North ".to_string();
+    form.target_address_count = "250".to_string();
+    let before = time::OffsetDateTime::now_utc() - time::Duration::seconds(1);
+    form.apply(&project).await?;
+    assert_eq!(project.get_project_name().await?, "Campaign North");
+    assert_eq!(project.get_target_address_count().await?, 250);
+    assert!(project.get_project_created_at().await? >= before);
+    assert_eq!(default_area_color(&project).await?, TEST_RED);
+
+    // 3. An empty target means none yet
+    form.target_address_count = String::new();
+    assert_eq!(form.settings()?.target_address_count, Some(0));
+
+    // 4. Once an area uses the chosen color, the form suggests a distinct one
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    project.add_area(new_area).await?;
+    let suggested = default_area_color(&project).await?;
+    assert_ne!(suggested, TEST_RED);
+    assert_eq!(suggested, project.suggest_area_color().await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_new_project_form_without_color_keeps_suggestions() -> anyhow::Result<()> {
+    // 1. Apply a form without a default area color
+    let (project, _temp_dir) = create_test_project().await;
+    let form = NewProjectForm {
+        name: "Campaign South".to_string(),
+        ..NewProjectForm::default()
+    };
+    form.apply(&project).await?;
+
+    // 2. No color is stored, so the add-area form starts with the suggestion
+    assert_eq!(project.get_metadata(DEFAULT_AREA_COLOR_KEY).await?, None);
+    assert_eq!(default_area_color(&project).await?, AREA_PALETTE[0]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_detect_addresses_imports_detections() -> anyhow::Result<()> {
+    // 1. Create area without addresses
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let (width, height) = (area_repo.get_image().width(), area_repo.get_image().height());
+
+    // 2. A failing detector leaves the area untouched
+    let err = detect_addresses(&area_repo, |_| anyhow::bail!("no models")).await.unwrap_err();
+    assert_eq!(err.to_string(), "no models");
+    assert!(AddressRepository::get_addresses(&area_repo).await?.is_empty());
+    assert_eq!(area_repo.get_area().await?.state, AreaState::Imported);
+
+    // 3. Detections from the area image become unverified addresses
+    let imported = detect_addresses(&area_repo, move |img| {
+        assert_eq!((img.width(), img.height()), (width, height));
+        Ok(vec![
+            HouseNumberDetection {
+                number: "12".to_string(),
+                x: 10,
+                y: 20,
+                radius: 8,
+                confidence: 0.9,
+            },
+            HouseNumberDetection {
+                number: "14a".to_string(),
+                x: 40,
+                y: 20,
+                radius: 9,
+                confidence: 0.75,
+            },
+        ])
+    })
+    .await?;
+    assert_eq!(imported.len(), 2);
+    assert_eq!(imported[1].house_number, "14a");
+    assert_eq!(imported[1].position, Point { x: 40, y: 20 });
+    assert!(!imported[1].verified);
+    assert_eq!(AddressRepository::get_addresses(&area_repo).await?, imported);
+    assert_eq!(area_repo.get_area().await?.state, AreaState::AddressesDetected);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_address_table_edits() -> anyhow::Result<()> {
+    // 1. Create area with one unverified address
+    let (project, _temp_dir) = create_test_project().await;
+    let (new_area, _img_file) = make_new_area("Test Area", TEST_RED);
+    let area_repo = project.add_area(new_area).await?;
+    let address = AddressRepository::add_address(&area_repo, &make_test_address("12", 10, 20)).await?;
+
+    // 2. Ticking the checkbox issues a verified-only update
+    let update = AddressEdit::Verified(true).to_update(&address).expect("verifying changes the address");
+    assert_eq!(update.verified, Some(true));
+    assert_eq!(update.house_number, None);
+    assert_eq!(update.position, None);
+    let address = AddressEdit::Verified(true).apply(&area_repo, &address).await?.unwrap();
+    assert!(address.verified);
+    assert!(AddressEdit::Verified(true).to_update(&address).is_none());
+
+    // 3. Renaming trims the input; blank or unchanged numbers are not written
+    assert!(AddressEdit::HouseNumber("  ".to_string()).to_update(&address).is_none());
+    assert!(AddressEdit::HouseNumber("12".to_string()).to_update(&address).is_none());
+    let address = AddressEdit::HouseNumber(" 12a ".to_string()).apply(&area_repo, &address).await?.unwrap();
+    assert_eq!(address.house_number, "12a");
+    assert!(address.verified);
+
+    // 4. Delete moves it to the trash
+    assert_eq!(AddressEdit::Delete.apply(&area_repo, &address).await?, None);
+    assert!(AddressRepository::get_addresses(&area_repo).await?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_color_input_while_typing() {
+    let magenta_ish = Color { r: 0xFF, g: 0x00, b: 0xAA };
+    assert_eq!(parse_color_input("f0a"), ColorInput::Valid(magenta_ish));
+    assert_eq!(parse_color_input("#f0a"), ColorInput::Valid(magenta_ish));
+    assert_eq!(parse_color_input("#FF00AA"), ColorInput::Valid(magenta_ish));
+
+    // Prefixes of valid colors are not errors yet
+    assert_eq!(parse_color_input("ff"), ColorInput::Incomplete);
+    assert_eq!(parse_color_input("#"), ColorInput::Incomplete);
+    assert_eq!(parse_color_input("#ff00"), ColorInput::Incomplete);
+
+    assert_eq!(parse_color_input("zzz"), ColorInput::Invalid);
+    assert_eq!(parse_color_input("#ff00aa001"), ColorInput::Invalid);
+}
+
+#[test]
+fn test_image_data_url_round_trips() -> anyhow::Result<()> {
+    let map = synthetic_map(64, 48, &[]);
+    let url = image_data_url(&map)?;
+
+    let encoded = url.strip_prefix("data:image/png;base64,").expect("PNG data URL");
+    let decoded = image::load_from_memory(&base64::engine::general_purpose::STANDARD.decode(encoded)?)?;
+    assert_eq!((decoded.width(), decoded.height()), (64, 48));
+    assert_eq!(decoded.to_rgb8(), map.to_rgb8());
+
+    Ok(())
+}